@@ -0,0 +1,87 @@
+#ifndef SENTENCER_H
+#define SENTENCER_H
+
+/* Generated from src/ffi.rs by cbindgen; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum SentencerKind {
+  SENTENCER_KIND_BODY,
+  SENTENCER_KIND_HEADING,
+  SENTENCER_KIND_FOOTNOTE,
+  SENTENCER_KIND_ENDNOTE,
+  SENTENCER_KIND_TABLE_CELL,
+  SENTENCER_KIND_COMMENT,
+  SENTENCER_KIND_SPEAKER_NOTE,
+  SENTENCER_KIND_HEADER,
+  SENTENCER_KIND_FOOTER,
+  SENTENCER_KIND_CAPTION,
+  SENTENCER_KIND_ALT_TEXT,
+  SENTENCER_KIND_LIST_ITEM,
+  SENTENCER_KIND_QUOTE,
+} SentencerKind;
+
+typedef struct SentencerSentence {
+  char *text;
+  size_t paragraph;
+  size_t start;
+  size_t end;
+  enum SentencerKind kind;
+} SentencerSentence;
+
+typedef struct SentencerSentences {
+  struct SentencerSentence *sentences;
+  size_t len;
+} SentencerSentences;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Parses the document at `path`, a NUL-terminated UTF-8 path.
+
+ # Safety
+
+ `path` must be NULL or point to a NUL-terminated string.
+ */
+struct SentencerSentences *sentencer_parse_file(const char *path);
+
+/*
+ Parses a document held in memory. `file_name` may be NULL; when given, its
+ extension tells the format, which is otherwise detected from the data.
+
+ # Safety
+
+ `data` must point to `len` readable bytes, and `file_name` must be NULL or
+ point to a NUL-terminated string.
+ */
+struct SentencerSentences *sentencer_parse_bytes(const uint8_t *data,
+                                                 size_t len,
+                                                 const char *file_name);
+
+/*
+ Frees what sentencer_parse_file or sentencer_parse_bytes returned. NULL is
+ ignored.
+
+ # Safety
+
+ `sentences` must be NULL or returned by this library, and not freed before.
+ */
+void sentencer_free_sentences(struct SentencerSentences *sentences);
+
+/*
+ Why the last call on this thread returned NULL, or NULL when it did not
+ fail. The message is valid until the next call on this thread.
+ */
+const char *sentencer_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SENTENCER_H */
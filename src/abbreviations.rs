@@ -0,0 +1,239 @@
+use crate::error::Result;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+// Never the end of a sentence
+const ENGLISH_ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "vs", "e.g", "i.e", "cf", "viz", "approx",
+];
+
+// Abbreviations that are also words, or that may end a sentence, and only
+// count as abbreviations where what follows shows it
+const ENGLISH_IN_CONTEXT: &[(&str, Context)] = &[
+    ("capt", Context::Name),
+    ("col", Context::Name),
+    ("ft", Context::Name),
+    ("gen", Context::Name),
+    ("gov", Context::Name),
+    ("hon", Context::Name),
+    ("lt", Context::Name),
+    ("mt", Context::Name),
+    ("rep", Context::Name),
+    ("rev", Context::Name),
+    ("sen", Context::Name),
+    ("sgt", Context::Name),
+    ("st", Context::Name),
+    ("no", Context::Number),
+    ("nos", Context::Number),
+    ("p", Context::Number),
+    ("pp", Context::Number),
+    ("vol", Context::Number),
+    ("vols", Context::Number),
+    ("fig", Context::Number),
+    ("figs", Context::Number),
+    ("ch", Context::Number),
+    ("est", Context::Number),
+    ("jan", Context::Number),
+    ("feb", Context::Number),
+    ("mar", Context::Number),
+    ("apr", Context::Number),
+    ("jun", Context::Number),
+    ("jul", Context::Number),
+    ("aug", Context::Number),
+    ("sep", Context::Number),
+    ("sept", Context::Number),
+    ("oct", Context::Number),
+    ("nov", Context::Number),
+    ("dec", Context::Number),
+    ("al", Context::Continuation),
+    ("etc", Context::Continuation),
+    ("ed", Context::Continuation),
+    ("eds", Context::Continuation),
+    ("inc", Context::Continuation),
+    ("ltd", Context::Continuation),
+    ("co", Context::Continuation),
+    ("corp", Context::Continuation),
+    ("dept", Context::Continuation),
+    ("jr", Context::Continuation),
+    ("sr", Context::Continuation),
+    ("u.s", Context::Continuation),
+    ("u.k", Context::Continuation),
+    ("a.m", Context::Continuation),
+    ("p.m", Context::Continuation),
+];
+
+// What has to follow an abbreviation in context for the sentence to go on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    // A capitalised name, as in "St. Louis"
+    Name,
+    // A number, as in "No. 5" or "Dec. 25"
+    Number,
+    // A word in lower case or a number, as in "etc. and so on"
+    Continuation,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Abbreviations {
+    entries: HashSet<String>,
+    in_context: HashMap<String, Context>,
+}
+
+impl Abbreviations {
+    pub fn new() -> Self {
+        Abbreviations::default()
+    }
+
+    pub fn english() -> Self {
+        let mut abbreviations = Abbreviations::new();
+        for abbreviation in ENGLISH_ABBREVIATIONS {
+            abbreviations.insert(abbreviation);
+        }
+        for (abbreviation, context) in ENGLISH_IN_CONTEXT {
+            abbreviations
+                .in_context
+                .insert(abbreviation.to_string(), *context);
+        }
+        abbreviations
+    }
+
+    // Abbreviations added by hand never end a sentence
+    pub fn insert(&mut self, abbreviation: &str) {
+        let normalized = abbreviation.trim().trim_end_matches('.').to_lowercase();
+        if !normalized.is_empty() {
+            self.in_context.remove(&normalized);
+            self.entries.insert(normalized);
+        }
+    }
+
+    // One abbreviation per line, with or without the trailing period.
+    // Blank lines and lines starting with '#' are ignored.
+    pub fn load_file(&mut self, file_path: &str) -> Result<()> {
+        let content = fs::read_to_string(file_path)?;
+        for line in content.lines() {
+            let line = line.trim();
+            if !line.is_empty() && !line.starts_with('#') {
+                self.insert(line);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        let normalized = word.trim_end_matches('.').to_lowercase();
+        self.entries.contains(&normalized) || self.in_context.contains_key(&normalized)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len() + self.in_context.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.in_context.is_empty()
+    }
+
+    // True when the sentence's final period belongs to an abbreviation or an
+    // initial (as in "J. R. R. Tolkien") rather than ending the sentence.
+    // `next` is the text that follows, which decides for abbreviations that
+    // are also words and for single capitals, as in "plan B. Then".
+    pub fn ends_with_abbreviation(&self, sentence: &str, next: &str) -> bool {
+        if !sentence.ends_with('.') {
+            return false;
+        }
+
+        let mut words = sentence.split_whitespace().rev().map(|word| {
+            word.trim_start_matches(|c: char| !c.is_alphanumeric())
+                .trim_end_matches('.')
+        });
+        let Some(stem) = words.next() else {
+            return false;
+        };
+        let previous = words.next().unwrap_or_default();
+        let next = next
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches(|c: char| !c.is_alphanumeric());
+        let next_is_name = next.chars().next().is_some_and(char::is_uppercase);
+
+        // An initial stands before another initial, or between names
+        if is_initial(stem) {
+            return is_initial(next.trim_end_matches('.'))
+                || (next_is_name && starts_name(previous));
+        }
+
+        let normalized = stem.to_lowercase();
+        if self.entries.contains(&normalized) {
+            return true;
+        }
+        let starts_with_digit = next.chars().next().is_some_and(|c| c.is_ascii_digit());
+        match self.in_context.get(&normalized) {
+            Some(Context::Name) => next_is_name,
+            Some(Context::Number) => starts_with_digit,
+            Some(Context::Continuation) => {
+                starts_with_digit || next.chars().next().is_some_and(char::is_lowercase)
+            }
+            None => false,
+        }
+    }
+}
+
+fn is_initial(word: &str) -> bool {
+    let mut chars = word.chars();
+    matches!((chars.next(), chars.next()), (Some(c), None) if c.is_uppercase())
+}
+
+fn starts_name(word: &str) -> bool {
+    word.chars().next().is_some_and(char::is_uppercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_defaults() {
+        let abbreviations = Abbreviations::english();
+
+        assert!(abbreviations.ends_with_abbreviation("Ask Dr.", "Jones"));
+        assert!(abbreviations.ends_with_abbreviation("Fruit (e.g.", "apples)"));
+        assert!(abbreviations.ends_with_abbreviation("Written by J.", "R. R. Tolkien"));
+        assert!(abbreviations.ends_with_abbreviation("George W.", "Bush"));
+        assert!(!abbreviations.ends_with_abbreviation("The end.", "Next"));
+        assert!(!abbreviations.ends_with_abbreviation("Really Dr?", "Yes"));
+        assert!(!abbreviations.ends_with_abbreviation("Go with plan B.", "Then"));
+    }
+
+    #[test]
+    fn test_abbreviations_in_context() {
+        let abbreviations = Abbreviations::english();
+
+        assert!(abbreviations.ends_with_abbreviation("See No.", "5"));
+        assert!(!abbreviations.ends_with_abbreviation("She said no.", "He"));
+        assert!(abbreviations.ends_with_abbreviation("Due Dec.", "25"));
+        assert!(!abbreviations.ends_with_abbreviation("It was Dec.", "Snow"));
+        assert!(abbreviations.ends_with_abbreviation("Apples, pears etc.", "are sold"));
+        assert!(!abbreviations.ends_with_abbreviation("Apples, pears etc.", "Then"));
+        assert!(abbreviations.ends_with_abbreviation("Born in St.", "Louis"));
+
+        let mut custom = Abbreviations::english();
+        custom.insert("etc.");
+        assert!(custom.ends_with_abbreviation("Apples, pears etc.", "Then"));
+    }
+
+    #[test]
+    fn test_load_file() -> Result<()> {
+        let test_file = "abbreviations_test.txt";
+        fs::write(test_file, "# custom list\nca.\n\nGmbH\n")?;
+
+        let mut abbreviations = Abbreviations::new();
+        let result = abbreviations.load_file(test_file);
+        fs::remove_file(test_file).ok();
+        result?;
+
+        assert_eq!(abbreviations.len(), 2);
+        assert!(abbreviations.contains("ca."));
+        assert!(abbreviations.contains("gmbh"));
+        Ok(())
+    }
+}
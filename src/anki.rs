@@ -0,0 +1,134 @@
+use crate::annotation::Annotation;
+use crate::error::Result;
+use crate::sentence::Sentence;
+use regex::RegexBuilder;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+// A flashcard in Anki's plain text import format. Cloze cards hide the chosen
+// words of the sentence; basic cards ask with the whole sentence. The back is
+// the note made on the sentence while reading, such as a translation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    pub front: String,
+    pub back: String,
+    pub tags: Vec<String>,
+    pub cloze: bool,
+}
+
+// Annotations are matched by sentence text, so cards are still found when the
+// document has changed since it was annotated. Without annotations or cloze
+// words every sentence becomes a card; otherwise only the sentences with a
+// note, tags or one of the words do.
+pub fn cards(
+    sentences: &[Sentence],
+    annotations: &BTreeMap<usize, Annotation>,
+    cloze_words: &[String],
+) -> Result<Vec<Card>> {
+    let patterns = cloze_words
+        .iter()
+        .map(|word| {
+            RegexBuilder::new(&format!(r"\b{}\b", regex::escape(word)))
+                .case_insensitive(true)
+                .build()
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let notes: HashMap<&str, &Annotation> = annotations
+        .values()
+        .filter(|annotation| !annotation.is_empty())
+        .map(|annotation| (annotation.sentence.as_str(), annotation))
+        .collect();
+    let every_sentence = notes.is_empty() && patterns.is_empty();
+
+    let mut cards = Vec::new();
+    for sentence in sentences {
+        let annotation = notes.get(sentence.text.as_str());
+        let mut front = sentence.text.clone();
+        let mut clozes = 0;
+        for pattern in &patterns {
+            if pattern.is_match(&front) {
+                clozes += 1;
+                front = pattern
+                    .replace_all(&front, format!("{{{{c{}::$0}}}}", clozes))
+                    .into_owned();
+            }
+        }
+        if clozes == 0 && annotation.is_none() && !every_sentence {
+            continue;
+        }
+
+        cards.push(Card {
+            front,
+            back: annotation.map_or(String::new(), |a| a.note.clone()),
+            tags: annotation.map_or(Vec::new(), |a| a.tags.clone()),
+            cloze: clozes > 0,
+        });
+    }
+
+    Ok(cards)
+}
+
+// The header lines tell Anki the note type and tags of each row, so cloze and
+// basic cards can be imported from the same file
+pub fn write_cards<W: Write>(writer: &mut W, cards: &[Card]) -> Result<()> {
+    writeln!(writer, "#separator:tab")?;
+    writeln!(writer, "#html:false")?;
+    writeln!(writer, "#notetype column:1")?;
+    writeln!(writer, "#tags column:4")?;
+
+    let mut table = csv::WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_writer(writer);
+    for card in cards {
+        let note_type = if card.cloze { "Cloze" } else { "Basic" };
+        table.write_record([note_type, &card.front, &card.back, &card.tags.join(" ")])?;
+    }
+    table.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anki_cards() -> Result<()> {
+        let sentences = vec![
+            Sentence::new("Der Hund schläft."),
+            Sentence::new("Ein hund bellt, der Hund läuft."),
+            Sentence::new("Die Katze."),
+        ];
+        let annotations = BTreeMap::from([(
+            2,
+            Annotation {
+                sentence: "Die Katze.".to_string(),
+                note: "The cat.".to_string(),
+                tags: vec!["animals".to_string()],
+            },
+        )]);
+
+        let deck = cards(&sentences, &annotations, &["hund".to_string()])?;
+        let fronts: Vec<&str> = deck.iter().map(|card| card.front.as_str()).collect();
+        assert_eq!(
+            fronts,
+            vec![
+                "Der {{c1::Hund}} schläft.",
+                "Ein {{c1::hund}} bellt, der {{c1::Hund}} läuft.",
+                "Die Katze.",
+            ]
+        );
+
+        let mut output = Vec::new();
+        write_cards(&mut output, &deck[1..])?;
+        assert_eq!(
+            String::from_utf8(output)?,
+            "#separator:tab\n#html:false\n#notetype column:1\n#tags column:4\n\
+             Cloze\tEin {{c1::hund}} bellt, der {{c1::Hund}} läuft.\t\t\n\
+             Basic\tDie Katze.\tThe cat.\tanimals\n"
+        );
+
+        assert_eq!(cards(&sentences, &BTreeMap::new(), &[])?.len(), 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use crate::error::{Result, SentencerError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+
+// A note and tags attached to a sentence while reading. The sentence text is
+// kept with them so annotations can be exported without the document.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub sentence: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.note.is_empty() && self.tags.is_empty()
+    }
+
+    // Tags are separated by spaces or commas; a leading '#' is optional
+    pub fn parse_tags(input: &str) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in input.split([' ', ',']) {
+            let tag = tag.trim_start_matches('#');
+            if !tag.is_empty() && !tags.iter().any(|existing| existing == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+        tags
+    }
+
+    // Used when two annotated sentences are joined into one
+    pub fn merge(&mut self, other: Annotation) {
+        if !other.note.is_empty() {
+            if !self.note.is_empty() {
+                self.note.push(' ');
+            }
+            self.note.push_str(&other.note);
+        }
+        for tag in other.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationFormat {
+    Json,
+    Markdown,
+}
+
+impl AnnotationFormat {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "json" => Ok(AnnotationFormat::Json),
+            "markdown" | "md" => Ok(AnnotationFormat::Markdown),
+            _ => Err(SentencerError::InvalidArgument(format!(
+                "Unknown annotation format '{}' (expected json or markdown)",
+                name
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedAnnotation<'a> {
+    index: usize,
+    sentence: &'a str,
+    note: &'a str,
+    tags: &'a [String],
+}
+
+// Annotations are keyed by sentence index; exports number sentences from 1
+pub fn export_annotations<W: Write>(
+    writer: &mut W,
+    annotations: &BTreeMap<usize, Annotation>,
+    title: &str,
+    format: AnnotationFormat,
+) -> Result<()> {
+    match format {
+        AnnotationFormat::Json => {
+            let rows: Vec<ExportedAnnotation> = annotations
+                .iter()
+                .map(|(&index, annotation)| ExportedAnnotation {
+                    index: index + 1,
+                    sentence: &annotation.sentence,
+                    note: &annotation.note,
+                    tags: &annotation.tags,
+                })
+                .collect();
+            serde_json::to_writer_pretty(&mut *writer, &rows)?;
+            writeln!(writer)?;
+        }
+        AnnotationFormat::Markdown => {
+            writeln!(writer, "# Annotations: {}", title)?;
+            for (index, annotation) in annotations {
+                writeln!(writer, "\n## Sentence {}\n", index + 1)?;
+                writeln!(writer, "> {}", annotation.sentence)?;
+                if !annotation.note.is_empty() {
+                    writeln!(writer, "\n{}", annotation.note)?;
+                }
+                if !annotation.tags.is_empty() {
+                    let tags: Vec<String> = annotation
+                        .tags
+                        .iter()
+                        .map(|tag| format!("`{}`", tag))
+                        .collect();
+                    writeln!(writer, "\nTags: {}", tags.join(", "))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_merge_tags() {
+        assert_eq!(
+            Annotation::parse_tags("#typo, style  typo"),
+            vec!["typo", "style"]
+        );
+
+        let mut first = Annotation {
+            sentence: "One.".to_string(),
+            note: "Check this.".to_string(),
+            tags: vec!["typo".to_string()],
+        };
+        first.merge(Annotation {
+            sentence: "Two.".to_string(),
+            note: "And this.".to_string(),
+            tags: vec!["typo".to_string(), "style".to_string()],
+        });
+        assert_eq!(first.note, "Check this. And this.");
+        assert_eq!(first.tags, vec!["typo", "style"]);
+    }
+
+    #[test]
+    fn test_export_annotations() -> Result<()> {
+        let annotations = BTreeMap::from([(
+            2,
+            Annotation {
+                sentence: "Thre sentences.".to_string(),
+                note: "Spelling.".to_string(),
+                tags: vec!["typo".to_string()],
+            },
+        )]);
+
+        let mut markdown = Vec::new();
+        export_annotations(
+            &mut markdown,
+            &annotations,
+            "doc.odt",
+            AnnotationFormat::Markdown,
+        )?;
+        let mut json = Vec::new();
+        export_annotations(&mut json, &annotations, "doc.odt", AnnotationFormat::Json)?;
+
+        assert_eq!(
+            String::from_utf8(markdown)?,
+            "# Annotations: doc.odt\n\n## Sentence 3\n\n> Thre sentences.\n\nSpelling.\n\nTags: `typo`\n"
+        );
+        let value: serde_json::Value = serde_json::from_slice(&json)?;
+        assert_eq!(
+            value,
+            serde_json::json!([{
+                "index": 3, "sentence": "Thre sentences.", "note": "Spelling.", "tags": ["typo"]
+            }])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use crate::error::{Result, SentencerError};
+use crate::filter::SentenceFilter;
+use crate::parser::{DocumentFormat, ParseOptions, parser_for};
+use crate::search::SearchQuery;
+use crate::sentence::Sentence;
+use crate::splitter::Splitter;
+use crate::stats::DocumentStats;
+use rayon::prelude::*;
+use std::fs;
+use std::path::Path;
+
+// Patterns without glob characters are passed through untouched, so a
+// missing file is reported by the parser like any other unreadable file
+pub fn expand_patterns<S: AsRef<str>>(patterns: &[S]) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+
+    for pattern in patterns {
+        let pattern = pattern.as_ref();
+        if !pattern.contains(['*', '?', '[']) {
+            paths.push(pattern.to_string());
+            continue;
+        }
+
+        let entries = glob::glob(pattern).map_err(|e| {
+            SentencerError::InvalidArgument(format!("Invalid pattern '{}': {}", pattern, e))
+        })?;
+        let mut matched = false;
+        for entry in entries {
+            let path = entry.map_err(std::io::Error::from)?;
+            if path.is_file() {
+                paths.push(path.to_string_lossy().into_owned());
+                matched = true;
+            }
+        }
+        if !matched {
+            return Err(SentencerError::InvalidArgument(format!(
+                "No files match '{}'",
+                pattern
+            )));
+        }
+    }
+
+    Ok(paths)
+}
+
+// Walks `dir` and its subdirectories for files with a supported extension,
+// returned in sorted order so reports are stable
+pub fn find_documents(dir: &str) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    let mut dirs = vec![Path::new(dir).to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let supported = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(|ext| DocumentFormat::from_extension(&ext.to_lowercase()))
+                .is_some();
+            if supported {
+                paths.push(path.to_string_lossy().into_owned());
+            }
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+// Files are parsed in parallel; results come back in the order of `paths`
+pub fn parse_files(
+    paths: &[String],
+    splitter: &Splitter,
+    options: &ParseOptions,
+) -> Vec<Result<Vec<Sentence>>> {
+    paths
+        .par_iter()
+        .map(|path| parser_for(path, splitter.clone(), options.clone())?.parse_file(path))
+        .collect()
+}
+
+// Like parse_files, but only the statistics of the sentences `filter`
+// accepts are kept for each document
+pub fn stats_files(
+    paths: &[String],
+    splitter: &Splitter,
+    options: &ParseOptions,
+    filter: &SentenceFilter,
+) -> Vec<Result<DocumentStats>> {
+    paths
+        .par_iter()
+        .map(|path| {
+            let mut sentences =
+                parser_for(path, splitter.clone(), options.clone())?.parse_file(path)?;
+            filter.retain(&mut sentences);
+            Ok(DocumentStats::from_sentences(&sentences))
+        })
+        .collect()
+}
+
+// Like parse_files, but only the sentences `query` matches are kept, with
+// their positions in the document
+pub fn search_files(
+    paths: &[String],
+    splitter: &Splitter,
+    options: &ParseOptions,
+    query: &SearchQuery,
+) -> Vec<Result<Vec<(usize, Sentence)>>> {
+    paths
+        .par_iter()
+        .map(|path| {
+            let sentences =
+                parser_for(path, splitter.clone(), options.clone())?.parse_file(path)?;
+            Ok(sentences
+                .into_iter()
+                .enumerate()
+                .filter(|(_, sentence)| query.is_match(&sentence.text))
+                .collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_expand_and_parse_files() -> Result<()> {
+        let dir = "batch_test_files";
+        fs::create_dir_all(dir)?;
+        fs::write(format!("{}/one.txt", dir), "First file. Two sentences.")?;
+        fs::write(format!("{}/two.txt", dir), "Second file.")?;
+
+        let paths = expand_patterns(&[format!("{}/*.txt", dir), "missing.txt".to_string()]);
+        let no_match = expand_patterns(&[format!("{}/*.odt", dir)]);
+        let results = match &paths {
+            Ok(paths) => parse_files(paths, &Splitter::regex()?, &ParseOptions::default()),
+            Err(_) => Vec::new(),
+        };
+
+        fs::remove_dir_all(dir).ok();
+
+        assert_eq!(
+            paths?,
+            vec![
+                format!("{}/one.txt", dir),
+                format!("{}/two.txt", dir),
+                "missing.txt".to_string()
+            ]
+        );
+        assert!(no_match.is_err());
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().map(|s| s.len()).ok(), Some(2));
+        assert!(results[1].is_ok());
+        assert!(results[2].is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_documents() -> Result<()> {
+        let dir = "batch_find_files";
+        fs::create_dir_all(format!("{}/nested/deeper", dir))?;
+        fs::write(format!("{}/b.md", dir), "# Title")?;
+        fs::write(format!("{}/notes.log", dir), "Not a document.")?;
+        fs::write(format!("{}/nested/a.TXT", dir), "Text.")?;
+        fs::write(format!("{}/nested/deeper/c.txt", dir), "More text.")?;
+
+        let paths = find_documents(dir);
+        let stats = match &paths {
+            Ok(paths) => stats_files(
+                paths,
+                &Splitter::regex()?,
+                &ParseOptions::default(),
+                &SentenceFilter::new(),
+            ),
+            Err(_) => Vec::new(),
+        };
+        let found = match &paths {
+            Ok(paths) => search_files(
+                paths,
+                &Splitter::regex()?,
+                &ParseOptions::default(),
+                &SearchQuery::new("TEXT", false)?,
+            ),
+            Err(_) => Vec::new(),
+        };
+        fs::remove_dir_all(dir).ok();
+
+        assert_eq!(
+            paths?,
+            vec![
+                format!("{}/b.md", dir),
+                format!("{}/nested/a.TXT", dir),
+                format!("{}/nested/deeper/c.txt", dir),
+            ]
+        );
+        let sentences: Vec<usize> = stats
+            .into_iter()
+            .map(|stats| stats.map(|stats| stats.sentences))
+            .collect::<Result<_>>()?;
+        assert_eq!(sentences, vec![1, 1, 1]);
+        let found: Vec<Vec<(usize, Sentence)>> = found.into_iter().collect::<Result<_>>()?;
+        assert!(found[0].is_empty());
+        assert_eq!(found[2], vec![(0, Sentence::new("More text."))]);
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use crate::error::Result;
+use crate::parser::{self, DocumentFormat, ParseOptions, Source};
+use crate::splitter::{SentenceSplitter, Splitter};
+use serde::{Serialize, Serializer};
+use std::fs;
+use std::time::{Duration, Instant};
+
+// Where the time goes when one document is parsed. Reading, extracting and
+// splitting are timed one after another on their own; parsing is the whole
+// pipeline as every command runs it, extraction and splitting interleaved.
+#[derive(Debug, Clone, Serialize)]
+pub struct Timings {
+    pub bytes: usize,
+    pub characters: usize,
+    pub sentences: usize,
+    #[serde(serialize_with = "milliseconds")]
+    pub read: Duration,
+    #[serde(serialize_with = "milliseconds")]
+    pub extract: Duration,
+    #[serde(serialize_with = "milliseconds")]
+    pub split: Duration,
+    #[serde(serialize_with = "milliseconds")]
+    pub parse: Duration,
+    // Most memory the process held at once, in bytes, where the system tells
+    pub peak_rss: Option<u64>,
+}
+
+pub fn time_document(path: &str, splitter: Splitter, options: ParseOptions) -> Result<Timings> {
+    let start = Instant::now();
+    let data = fs::read(path)?;
+    let read = start.elapsed();
+
+    let start = Instant::now();
+    let format = DocumentFormat::detect_bytes(path, &data)?;
+    let text = parser::document_text(Source::Bytes(&data), format)?;
+    let extract = start.elapsed();
+
+    let start = Instant::now();
+    splitter.split(&text);
+    let split = start.elapsed();
+
+    let start = Instant::now();
+    let sentences = parser::parser_for_bytes(path, &data, splitter, options)?.parse_bytes(&data)?;
+    let parse = start.elapsed();
+
+    Ok(Timings {
+        bytes: data.len(),
+        characters: text.chars().count(),
+        sentences: sentences.len(),
+        read,
+        extract,
+        split,
+        parse,
+        peak_rss: peak_rss(),
+    })
+}
+
+// Linux reports the high-water mark in /proc; other systems are not asked
+fn peak_rss() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+fn milliseconds<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_document() -> Result<()> {
+        let path = "test_bench.txt";
+        fs::write(
+            path,
+            "One sentence here. And another one.\n\nA second paragraph.",
+        )?;
+        let timings = time_document(path, Splitter::regex()?, ParseOptions::default());
+        fs::remove_file(path).ok();
+
+        let timings = timings?;
+        assert_eq!((timings.bytes, timings.sentences), (56, 3));
+        assert!(timings.parse > Duration::ZERO);
+        let json = serde_json::to_value(&timings)?;
+        assert!(json["parse"].as_f64().is_some_and(|ms| ms > 0.0));
+        if cfg!(target_os = "linux") {
+            assert!(timings.peak_rss.is_some_and(|bytes| bytes > 0));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use crate::error::{Result, SentencerError};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Settings read at startup from ~/.config/sentencer/config.toml, or from the
+// file named by $SENTENCER_CONFIG. Every setting is optional, and
+// command-line flags take precedence over them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub splitting: SplittingConfig,
+    pub ui: UiConfig,
+    pub keys: KeysConfig,
+    pub check: CheckConfig,
+    pub spelling: SpellingConfig,
+    pub rewrite: RewriteConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SplittingConfig {
+    pub splitter: Option<String>,
+    // Replaces the pattern the regex and rules splitters end sentences at
+    pub boundary_regex: Option<String>,
+    // Sentences with fewer characters are joined with the next one
+    pub min_length: usize,
+    pub keep_punctuation: bool,
+    // Added to the English abbreviations of the rules splitter
+    pub abbreviations: Vec<String>,
+    pub abbreviation_files: Vec<String>,
+    pub language: Option<String>,
+}
+
+impl Default for SplittingConfig {
+    fn default() -> Self {
+        SplittingConfig {
+            splitter: None,
+            boundary_regex: None,
+            min_length: 0,
+            keep_punctuation: true,
+            abbreviations: Vec::new(),
+            abbreviation_files: Vec::new(),
+            language: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    // The same as passing --editor, --resume and --watch
+    pub editor: bool,
+    pub resume: bool,
+    pub watch: bool,
+    // Widest the sentence box grows, in columns
+    pub max_width: Option<u16>,
+    // Show the previous and next sentence dimmed around the current one
+    pub context: bool,
+    // Text-to-speech command reading sentences aloud, given the text on
+    // standard input [default: "espeak-ng", or "say" on macOS]
+    pub speech_command: Option<String>,
+}
+
+// The LanguageTool server sentences are sent to by `check` and the reader's
+// grammar check [default: the public server at api.languagetool.org]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckConfig {
+    pub url: Option<String>,
+}
+
+// The Hunspell dictionary misspelled words are looked up in, by name such as
+// "en_GB" or by the path of its .dic file [default: the one for the
+// document's language]. Only used by builds with the spellcheck feature.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpellingConfig {
+    pub dictionary: Option<String>,
+}
+
+// The OpenAI-compatible API asked for rewrites while editing a sentence. The
+// key is read from the environment variable named by api_key_env rather than
+// kept in this file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RewriteConfig {
+    // [default: https://api.openai.com/v1]
+    pub url: Option<String>,
+    pub model: Option<String>,
+    // [default: OPENAI_API_KEY]
+    pub api_key_env: Option<String>,
+}
+
+// Key bindings of the interactive reader: a preset ("default", "vim" or
+// "emacs") and the keys of individual actions on top of it, such as
+// `quit = ["q", "C-x C-c"]` under [keys.bindings]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    pub preset: Option<String>,
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        match env::var_os("SENTENCER_CONFIG") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => dirs::config_dir().map(|dir| dir.join("sentencer").join("config.toml")),
+        }
+    }
+
+    pub fn load_default() -> Result<Self> {
+        match Config::default_path() {
+            Some(path) => Config::load(path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    // A missing file leaves every setting at its default
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        toml::from_str(&content)
+            .map_err(|e| SentencerError::Config(format!("{}: {}", path.display(), e.message())))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| SentencerError::Config(e.message().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() -> Result<()> {
+        let config = Config::parse(
+            r#"
+[splitting]
+splitter = "regex"
+boundary_regex = '[.!?;]+\s+'
+min_length = 12
+keep_punctuation = false
+abbreviations = ["approx", "ca"]
+
+[ui]
+editor = true
+max_width = 80
+
+[keys]
+preset = "vim"
+
+[check]
+url = "http://localhost:8081"
+
+[keys.bindings]
+quit = ["Q", "C-c"]
+"#,
+        )?;
+
+        assert_eq!(config.splitting.splitter.as_deref(), Some("regex"));
+        assert_eq!(
+            config.splitting.boundary_regex.as_deref(),
+            Some(r"[.!?;]+\s+")
+        );
+        assert_eq!(config.splitting.min_length, 12);
+        assert!(!config.splitting.keep_punctuation);
+        assert_eq!(config.splitting.abbreviations, vec!["approx", "ca"]);
+        assert!(config.ui.editor && !config.ui.resume);
+        assert_eq!(config.ui.max_width, Some(80));
+        assert_eq!(config.keys.preset.as_deref(), Some("vim"));
+        assert_eq!(config.keys.bindings["quit"], vec!["Q", "C-c"]);
+        assert_eq!(config.check.url.as_deref(), Some("http://localhost:8081"));
+
+        assert_eq!(Config::parse("")?, Config::default());
+        assert!(Config::default().splitting.keep_punctuation);
+        assert!(matches!(
+            Config::parse("[splitting]\nsplitter_name = \"regex\""),
+            Err(SentencerError::Config(_))
+        ));
+        assert_eq!(Config::load("no_such_config.toml")?, Config::default());
+        Ok(())
+    }
+}
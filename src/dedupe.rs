@@ -0,0 +1,170 @@
+use crate::sentence::Sentence;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+// Word pairs shared by this many sentences say nothing about repetition
+// ("of the", "it was") and would compare every sentence with every other
+const MAX_SHINGLE_SENTENCES: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DedupeOptions {
+    // Share of word pairs two sentences must have in common to be reported
+    pub threshold: f64,
+    // Shorter sentences, such as "Yes." or "Chapter 2", are not compared
+    pub min_words: usize,
+}
+
+impl Default for DedupeOptions {
+    fn default() -> Self {
+        DedupeOptions {
+            threshold: 0.7,
+            min_words: 4,
+        }
+    }
+}
+
+// Indices count sentences from 1, as in exports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateReport {
+    // Sentences that are the same apart from case and punctuation
+    pub exact: Vec<Vec<usize>>,
+    pub near: Vec<NearDuplicate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NearDuplicate {
+    pub first: usize,
+    pub second: usize,
+    pub similarity: f64,
+}
+
+impl DuplicateReport {
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty() && self.near.is_empty()
+    }
+}
+
+// Near-duplicates are found by the Jaccard similarity of the word pairs
+// (shingles) of two sentences. Only sentences sharing a pair are compared,
+// which keeps long manuscripts fast.
+pub fn find_duplicates(sentences: &[Sentence], options: DedupeOptions) -> DuplicateReport {
+    let words: Vec<Vec<String>> = sentences
+        .iter()
+        .map(|sentence| normalized_words(&sentence.text))
+        .collect();
+
+    let mut groups: HashMap<&[String], Vec<usize>> = HashMap::new();
+    for (i, words) in words.iter().enumerate() {
+        if words.len() >= options.min_words {
+            groups.entry(words.as_slice()).or_default().push(i);
+        }
+    }
+    let mut exact: Vec<Vec<usize>> = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .collect();
+    exact.sort();
+
+    // Each group is compared once, through its first sentence
+    let repeated: HashSet<usize> = exact
+        .iter()
+        .flat_map(|group| group[1..].iter().copied())
+        .collect();
+    let mut shingles: Vec<HashSet<(&str, &str)>> = Vec::new();
+    let mut candidates = Vec::new();
+    for (i, words) in words.iter().enumerate() {
+        if words.len() >= options.min_words && !repeated.contains(&i) {
+            candidates.push(i);
+            shingles.push(
+                words
+                    .windows(2)
+                    .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+                    .collect(),
+            );
+        }
+    }
+
+    let mut postings: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+    for (candidate, pairs) in shingles.iter().enumerate() {
+        for &pair in pairs {
+            postings.entry(pair).or_default().push(candidate);
+        }
+    }
+
+    let mut near = Vec::new();
+    for (candidate, pairs) in shingles.iter().enumerate() {
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for pair in pairs {
+            let others = &postings[pair];
+            if others.len() > MAX_SHINGLE_SENTENCES {
+                continue;
+            }
+            for &other in others.iter().filter(|&&other| other < candidate) {
+                *shared.entry(other).or_default() += 1;
+            }
+        }
+
+        for (other, count) in shared {
+            let union = pairs.len() + shingles[other].len() - count;
+            let similarity = count as f64 / union as f64;
+            if similarity >= options.threshold {
+                near.push(NearDuplicate {
+                    first: candidates[other] + 1,
+                    second: candidates[candidate] + 1,
+                    similarity,
+                });
+            }
+        }
+    }
+    near.sort_by_key(|pair| (pair.first, pair.second));
+
+    let exact = exact
+        .into_iter()
+        .map(|group| group.into_iter().map(|i| i + 1).collect())
+        .collect();
+    DuplicateReport { exact, near }
+}
+
+// Lowercase words without punctuation, so "However, it rained." and
+// "however it rained" count as the same sentence
+pub(crate) fn normalized_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_duplicates() {
+        let sentences: Vec<Sentence> = [
+            "The rain fell on the quiet town all night.",
+            "Yes.",
+            "She opened the door and looked outside.",
+            "The rain fell on the quiet town all night long.",
+            "Yes.",
+            "she opened the door, and looked outside!",
+            "Something else entirely happened next morning.",
+            "She opened the door and looked outside.",
+        ]
+        .into_iter()
+        .map(Sentence::new)
+        .collect();
+
+        let report = find_duplicates(&sentences, DedupeOptions::default());
+
+        assert_eq!(report.exact, vec![vec![3, 6, 8]]);
+        assert_eq!(report.near.len(), 1);
+        assert_eq!((report.near[0].first, report.near[0].second), (1, 4));
+        assert!((report.near[0].similarity - 8.0 / 9.0).abs() < 1e-9);
+        assert!(find_duplicates(&sentences[..3], DedupeOptions::default()).is_empty());
+    }
+}
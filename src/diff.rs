@@ -0,0 +1,193 @@
+use crate::dedupe::normalized_words;
+use serde::Serialize;
+use similar::{Algorithm, DiffOp, TextDiff};
+
+const CONTEXT_LINES: usize = 3;
+
+// One sentence per line, so each hunk shows the sentences that were edited
+pub fn unified_diff(
+    original: &[String],
+    edited: &[String],
+    original_name: &str,
+    edited_name: &str,
+) -> String {
+    let original = lines(original);
+    let edited = lines(edited);
+
+    TextDiff::from_lines(&original, &edited)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(original_name, edited_name)
+        .to_string()
+}
+
+// A difference between two versions of a document. Indices count sentences
+// from 1, as in exports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub enum SentenceChange {
+    Added {
+        new: usize,
+        text: String,
+    },
+    Removed {
+        old: usize,
+        text: String,
+    },
+    Changed {
+        old: usize,
+        new: usize,
+        old_text: String,
+        new_text: String,
+    },
+}
+
+// Sentences are aligned by the longest common subsequence of their words,
+// ignoring case and punctuation, so a sentence that only had its punctuation
+// edited is reported as changed rather than removed and added again. Where
+// several sentences were replaced, they are paired up in order.
+pub fn compare_sentences<S: AsRef<str>>(old: &[S], new: &[S]) -> Vec<SentenceChange> {
+    let normalize = |sentences: &[S]| -> Vec<String> {
+        sentences
+            .iter()
+            .map(|sentence| normalized_words(sentence.as_ref()).join(" "))
+            .collect()
+    };
+    let old_keys = normalize(old);
+    let new_keys = normalize(new);
+
+    let text = |sentences: &[S], i: usize| sentences[i].as_ref().to_string();
+    let changed = |o: usize, n: usize| SentenceChange::Changed {
+        old: o + 1,
+        new: n + 1,
+        old_text: text(old, o),
+        new_text: text(new, n),
+    };
+    let mut changes = Vec::new();
+    for op in similar::capture_diff_slices(Algorithm::Lcs, &old_keys, &new_keys) {
+        match op {
+            DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            } => {
+                for i in 0..len {
+                    if old[old_index + i].as_ref() != new[new_index + i].as_ref() {
+                        changes.push(changed(old_index + i, new_index + i));
+                    }
+                }
+            }
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => {
+                changes.extend(
+                    (old_index..old_index + old_len).map(|o| SentenceChange::Removed {
+                        old: o + 1,
+                        text: text(old, o),
+                    }),
+                )
+            }
+            DiffOp::Insert {
+                new_index, new_len, ..
+            } => changes.extend(
+                (new_index..new_index + new_len).map(|n| SentenceChange::Added {
+                    new: n + 1,
+                    text: text(new, n),
+                }),
+            ),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => {
+                let paired = old_len.min(new_len);
+                for i in 0..paired {
+                    changes.push(changed(old_index + i, new_index + i));
+                }
+                for o in old_index + paired..old_index + old_len {
+                    changes.push(SentenceChange::Removed {
+                        old: o + 1,
+                        text: text(old, o),
+                    });
+                }
+                for n in new_index + paired..new_index + new_len {
+                    changes.push(SentenceChange::Added {
+                        new: n + 1,
+                        text: text(new, n),
+                    });
+                }
+            }
+        }
+    }
+
+    changes
+}
+
+fn lines(sentences: &[String]) -> String {
+    sentences
+        .iter()
+        .map(|sentence| format!("{}\n", sentence))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let original: Vec<String> = ["One.", "Two.", "Three."].map(String::from).to_vec();
+        let mut edited = original.clone();
+        edited[1] = "Deux.".to_string();
+
+        let diff = unified_diff(&original, &edited, "a/doc.odt", "b/doc.odt");
+
+        assert_eq!(
+            diff,
+            "--- a/doc.odt\n+++ b/doc.odt\n@@ -1,3 +1,3 @@\n One.\n-Two.\n+Deux.\n Three.\n"
+        );
+        assert_eq!(unified_diff(&original, &original, "a", "b"), "");
+    }
+
+    #[test]
+    fn test_compare_sentences() {
+        let old = ["Intro.", "It rained all day.", "Gone soon.", "The end."];
+        let new = [
+            "Intro.",
+            "It rained, all day!",
+            "A new sentence.",
+            "The end.",
+            "Epilogue.",
+        ];
+
+        let changes = compare_sentences(&old, &new);
+
+        assert_eq!(
+            changes,
+            vec![
+                SentenceChange::Changed {
+                    old: 2,
+                    new: 2,
+                    old_text: "It rained all day.".to_string(),
+                    new_text: "It rained, all day!".to_string(),
+                },
+                SentenceChange::Changed {
+                    old: 3,
+                    new: 3,
+                    old_text: "Gone soon.".to_string(),
+                    new_text: "A new sentence.".to_string(),
+                },
+                SentenceChange::Added {
+                    new: 5,
+                    text: "Epilogue.".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&changes[2]).ok(),
+            Some(serde_json::json!({"change": "added", "new": 5, "text": "Epilogue."}))
+        );
+        assert!(compare_sentences(&old, &old).is_empty());
+    }
+}
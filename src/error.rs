@@ -0,0 +1,86 @@
+use std::result;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SentencerError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error("Error parsing XML: {0}")]
+    Xml(#[from] quick_xml::Error),
+
+    #[error("Invalid text encoding: {0}")]
+    Encoding(#[from] std::str::Utf8Error),
+
+    #[error("Invalid regular expression: {0}")]
+    Regex(#[from] regex::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("Unsupported document format: {0}")]
+    UnsupportedFormat(String),
+
+    // The archive and XML are readable but the document structure is not
+    #[error("Invalid document: {0}")]
+    InvalidDocument(String),
+
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+
+    #[error("Could not save document: {0}")]
+    Save(String),
+
+    // Encrypted documents can only be read with their password
+    #[error("Password required to read encrypted document: {0}")]
+    PasswordRequired(String),
+
+    #[error("Could not decrypt document: {0}")]
+    Decrypt(String),
+
+    #[error("Could not download document: {0}")]
+    Download(String),
+
+    #[error("Invalid spelling dictionary: {0}")]
+    Dictionary(String),
+
+    #[error("Grammar check failed: {0}")]
+    Grammar(String),
+
+    #[error("Rewrite failed: {0}")]
+    Rewrite(String),
+
+    #[error("Search index error: {0}")]
+    Index(String),
+
+    #[error("Pandoc conversion failed: {0}")]
+    Pandoc(String),
+
+    #[cfg(feature = "cli")]
+    #[error("Could not watch document: {0}")]
+    Watch(#[from] notify::Error),
+}
+
+impl From<quick_xml::events::attributes::AttrError> for SentencerError {
+    fn from(error: quick_xml::events::attributes::AttrError) -> Self {
+        SentencerError::Xml(error.into())
+    }
+}
+
+impl From<std::string::FromUtf8Error> for SentencerError {
+    fn from(error: std::string::FromUtf8Error) -> Self {
+        SentencerError::Encoding(error.utf8_error())
+    }
+}
+
+pub type Error = SentencerError;
+pub type Result<T> = result::Result<T, Error>;
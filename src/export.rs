@@ -0,0 +1,500 @@
+use crate::anki;
+use crate::error::{Result, SentencerError};
+use crate::parser::DocumentMetadata;
+use crate::sentence::{CellPosition, Link, Sentence, SentenceKind};
+use crate::subtitles;
+use crate::writer;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    // One JSON object per line
+    Jsonl,
+    Csv,
+    Tsv,
+    // A new document with the sentences regrouped into their paragraphs
+    Odt,
+    // One CSV row per hyperlink, with the sentence it appears in
+    Links,
+    // Flashcards for Anki's text import
+    Anki,
+    // Subtitles with one cue per sentence, timed by its length
+    Srt,
+    Vtt,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "json" => Ok(ExportFormat::Json),
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            "csv" => Ok(ExportFormat::Csv),
+            "tsv" => Ok(ExportFormat::Tsv),
+            "odt" => Ok(ExportFormat::Odt),
+            "links" => Ok(ExportFormat::Links),
+            "anki" => Ok(ExportFormat::Anki),
+            "srt" => Ok(ExportFormat::Srt),
+            "vtt" | "webvtt" => Ok(ExportFormat::Vtt),
+            _ => Err(SentencerError::InvalidArgument(format!(
+                "Unknown export format '{}' (expected json, jsonl, csv, tsv, odt, links, anki, srt or vtt)",
+                name
+            ))),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+            ExportFormat::Odt => "odt",
+            ExportFormat::Links => "links.csv",
+            ExportFormat::Anki => "anki.txt",
+            ExportFormat::Srt => "srt",
+            ExportFormat::Vtt => "vtt",
+        }
+    }
+}
+
+// Offsets are character (not byte) positions in the whitespace-normalized
+// document text the parser produced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportedSentence<'a> {
+    // Only set when several documents are exported together
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<&'a str>,
+    pub index: usize,
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+    pub paragraph: usize,
+    pub kind: SentenceKind,
+    pub heading_path: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list_level: Option<usize>,
+    #[serde(skip_serializing_if = "<[Link]>::is_empty")]
+    pub links: &'a [Link],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<&'a CellPosition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slide: Option<usize>,
+}
+
+impl<'a> ExportedSentence<'a> {
+    fn new(index: usize, sentence: &'a Sentence) -> Self {
+        ExportedSentence {
+            file: None,
+            index,
+            text: &sentence.text,
+            start: sentence.char_range.start,
+            end: sentence.char_range.end,
+            paragraph: sentence.paragraph_index,
+            kind: sentence.kind,
+            heading_path: &sentence.heading_path,
+            list_level: sentence.list_level,
+            links: &sentence.links,
+            cell: sentence.cell.as_ref(),
+            slide: sentence.slide,
+        }
+    }
+}
+
+pub fn exported_sentences(sentences: &[Sentence]) -> Vec<ExportedSentence<'_>> {
+    sentences
+        .iter()
+        .enumerate()
+        .map(|(i, sentence)| ExportedSentence::new(i + 1, sentence))
+        .collect()
+}
+
+pub fn export<W: Write>(
+    writer: &mut W,
+    sentences: &[Sentence],
+    format: ExportFormat,
+) -> Result<()> {
+    if format == ExportFormat::Odt {
+        writer.write_all(&writer::new_odt(&[sentences])?)?;
+        return Ok(());
+    }
+    if format == ExportFormat::Anki {
+        return anki::write_cards(writer, &anki::cards(sentences, &BTreeMap::new(), &[])?);
+    }
+    if matches!(format, ExportFormat::Srt | ExportFormat::Vtt) {
+        let cues = subtitles::cues(sentences, subtitles::DEFAULT_WPM);
+        return subtitles::write_subtitles(writer, &cues, format);
+    }
+
+    write_rows(writer, &exported_sentences(sentences), format)
+}
+
+// Exports several documents as one stream, tagging every sentence with the
+// file it came from
+pub fn export_batch<W: Write>(
+    writer: &mut W,
+    documents: &[(String, Vec<Sentence>)],
+    format: ExportFormat,
+) -> Result<()> {
+    if format == ExportFormat::Odt {
+        let documents: Vec<&[Sentence]> = documents.iter().map(|(_, s)| s.as_slice()).collect();
+        writer.write_all(&writer::new_odt(&documents)?)?;
+        return Ok(());
+    }
+    // Cards go into one deck and cues follow on from the previous document
+    if matches!(
+        format,
+        ExportFormat::Anki | ExportFormat::Srt | ExportFormat::Vtt
+    ) {
+        let sentences: Vec<Sentence> = documents
+            .iter()
+            .flat_map(|(_, sentences)| sentences.iter().cloned())
+            .collect();
+        return export(writer, &sentences, format);
+    }
+
+    write_rows(writer, &file_rows(documents), format)
+}
+
+// JSON exports of a document that records metadata are an object holding the
+// metadata and the sentences; other exports are the same as `export`
+pub fn export_with_metadata<W: Write>(
+    writer: &mut W,
+    sentences: &[Sentence],
+    metadata: &DocumentMetadata,
+    format: ExportFormat,
+) -> Result<()> {
+    if format != ExportFormat::Json || metadata.is_empty() {
+        return export(writer, sentences, format);
+    }
+
+    write_json_document(writer, metadata, &exported_sentences(sentences))
+}
+
+// Here the metadata is an object keyed by the file of each document
+pub fn export_batch_with_metadata<W: Write>(
+    writer: &mut W,
+    documents: &[(String, Vec<Sentence>)],
+    metadata: &[DocumentMetadata],
+    format: ExportFormat,
+) -> Result<()> {
+    let recorded: BTreeMap<&str, &DocumentMetadata> = documents
+        .iter()
+        .zip(metadata)
+        .filter(|(_, metadata)| !metadata.is_empty())
+        .map(|((file, _), metadata)| (file.as_str(), metadata))
+        .collect();
+    if format != ExportFormat::Json || recorded.is_empty() {
+        return export_batch(writer, documents, format);
+    }
+
+    write_json_document(writer, &recorded, &file_rows(documents))
+}
+
+// The sentences of every document, each tagged with its file
+fn file_rows(documents: &[(String, Vec<Sentence>)]) -> Vec<ExportedSentence<'_>> {
+    documents
+        .iter()
+        .flat_map(|(file, sentences)| {
+            exported_sentences(sentences)
+                .into_iter()
+                .map(move |row| ExportedSentence {
+                    file: Some(file),
+                    ..row
+                })
+        })
+        .collect()
+}
+
+fn write_json_document<W: Write, M: Serialize>(
+    writer: &mut W,
+    metadata: &M,
+    rows: &[ExportedSentence],
+) -> Result<()> {
+    let document = serde_json::json!({ "metadata": metadata, "sentences": rows });
+    serde_json::to_writer_pretty(&mut *writer, &document)?;
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+// Writes each sentence as JSONL as soon as it arrives, so a large document is
+// never held in memory as a whole. Returns the number of sentences written.
+pub fn stream_jsonl<W, I>(writer: &mut W, sentences: I, file: Option<&str>) -> Result<usize>
+where
+    W: Write,
+    I: IntoIterator<Item = Result<Sentence>>,
+{
+    let mut count = 0;
+    for sentence in sentences {
+        let sentence = sentence?;
+        count += 1;
+        let row = ExportedSentence {
+            file,
+            ..ExportedSentence::new(count, &sentence)
+        };
+        serde_json::to_writer(&mut *writer, &row)?;
+        writeln!(writer)?;
+    }
+
+    Ok(count)
+}
+
+fn write_rows<W: Write>(
+    writer: &mut W,
+    rows: &[ExportedSentence],
+    format: ExportFormat,
+) -> Result<()> {
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *writer, rows)?;
+            writeln!(writer)?;
+        }
+        ExportFormat::Jsonl => {
+            for row in rows {
+                serde_json::to_writer(&mut *writer, row)?;
+                writeln!(writer)?;
+            }
+        }
+        ExportFormat::Csv => write_table(writer, rows, b',')?,
+        ExportFormat::Tsv => write_table(writer, rows, b'\t')?,
+        ExportFormat::Links => write_links(writer, rows)?,
+        ExportFormat::Odt | ExportFormat::Anki | ExportFormat::Srt | ExportFormat::Vtt => {
+            unreachable!("only tables and JSON are written row by row")
+        }
+    }
+
+    Ok(())
+}
+
+// One row per sentence with simple statistics, for spreadsheets
+fn write_table<W: Write>(writer: &mut W, rows: &[ExportedSentence], delimiter: u8) -> Result<()> {
+    let mut table = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(writer);
+
+    let with_file = rows.iter().any(|row| row.file.is_some());
+    let header = [
+        "file",
+        "index",
+        "text",
+        "words",
+        "characters",
+        "paragraph",
+        "kind",
+    ];
+    table.write_record(if with_file { &header[..] } else { &header[1..] })?;
+
+    for row in rows {
+        let mut record = vec![
+            row.index.to_string(),
+            row.text.to_string(),
+            row.text.split_whitespace().count().to_string(),
+            row.text.chars().count().to_string(),
+            row.paragraph.to_string(),
+            row.kind.name().to_string(),
+        ];
+        if with_file {
+            record.insert(0, row.file.unwrap_or_default().to_string());
+        }
+        table.write_record(&record)?;
+    }
+    table.flush()?;
+
+    Ok(())
+}
+
+fn write_links<W: Write>(writer: &mut W, rows: &[ExportedSentence]) -> Result<()> {
+    let mut table = csv::Writer::from_writer(writer);
+
+    let with_file = rows.iter().any(|row| row.file.is_some());
+    let header = ["file", "index", "href", "link", "sentence"];
+    table.write_record(if with_file { &header[..] } else { &header[1..] })?;
+
+    for row in rows {
+        for link in row.links {
+            let mut record = vec![
+                row.index.to_string(),
+                link.href.clone(),
+                link.text.clone(),
+                row.text.to_string(),
+            ];
+            if with_file {
+                record.insert(0, row.file.unwrap_or_default().to_string());
+            }
+            table.write_record(&record)?;
+        }
+    }
+    table.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_export() -> Result<()> {
+        let sentences = vec![
+            Sentence {
+                heading_path: vec!["Intro".to_string()],
+                ..Sentence::new("Héllo there.")
+            },
+            Sentence {
+                paragraph_index: 1,
+                char_range: 13..17,
+                kind: SentenceKind::Footnote,
+                ..Sentence::new("Bye!")
+            },
+        ];
+
+        let mut output = Vec::new();
+        export(&mut output, &sentences, ExportFormat::Json)?;
+
+        let value: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(
+            value,
+            serde_json::json!([
+                {
+                    "index": 1, "text": "Héllo there.", "start": 0, "end": 12,
+                    "paragraph": 0, "kind": "body", "heading_path": ["Intro"]
+                },
+                {
+                    "index": 2, "text": "Bye!", "start": 13, "end": 17,
+                    "paragraph": 1, "kind": "footnote", "heading_path": []
+                }
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_export_with_metadata() -> Result<()> {
+        let sentences = vec![Sentence::new("Call me Ishmael.")];
+        let metadata = DocumentMetadata {
+            title: Some("Moby-Dick".to_string()),
+            word_count: Some(3),
+            ..DocumentMetadata::default()
+        };
+
+        let mut output = Vec::new();
+        export_with_metadata(&mut output, &sentences, &metadata, ExportFormat::Json)?;
+        let value: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(
+            value["metadata"],
+            serde_json::json!({ "title": "Moby-Dick", "word_count": 3 })
+        );
+        assert_eq!(value["sentences"][0]["text"], "Call me Ishmael.");
+
+        // Without metadata the export stays a list of sentences
+        let mut plain = Vec::new();
+        let documents = vec![("a.md".to_string(), sentences)];
+        let none = [DocumentMetadata::default()];
+        export_batch_with_metadata(&mut plain, &documents, &none, ExportFormat::Json)?;
+        let value: serde_json::Value = serde_json::from_slice(&plain)?;
+        assert_eq!(value[0]["file"], "a.md");
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_and_tsv_export() -> Result<()> {
+        let sentences = vec![
+            Sentence::new("Héllo, \"world\"."),
+            Sentence {
+                paragraph_index: 2,
+                kind: SentenceKind::ListItem,
+                ..Sentence::new("Bye now!")
+            },
+        ];
+
+        let mut csv = Vec::new();
+        export(&mut csv, &sentences, ExportFormat::Csv)?;
+        let mut tsv = Vec::new();
+        export(&mut tsv, &sentences, ExportFormat::Tsv)?;
+
+        assert_eq!(
+            String::from_utf8(csv)?,
+            "index,text,words,characters,paragraph,kind\n\
+             1,\"Héllo, \"\"world\"\".\",2,15,0,body\n\
+             2,Bye now!,2,8,2,list_item\n"
+        );
+        assert_eq!(
+            String::from_utf8(tsv)?,
+            "index\ttext\twords\tcharacters\tparagraph\tkind\n\
+             1\t\"Héllo, \"\"world\"\".\"\t2\t15\t0\tbody\n\
+             2\tBye now!\t2\t8\t2\tlist_item\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_links_export() -> Result<()> {
+        let sentences = vec![
+            Sentence::new("No links here."),
+            Sentence {
+                links: vec![Link {
+                    text: "the site".to_string(),
+                    href: "https://example.com/".to_string(),
+                }],
+                ..Sentence::new("Visit the site.")
+            },
+        ];
+
+        let mut output = Vec::new();
+        export(&mut output, &sentences, ExportFormat::Links)?;
+
+        assert_eq!(
+            String::from_utf8(output)?,
+            "index,href,link,sentence\n\
+             2,https://example.com/,the site,Visit the site.\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_jsonl_export() -> Result<()> {
+        let documents = vec![
+            ("a.odt".to_string(), vec![Sentence::new("First.")]),
+            ("b.md".to_string(), vec![Sentence::new("Second.")]),
+        ];
+
+        let mut output = Vec::new();
+        export_batch(&mut output, &documents, ExportFormat::Jsonl)?;
+
+        let lines: Vec<serde_json::Value> = output
+            .split(|&byte| byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(serde_json::from_slice)
+            .collect::<std::result::Result<_, _>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["file"], "a.odt");
+        assert_eq!(lines[1]["file"], "b.md");
+        assert_eq!(lines[1]["text"], "Second.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_jsonl_matches_export() -> Result<()> {
+        let sentences = vec![Sentence::new("One."), Sentence::new("Two.")];
+
+        let mut exported = Vec::new();
+        export(&mut exported, &sentences, ExportFormat::Jsonl)?;
+        let mut streamed = Vec::new();
+        let count = stream_jsonl(&mut streamed, sentences.into_iter().map(Ok), None)?;
+
+        assert_eq!(count, 2);
+        assert_eq!(streamed, exported);
+
+        let failing = vec![
+            Ok(Sentence::new("One.")),
+            Err(SentencerError::InvalidDocument("broken".to_string())),
+        ];
+        let mut partial = Vec::new();
+        assert!(stream_jsonl(&mut partial, failing, Some("a.odt")).is_err());
+        assert_eq!(partial.iter().filter(|&&byte| byte == b'\n').count(), 1);
+        Ok(())
+    }
+}
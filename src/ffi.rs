@@ -0,0 +1,236 @@
+// C interface for editors written in C or C++. Documents are parsed with the
+// rules splitter and default options, as `sentencer export` does. Returned
+// sentence arrays belong to the caller until given back to
+// sentencer_free_sentences; on failure NULL is returned and
+// sentencer_last_error tells why. include/sentencer.h is generated from this
+// file with `cbindgen --config cbindgen.toml --output include/sentencer.h src/ffi.rs`.
+
+use crate::abbreviations::Abbreviations;
+use crate::error::{Result, SentencerError};
+use crate::parser::{self, ParseOptions};
+use crate::sentence::{Sentence, SentenceKind};
+use crate::splitter::Splitter;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SentencerKind {
+    Body,
+    Heading,
+    Footnote,
+    Endnote,
+    TableCell,
+    Comment,
+    SpeakerNote,
+    Header,
+    Footer,
+    Caption,
+    AltText,
+    ListItem,
+    Quote,
+}
+
+impl From<SentenceKind> for SentencerKind {
+    fn from(kind: SentenceKind) -> Self {
+        match kind {
+            SentenceKind::Body => SentencerKind::Body,
+            SentenceKind::Heading => SentencerKind::Heading,
+            SentenceKind::Footnote => SentencerKind::Footnote,
+            SentenceKind::Endnote => SentencerKind::Endnote,
+            SentenceKind::TableCell => SentencerKind::TableCell,
+            SentenceKind::Comment => SentencerKind::Comment,
+            SentenceKind::SpeakerNote => SentencerKind::SpeakerNote,
+            SentenceKind::Header => SentencerKind::Header,
+            SentenceKind::Footer => SentencerKind::Footer,
+            SentenceKind::Caption => SentencerKind::Caption,
+            SentenceKind::AltText => SentencerKind::AltText,
+            SentenceKind::ListItem => SentencerKind::ListItem,
+            SentenceKind::Quote => SentencerKind::Quote,
+        }
+    }
+}
+
+// Offsets count characters of the whitespace-normalized document text, as in
+// the JSON export
+#[repr(C)]
+#[derive(Debug)]
+pub struct SentencerSentence {
+    // NUL-terminated UTF-8
+    pub text: *mut c_char,
+    pub paragraph: usize,
+    pub start: usize,
+    pub end: usize,
+    pub kind: SentencerKind,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct SentencerSentences {
+    pub sentences: *mut SentencerSentence,
+    pub len: usize,
+}
+
+/// Parses the document at `path`, a NUL-terminated UTF-8 path.
+///
+/// # Safety
+///
+/// `path` must be NULL or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sentencer_parse_file(path: *const c_char) -> *mut SentencerSentences {
+    // SAFETY: the caller passes NULL or a NUL-terminated string
+    let path = unsafe { string_argument(path, "path") };
+    answer(path.and_then(|path| {
+        parser::parser_for(&path, splitter()?, ParseOptions::default())?.parse_file(&path)
+    }))
+}
+
+/// Parses a document held in memory. `file_name` may be NULL; when given, its
+/// extension tells the format, which is otherwise detected from the data.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, and `file_name` must be NULL or
+/// point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sentencer_parse_bytes(
+    data: *const u8,
+    len: usize,
+    file_name: *const c_char,
+) -> *mut SentencerSentences {
+    let file_name = match file_name.is_null() {
+        true => Ok(String::new()),
+        // SAFETY: the caller passes a NUL-terminated string
+        false => unsafe { string_argument(file_name, "file_name") },
+    };
+    let data = match data.is_null() {
+        true if len > 0 => Err(null_argument("data")),
+        true => Ok(&[][..]),
+        // SAFETY: the caller passes `len` readable bytes
+        false => Ok(unsafe { std::slice::from_raw_parts(data, len) }),
+    };
+    answer(file_name.and_then(|file_name| {
+        let data = data?;
+        parser::parser_for_bytes(&file_name, data, splitter()?, ParseOptions::default())?
+            .parse_bytes(data)
+    }))
+}
+
+/// Frees what sentencer_parse_file or sentencer_parse_bytes returned. NULL is
+/// ignored.
+///
+/// # Safety
+///
+/// `sentences` must be NULL or returned by this library, and not freed before.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sentencer_free_sentences(sentences: *mut SentencerSentences) {
+    if sentences.is_null() {
+        return;
+    }
+    // SAFETY: the array and its strings were leaked by `answer`, and are
+    // taken back exactly once
+    unsafe {
+        let sentences = Box::from_raw(sentences);
+        let array = Box::from_raw(ptr::slice_from_raw_parts_mut(
+            sentences.sentences,
+            sentences.len,
+        ));
+        for sentence in array.iter() {
+            drop(CString::from_raw(sentence.text));
+        }
+    }
+}
+
+/// Why the last call on this thread returned NULL, or NULL when it did not
+/// fail. The message is valid until the next call on this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn sentencer_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|error| error.as_ref().map_or(ptr::null(), |error| error.as_ptr()))
+}
+
+fn splitter() -> Result<Splitter> {
+    Splitter::rules(Abbreviations::english())
+}
+
+// SAFETY: `argument` must be NULL or point to a NUL-terminated string
+unsafe fn string_argument(argument: *const c_char, name: &str) -> Result<String> {
+    if argument.is_null() {
+        return Err(null_argument(name));
+    }
+    // SAFETY: checked for NULL above; the caller vouches for the rest
+    let argument = unsafe { CStr::from_ptr(argument) };
+    Ok(argument.to_str()?.to_string())
+}
+
+fn null_argument(name: &str) -> SentencerError {
+    SentencerError::InvalidArgument(format!("{} is NULL", name))
+}
+
+fn answer(result: Result<Vec<Sentence>>) -> *mut SentencerSentences {
+    let sentences = match result {
+        Ok(sentences) => sentences,
+        Err(e) => {
+            // Messages never hold NUL, which only a path or text could bring in
+            let message = CString::new(e.to_string().replace('\0', "")).unwrap_or_default();
+            LAST_ERROR.set(Some(message));
+            return ptr::null_mut();
+        }
+    };
+    LAST_ERROR.set(None);
+
+    let array: Box<[SentencerSentence]> = sentences
+        .into_iter()
+        .map(|sentence| SentencerSentence {
+            // Plain text documents are the only ones that can hold NUL
+            text: CString::new(sentence.text.replace('\0', ""))
+                .unwrap_or_default()
+                .into_raw(),
+            paragraph: sentence.paragraph_index,
+            start: sentence.char_range.start,
+            end: sentence.char_range.end,
+            kind: sentence.kind.into(),
+        })
+        .collect();
+    let len = array.len();
+    Box::into_raw(Box::new(SentencerSentences {
+        sentences: Box::into_raw(array).cast(),
+        len,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bytes_and_free() {
+        let text = "# Title\n\nOne sentence. Another one.";
+        let name = c"notes.md";
+        // SAFETY: the data and name outlive the calls, and the result is freed once
+        unsafe {
+            let sentences = sentencer_parse_bytes(text.as_ptr(), text.len(), name.as_ptr());
+            assert!(!sentences.is_null());
+            assert!(sentencer_last_error().is_null());
+            let array = std::slice::from_raw_parts((*sentences).sentences, (*sentences).len);
+            let texts: Vec<_> = array
+                .iter()
+                .map(|sentence| CStr::from_ptr(sentence.text).to_str().unwrap_or_default())
+                .collect();
+            assert_eq!(texts, vec!["Title", "One sentence.", "Another one."]);
+            assert_eq!(array[0].kind, SentencerKind::Heading);
+            assert_eq!((array[2].start, array[2].end), (20, 32));
+            sentencer_free_sentences(sentences);
+
+            assert!(sentencer_parse_file(c"missing.odt".as_ptr()).is_null());
+            assert!(!sentencer_last_error().is_null());
+            assert!(sentencer_parse_file(ptr::null()).is_null());
+            let error = CStr::from_ptr(sentencer_last_error());
+            assert_eq!(error.to_str().unwrap_or_default(), "path is NULL");
+        }
+    }
+}
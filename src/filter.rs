@@ -0,0 +1,123 @@
+use crate::error::Result;
+use crate::search::SearchQuery;
+use crate::sentence::{Sentence, SentenceKind};
+
+// Narrows exports and statistics down to some of the sentences, such as only
+// the long ones or only those mentioning a term. Patterns are regular
+// expressions matched case-insensitively, like `search --regex`.
+#[derive(Debug, Clone, Default)]
+pub struct SentenceFilter {
+    min_words: Option<usize>,
+    max_chars: Option<usize>,
+    matching: Option<SearchQuery>,
+    excluding: Option<SearchQuery>,
+    // Any kind is accepted when empty
+    kinds: Vec<SentenceKind>,
+}
+
+impl SentenceFilter {
+    pub fn new() -> Self {
+        SentenceFilter::default()
+    }
+
+    pub fn with_min_words(mut self, min_words: usize) -> Self {
+        self.min_words = Some(min_words);
+        self
+    }
+
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+
+    pub fn with_match(mut self, pattern: &str) -> Result<Self> {
+        self.matching = Some(SearchQuery::new(pattern, true)?);
+        Ok(self)
+    }
+
+    pub fn with_exclude(mut self, pattern: &str) -> Result<Self> {
+        self.excluding = Some(SearchQuery::new(pattern, true)?);
+        Ok(self)
+    }
+
+    pub fn with_kinds(mut self, kinds: &[SentenceKind]) -> Self {
+        self.kinds = kinds.to_vec();
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_words.is_none()
+            && self.max_chars.is_none()
+            && self.matching.is_none()
+            && self.excluding.is_none()
+            && self.kinds.is_empty()
+    }
+
+    pub fn accepts(&self, sentence: &Sentence) -> bool {
+        let text = &sentence.text;
+        self.min_words
+            .is_none_or(|min_words| text.split_whitespace().count() >= min_words)
+            && self
+                .max_chars
+                .is_none_or(|max_chars| text.chars().count() <= max_chars)
+            && self
+                .matching
+                .as_ref()
+                .is_none_or(|query| query.is_match(text))
+            && self
+                .excluding
+                .as_ref()
+                .is_none_or(|query| !query.is_match(text))
+            && (self.kinds.is_empty() || self.kinds.contains(&sentence.kind))
+    }
+
+    pub fn retain(&self, sentences: &mut Vec<Sentence>) {
+        if !self.is_empty() {
+            sentences.retain(|sentence| self.accepts(sentence));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_filter() -> Result<()> {
+        let mut sentences = vec![
+            Sentence::new("Short one."),
+            Sentence::new("This sentence mentions the Budget twice: budget."),
+            Sentence::new("A longer sentence about nothing in particular."),
+            Sentence::new("Budget draft, page one."),
+        ];
+
+        let filter = SentenceFilter::new()
+            .with_min_words(4)
+            .with_max_chars(48)
+            .with_match("budget")?
+            .with_exclude(r"\bdraft\b")?;
+        filter.retain(&mut sentences);
+
+        let texts: Vec<&str> = sentences.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["This sentence mentions the Budget twice: budget."]
+        );
+        assert!(SentenceFilter::new().is_empty());
+
+        let mut sentences = vec![
+            Sentence {
+                kind: SentenceKind::Heading,
+                ..Sentence::new("Budget")
+            },
+            Sentence::new("The budget grew."),
+        ];
+        SentenceFilter::new()
+            .with_kinds(&[SentenceKind::Heading, SentenceKind::Quote])
+            .retain(&mut sentences);
+        assert_eq!(sentences.len(), 1);
+        assert_eq!(sentences[0].kind, SentenceKind::Heading);
+        assert!(SentenceFilter::new().with_match("(").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use crate::error::{Result, SentencerError};
+use crate::language::Language;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+// The public server takes up to 20 KB of text per request
+pub const DEFAULT_LANGUAGETOOL_URL: &str = "https://api.languagetool.org";
+const MAX_REQUEST_CHARS: usize = 15_000;
+// Sentences are sent as paragraphs so rules never see two as one
+const SENTENCE_SEPARATOR: &str = "\n\n";
+
+// A grammar or style problem, with its position in characters into the
+// sentence it was found in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrammarIssue {
+    pub offset: usize,
+    pub length: usize,
+    pub message: String,
+    pub rule: String,
+    // Suggested corrections, best first
+    pub replacements: Vec<String>,
+}
+
+impl GrammarIssue {
+    pub fn byte_range(&self, text: &str) -> Range<usize> {
+        let byte_offset = |chars: usize| {
+            text.char_indices()
+                .nth(chars)
+                .map_or(text.len(), |(byte, _)| byte)
+        };
+        byte_offset(self.offset)..byte_offset(self.offset + self.length)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckResponse {
+    matches: Vec<Match>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Match {
+    message: String,
+    offset: usize,
+    length: usize,
+    #[serde(default)]
+    replacements: Vec<Replacement>,
+    rule: Rule,
+}
+
+#[derive(Debug, Deserialize)]
+struct Replacement {
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Rule {
+    id: String,
+}
+
+// A LanguageTool server, either the public one or one run locally with
+// `java -jar languagetool-server.jar --port 8081`
+#[derive(Debug, Clone)]
+pub struct LanguageTool {
+    url: String,
+    language: String,
+}
+
+impl Default for LanguageTool {
+    fn default() -> Self {
+        LanguageTool::new(DEFAULT_LANGUAGETOOL_URL)
+    }
+}
+
+impl LanguageTool {
+    // The server's address, with or without the /v2 of its API
+    pub fn new(url: &str) -> Self {
+        LanguageTool {
+            url: url.trim_end_matches('/').to_string(),
+            language: "auto".to_string(),
+        }
+    }
+
+    // Without a language the server detects it
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = language.code().to_string();
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    // The issues of each sentence, in the order of the sentences. Sentences
+    // are sent together, as few requests as the size limit allows.
+    pub fn check<S: AsRef<str>>(&self, sentences: &[S]) -> Result<Vec<Vec<GrammarIssue>>> {
+        let mut issues = vec![Vec::new(); sentences.len()];
+        let mut start = 0;
+        while start < sentences.len() {
+            let mut end = start + 1;
+            let mut chars = sentences[start].as_ref().chars().count();
+            while let Some(sentence) = sentences.get(end) {
+                chars += SENTENCE_SEPARATOR.len() + sentence.as_ref().chars().count();
+                if chars > MAX_REQUEST_CHARS {
+                    break;
+                }
+                end += 1;
+            }
+
+            for (index, issue) in self.check_batch(&sentences[start..end])? {
+                issues[start + index].push(issue);
+            }
+            start = end;
+        }
+        Ok(issues)
+    }
+
+    fn check_batch<S: AsRef<str>>(&self, sentences: &[S]) -> Result<Vec<(usize, GrammarIssue)>> {
+        let texts: Vec<&str> = sentences.iter().map(AsRef::as_ref).collect();
+        let text = texts.join(SENTENCE_SEPARATOR);
+        let endpoint = match self.url.ends_with("/v2") {
+            true => format!("{}/check", self.url),
+            false => format!("{}/v2/check", self.url),
+        };
+
+        let failed = |message: String| SentencerError::Grammar(message);
+        let response = ureq::post(&endpoint)
+            .send_form(&[("language", self.language.as_str()), ("text", &text)])
+            .map_err(|e| failed(e.to_string()))?
+            .into_string()?;
+        let response: CheckResponse = serde_json::from_str(&response)
+            .map_err(|e| failed(format!("{}: unexpected response: {}", endpoint, e)))?;
+
+        // LanguageTool counts offsets in UTF-16 code units, as Java does
+        let separator = SENTENCE_SEPARATOR.encode_utf16().count();
+        let mut starts = Vec::new();
+        let mut position = 0;
+        for text in &texts {
+            starts.push(position);
+            position += text.encode_utf16().count() + separator;
+        }
+
+        let mut issues = Vec::new();
+        for found in response.matches {
+            let index = starts
+                .partition_point(|&start| start <= found.offset)
+                .saturating_sub(1);
+            let text = texts[index];
+            // Issues reaching into the next sentence end with this one
+            let offset = found.offset - starts[index];
+            let end = offset + found.length;
+            let offset_chars = char_offset(text, offset);
+            issues.push((
+                index,
+                GrammarIssue {
+                    offset: offset_chars,
+                    length: char_offset(text, end) - offset_chars,
+                    message: found.message,
+                    rule: found.rule.id,
+                    replacements: found
+                        .replacements
+                        .into_iter()
+                        .map(|replacement| replacement.value)
+                        .collect(),
+                },
+            ));
+        }
+        Ok(issues)
+    }
+}
+
+// Characters before a position given in UTF-16 code units, up to the end of the text
+fn char_offset(text: &str, utf16_offset: usize) -> usize {
+    let mut units = 0;
+    for (chars, c) in text.chars().enumerate() {
+        if units >= utf16_offset {
+            return chars;
+        }
+        units += c.len_utf16();
+    }
+    text.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::serve_once;
+
+    #[test]
+    fn test_check_maps_offsets_into_sentences() -> Result<()> {
+        // "go" in the first sentence and "a" in the second, which starts
+        // after the 9 code units of the first, 2 of them for the emoji, and
+        // the separator
+        let (url, request) = serve_once(
+            r#"{"matches": [
+                {"message": "Agreement", "offset": 6, "length": 2,
+                 "replacements": [{"value": "goes"}], "rule": {"id": "AGREEMENT"}},
+                {"message": "Use an", "offset": 14, "length": 1,
+                 "replacements": [{"value": "an"}], "rule": {"id": "EN_A_VS_AN"}}
+            ]}"#,
+        )?;
+
+        let checker = LanguageTool::new(&url).with_language(Language::English);
+        let sentences = ["🙂 He go.", "It a apple."];
+        let issues = checker.check(&sentences)?;
+
+        assert_eq!(issues.len(), 2);
+        assert_eq!((issues[0][0].offset, issues[0][0].length), (5, 2));
+        assert_eq!(&sentences[0][issues[0][0].byte_range(sentences[0])], "go");
+        assert_eq!(issues[0][0].replacements, vec!["goes"]);
+        assert_eq!(issues[1][0].rule, "EN_A_VS_AN");
+        assert_eq!(&sentences[1][issues[1][0].byte_range(sentences[1])], "a");
+
+        let request = request.recv().unwrap_or_default();
+        assert!(request.contains("language=en"));
+        assert!(request.contains("text=%F0%9F%99%82+He+go.%0A%0AIt+a+apple."));
+        Ok(())
+    }
+
+    #[test]
+    fn test_char_offset() {
+        assert_eq!(char_offset("a😀b", 0), 0);
+        assert_eq!(char_offset("a😀b", 3), 2);
+        assert_eq!(char_offset("a😀b", 10), 3);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::error::{Result, SentencerError};
+use crate::sentence::Sentence;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, STORED, STRING, Schema, TEXT, Value};
+use tantivy::{Index, IndexWriter, TantivyDocument, Term, doc};
+
+// Memory the writer fills with new sentences before writing them out
+const WRITER_MEMORY: usize = 50_000_000;
+
+// A sentence found in the index, by the file it is in and its 1-based number
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexHit {
+    pub file: String,
+    pub sentence: usize,
+    pub text: String,
+    pub score: f32,
+}
+
+// A tantivy full-text index with one entry per sentence, for searching
+// corpora too large to parse again for every search
+pub struct SentenceIndex {
+    index: Index,
+    file: Field,
+    sentence: Field,
+    text: Field,
+}
+
+impl fmt::Debug for SentenceIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SentenceIndex").finish_non_exhaustive()
+    }
+}
+
+impl SentenceIndex {
+    // Creates the index when `dir` does not hold one yet
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        let mut schema = Schema::builder();
+        let file = schema.add_text_field("file", STRING | STORED);
+        let sentence = schema.add_u64_field("sentence", STORED);
+        let text = schema.add_text_field("text", TEXT | STORED);
+
+        let directory = MmapDirectory::open(dir).map_err(index_error)?;
+        let index = Index::open_or_create(directory, schema.build()).map_err(index_error)?;
+        Ok(SentenceIndex {
+            index,
+            file,
+            sentence,
+            text,
+        })
+    }
+
+    // Files indexed before are replaced, so building again after documents
+    // changed leaves no stale sentences. Returns the number of sentences added.
+    pub fn add_documents(&self, documents: &[(String, Vec<Sentence>)]) -> Result<usize> {
+        let mut writer: IndexWriter = self.index.writer(WRITER_MEMORY).map_err(index_error)?;
+        let mut added = 0;
+        for (file, sentences) in documents {
+            writer.delete_term(Term::from_field_text(self.file, file));
+            for (index, sentence) in sentences.iter().enumerate() {
+                writer
+                    .add_document(doc!(
+                        self.file => file.as_str(),
+                        self.sentence => index as u64 + 1,
+                        self.text => sentence.text.as_str(),
+                    ))
+                    .map_err(index_error)?;
+                added += 1;
+            }
+        }
+        writer.commit().map_err(index_error)?;
+        Ok(added)
+    }
+
+    // Words of the query must all be in a sentence; tantivy's query syntax,
+    // such as "exact phrase" or -word, works as well. Best matches first.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<IndexHit>> {
+        let mut parser = QueryParser::for_index(&self.index, vec![self.text]);
+        parser.set_conjunction_by_default();
+        let query = parser.parse_query(query).map_err(|e| {
+            SentencerError::InvalidArgument(format!("Invalid query '{}': {}", query, e))
+        })?;
+
+        let searcher = self.index.reader().map_err(index_error)?.searcher();
+        let found = searcher
+            .search(&query, &TopDocs::with_limit(limit))
+            .map_err(index_error)?;
+        let mut hits = Vec::new();
+        for (score, address) in found {
+            let document: TantivyDocument = searcher.doc(address).map_err(index_error)?;
+            let text = |field: Field| {
+                document
+                    .get_first(field)
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            hits.push(IndexHit {
+                file: text(self.file),
+                sentence: document
+                    .get_first(self.sentence)
+                    .and_then(|value| value.as_u64())
+                    .unwrap_or_default() as usize,
+                text: text(self.text),
+                score,
+            });
+        }
+        Ok(hits)
+    }
+}
+
+fn index_error(error: impl fmt::Display) -> SentencerError {
+    SentencerError::Index(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_and_search_sentences() -> Result<()> {
+        let dir = Path::new("test_sentence_index");
+        let result = (|| -> Result<()> {
+            let index = SentenceIndex::open(dir)?;
+            let sentences = |texts: &[&str]| texts.iter().map(|t| Sentence::new(*t)).collect();
+            index.add_documents(&[
+                (
+                    "a.odt".to_string(),
+                    sentences(&["The cat sat.", "A dog barked at the cat."]),
+                ),
+                ("b.odt".to_string(), sentences(&["No animals here."])),
+            ])?;
+
+            let hits = index.search("cat dog", 10)?;
+            assert_eq!(hits.len(), 1);
+            assert_eq!((hits[0].file.as_str(), hits[0].sentence), ("a.odt", 2));
+            assert_eq!(hits[0].text, "A dog barked at the cat.");
+
+            // Indexing a file again replaces its sentences
+            let index = SentenceIndex::open(dir)?;
+            index.add_documents(&[("a.odt".to_string(), sentences(&["Only birds now."]))])?;
+            assert!(index.search("cat", 10)?.is_empty());
+            assert_eq!(index.search("birds", 10)?[0].sentence, 1);
+            assert!(index.search("\"unclosed", 10).is_err());
+            Ok(())
+        })();
+
+        fs::remove_dir_all(dir).ok();
+        result
+    }
+}
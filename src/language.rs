@@ -0,0 +1,90 @@
+use crate::error::{Result, SentencerError};
+
+// Languages with splitting rules of their own; anything else is split with
+// the default rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+    Spanish,
+    Japanese,
+    Chinese,
+}
+
+impl Language {
+    // Accepts ISO 639 codes with an optional region, such as "de" or "es-MX"
+    pub fn from_code(code: &str) -> Option<Self> {
+        let primary = code.split(['-', '_']).next()?.to_lowercase();
+
+        match primary.as_str() {
+            "en" | "eng" => Some(Language::English),
+            "de" | "deu" | "ger" => Some(Language::German),
+            "es" | "spa" => Some(Language::Spanish),
+            "ja" | "jpn" => Some(Language::Japanese),
+            "zh" | "zho" | "chi" | "cmn" => Some(Language::Chinese),
+            _ => None,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        Language::from_code(name).ok_or_else(|| {
+            SentencerError::InvalidArgument(format!(
+                "Unknown language '{}' (expected en, de, es, ja or zh)",
+                name
+            ))
+        })
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+            Language::Spanish => "es",
+            Language::Japanese => "ja",
+            Language::Chinese => "zh",
+        }
+    }
+
+    // Guesses the language from a sample of the text itself
+    pub fn detect(text: &str) -> Option<Self> {
+        let sample: String = text.chars().take(4000).collect();
+        let info = whatlang::detect(&sample)?;
+
+        match info.lang() {
+            whatlang::Lang::Eng => Some(Language::English),
+            whatlang::Lang::Deu => Some(Language::German),
+            whatlang::Lang::Spa => Some(Language::Spanish),
+            whatlang::Lang::Jpn => Some(Language::Japanese),
+            whatlang::Lang::Cmn => Some(Language::Chinese),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_codes() {
+        assert_eq!(Language::from_code("de-AT"), Some(Language::German));
+        assert_eq!(Language::from_code("es_MX"), Some(Language::Spanish));
+        assert_eq!(Language::from_code("JA"), Some(Language::Japanese));
+        assert_eq!(Language::from_code("fr"), None);
+        assert!(Language::from_name("xx").is_err());
+    }
+
+    #[test]
+    fn test_detect_from_text() {
+        assert_eq!(
+            Language::detect(
+                "Der schnelle braune Fuchs springt über den faulen Hund und läuft weiter."
+            ),
+            Some(Language::German)
+        );
+        assert_eq!(
+            Language::detect("¿Dónde está la biblioteca? Necesito encontrar un libro para mañana."),
+            Some(Language::Spanish)
+        );
+    }
+}
@@ -0,0 +1,92 @@
+// Parsing, splitting and everything working on sentences builds without
+// default features and reads documents from bytes, which is what builds for
+// WebAssembly get:
+//
+//     cargo check --lib --no-default-features --target wasm32-unknown-unknown
+//
+// Reading from paths, memory mapping, the pandoc fallback, batches, the
+// config file and the reading state need the native feature, which the cli
+// and ffi features turn on. The terminal reader and the clients of network
+// services need the cli feature.
+pub mod abbreviations;
+pub mod anki;
+pub mod annotation;
+#[cfg(feature = "native")]
+pub mod batch;
+#[cfg(feature = "cli")]
+pub mod bench;
+#[cfg(feature = "native")]
+pub mod config;
+pub mod dedupe;
+pub mod diff;
+pub mod error;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filter;
+#[cfg(feature = "cli")]
+pub mod grammar;
+#[cfg(feature = "index")]
+pub mod index;
+pub mod language;
+pub mod normalize;
+pub mod parser;
+#[cfg(feature = "cli")]
+pub mod remote;
+pub mod replace;
+#[cfg(feature = "cli")]
+pub mod rewrite;
+pub mod search;
+pub mod sentence;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "cli")]
+pub mod session;
+#[cfg(feature = "spellcheck")]
+pub mod spelling;
+pub mod splitter;
+pub mod srx;
+#[cfg(feature = "native")]
+pub mod state;
+pub mod stats;
+pub mod subtitles;
+#[cfg(feature = "cli")]
+pub mod ui;
+pub mod vocab;
+pub mod writer;
+
+#[cfg(test)]
+mod test_utils;
+
+pub use abbreviations::Abbreviations;
+pub use annotation::{Annotation, AnnotationFormat};
+#[cfg(feature = "native")]
+pub use config::Config;
+pub use dedupe::{DedupeOptions, DuplicateReport};
+pub use error::{Error, Result, SentencerError};
+pub use export::ExportFormat;
+pub use filter::SentenceFilter;
+#[cfg(feature = "cli")]
+pub use grammar::{GrammarIssue, LanguageTool};
+pub use language::Language;
+#[cfg(feature = "native")]
+pub use parser::PandocTarget;
+pub use parser::{
+    ChangesView, CommentsMode, DocumentFormat, DocumentMetadata, DocumentParser, DocxParser,
+    EpubParser, Fb2Parser, HtmlParser, Limits, MarkdownParser, NotesMode, OdpParser, OdsParser,
+    OdtParser, ParseOptions, RtfParser, TextParser,
+};
+pub use search::{ConcordanceLine, SearchQuery};
+pub use sentence::{CellPosition, Link, Sentence, SentenceKind};
+#[cfg(feature = "cli")]
+pub use session::Session;
+pub use splitter::{
+    RegexSplitter, RuleSplitter, SentenceSplitter, SplitOptions, Splitter, UnicodeSplitter,
+};
+pub use srx::SrxSplitter;
+#[cfg(feature = "native")]
+pub use state::StateStore;
+pub use stats::{DocumentStats, FileStats, ReadingStats, StatsReport};
+#[cfg(feature = "cli")]
+pub use ui::{DocumentEntry, Keymap, Navigator, ReaderEvent};
+pub use writer::{Backup, SaveOptions, save_odt, save_odt_with};
@@ -1,535 +1,2249 @@
-use quick_xml::events::Event;
-use quick_xml::{Error, Reader};
-use regex::Regex;
-use std::fs::File;
-use std::io::{self, BufReader, Read, Write};
-use zip::ZipArchive;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use ratatui::crossterm::style::{Color, Stylize};
+use sentencer::diff::SentenceChange;
+#[cfg(feature = "index")]
+use sentencer::index::SentenceIndex;
+use sentencer::remote::Download;
+use sentencer::replace::Substitution;
+use sentencer::rewrite::{self, Rewriter};
+#[cfg(feature = "server")]
+use sentencer::server::{DocumentServer, LiveSync};
+#[cfg(feature = "spellcheck")]
+use sentencer::spelling::{self, SpellChecker};
+use sentencer::vocab::Stopwords;
+use sentencer::{
+    Abbreviations, AnnotationFormat, Backup, ChangesView, CommentsMode, Config, DedupeOptions,
+    DocumentEntry, DocumentFormat, DocumentMetadata, DocumentStats, ExportFormat, FileStats,
+    Keymap, Language, LanguageTool, Limits, Navigator, NotesMode, OdtParser, PandocTarget,
+    ParseOptions, ReaderEvent, ReadingStats, Result, SaveOptions, SearchQuery, Sentence,
+    SentenceFilter, SentenceKind, SentencerError, Session, SplitOptions, Splitter, StateStore,
+    StatsReport, anki, annotation, batch, bench, dedupe, diff, export, grammar, parser, remote,
+    state, subtitles, vocab,
+};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "server")]
+use std::sync::mpsc;
+use std::sync::mpsc::Sender;
+
+// Exit codes let scripts tell bad input apart from unreadable or broken documents
+const EXIT_USAGE: i32 = 2;
+
+fn exit_code(error: &SentencerError) -> i32 {
+    match error {
+        SentencerError::InvalidArgument(_)
+        | SentencerError::Regex(_)
+        | SentencerError::Config(_)
+        | SentencerError::Dictionary(_) => EXIT_USAGE,
+        SentencerError::Io(_)
+        | SentencerError::Download(_)
+        | SentencerError::Grammar(_)
+        | SentencerError::Rewrite(_)
+        | SentencerError::Index(_)
+        | SentencerError::Pandoc(_)
+        | SentencerError::Watch(_) => 3,
+        SentencerError::UnsupportedFormat(_) => 4,
+        SentencerError::Zip(_)
+        | SentencerError::Xml(_)
+        | SentencerError::Encoding(_)
+        | SentencerError::InvalidDocument(_) => 5,
+        SentencerError::Save(_) => 6,
+        SentencerError::PasswordRequired(_) | SentencerError::Decrypt(_) => 7,
+        SentencerError::Json(_) | SentencerError::Csv(_) => 1,
+    }
+}
 
-#[derive(Debug)]
-pub struct OdtParser {
-    sentence_regex: Regex,
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(exit_code(&e));
+    }
 }
 
-impl OdtParser {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let sentence_regex = Regex::new(r"[.!?]+\s+")?;
+#[derive(Debug, Parser)]
+#[command(
+    name = "sentencer",
+    version,
+    about = "Read and edit documents one sentence at a time",
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    // `sentencer <document>` is shorthand for `sentencer read <document>`
+    #[command(flatten)]
+    read: Option<ReadArgs>,
+}
 
-        Ok(OdtParser { sentence_regex })
-    }
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Read and edit a document interactively
+    Read(ReadArgs),
+    /// Export sentences with their metadata
+    Export(ExportArgs),
+    /// Show sentence, word and character counts
+    Stats(StatsArgs),
+    /// Print the sentences that match a pattern
+    Search(SearchArgs),
+    /// Search every document in folders for a pattern, printing each matching sentence
+    Grep(GrepArgs),
+    /// Build and query a full-text index of the sentences of many documents
+    #[cfg(feature = "index")]
+    #[command(subcommand)]
+    Index(IndexCommand),
+    /// Export the notes and tags made while reading a document
+    Annotations(AnnotationsArgs),
+    /// Report repeated and nearly repeated sentences
+    Dedupe(DedupeArgs),
+    /// Show the sentences added, removed or changed between two versions of a document
+    Diff(DiffArgs),
+    /// Replace a regex in every sentence of an ODT document, after a preview
+    Replace(ReplaceArgs),
+    /// List the words of a document by frequency, with example sentences
+    Vocab(VocabArgs),
+    /// Show every match of a term in its context, one line each
+    Concordance(ConcordanceArgs),
+    /// Check grammar and style with a LanguageTool server
+    Check(CheckArgs),
+    /// Write the edits of a session into its ODT document, or print them as a diff
+    Apply(ApplyArgs),
+    /// Time the reading, extraction and splitting of a document and report peak memory
+    #[command(alias = "bench")]
+    Time(TimeArgs),
+    /// Read and edit a document over HTTP through a JSON API
+    #[cfg(feature = "server")]
+    Serve(ServeArgs),
+}
 
-    pub fn parse_file(&self, file_path: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
-        let mut archive = ZipArchive::new(reader)?;
+#[derive(Debug, Args)]
+struct ParseArgs {
+    /// Sentence splitter [default: rules]
+    #[arg(long, value_parser = ["regex", "unicode", "uax29", "rules"])]
+    splitter: Option<String>,
+
+    /// Regex the regex and rules splitters end sentences at
+    #[arg(long, value_name = "REGEX")]
+    boundary: Option<String>,
+
+    /// Join sentences shorter than this many characters with the next one
+    #[arg(long, value_name = "CHARS")]
+    min_length: Option<usize>,
+
+    /// Keep the punctuation that ends each sentence [default: true]
+    #[arg(long, value_name = "BOOL")]
+    keep_punctuation: Option<bool>,
+
+    /// Extra abbreviations for the rules splitter, one per line
+    #[arg(long, value_name = "FILE")]
+    abbrev_file: Option<String>,
+
+    /// Split with the segmentation rules of an SRX file instead
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["splitter", "abbrev_file"])]
+    srx: Option<String>,
+
+    /// How footnotes and endnotes are read
+    #[arg(long, default_value = "include", value_parser = NotesMode::from_name)]
+    notes: NotesMode,
+
+    /// How reviewer comments in ODT documents are read
+    #[arg(long, default_value = "exclude", value_parser = CommentsMode::from_name)]
+    comments: CommentsMode,
+
+    /// Tracked changes in ODT documents: accepted, rejected or annotated
+    #[arg(long, default_value = "accepted", value_parser = ChangesView::from_name)]
+    changes: ChangesView,
+
+    /// Read each table cell as its own sentence
+    #[arg(long)]
+    table_cells: bool,
+
+    /// Read each list item as one sentence, even when it holds several
+    #[arg(long)]
+    list_items: bool,
+
+    /// Read each spreadsheet row as one sentence instead of one per cell
+    #[arg(long)]
+    sheet_rows: bool,
+
+    /// Read the page headers and footers of ODT documents, before and after the body
+    #[arg(long)]
+    headers_footers: bool,
+
+    /// Read the titles and descriptions of images in ODT documents, after the
+    /// paragraph each image is in
+    #[arg(long)]
+    alt_text: bool,
+
+    /// Read the generated tables of contents and indexes of ODT documents
+    #[arg(long)]
+    include_toc: bool,
+
+    /// Read the subdocuments of ODT master documents from the copies kept in
+    /// the master instead of the linked files
+    #[arg(long)]
+    no_linked_files: bool,
+
+    /// Keep bold, italic and underlined text of ODT documents as **, * and _
+    /// markers around it
+    #[arg(long)]
+    emphasis_markers: bool,
+
+    /// Language for the splitting rules (en, de, es, ja or zh), detected when omitted
+    #[arg(long, value_parser = Language::from_name)]
+    language: Option<Language>,
+
+    /// Password of encrypted ODT documents, asked for when reading interactively
+    #[arg(long)]
+    password: Option<String>,
+
+    /// Largest size in MiB a compressed ODT part may expand to
+    #[arg(long, value_name = "MIB", default_value_t = 256)]
+    max_entry_size: u64,
+
+    /// Largest size in MiB of a document downloaded from a URL
+    #[arg(long, value_name = "MIB", default_value_t = 100)]
+    max_download_size: u64,
+
+    /// Deepest nesting of XML elements read from ODT documents
+    #[arg(long, value_name = "LEVELS", default_value_t = 256)]
+    max_xml_depth: usize,
+
+    /// Convert documents in formats read by none of the parsers with pandoc
+    /// first, to text or odt [default: odt]
+    #[arg(long, value_name = "FORMAT", value_parser = PandocTarget::from_name)]
+    #[arg(num_args = 0..=1, require_equals = true, default_missing_value = "odt")]
+    via_pandoc: Option<PandocTarget>,
+
+    /// Map ODT files into memory instead of reading them, for very large
+    /// documents; the file must not be truncated while it is read
+    #[arg(long)]
+    mmap: bool,
+
+    // Settings from the configuration file, which the flags above override
+    #[arg(skip)]
+    config: Config,
+}
 
-        let mut content_file = archive.by_name("content.xml")?;
-        let mut content = String::new();
-        content_file.read_to_string(&mut content)?;
+impl ParseArgs {
+    fn splitter(&self) -> Result<Splitter> {
+        let config = &self.config.splitting;
+        let options = SplitOptions {
+            min_length: self.min_length.unwrap_or(config.min_length),
+            keep_punctuation: self.keep_punctuation.unwrap_or(config.keep_punctuation),
+        };
+        if let Some(srx) = &self.srx {
+            return Ok(Splitter::srx(srx)?.with_options(options));
+        }
 
-        let text = self.extract_text_from_xml(&content)?;
+        let name = self
+            .splitter
+            .as_deref()
+            .or(config.splitter.as_deref())
+            .unwrap_or("rules");
+        let splitter = match (name, &self.abbrev_file) {
+            ("rules", abbrev_file) => {
+                let mut abbreviations = Abbreviations::english();
+                for abbreviation in &config.abbreviations {
+                    abbreviations.insert(abbreviation);
+                }
+                for file in config.abbreviation_files.iter().chain(abbrev_file) {
+                    abbreviations.load_file(file)?;
+                }
+                Splitter::rules(abbreviations)?
+            }
+            (_, Some(_)) => {
+                return Err(SentencerError::InvalidArgument(
+                    "--abbrev-file can only be used with the rules splitter".to_string(),
+                ));
+            }
+            (name, None) => Splitter::from_name(name)?,
+        };
+
+        // The configured boundary is left out for splitters that cannot use it
+        let splitter = match (&self.boundary, &config.boundary_regex) {
+            (Some(boundary), _) => splitter.with_boundary(boundary)?,
+            (None, Some(boundary)) if matches!(name, "regex" | "rules") => {
+                splitter.with_boundary(boundary)?
+            }
+            _ => splitter,
+        };
+        Ok(splitter.with_options(options))
+    }
 
-        let sentences = self.split_into_sentences(&text);
+    fn apply_config(&mut self, config: Config) -> Result<()> {
+        if let (None, Some(name)) = (self.language, &config.splitting.language) {
+            self.language = Some(Language::from_name(name)?);
+        }
+        self.config = config;
+        Ok(())
+    }
 
-        Ok(sentences)
+    fn options(&self) -> ParseOptions {
+        ParseOptions {
+            notes: self.notes,
+            comments: self.comments,
+            changes: self.changes,
+            table_cells_as_sentences: self.table_cells,
+            list_items_as_sentences: self.list_items,
+            spreadsheet_rows: self.sheet_rows,
+            language: self.language,
+            password: self.password.clone(),
+            limits: Limits {
+                max_entry_size: self.max_entry_size * 1024 * 1024,
+                max_xml_depth: self.max_xml_depth,
+            },
+            mmap: self.mmap,
+            via_pandoc: self.via_pandoc,
+            headers_footers: self.headers_footers,
+            alt_text: self.alt_text,
+            include_toc: self.include_toc,
+            skip_linked_files: self.no_linked_files,
+            emphasis_markers: self.emphasis_markers,
+        }
     }
+}
 
-    pub fn interactive_mode(
-        &self,
-        sentences: Vec<String>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if sentences.is_empty() {
-            println!("No sentences found in the document.");
-            return Ok(());
+#[derive(Debug, Args)]
+struct SaveArgs {
+    /// Write edits into the saved ODT document as tracked changes, to be
+    /// accepted or rejected in LibreOffice
+    #[arg(long)]
+    save_as_tracked_changes: bool,
+
+    /// Name the tracked changes are attributed to; the user name when omitted
+    #[arg(long, value_name = "NAME", requires = "save_as_tracked_changes")]
+    author: Option<String>,
+
+    /// Show what saving would change without writing any file
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Copy kept of a document saved over: simple (FILE.bak), numbered
+    /// (FILE.~1~, FILE.~2~, ...), none, or a name with {} for the file name
+    #[arg(long, value_name = "MODE", default_value = "simple", value_parser = Backup::from_name)]
+    backup: Backup,
+}
+
+impl SaveArgs {
+    fn options(&self) -> SaveOptions {
+        let author = || {
+            self.author
+                .clone()
+                .or_else(|| std::env::var("USER").ok())
+                .or_else(|| std::env::var("USERNAME").ok())
+                .unwrap_or_else(|| "sentencer".to_string())
+        };
+        SaveOptions {
+            tracked_changes_author: self.save_as_tracked_changes.then(author),
+            dry_run: self.dry_run,
+            backup: self.backup.clone(),
         }
+    }
+}
 
-        let mut current_index = 0;
-        let total_sentences = sentences.len();
+#[derive(Debug, Args)]
+struct ReadArgs {
+    /// Documents to read, or http(s) URLs to download them from; `]` and `[`
+    /// move between several
+    #[arg(required_unless_present = "session")]
+    files: Vec<String>,
+
+    /// Keep edits, bookmarks, notes and the position in this session file,
+    /// created with the document on first use and resumed from afterwards
+    #[arg(long, value_name = "FILE")]
+    session: Option<String>,
+
+    /// On quitting, export the sentences of every document opened, as edited, to this file
+    #[arg(long, value_name = "FILE")]
+    export: Option<String>,
+
+    /// Format of the --export file
+    #[arg(long, default_value = "json", value_parser = ExportFormat::from_name, requires = "export")]
+    export_format: ExportFormat,
+
+    /// Save edits to this file instead of the source document
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<String>,
+
+    #[command(flatten)]
+    save: SaveArgs,
+
+    /// Continue from the saved position without asking
+    #[arg(long)]
+    resume: bool,
+
+    /// Make `e` edit sentences in $VISUAL or $EDITOR
+    #[arg(long)]
+    editor: bool,
+
+    /// Open the documents for reading only, refusing edits
+    #[arg(long, conflicts_with = "editor")]
+    read_only: bool,
+
+    /// Parse the document again whenever it changes on disk
+    #[arg(long)]
+    watch: bool,
+
+    /// Read sentences aloud from the start position, moving on after each one
+    #[arg(long)]
+    speak: bool,
+
+    /// Show the title, author, dates and word count the documents record, then exit
+    #[arg(long, conflicts_with = "session")]
+    info: bool,
+
+    /// Step only through sentences of this kind, such as heading or quote;
+    /// `K` changes it while reading
+    #[arg(long, value_name = "KIND", value_parser = SentenceKind::from_name)]
+    kind: Option<SentenceKind>,
+
+    /// Show the current sentence in browsers at http://127.0.0.1:PORT, following
+    /// the reader's position and edits
+    #[cfg(feature = "server")]
+    #[arg(long, value_name = "PORT")]
+    live: Option<u16>,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
 
-        self.clear_screen();
-        self.show_instructions();
+#[derive(Debug, Args)]
+struct ExportArgs {
+    /// Documents or glob patterns to export
+    #[arg(required = true)]
+    files: Vec<String>,
 
-        loop {
-            self.display_sentence(
-                &sentences[current_index],
-                current_index + 1,
-                total_sentences,
-            );
+    /// Output format (json, jsonl, csv, tsv, odt, links for a CSV of hyperlinks, anki for
+    /// flashcards of the annotated sentences, or srt and vtt for subtitles)
+    #[arg(long, default_value = "json", value_parser = ExportFormat::from_name)]
+    format: ExportFormat,
 
-            let input = self.get_user_input()?;
+    /// Hide this word in Anki cards of the sentences containing it (can be repeated)
+    #[arg(long, value_name = "WORD")]
+    cloze: Vec<String>,
 
-            match input.as_str() {
-                "n" | "next" | "" => {
-                    if current_index < total_sentences - 1 {
-                        current_index += 1;
-                        self.clear_screen();
-                    } else {
-                        println!("You've reached the end of the document");
-                        println!("Press 'p' to go back or 'q' to quit.");
-                    }
-                }
-                "p" | "prev" | "previous" => {
-                    if current_index > 0 {
-                        current_index -= 1;
-                        self.clear_screen();
-                    } else {
-                        println!("You're at the beginning of the document.");
-                        println!("Press 'n' or 'Enter' to proceed or 'q' to quit.");
-                    }
-                }
-                "f" | "first" => {
-                    current_index = 0;
-                    self.clear_screen();
-                }
-                "l" | "last" => {
-                    current_index = total_sentences - 1;
-                    self.clear_screen();
-                }
-                "h" | "help" => {
-                    self.clear_screen();
-                    self.show_instructions();
-                }
-                "q" | "quit" => {
-                    println!("Gooooodbye...");
-                    break;
+    /// Speaking speed the subtitle cues are timed for [default: 160]
+    #[arg(long, value_name = "WORDS", value_parser = clap::value_parser!(u32).range(1..))]
+    wpm: Option<u32>,
+
+    /// Write everything to this file instead of stdout
+    #[arg(short, long, value_name = "FILE", conflicts_with = "output_dir")]
+    output: Option<String>,
+
+    /// Write one file per document into this directory
+    #[arg(long, value_name = "DIR")]
+    output_dir: Option<String>,
+
+    #[command(flatten)]
+    filter: FilterArgs,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+struct StatsArgs {
+    /// Documents or glob patterns, or directories with --recursive
+    #[arg(required = true)]
+    files: Vec<String>,
+
+    /// Read every supported document in the given directories and below
+    #[arg(short, long)]
+    recursive: bool,
+
+    /// Report format
+    #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+    format: String,
+
+    #[command(flatten)]
+    filter: FilterArgs,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+// Sentences left out of exports and statistics
+#[derive(Debug, Args)]
+struct FilterArgs {
+    /// Only sentences with at least this many words
+    #[arg(long, value_name = "WORDS")]
+    min_words: Option<usize>,
+
+    /// Only sentences with at most this many characters
+    #[arg(long, value_name = "CHARS")]
+    max_chars: Option<usize>,
+
+    /// Only sentences matching this regex, case-insensitively
+    #[arg(long = "match", value_name = "REGEX")]
+    matching: Option<String>,
+
+    /// Leave out sentences matching this regex, case-insensitively
+    #[arg(long, value_name = "REGEX")]
+    exclude: Option<String>,
+
+    /// Only sentences of these kinds, such as heading,list_item,quote
+    #[arg(long, value_name = "KINDS", value_delimiter = ',', value_parser = SentenceKind::from_name)]
+    kind: Vec<SentenceKind>,
+}
+
+impl FilterArgs {
+    fn filter(&self) -> Result<SentenceFilter> {
+        let mut filter = SentenceFilter::new();
+        if let Some(min_words) = self.min_words {
+            filter = filter.with_min_words(min_words);
+        }
+        if let Some(max_chars) = self.max_chars {
+            filter = filter.with_max_chars(max_chars);
+        }
+        if let Some(pattern) = &self.matching {
+            filter = filter.with_match(pattern)?;
+        }
+        if let Some(pattern) = &self.exclude {
+            filter = filter.with_exclude(pattern)?;
+        }
+        if !self.kind.is_empty() {
+            filter = filter.with_kinds(&self.kind);
+        }
+        Ok(filter)
+    }
+}
+
+#[derive(Debug, Args)]
+struct SearchArgs {
+    /// Text to search for, case-insensitively
+    pattern: String,
+
+    /// Documents or glob patterns to search
+    #[arg(required = true)]
+    files: Vec<String>,
+
+    /// Treat the pattern as a regular expression
+    #[arg(long)]
+    regex: bool,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+struct GrepArgs {
+    /// Text to search for, case-insensitively
+    pattern: String,
+
+    /// Documents, glob patterns or directories to search
+    #[arg(required = true)]
+    paths: Vec<String>,
+
+    /// Search every supported document in the given directories and below
+    #[arg(short, long)]
+    recursive: bool,
+
+    /// Treat the pattern as a regular expression
+    #[arg(long)]
+    regex: bool,
+
+    /// Output format
+    #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+    format: String,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[cfg(feature = "index")]
+#[derive(Debug, Subcommand)]
+enum IndexCommand {
+    /// Add documents to the index, replacing what was indexed of them before
+    Build(Box<IndexBuildArgs>),
+    /// Print the indexed sentences that contain all the words of a query
+    Query(IndexQueryArgs),
+}
+
+#[cfg(feature = "index")]
+#[derive(Debug, Args)]
+struct IndexBuildArgs {
+    /// Documents, glob patterns or directories, which are searched for documents
+    #[arg(required = true)]
+    paths: Vec<String>,
+
+    /// Directory of the index [default: sentencer/index in the user data directory]
+    #[arg(long, value_name = "DIR")]
+    index: Option<PathBuf>,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[cfg(feature = "index")]
+#[derive(Debug, Args)]
+struct IndexQueryArgs {
+    /// Words to find; "quoted phrases" and -excluded words work too
+    query: String,
+
+    /// Directory of the index [default: sentencer/index in the user data directory]
+    #[arg(long, value_name = "DIR")]
+    index: Option<PathBuf>,
+
+    /// Most sentences to print
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+
+    /// Output format
+    #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+    format: String,
+}
+
+#[derive(Debug, Args)]
+struct AnnotationsArgs {
+    /// Document the annotations were made on
+    file: String,
+
+    /// Output format (markdown or json)
+    #[arg(long, default_value = "markdown", value_parser = AnnotationFormat::from_name)]
+    format: AnnotationFormat,
+
+    /// Write to this file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct DedupeArgs {
+    /// Document to check
+    file: String,
+
+    /// Share of word pairs (0 to 1) sentences must have in common to be near-duplicates
+    #[arg(long, default_value_t = DedupeOptions::default().threshold)]
+    threshold: f64,
+
+    /// Ignore sentences with fewer words
+    #[arg(long, value_name = "WORDS", default_value_t = DedupeOptions::default().min_words)]
+    min_words: usize,
+
+    /// Report format
+    #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+    format: String,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+struct DiffArgs {
+    /// Earlier version of the document
+    old: String,
+
+    /// Later version of the document
+    new: String,
+
+    /// Report format
+    #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+    format: String,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+struct ReplaceArgs {
+    /// Regular expression to replace
+    pattern: String,
+
+    /// Replacement text; $1 or \1 insert a group of the match
+    replacement: String,
+
+    /// ODT document to change
+    file: String,
+
+    /// Match regardless of case
+    #[arg(short, long)]
+    ignore_case: bool,
+
+    /// Only replace the first match in each sentence
+    #[arg(long)]
+    first: bool,
+
+    /// Apply without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Save the result to this file instead of overwriting the document
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<String>,
+
+    #[command(flatten)]
+    save: SaveArgs,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+struct VocabArgs {
+    /// Document to count the words of
+    file: String,
+
+    /// Leave out common words of the document's language
+    #[arg(long)]
+    stopwords: bool,
+
+    /// More words to leave out, one per line
+    #[arg(long, value_name = "FILE")]
+    stopword_file: Option<String>,
+
+    /// Only the most frequent words
+    #[arg(long, value_name = "WORDS")]
+    limit: Option<usize>,
+
+    /// Example sentences shown for each word
+    #[arg(long, value_name = "SENTENCES", default_value_t = 1)]
+    examples: usize,
+
+    /// Output format (text or csv)
+    #[arg(long, default_value = "text", value_parser = ["text", "csv"])]
+    format: String,
+
+    /// Write to this file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<String>,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+struct ConcordanceArgs {
+    /// Term to look for, case-insensitively
+    term: String,
+
+    /// Documents or glob patterns to search
+    #[arg(required = true)]
+    files: Vec<String>,
+
+    /// Treat the term as a regular expression
+    #[arg(long)]
+    regex: bool,
+
+    /// Characters of context shown on each side of the term
+    #[arg(short, long, value_name = "CHARS", default_value_t = 30)]
+    width: usize,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+struct CheckArgs {
+    /// Document to check
+    file: String,
+
+    /// LanguageTool server to send the sentences to [default: https://api.languagetool.org]
+    #[arg(long)]
+    url: Option<String>,
+
+    /// Report format
+    #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+    format: String,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+struct ApplyArgs {
+    /// Session file written by `read --session`
+    session: String,
+
+    /// Save the edited document to this file instead of over the original
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<String>,
+
+    #[command(flatten)]
+    save: SaveArgs,
+
+    /// Print the edits as a unified diff instead of saving them
+    #[arg(long)]
+    diff: bool,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+struct TimeArgs {
+    /// Document to time
+    file: String,
+
+    /// Report format
+    #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+    format: String,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+#[cfg(feature = "server")]
+#[derive(Debug, Args)]
+struct ServeArgs {
+    /// Document to serve
+    file: String,
+
+    /// Port to listen on
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+
+    /// Address to listen on; 0.0.0.0 makes the document reachable from other machines
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Save edits to this file instead of the source document
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<String>,
+
+    #[command(flatten)]
+    save: SaveArgs,
+
+    #[command(flatten)]
+    parse: ParseArgs,
+}
+
+fn run() -> Result<()> {
+    let cli = Cli::parse();
+    let config = Config::load_default()?;
+
+    match (cli.command, cli.read) {
+        (Some(Command::Read(mut args)), _) | (None, Some(mut args)) => {
+            args.parse.apply_config(config)?;
+            read(args)
+        }
+        (Some(Command::Export(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            export_files(args)
+        }
+        (Some(Command::Stats(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            stats(args)
+        }
+        (Some(Command::Search(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            search(args)
+        }
+        (Some(Command::Grep(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            grep(args)
+        }
+        #[cfg(feature = "index")]
+        (Some(Command::Index(IndexCommand::Build(mut args))), _) => {
+            args.parse.apply_config(config)?;
+            build_index(*args)
+        }
+        #[cfg(feature = "index")]
+        (Some(Command::Index(IndexCommand::Query(args))), _) => query_index(args),
+        (Some(Command::Annotations(args)), _) => annotations(args),
+        (Some(Command::Dedupe(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            dedupe(args)
+        }
+        (Some(Command::Diff(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            diff_documents(args)
+        }
+        (Some(Command::Replace(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            replace(args)
+        }
+        (Some(Command::Vocab(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            vocab(args)
+        }
+        (Some(Command::Concordance(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            concordance(args)
+        }
+        (Some(Command::Check(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            check(args)
+        }
+        (Some(Command::Apply(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            apply(args)
+        }
+        (Some(Command::Time(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            time(args)
+        }
+        #[cfg(feature = "server")]
+        (Some(Command::Serve(mut args)), _) => {
+            args.parse.apply_config(config)?;
+            serve(args)
+        }
+        (None, None) => {
+            Cli::command().print_help()?;
+            std::process::exit(EXIT_USAGE);
+        }
+    }
+}
+
+fn read(args: ReadArgs) -> Result<()> {
+    if args.info {
+        return show_info(&args);
+    }
+    if args.files.len() > 1 && (args.session.is_some() || args.output.is_some()) {
+        return Err(SentencerError::InvalidArgument(
+            "--session and --output work with a single document".to_string(),
+        ));
+    }
+    let mut session_file = match &args.session {
+        Some(session_path) => Some(Session::open(
+            session_path,
+            args.files.first().map(String::as_str),
+        )?),
+        None => None,
+    };
+    let names = match &session_file {
+        Some(session) => vec![session.document.clone()],
+        None => args.files.clone(),
+    };
+
+    // Every document read sends its events to the same browsers
+    #[cfg(feature = "server")]
+    let events = match args.live {
+        Some(port) => Some(follow_live(port)?),
+        None => None,
+    };
+    #[cfg(not(feature = "server"))]
+    let events = None;
+
+    let mut documents: Vec<DocumentEntry> =
+        names.iter().map(|name| DocumentEntry::new(name)).collect();
+    let mut opened: Vec<Option<Vec<Sentence>>> = vec![None; documents.len()];
+    let mut reading = ReadingStats::default();
+    let mut index = 0;
+    let mut previous = None;
+    let mut failure = None;
+    // The reader only gives up when none of the documents can be read
+    let mut first_error = None;
+    while index < documents.len() {
+        let arrival = match failure.take() {
+            Some(message) => Arrival::Returned(message),
+            None if previous.is_some() => Arrival::Switched,
+            None => Arrival::Opened,
+        };
+        let opening = read_document(
+            &args,
+            &documents,
+            index,
+            arrival,
+            &reading,
+            session_file.as_mut(),
+            events.as_ref(),
+        )?;
+        // Documents without sentences or that cannot be read are passed over,
+        // returning to the one the reader came from, or going on to the next
+        // one when nothing is open yet, with why shown in the footer
+        let visit = match opening {
+            Opening::Read(visit) => visit,
+            Opening::Empty => {
+                failure = Some(format!("{} has no sentences", documents[index].name));
+                match previous {
+                    Some(previous) => index = previous,
+                    None => index += 1,
                 }
-                num_str if num_str.chars().all(|c| c.is_ascii_digit()) => {
-                    if let Ok(sentence_num) = num_str.parse::<usize>() {
-                        if sentence_num > 0 && sentence_num <= total_sentences {
-                            current_index = sentence_num - 1;
-                            self.clear_screen();
-                        } else {
-                            println!(
-                                "Invalid sentence number. Must be between 1 and {}.",
-                                total_sentences
-                            );
-                        }
+                continue;
+            }
+            Opening::Failed(e) => {
+                failure = Some(format!("Could not read {}: {}", documents[index].name, e));
+                match previous {
+                    Some(previous) => index = previous,
+                    None => {
+                        first_error.get_or_insert((index, e));
+                        index += 1;
                     }
                 }
-                _ => {
-                    println!("Unknow command: {}. Type 'h' for help.", input);
-                }
+                continue;
             }
+        };
+
+        documents[index].progress = Some((visit.position, visit.sentences.len()));
+        reading.add(&visit.reading);
+        opened[index] = Some(visit.sentences);
+        match visit.next_document {
+            Some(next) => {
+                previous = Some(index);
+                index = next;
+            }
+            None => break,
         }
+    }
 
-        Ok(())
+    if opened.iter().all(Option::is_none)
+        && let Some((index, e)) = first_error
+    {
+        eprintln!("Error parsing file: '{}': {}", documents[index].name, e);
+        eprintln!("Troubleshooting: File exist? Valid Format? Permissions? Corrupted File?");
+        std::process::exit(exit_code(&e));
     }
 
-    fn clear_screen(&self) {
-        print!("\x1B[2J\x1B[1;1H");
-        io::stdout().flush().unwrap();
+    let opened: Vec<Document> = names
+        .into_iter()
+        .zip(opened)
+        .filter_map(|(name, sentences)| Some((name, sentences?)))
+        .collect();
+    if opened.len() > 1 && reading.sentences > 0 {
+        println!("Read {} across {} documents", reading, opened.len());
     }
+    if let Some(export_path) = &args.export {
+        let metadata = documents_metadata(&opened);
+        write_exports(
+            &opened,
+            &metadata,
+            args.export_format,
+            Some(export_path),
+            None,
+        )?;
+        println!("Exported {} documents to {}", opened.len(), export_path);
+    }
+    Ok(())
+}
+
+fn show_info(args: &ReadArgs) -> Result<()> {
+    let (inputs, _downloads) = input_paths(&args.files, &args.parse, false)?;
 
-    fn show_instructions(&self) {
-        println!("ODT Navigator");
-        println!("==========================");
-        println!();
-        println!("Commands:");
-        println!(" Enter/n/next -> Next sentence");
-        println!(" p/prev       -> Prev sentence");
-        println!(" f/first      -> Go to first sentence");
-        println!(" l/last       -> Go to last sentence");
-        println!(" [number]     -> Jump to sentence number");
-        println!(" h/help       -> Show this help...");
-        println!(" q/quit       -> Quit");
-        println!();
-        println!("Press Enter to start...");
+    for (i, (name, path)) in inputs.iter().enumerate() {
+        let metadata = parser::read_metadata(path)?;
+        if i > 0 {
+            println!();
+        }
+        println!("{}", name);
+        if metadata.is_empty() {
+            println!("  No metadata recorded");
+        }
+        let fields = [
+            ("Title:", metadata.title),
+            ("Author:", metadata.author),
+            ("Created:", metadata.created),
+            ("Modified:", metadata.modified),
+            ("Words:", metadata.word_count.map(|count| count.to_string())),
+        ];
+        for (label, value) in fields {
+            if let Some(value) = value {
+                println!("  {:<10}{}", label, value);
+            }
+        }
 
-        self.get_user_input().ok();
-        self.clear_screen();
+        // Headers and footers are left out of reading unless asked for
+        if DocumentFormat::detect(path)? == DocumentFormat::Odt {
+            let parser =
+                OdtParser::with_splitter(args.parse.splitter()?).with_options(args.parse.options());
+            for sentence in parser.page_sentences(path)? {
+                let label = match sentence.kind {
+                    SentenceKind::Header => "Header:",
+                    _ => "Footer:",
+                };
+                println!("  {:<10}{}", label, sentence.text);
+            }
+        }
     }
+    Ok(())
+}
 
-    fn display_sentence(&self, sentence: &str, current: usize, total: usize) {
-        println!("ODT Navigator");
-        println!("==========================");
-        println!();
-        println!("Sentence {} of {}", current, total);
-        println!(
-            "Progress: [{}{}] {:.1}%",
-            "█".repeat(current * 30 / total),
-            "░".repeat(30 - (current * 30 / total)),
-            (current as f64 / total as f64) * 100.0
-        );
-        println!();
-        println!("┌─────────────────────────────────────────────────────────────┐");
+// How the reader came to a document
+enum Arrival {
+    Opened,
+    Switched,
+    // Passed on from a document that was empty or could not be read, with why
+    Returned(String),
+}
 
-        let wrapped_lines = self.wrap_text(sentence, 59);
-        for line in wrapped_lines {
-            println!("| {:<59} |", line);
-        }
+// How opening a document went
+enum Opening {
+    Read(Visit),
+    // The document has no sentences
+    Empty,
+    // The document could not be downloaded or parsed
+    Failed(SentencerError),
+}
+
+// How reading one document ended
+struct Visit {
+    sentences: Vec<Sentence>,
+    position: usize,
+    reading: ReadingStats,
+    next_document: Option<usize>,
+}
 
-        println!("└─────────────────────────────────────────────────────────────┘");
-        println!();
-        println!("Command (Enter=next, p=prev, h=help, q=quit)");
-        io::stdout().flush().unwrap();
+// Reads documents[index] until the reader quits or moves to another document.
+// `earlier` is the reading done in the other documents since starting, which
+// the reading speed is shared with.
+fn read_document(
+    args: &ReadArgs,
+    documents: &[DocumentEntry],
+    index: usize,
+    arrival: Arrival,
+    earlier: &ReadingStats,
+    mut session_file: Option<&mut Session>,
+    events: Option<&Sender<ReaderEvent>>,
+) -> Result<Opening> {
+    let splitter = args.parse.splitter()?;
+    let ui = args.parse.config.ui.clone();
+    let keymap = Keymap::from_config(&args.parse.config.keys)?;
+    let document = documents[index].name.as_str();
+    let download = match remote::is_url(document) {
+        true => match fetch(document, &args.parse) {
+            Ok(download) => Some(download),
+            Err(e) => return Ok(Opening::Failed(e)),
+        },
+        false => None,
+    };
+    let file_path = download
+        .as_ref()
+        .map_or(document, |download| download.path());
+    // Edits to a downloaded document are saved to a local copy
+    let output_path = args.output.clone().or_else(|| {
+        download
+            .as_ref()
+            .map(|download| download.file_name().to_string())
+    });
+
+    println!("Parsing file: {}", document);
+    println!("Please wait... \n");
+
+    let mut options = args.parse.options();
+    if options.password.is_none()
+        && io::stdin().is_terminal()
+        && OdtParser::is_encrypted(file_path).unwrap_or(false)
+    {
+        options.password = Some(rpassword::prompt_password("Password: ")?);
     }
 
-    fn wrap_text(&self, text: &str, width: usize) -> Vec<String> {
-        let mut lines = Vec::new();
-        let mut current_line = String::new();
+    match parser::parser_for(file_path, splitter.clone(), options.clone())
+        .and_then(|parser| parser.parse_file(file_path))
+    {
+        Ok(sentences) => {
+            if sentences.is_empty() {
+                println!("No sentences found in the document.");
+                return Ok(Opening::Empty);
+            }
+
+            println!("Sucessfully parsed {} sentences!", sentences.len());
+            println!("Starting interactive mode... \n");
 
-        for word in text.split_whitespace() {
-            if current_line.len() + word.len() + 1 <= width {
-                if !current_line.is_empty() {
-                    current_line.push(' ');
+            // Reading state is a convenience, so problems with it never stop the navigator
+            let mut store = StateStore::open_default().ok();
+            let saved_state = store
+                .as_ref()
+                .zip(state::document_key(file_path).ok())
+                .and_then(|(store, key)| store.document(&key).cloned())
+                .unwrap_or_default();
+            let saved_position = Some(saved_state.position)
+                .filter(|&position| position > 0 && position < sentences.len());
+
+            // Documents moved to from another one continue where they were left
+            let start_position = match (&session_file, saved_position) {
+                (Some(session), _) => session.position,
+                (None, Some(position))
+                    if !matches!(arrival, Arrival::Opened)
+                        || args.resume
+                        || ui.resume
+                        || confirm_resume(position)? =>
+                {
+                    position
                 }
-                current_line.push_str(word);
-            } else {
-                if !current_line.is_empty() {
-                    lines.push(current_line);
-                    current_line = String::new();
-                }
-                if word.len() <= width {
-                    current_line.push_str(word);
-                } else {
-                    //break long words
-                    lines.push(word[..width].to_string());
-                    if word.len() > width {
-                        current_line = word[width..].to_string();
-                    }
+                _ => 0,
+            };
+            let (bookmarks, annotations, edits) = match &session_file {
+                Some(session) => (
+                    session.bookmarks.clone(),
+                    session.annotations.clone(),
+                    session.edits.clone(),
+                ),
+                None => (
+                    saved_state.bookmarks.clone(),
+                    saved_state.annotations.clone(),
+                    Vec::new(),
+                ),
+            };
+            let mut history = saved_state.reading;
+            history.add(earlier);
+
+            #[cfg(feature = "spellcheck")]
+            let spell_checker = spell_checker(&args.parse, &sentences)?;
+            let mut navigator = Navigator::new(sentences)
+                .with_edits(&edits)
+                .with_save_path(file_path, output_path.as_deref())
+                .with_save_options(args.save.options())
+                .with_position(start_position)
+                .with_kind_filter(args.kind)
+                .with_external_editor(args.editor || ui.editor)
+                .with_read_only(args.read_only)
+                .with_context(ui.context)
+                .with_speaking(args.speak)
+                .with_grammar_checker(grammar_checker(None, &args.parse))
+                .with_rewriter(rewriter(&args.parse))
+                .with_keymap(keymap)
+                .with_bookmarks(bookmarks)
+                .with_annotations(annotations)
+                .with_reading_history(history)
+                .with_documents(documents.to_vec(), index)
+                .with_metadata(parser::read_metadata(file_path).unwrap_or_default());
+            if let Some(events) = events {
+                navigator = navigator.with_events(events.clone());
+            }
+            if let Some(max_width) = ui.max_width {
+                navigator = navigator.with_max_width(max_width);
+            }
+            if let Some(command) = &ui.speech_command {
+                navigator = navigator.with_speech_command(command);
+            }
+            #[cfg(feature = "spellcheck")]
+            if let Some(spell_checker) = spell_checker {
+                navigator = navigator.with_spell_checker(spell_checker);
+            }
+            if let Arrival::Returned(message) = arrival {
+                navigator = navigator.with_message(message);
+            }
+            if args.watch || ui.watch {
+                let path = file_path.to_string();
+                navigator = navigator.with_watch(move || {
+                    parser::parser_for(&path, splitter.clone(), options.clone())?.parse_file(&path)
+                })?;
+            }
+            navigator.run()?;
+
+            let read_now = navigator.reading_session();
+            let mut reading = saved_state.reading;
+            reading.add(&read_now);
+            if read_now.sentences > 0 && documents.len() == 1 {
+                println!("Read {}", read_now);
+                println!("Over {} sessions: {}", reading.sessions, reading);
+            }
+
+            if let (Some(session), Some(session_path)) = (session_file.as_mut(), &args.session) {
+                session.position = navigator.position();
+                session.bookmarks = navigator.bookmarks().clone();
+                session.annotations = navigator.annotations().clone();
+                session.edits = navigator.edits();
+                session.save(session_path)?;
+                println!("Saved session to {}", session_path);
+            }
+
+            // The key is computed again because saving edits changes the content hash
+            if let (Some(store), Ok(key)) = (store.as_mut(), state::document_key(file_path)) {
+                let state = store.document_mut(&key);
+                state.position = navigator.position();
+                state.bookmarks = navigator.bookmarks().clone();
+                state.annotations = navigator.annotations().clone();
+                state.reading = reading;
+                if let Err(e) = store.save() {
+                    eprintln!("Could not save reading position: {}", e);
                 }
             }
+
+            Ok(Opening::Read(Visit {
+                sentences: navigator.sentences().to_vec(),
+                position: navigator.position(),
+                reading: read_now,
+                next_document: navigator.next_document(),
+            }))
         }
+        Err(e) => Ok(Opening::Failed(e)),
+    }
+}
 
-        if !current_line.is_empty() {
-            lines.push(current_line);
+// A parsed document and the path it was read from
+type Document = (String, Vec<Sentence>);
+
+// Files that fail are reported and skipped; the returned exit code reflects
+// the first failure
+fn parse_documents(patterns: &[String], parse: &ParseArgs) -> Result<(Vec<Document>, Option<i32>)> {
+    let (inputs, _downloads) = input_paths(patterns, parse, false)?;
+    let paths: Vec<String> = inputs.iter().map(|(_, path)| path.clone()).collect();
+    let results = batch::parse_files(&paths, &parse.splitter()?, &parse.options());
+
+    let mut failure = None;
+    let mut documents = Vec::new();
+    for ((path, _), result) in inputs.into_iter().zip(results) {
+        match result {
+            Ok(sentences) => documents.push((path, sentences)),
+            Err(e) => {
+                eprintln!("Error parsing file: '{}': {}", path, e);
+                failure.get_or_insert(exit_code(&e));
+            }
         }
+    }
 
-        if lines.is_empty() {
-            lines.push(String::new());
+    Ok((documents, failure))
+}
+
+// The name a document is reported under and the path it is read from
+type Input = (String, String);
+
+// Patterns are expanded and remote documents downloaded, keeping their URL as
+// their name. The downloads are deleted once the returned list is dropped.
+fn input_paths(
+    patterns: &[String],
+    parse: &ParseArgs,
+    recursive: bool,
+) -> Result<(Vec<Input>, Vec<Download>)> {
+    let mut inputs = Vec::new();
+    let mut downloads = Vec::new();
+    for pattern in patterns {
+        if remote::is_url(pattern) {
+            let download = fetch(pattern, parse)?;
+            inputs.push((pattern.clone(), download.path().to_string()));
+            downloads.push(download);
+            continue;
         }
 
-        lines
+        let paths = if recursive && Path::new(pattern).is_dir() {
+            batch::find_documents(pattern)?
+        } else {
+            batch::expand_patterns(&[pattern])?
+        };
+        inputs.extend(paths.into_iter().map(|path| (path.clone(), path)));
+    }
+
+    Ok((inputs, downloads))
+}
+
+// Progress is shown only when stderr is a terminal
+fn fetch(url: &str, parse: &ParseArgs) -> Result<Download> {
+    let show_progress = io::stderr().is_terminal();
+    let max_size = parse.max_download_size * 1024 * 1024;
+
+    let download = remote::download(url, max_size, |received, total| {
+        if !show_progress {
+            return;
+        }
+        match total {
+            Some(total) => eprint!("\rDownloading {}: {}%", url, received * 100 / total.max(1)),
+            None => eprint!("\rDownloading {}: {} KiB", url, received / 1024),
+        }
+    });
+    if show_progress {
+        eprintln!();
     }
+    download
+}
 
-    fn get_user_input(&self) -> Result<String, Box<dyn std::error::Error>> {
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        Ok(input.trim().to_lowercase())
+fn finish(failure: Option<i32>) -> Result<()> {
+    if let Some(code) = failure {
+        std::process::exit(code);
     }
+    Ok(())
+}
 
-    fn extract_text_from_xml(
-        &self,
-        xml_content: &str,
-    ) -> Result<String, Box<dyn std::error::Error>> {
-        let mut reader = Reader::from_str(xml_content);
-        reader.trim_text(true);
+fn export_files(args: ExportArgs) -> Result<()> {
+    if !args.cloze.is_empty() && args.format != ExportFormat::Anki {
+        return Err(SentencerError::InvalidArgument(
+            "--cloze is only used with --format anki".to_string(),
+        ));
+    }
+    let subtitles = matches!(args.format, ExportFormat::Srt | ExportFormat::Vtt);
+    if args.wpm.is_some() && !subtitles {
+        return Err(SentencerError::InvalidArgument(
+            "--wpm is only used with --format srt or vtt".to_string(),
+        ));
+    }
+    if args.format == ExportFormat::Jsonl {
+        return stream_exports(&args);
+    }
+    if args.format == ExportFormat::Odt
+        && args.output.is_none()
+        && args.output_dir.is_none()
+        && io::stdout().is_terminal()
+    {
+        return Err(SentencerError::InvalidArgument(
+            "ODT export needs --output or --output-dir".to_string(),
+        ));
+    }
 
-        let mut text_content = String::new();
-        let mut buf = Vec::new();
-        let mut in_text_element = false;
+    let filter = args.filter.filter()?;
+    let (mut documents, failure) = parse_documents(&args.files, &args.parse)?;
+    for (_, sentences) in &mut documents {
+        filter.retain(sentences);
+    }
+    if args.format == ExportFormat::Anki {
+        export_anki(&documents, &args)?;
+        return finish(failure);
+    }
+    if subtitles {
+        export_subtitles(&documents, &args)?;
+        return finish(failure);
+    }
 
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref e)) => match e.name().as_ref() {
-                    b"text:p" | b"text.span" | b"text.h" => {
-                        in_text_element = true;
-                    }
-                    _ => {}
-                },
-                Ok(Event::End(ref e)) => match e.name().as_ref() {
-                    b"text:p" | b"text.h" => {
-                        text_content.push(' ');
-                        in_text_element = false;
-                    }
-                    b"text:span" => {
-                        in_text_element = false;
-                    }
-                    _ => {}
-                },
-                Ok(Event::Text(e)) => {
-                    if in_text_element {
-                        let text = e.unescape()?;
-                        text_content.push_str(&text);
-                    }
-                }
-                Ok(Event::Eof) => break,
-                Err(e) => return Err(format!("Error parsing XML: {}", e).into()),
-                _ => {}
+    let metadata = documents_metadata(&documents);
+    write_exports(
+        &documents,
+        &metadata,
+        args.format,
+        args.output.as_deref(),
+        args.output_dir.as_deref(),
+    )?;
+    finish(failure)
+}
+
+fn stats(args: StatsArgs) -> Result<()> {
+    let (inputs, downloads) = input_paths(&args.files, &args.parse, args.recursive)?;
+    let paths: Vec<String> = inputs.iter().map(|(_, path)| path.clone()).collect();
+
+    // Only the numbers are kept, so large trees do not fill memory
+    let results = batch::stats_files(
+        &paths,
+        &args.parse.splitter()?,
+        &args.parse.options(),
+        &args.filter.filter()?,
+    );
+    drop(downloads);
+    let mut failure = None;
+    let mut files = Vec::new();
+    for ((file, _), result) in inputs.into_iter().zip(results) {
+        match result {
+            Ok(stats) => files.push(FileStats { file, stats }),
+            Err(e) => {
+                eprintln!("Error parsing file: '{}': {}", file, e);
+                failure.get_or_insert(exit_code(&e));
             }
-            buf.clear();
         }
-
-        Ok(text_content)
     }
+    let report = StatsReport::new(files);
+
+    if args.format == "json" {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, &report)?;
+        writeln!(stdout)?;
+    } else {
+        for file in &report.files {
+            print_stats(&file.file, &file.stats);
+        }
+        if report.files.len() > 1 {
+            print_stats(
+                &format!("Total ({} files)", report.files.len()),
+                &report.total,
+            );
+        }
+    }
+    finish(failure)
+}
 
-    fn split_into_sentences(&self, text: &str) -> Vec<String> {
-        let cleaned_text = text.trim().replace('\n', " ");
-        let cleaned_text = Regex::new(r"\s+").unwrap().replace_all(&cleaned_text, " ");
+fn print_stats(title: &str, stats: &DocumentStats) {
+    println!("{}", title);
+    println!("  Sentences:  {}", stats.sentences);
+    println!(
+        "  Words:      {} ({:.1} per sentence)",
+        stats.words,
+        stats.average_words()
+    );
+    println!("  Characters: {}", stats.characters);
+    println!("  Paragraphs: {}", stats.paragraphs);
+    println!("  Longest:    {} words", stats.longest_sentence);
+}
 
-        let parts: Vec<&str> = self.sentence_regex.split(&cleaned_text).collect();
+fn search(args: SearchArgs) -> Result<()> {
+    let query = SearchQuery::new(&args.pattern, args.regex)?;
+    let (documents, failure) = parse_documents(&args.files, &args.parse)?;
 
-        let mut sentences = Vec::new();
-        for (i, part) in parts.iter().enumerate() {
-            let trimmed = part.trim();
-            if !trimmed.is_empty() {
-                if i < parts.len() - 1 {
-                    let next_start =
-                        part.as_ptr() as usize + part.len() - cleaned_text.as_ptr() as usize;
-                    if let Some(punct_match) =
-                        self.sentence_regex.find_at(&cleaned_text, next_start)
-                    {
-                        let punct = punct_match.as_str().trim();
-                        sentences.push(format!("{}{}", trimmed, punct));
-                    } else {
-                        sentences.push(trimmed.to_string());
-                    }
-                } else {
-                    sentences.push(trimmed.to_string());
-                }
+    // Like grep, file names are only shown when several files are searched
+    let show_file = documents.len() > 1;
+    for (path, sentences) in &documents {
+        for index in query.matching_indices(sentences) {
+            if show_file {
+                print!("{}:", path);
             }
+            println!("{}: {}", index + 1, sentences[index].text);
         }
-
-        sentences
     }
+    finish(failure)
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = std::env::args().collect();
+// Unlike search, documents are searched as they are parsed, so only the
+// matching sentences are held in memory
+fn grep(args: GrepArgs) -> Result<()> {
+    let query = SearchQuery::new(&args.pattern, args.regex)?;
+    let (inputs, downloads) = input_paths(&args.paths, &args.parse, args.recursive)?;
+    let paths: Vec<String> = inputs.iter().map(|(_, path)| path.clone()).collect();
+    let results = batch::search_files(
+        &paths,
+        &args.parse.splitter()?,
+        &args.parse.options(),
+        &query,
+    );
+    drop(downloads);
+
+    let mut failure = None;
+    let mut found = Vec::new();
+    for ((file, _), result) in inputs.into_iter().zip(results) {
+        match result {
+            Ok(matches) => found.push((file, matches)),
+            Err(e) => {
+                eprintln!("Error parsing file: '{}': {}", file, e);
+                failure.get_or_insert(exit_code(&e));
+            }
+        }
+    }
 
-    if args.len() != 2 {
-        eprintln!("Usage: {} <odt_file>", args[0]);
-        eprintln!("Example: {} document.odt", args[0]);
-        std::process::exit(1);
+    if args.format == "json" {
+        let report: Vec<serde_json::Value> = found
+            .iter()
+            .flat_map(|(file, matches)| {
+                matches.iter().map(move |(index, sentence)| {
+                    serde_json::json!({
+                        "file": file,
+                        "sentence": index + 1,
+                        "text": sentence.text,
+                    })
+                })
+            })
+            .collect();
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, &report)?;
+        writeln!(stdout)?;
+        return finish(failure);
     }
 
-    let file_path = &args[1];
-    let parser = OdtParser::new()?;
+    let color = io::stdout().is_terminal();
+    for (file, matches) in &found {
+        for (index, sentence) in matches {
+            let text = match color {
+                true => highlight_matches(&sentence.text, &query),
+                false => sentence.text.clone(),
+            };
+            println!("{}:{}: {}", file, index + 1, text);
+        }
+    }
+    finish(failure)
+}
 
-    println!("Parsing ODT file: {}", file_path);
-    println!("Please wait... \n");
+#[cfg(feature = "index")]
+fn open_index(dir: Option<PathBuf>) -> Result<SentenceIndex> {
+    let dir = match dir {
+        Some(dir) => dir,
+        None => dirs::data_dir()
+            .ok_or_else(|| {
+                SentencerError::InvalidArgument(
+                    "Could not determine the user data directory; pass --index".to_string(),
+                )
+            })?
+            .join("sentencer")
+            .join("index"),
+    };
+    SentenceIndex::open(&dir)
+}
 
-    match parser.parse_file(file_path) {
-        Ok(sentences) => {
-            if sentences.is_empty() {
-                println!("No sentences found in the document.");
-                return Ok(());
+// Local documents are indexed by their absolute path, so results can be
+// opened from any directory
+#[cfg(feature = "index")]
+fn build_index(args: IndexBuildArgs) -> Result<()> {
+    let index = open_index(args.index)?;
+    let (inputs, downloads) = input_paths(&args.paths, &args.parse, true)?;
+    let paths: Vec<String> = inputs.iter().map(|(_, path)| path.clone()).collect();
+    let results = batch::parse_files(&paths, &args.parse.splitter()?, &args.parse.options());
+    drop(downloads);
+
+    let mut failure = None;
+    let mut documents = Vec::new();
+    for ((name, path), result) in inputs.into_iter().zip(results) {
+        match result {
+            Ok(sentences) => {
+                let file = match remote::is_url(&name) {
+                    true => name,
+                    false => fs::canonicalize(&path)?.to_string_lossy().into_owned(),
+                };
+                documents.push((file, sentences));
             }
+            Err(e) => {
+                eprintln!("Error parsing file: '{}': {}", name, e);
+                failure.get_or_insert(exit_code(&e));
+            }
+        }
+    }
 
-            println!("Sucessfully parsed {} sentences!", sentences.len());
-            println!("Starting interactive mode... \n");
+    let added = index.add_documents(&documents)?;
+    println!(
+        "Indexed {} sentences from {} documents.",
+        added,
+        documents.len()
+    );
+    finish(failure)
+}
 
-            parser.interactive_mode(sentences)?;
-        }
-        Err(e) => {
-            eprintln!("Error parsing file: '{}': {}", file_path, e);
-            eprintln!("Troubleshooting: File exist? Valid Format? Permissions? Corrupted File?");
-            std::process::exit(1);
-        }
+#[cfg(feature = "index")]
+fn query_index(args: IndexQueryArgs) -> Result<()> {
+    let hits = open_index(args.index)?.search(&args.query, args.limit)?;
+    if args.format == "json" {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, &hits)?;
+        writeln!(stdout)?;
+        return Ok(());
     }
 
+    for hit in &hits {
+        println!("{}:{}: {}", hit.file, hit.sentence, hit.text);
+    }
+    if hits.is_empty() {
+        println!("No sentences found.");
+    }
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use std::io::Write;
-    use zip::{ZipWriter, write::FileOptions};
-
-    // Helper function to create a test ODT file
-    fn create_test_odt_file(
-        file_path: &str,
-        content: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let file = File::create(file_path)?;
-        let mut zip = ZipWriter::new(file);
-
-        // Create mimetype file (required for ODT)
-        zip.start_file(
-            "mimetype",
-            FileOptions::default().compression_method(zip::CompressionMethod::Stored),
-        )?;
-        zip.write_all(b"application/vnd.oasis.opendocument.text")?;
-
-        // Create META-INF/manifest.xml (required for ODT)
-        zip.start_file("META-INF/manifest.xml", FileOptions::default())?;
-        let manifest = r#"<?xml version="1.0" encoding="UTF-8"?>
-<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0">
-    <manifest:file-entry manifest:full-path="/" manifest:media-type="application/vnd.oasis.opendocument.text"/>
-    <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
-</manifest:manifest>"#;
-        zip.write_all(manifest.as_bytes())?;
-
-        // Create content.xml with the test content
-        zip.start_file("content.xml", FileOptions::default())?;
-        let content_xml = format!(
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-<office:document-content 
-    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
-    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
-    <office:body>
-        <office:text>
-            {}
-        </office:text>
-    </office:body>
-</office:document-content>"#,
-            content
+fn highlight_matches(text: &str, query: &SearchQuery) -> String {
+    let mut highlighted = String::new();
+    let mut end = 0;
+    for range in query.find_ranges(text) {
+        highlighted.push_str(&text[end..range.start]);
+        highlighted.push_str(&text[range.clone()].red().bold().to_string());
+        end = range.end;
+    }
+    highlighted.push_str(&text[end..]);
+    highlighted
+}
+
+fn dedupe(args: DedupeArgs) -> Result<()> {
+    if !(0.0..=1.0).contains(&args.threshold) {
+        return Err(SentencerError::InvalidArgument(
+            "--threshold must be between 0 and 1".to_string(),
+        ));
+    }
+    let (documents, failure) = parse_documents(std::slice::from_ref(&args.file), &args.parse)?;
+    let Some((_, sentences)) = documents.first() else {
+        return finish(failure);
+    };
+
+    let options = DedupeOptions {
+        threshold: args.threshold,
+        min_words: args.min_words,
+    };
+    let report = dedupe::find_duplicates(sentences, options);
+    if args.format == "json" {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, &report)?;
+        writeln!(stdout)?;
+        return Ok(());
+    }
+
+    if report.is_empty() {
+        println!("No repeated sentences.");
+    }
+    let text = |index: usize| &sentences[index - 1].text;
+    for group in &report.exact {
+        let indices: Vec<String> = group.iter().map(usize::to_string).collect();
+        println!("Repeated as {}: {}", indices.join(", "), text(group[0]));
+    }
+    for pair in &report.near {
+        println!(
+            "Similar ({:.0}%): {} and {}",
+            pair.similarity * 100.0,
+            pair.first,
+            pair.second
         );
-        zip.write_all(content_xml.as_bytes())?;
+        println!("  {}: {}", pair.first, text(pair.first));
+        println!("  {}: {}", pair.second, text(pair.second));
+    }
+    Ok(())
+}
 
-        zip.finish()?;
-        Ok(())
+fn diff_documents(args: DiffArgs) -> Result<()> {
+    let files = [args.old.clone(), args.new.clone()];
+    let (documents, failure) = parse_documents(&files, &args.parse)?;
+    let [(_, old), (_, new)] = documents.as_slice() else {
+        return finish(failure);
+    };
+
+    let changes = diff::compare_sentences(&texts(old), &texts(new));
+    if args.format == "json" {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, &changes)?;
+        writeln!(stdout)?;
+        return Ok(());
     }
 
-    #[test]
-    fn test_odt_file_parsing() -> Result<(), Box<dyn std::error::Error>> {
-        let test_file = "test_document.odt";
-
-        // Create test content with multiple paragraphs and sentences
-        let test_content = r#"
-            <text:p>This is the first paragraph with two sentences. Here is the second sentence!</text:p>
-            <text:p>This is a second paragraph. It also has multiple sentences? Yes, it does.</text:p>
-            <text:h>This is a heading.</text:h>
-            <text:p>Final paragraph after the heading.</text:p>
-        "#;
-
-        // Create the test ODT file
-        create_test_odt_file(test_file, test_content)?;
-
-        // Parse the file
-        let parser = OdtParser::new()?;
-        let sentences = parser.parse_file(test_file)?;
-
-        // Clean up test file
-        fs::remove_file(test_file).ok();
-
-        // Verify results
-        assert!(!sentences.is_empty(), "Should have parsed some sentences");
-
-        // Check that we got the expected sentences
-        let expected_phrases = vec![
-            "This is the first paragraph with two sentences",
-            "Here is the second sentence",
-            "This is a second paragraph",
-            "It also has multiple sentences",
-            "Yes, it does",
-            "This is a heading",
-            "Final paragraph after the heading",
-        ];
+    // Colors only when a person is likely to be reading
+    let color = io::stdout().is_terminal();
+    let paint = |line: String, tint: Color| {
+        if color {
+            line.with(tint).to_string()
+        } else {
+            line
+        }
+    };
+    for change in &changes {
+        match change {
+            SentenceChange::Added { new, text } => {
+                println!("{}", paint(format!("+ {}: {}", new, text), Color::Green))
+            }
+            SentenceChange::Removed { old, text } => {
+                println!("{}", paint(format!("- {}: {}", old, text), Color::Red))
+            }
+            SentenceChange::Changed {
+                old,
+                new,
+                old_text,
+                new_text,
+            } => {
+                println!("{}", paint(format!("~ {} -> {}", old, new), Color::Yellow));
+                println!("{}", paint(format!("  - {}", old_text), Color::Red));
+                println!("{}", paint(format!("  + {}", new_text), Color::Green));
+            }
+        }
+    }
+    if changes.is_empty() {
+        println!("No sentences differ.");
+    }
+    Ok(())
+}
 
-        println!("Parsed sentences:");
-        for (i, sentence) in sentences.iter().enumerate() {
-            println!("  {}: {}", i + 1, sentence);
+fn replace(args: ReplaceArgs) -> Result<()> {
+    let substitution = Substitution::new(
+        &args.pattern,
+        &args.replacement,
+        !args.first,
+        args.ignore_case,
+    )?;
+    let (documents, failure) = parse_documents(std::slice::from_ref(&args.file), &args.parse)?;
+    let Some((_, sentences)) = documents.first() else {
+        return finish(failure);
+    };
+
+    let changes = substitution.changes(&texts(sentences));
+    if changes.is_empty() {
+        println!("No sentences match '{}'.", args.pattern);
+        return Ok(());
+    }
+    let color = io::stdout().is_terminal();
+    for (index, text) in &changes {
+        let old = format!("- {}: {}", index + 1, sentences[*index].text);
+        let new = format!("+ {}: {}", index + 1, text);
+        if color {
+            println!("{}\n{}", old.red(), new.green());
+        } else {
+            println!("{}\n{}", old, new);
         }
+    }
 
-        // Verify we have at least the expected number of sentences
-        assert!(
-            sentences.len() >= 6,
-            "Should have at least 6 sentences, got {}",
-            sentences.len()
+    let output_path = args.output.as_deref().unwrap_or(&args.file);
+    if !args.yes && !args.save.dry_run {
+        print!(
+            "Replace in {} sentences and save to {}? [y/N] ",
+            changes.len(),
+            output_path
         );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Nothing was changed.");
+            return Ok(());
+        }
+    }
 
-        // Check that some expected content is present
-        let all_text = sentences.join(" ");
-        assert!(
-            all_text.contains("first paragraph"),
-            "Should contain 'first paragraph'"
-        );
-        assert!(
-            all_text.contains("second sentence"),
-            "Should contain 'second sentence'"
+    let original: Vec<String> = changes
+        .iter()
+        .map(|(index, _)| sentences[*index].text.clone())
+        .collect();
+    let edited: Vec<String> = changes.into_iter().map(|(_, text)| text).collect();
+    sentencer::save_odt_with(
+        &args.file,
+        output_path,
+        &original,
+        &edited,
+        &args.save.options(),
+    )?;
+    if args.save.dry_run {
+        println!(
+            "Dry run: {} sentences would change in {}; nothing was written.",
+            edited.len(),
+            output_path
         );
-        assert!(all_text.contains("heading"), "Should contain 'heading'");
+        return Ok(());
+    }
+    println!("Replaced in {} sentences.", edited.len());
+    Ok(())
+}
 
-        Ok(())
+fn vocab(args: VocabArgs) -> Result<()> {
+    let (documents, failure) = parse_documents(std::slice::from_ref(&args.file), &args.parse)?;
+    let Some((_, sentences)) = documents.first() else {
+        return finish(failure);
+    };
+
+    let mut stopwords = Stopwords::new();
+    if args.stopwords {
+        let language = args
+            .parse
+            .language
+            .or_else(|| Language::detect(&texts(sentences).join(" ")))
+            .unwrap_or(Language::English);
+        stopwords = Stopwords::for_language(language);
+    }
+    if let Some(stopword_file) = &args.stopword_file {
+        stopwords.load_file(stopword_file)?;
     }
 
-    #[test]
-    fn test_file_not_found() {
-        let parser = OdtParser::new().unwrap();
-        let result = parser.parse_file("nonexistent.odt");
-        assert!(result.is_err(), "Should return error for nonexistent file");
+    let mut frequencies = vocab::word_frequencies(sentences, &stopwords);
+    if let Some(limit) = args.limit {
+        frequencies.truncate(limit);
     }
 
-    #[test]
-    fn test_invalid_odt_file() -> Result<(), Box<dyn std::error::Error>> {
-        let test_file = "invalid.odt";
+    let mut writer = output_writer(args.output.as_deref())?;
+    if args.format == "csv" {
+        return vocab::write_csv(&mut writer, &frequencies, sentences, args.examples);
+    }
+    for frequency in &frequencies {
+        writeln!(writer, "{:>7}  {}", frequency.count, frequency.word)?;
+        for &index in frequency.sentences.iter().take(args.examples) {
+            writeln!(writer, "         {}: {}", index, sentences[index - 1].text)?;
+        }
+    }
+    Ok(())
+}
 
-        // Create a file that's not a valid ODT (missing content.xml)
-        let file = File::create(test_file)?;
-        let mut zip = ZipWriter::new(file);
-        zip.start_file("dummy.txt", FileOptions::default())?;
-        zip.write_all(b"This is not an ODT file")?;
-        zip.finish()?;
+fn texts(sentences: &[Sentence]) -> Vec<&str> {
+    sentences
+        .iter()
+        .map(|sentence| sentence.text.as_str())
+        .collect()
+}
 
-        let parser = OdtParser::new()?;
-        let result = parser.parse_file(test_file);
+// The terms line up in one column, like a printed concordance
+fn concordance(args: ConcordanceArgs) -> Result<()> {
+    let query = SearchQuery::new(&args.term, args.regex)?;
+    let (documents, failure) = parse_documents(&args.files, &args.parse)?;
+
+    let color = io::stdout().is_terminal();
+    let show_file = documents.len() > 1;
+    for (path, sentences) in &documents {
+        for line in query.concordance(&texts(sentences), args.width) {
+            if show_file {
+                print!("{}:", path);
+            }
+            let keyword = if color {
+                line.keyword.bold().to_string()
+            } else {
+                line.keyword
+            };
+            println!(
+                "{:>5}  {:>width$}{}{}",
+                line.sentence + 1,
+                line.left,
+                keyword,
+                line.right,
+                width = args.width
+            );
+        }
+    }
+    finish(failure)
+}
 
-        // Clean up
-        fs::remove_file(test_file).ok();
+// A configured dictionary has to load; the default one for the document's
+// language is only used when it is installed
+#[cfg(feature = "spellcheck")]
+fn spell_checker(parse: &ParseArgs, sentences: &[Sentence]) -> Result<Option<SpellChecker>> {
+    if let Some(dictionary) = &parse.config.spelling.dictionary {
+        return SpellChecker::open(dictionary).map(Some);
+    }
+    let language = parse
+        .language
+        .or_else(|| Language::detect(&texts(sentences).join(" ")))
+        .unwrap_or(Language::English);
+    Ok(spelling::default_dictionary(language)
+        .and_then(|dictionary| SpellChecker::open(dictionary).ok()))
+}
 
-        assert!(result.is_err(), "Should return error for invalid ODT file");
-        Ok(())
+// The API key comes from the environment, so it stays out of the config file
+fn rewriter(parse: &ParseArgs) -> Rewriter {
+    let config = &parse.config.rewrite;
+    let mut rewriter = Rewriter::new(
+        config
+            .url
+            .as_deref()
+            .unwrap_or(rewrite::DEFAULT_REWRITE_URL),
+    );
+    if let Some(model) = &config.model {
+        rewriter = rewriter.with_model(model);
+    }
+    let key_variable = config.api_key_env.as_deref().unwrap_or("OPENAI_API_KEY");
+    match std::env::var(key_variable) {
+        Ok(api_key) if !api_key.is_empty() => rewriter.with_api_key(&api_key),
+        _ => rewriter,
+    }
+}
+
+// The --url flag takes precedence over the config file's server
+fn grammar_checker(url: Option<&str>, parse: &ParseArgs) -> LanguageTool {
+    let url = url
+        .or(parse.config.check.url.as_deref())
+        .unwrap_or(grammar::DEFAULT_LANGUAGETOOL_URL);
+    let checker = LanguageTool::new(url);
+    match parse.language {
+        Some(language) => checker.with_language(language),
+        None => checker,
+    }
+}
+
+fn check(args: CheckArgs) -> Result<()> {
+    let (documents, failure) = parse_documents(std::slice::from_ref(&args.file), &args.parse)?;
+    let Some((_, sentences)) = documents.first() else {
+        return finish(failure);
+    };
+
+    let checker = grammar_checker(args.url.as_deref(), &args.parse);
+    let issues = checker.check(&texts(sentences))?;
+    if args.format == "json" {
+        let report: Vec<serde_json::Value> = sentences
+            .iter()
+            .zip(&issues)
+            .enumerate()
+            .filter(|(_, (_, issues))| !issues.is_empty())
+            .map(|(i, (sentence, issues))| {
+                serde_json::json!({
+                    "sentence": i + 1,
+                    "text": sentence.text,
+                    "issues": issues,
+                })
+            })
+            .collect();
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, &report)?;
+        writeln!(stdout)?;
+        return Ok(());
+    }
+
+    let color = io::stdout().is_terminal();
+    let mut total = 0;
+    for (i, (sentence, issues)) in sentences.iter().zip(&issues).enumerate() {
+        if issues.is_empty() {
+            continue;
+        }
+        println!("{}: {}", i + 1, sentence.text);
+        for issue in issues {
+            let found = &sentence.text[issue.byte_range(&sentence.text)];
+            let found = if color {
+                found.with(Color::Red).to_string()
+            } else {
+                found.to_string()
+            };
+            print!("    \"{}\": {}", found, issue.message);
+            if !issue.replacements.is_empty() {
+                print!(" → {}", issue.replacements.join(", "));
+            }
+            println!(" [{}]", issue.rule);
+        }
+        total += issues.len();
+    }
+    let flagged = issues.iter().filter(|issues| !issues.is_empty()).count();
+    match (total, flagged) {
+        (0, _) => println!("No grammar or style issues found."),
+        (1, _) => println!("1 issue."),
+        (total, 1) => println!("{} issues in 1 sentence.", total),
+        (total, flagged) => println!("{} issues in {} sentences.", total, flagged),
     }
+    finish(failure)
+}
 
-    #[test]
-    fn test_xml_extraction() -> Result<(), Box<dyn std::error::Error>> {
-        let parser = OdtParser::new()?;
-
-        // Test XML content extraction directly
-        let xml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
-<office:document-content 
-    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
-    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
-    <office:body>
-        <office:text>
-            <text:p>First paragraph.</text:p>
-            <text:p>Second paragraph with <text:span>inline text</text:span>.</text:p>
-        </office:text>
-    </office:body>
-</office:document-content>"#;
-
-        let extracted_text = parser.extract_text_from_xml(xml_content)?;
-
-        assert!(
-            extracted_text.contains("First paragraph"),
-            "Should extract first paragraph"
+fn apply(args: ApplyArgs) -> Result<()> {
+    let mut session = Session::load(&args.session)?;
+    let (documents, failure) =
+        parse_documents(std::slice::from_ref(&session.document), &args.parse)?;
+    let Some((_, sentences)) = documents.first() else {
+        return finish(failure);
+    };
+
+    let (original, edited) = session.changes(sentences)?;
+    if original.is_empty() {
+        println!("The session has no edits.");
+        return Ok(());
+    }
+    // A dry run shows the edits it would apply
+    if args.diff || args.save.dry_run {
+        let name = Path::new(&session.document)
+            .file_name()
+            .map_or(session.document.clone(), |name| {
+                name.to_string_lossy().into_owned()
+            });
+        print!(
+            "{}",
+            diff::unified_diff(
+                &original,
+                &edited,
+                &format!("a/{}", name),
+                &format!("b/{}", name)
+            )
         );
-        assert!(
-            extracted_text.contains("Second paragraph"),
-            "Should extract second paragraph"
+    }
+    if args.diff {
+        return Ok(());
+    }
+
+    let output_path = args.output.as_deref().unwrap_or(&session.document);
+    sentencer::save_odt_with(
+        &session.document,
+        output_path,
+        &original,
+        &edited,
+        &args.save.options(),
+    )?;
+    if args.save.dry_run {
+        println!(
+            "Dry run: {} edits would be applied to {}; nothing was written.",
+            edited.len(),
+            output_path
         );
-        assert!(
-            extracted_text.contains("inline text"),
-            "Should extract span text"
+        return Ok(());
+    }
+    println!("Applied {} edits to {}", edited.len(), output_path);
+    // The document now reads as edited, which the session starts from again
+    if output_path == session.document {
+        session.edits.clear();
+        session.save(&args.session)?;
+    }
+    Ok(())
+}
+
+fn time(args: TimeArgs) -> Result<()> {
+    let timings = bench::time_document(&args.file, args.parse.splitter()?, args.parse.options())?;
+    if args.format == "json" {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, &timings)?;
+        writeln!(stdout)?;
+        return Ok(());
+    }
+
+    let mebibytes = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    println!(
+        "{} ({:.1} MiB, {} characters, {} sentences)",
+        args.file,
+        mebibytes(timings.bytes as u64),
+        timings.characters,
+        timings.sentences
+    );
+    for (stage, duration) in [
+        ("Read", timings.read),
+        ("Extract", timings.extract),
+        ("Split", timings.split),
+        ("Parse", timings.parse),
+    ] {
+        println!(
+            "  {:<9}{:>10.1} ms",
+            format!("{}:", stage),
+            duration.as_secs_f64() * 1000.0
         );
+    }
+    if let Some(peak_rss) = timings.peak_rss {
+        println!("  Peak RSS: {:.1} MiB", mebibytes(peak_rss));
+    }
+    Ok(())
+}
 
-        println!("Extracted text: '{}'", extracted_text);
+#[cfg(feature = "server")]
+fn serve(args: ServeArgs) -> Result<()> {
+    let (documents, failure) = parse_documents(std::slice::from_ref(&args.file), &args.parse)?;
+    let Some((_, sentences)) = documents.into_iter().next() else {
+        return finish(failure);
+    };
+
+    let address = format!("{}:{}", args.host, args.port);
+    let mut server = DocumentServer::new(sentences, &args.file)
+        .with_output_path(args.output.as_deref())
+        .with_save_options(args.save.options());
+    println!("Serving {} on http://{}", args.file, address);
+    server.serve(&address)
+}
 
-        Ok(())
+#[cfg(feature = "server")]
+fn follow_live(port: u16) -> Result<Sender<ReaderEvent>> {
+    let (events, received) = mpsc::channel();
+    let live = LiveSync::default();
+    let address = format!("127.0.0.1:{}", port);
+    live.listen(&address)?;
+    live.follow(received);
+    println!("Following along on http://{}", address);
+    Ok(events)
+}
+
+// Metadata only adds to exports, so documents it cannot be read from, such as
+// downloads already deleted, have none
+fn documents_metadata(documents: &[Document]) -> Vec<DocumentMetadata> {
+    documents
+        .iter()
+        .map(|(path, _)| parser::read_metadata(path).unwrap_or_default())
+        .collect()
+}
+
+// A single document is exported as before; several go to one combined
+// stream, or to one file each when an output directory is given
+fn write_exports(
+    documents: &[Document],
+    metadata: &[DocumentMetadata],
+    format: ExportFormat,
+    output_path: Option<&str>,
+    output_dir: Option<&str>,
+) -> Result<()> {
+    if let Some(output_dir) = output_dir {
+        fs::create_dir_all(output_dir)?;
+        for ((path, sentences), metadata) in documents.iter().zip(metadata) {
+            let target = export_target(output_dir, path, format);
+            export::export_with_metadata(&mut File::create(target)?, sentences, metadata, format)?;
+        }
+        return Ok(());
     }
 
-    #[test]
-    fn test_sentence_splitting() {
-        let parser = OdtParser::new().unwrap();
-        let text = "This is sentence one. This is sentence two! And this is sentence three?";
-        let sentences = parser.split_into_sentences(text);
+    let mut writer = output_writer(output_path)?;
+    match (documents, metadata) {
+        ([(_, sentences)], [metadata]) => {
+            export::export_with_metadata(&mut writer, sentences, metadata, format)
+        }
+        _ => export::export_batch_with_metadata(&mut writer, documents, metadata, format),
+    }
+}
 
-        assert_eq!(sentences.len(), 3);
-        assert_eq!(sentences[0], "This is sentence one.");
-        assert_eq!(sentences[1], "This is sentence two!");
-        assert_eq!(sentences[2], "And this is sentence three?");
+// JSONL rows stand on their own, so each document is written out while it is
+// being split rather than after every document has been parsed
+fn stream_exports(args: &ExportArgs) -> Result<()> {
+    let (inputs, downloads) = input_paths(&args.files, &args.parse, false)?;
+    let splitter = args.parse.splitter()?;
+    let options = args.parse.options();
+    let filter = args.filter.filter()?;
+    let tag_files = inputs.len() > 1;
+
+    let mut shared: Option<Box<dyn Write>> = match (&args.output_dir, &args.output) {
+        (Some(output_dir), _) => {
+            fs::create_dir_all(output_dir)?;
+            None
+        }
+        (None, Some(output_path)) => Some(Box::new(BufWriter::new(File::create(output_path)?))),
+        (None, None) => Some(Box::new(io::stdout().lock())),
+    };
+
+    let mut failure = None;
+    for (name, path) in &inputs {
+        let result =
+            parser::parser_for(path, splitter.clone(), options.clone()).and_then(|parser| {
+                let sentences = parser.sentences(path)?.filter(|sentence| {
+                    sentence
+                        .as_ref()
+                        .map_or(true, |sentence| filter.accepts(sentence))
+                });
+                match (&mut shared, &args.output_dir) {
+                    (Some(writer), _) => {
+                        export::stream_jsonl(writer, sentences, tag_files.then_some(name.as_str()))
+                    }
+                    (None, Some(output_dir)) => {
+                        let target = export_target(output_dir, name, args.format);
+                        let mut writer = BufWriter::new(File::create(target)?);
+                        let count = export::stream_jsonl(&mut writer, sentences, None)?;
+                        writer.flush()?;
+                        Ok(count)
+                    }
+                    (None, None) => unreachable!("stdout is used without an output directory"),
+                }
+            });
+        if let Err(e) = result {
+            eprintln!("Error exporting file: '{}': {}", name, e);
+            failure.get_or_insert(exit_code(&e));
+        }
+    }
+    if let Some(writer) = &mut shared {
+        writer.flush()?;
     }
+    drop(downloads);
+
+    finish(failure)
+}
 
-    #[test]
-    fn test_empty_text() {
-        let parser = OdtParser::new().unwrap();
-        let sentences = parser.split_into_sentences("");
-        assert_eq!(sentences.len(), 0);
+// The backs of the cards are the notes made on the sentences while reading
+fn export_anki(documents: &[Document], args: &ExportArgs) -> Result<()> {
+    let store = StateStore::open_default()?;
+    let deck = |path: &str, sentences: &[Sentence]| {
+        let annotations = store
+            .document(&state::document_key(path)?)
+            .map(|state| state.annotations.clone())
+            .unwrap_or_default();
+        anki::cards(sentences, &annotations, &args.cloze)
+    };
+
+    if let Some(output_dir) = &args.output_dir {
+        fs::create_dir_all(output_dir)?;
+        for (path, sentences) in documents {
+            let target = export_target(output_dir, path, args.format);
+            anki::write_cards(&mut File::create(target)?, &deck(path, sentences)?)?;
+        }
+        return Ok(());
     }
 
-    #[test]
-    fn test_complex_punctuation() {
-        let parser = OdtParser::new().unwrap();
-        let text = "What?! Really... Yes. No doubt about it!!! Absolutely.";
-        let sentences = parser.split_into_sentences(text);
+    let mut cards = Vec::new();
+    for (path, sentences) in documents {
+        cards.extend(deck(path, sentences)?);
+    }
+    anki::write_cards(&mut output_writer(args.output.as_deref())?, &cards)
+}
 
-        println!("Complex punctuation sentences:");
-        for (i, sentence) in sentences.iter().enumerate() {
-            println!("  {}: {}", i + 1, sentence);
+fn export_subtitles(documents: &[Document], args: &ExportArgs) -> Result<()> {
+    let wpm = args.wpm.unwrap_or(subtitles::DEFAULT_WPM);
+    if let Some(output_dir) = &args.output_dir {
+        fs::create_dir_all(output_dir)?;
+        for (path, sentences) in documents {
+            let target = export_target(output_dir, path, args.format);
+            let cues = subtitles::cues(sentences, wpm);
+            subtitles::write_subtitles(&mut File::create(target)?, &cues, args.format)?;
         }
+        return Ok(());
+    }
+
+    let cues = subtitles::cues(documents.iter().flat_map(|(_, s)| s), wpm);
+    subtitles::write_subtitles(
+        &mut output_writer(args.output.as_deref())?,
+        &cues,
+        args.format,
+    )
+}
+
+fn output_writer(output_path: Option<&str>) -> Result<Box<dyn Write>> {
+    Ok(match output_path {
+        Some(output_path) => Box::new(File::create(output_path)?),
+        None => Box::new(io::stdout().lock()),
+    })
+}
 
-        assert!(sentences.len() >= 3, "Should handle complex punctuation");
+fn export_target(output_dir: &str, path: &str, format: ExportFormat) -> PathBuf {
+    let stem = Path::new(path)
+        .file_stem()
+        .map_or("document".into(), |stem| stem.to_string_lossy());
+    Path::new(output_dir).join(format!("{}.{}", stem, format.extension()))
+}
+
+fn annotations(args: AnnotationsArgs) -> Result<()> {
+    let store = StateStore::open_default()?;
+    let key = state::document_key(&args.file)?;
+    let annotations = store
+        .document(&key)
+        .map(|state| state.annotations.clone())
+        .unwrap_or_default();
+    if annotations.is_empty() {
+        eprintln!("No annotations for '{}'.", args.file);
     }
+
+    let title = Path::new(&args.file)
+        .file_name()
+        .map_or(args.file.clone(), |name| {
+            name.to_string_lossy().into_owned()
+        });
+    let mut writer: Box<dyn Write> = match &args.output {
+        Some(output_path) => Box::new(File::create(output_path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    annotation::export_annotations(&mut writer, &annotations, &title, args.format)
+}
+
+fn confirm_resume(position: usize) -> Result<bool> {
+    print!("Resume at sentence {}? (y/n) ", position + 1);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(matches!(
+        input.trim().to_lowercase().as_str(),
+        "y" | "yes" | ""
+    ))
 }
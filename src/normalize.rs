@@ -0,0 +1,47 @@
+// Whitespace normalization shared by the parsers, the splitters and the
+// writer, which has to find sentences in paragraphs normalized exactly as
+// they were when parsed.
+
+// Zero-width space, word joiner and byte order mark: they take no room on
+// the page, so they are dropped rather than read as spaces
+pub fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{200B}' | '\u{2060}' | '\u{FEFF}')
+}
+
+// Runs of whitespace, no-break spaces and line breaks included, become one
+// space and invisible characters are dropped, in a single pass. The result
+// has no leading or trailing space.
+pub fn normalize_whitespace(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if is_invisible(c) {
+            continue;
+        }
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+        if space && !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        space = false;
+        normalized.push(c);
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_whitespace() {
+        assert_eq!(
+            normalize_whitespace("\u{FEFF} One\u{A0}\u{A0}two,\r\n\tthree\u{200B}four \u{2060}\n"),
+            "One two, threefour"
+        );
+        assert_eq!(normalize_whitespace(" \u{200B}\n "), "");
+        assert_eq!(normalize_whitespace("Already clean."), "Already clean.");
+    }
+}
@@ -0,0 +1,263 @@
+use super::{SizeLimit, Source, read_zip_entry};
+use crate::error::{Result, SentencerError};
+use aes::Aes256;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use blowfish::Blowfish;
+use cbc::cipher::block_padding::NoPadding;
+use cbc::cipher::{AsyncStreamCipher, BlockDecryptMut, KeyIvInit};
+use flate2::read::DeflateDecoder;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use zip::result::ZipError;
+
+const AES256_CBC: &str = "http://www.w3.org/2001/04/xmlenc#aes256-cbc";
+const BLOWFISH_CFB: &str = "Blowfish CFB";
+
+// How one entry of an ODF package is encrypted, as described in
+// META-INF/manifest.xml. The defaults are those of the original ODF 1.0
+// scheme, which omits them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EncryptionData {
+    checksum_type: String,
+    checksum: Vec<u8>,
+    algorithm: String,
+    iv: Vec<u8>,
+    start_key_algorithm: String,
+    key_derivation: String,
+    key_size: usize,
+    iterations: u32,
+    salt: Vec<u8>,
+}
+
+impl Default for EncryptionData {
+    fn default() -> Self {
+        EncryptionData {
+            checksum_type: "SHA1/1K".to_string(),
+            checksum: Vec::new(),
+            algorithm: BLOWFISH_CFB.to_string(),
+            iv: Vec::new(),
+            start_key_algorithm: "SHA1".to_string(),
+            key_derivation: "PBKDF2".to_string(),
+            key_size: 16,
+            iterations: 1024,
+            salt: Vec::new(),
+        }
+    }
+}
+
+// None when the entry is stored in the clear
+pub(crate) fn encryption_data(source: Source, entry_name: &str) -> Result<Option<EncryptionData>> {
+    let manifest = match read_zip_entry(source, "META-INF/manifest.xml") {
+        Ok(manifest) => manifest,
+        Err(SentencerError::Zip(ZipError::FileNotFound)) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    manifest_encryption_data(&manifest, entry_name)
+}
+
+fn manifest_encryption_data(manifest: &str, entry_name: &str) -> Result<Option<EncryptionData>> {
+    let mut reader = Reader::from_str(manifest);
+    let mut in_entry = false;
+    let mut encryption: Option<EncryptionData> = None;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let name = e.name();
+                if name.as_ref() == b"manifest:file-entry" {
+                    in_entry = attributes(e)?
+                        .iter()
+                        .any(|(key, value)| key == "manifest:full-path" && value == entry_name);
+                    continue;
+                }
+                if !in_entry {
+                    continue;
+                }
+
+                let data = encryption.get_or_insert_with(EncryptionData::default);
+                for (key, value) in attributes(e)? {
+                    match key.as_str() {
+                        "manifest:checksum-type" => data.checksum_type = value,
+                        "manifest:checksum" => data.checksum = decode(&value)?,
+                        "manifest:algorithm-name" => data.algorithm = value,
+                        "manifest:initialisation-vector" => data.iv = decode(&value)?,
+                        "manifest:start-key-generation-name" => data.start_key_algorithm = value,
+                        "manifest:key-derivation-name" => data.key_derivation = value,
+                        "manifest:key-size" => data.key_size = number(&value)?,
+                        "manifest:iteration-count" => data.iterations = number(&value)?,
+                        "manifest:salt" => data.salt = decode(&value)?,
+                        _ => {}
+                    }
+                }
+            }
+            Event::End(ref e) if in_entry && e.name().as_ref() == b"manifest:file-entry" => break,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(encryption)
+}
+
+// Returns the entry's content: ODF compresses an entry before encrypting it
+pub(crate) fn decrypt(
+    data: &[u8],
+    encryption: &EncryptionData,
+    password: &str,
+    max_size: u64,
+) -> Result<Vec<u8>> {
+    if encryption.key_derivation != "PBKDF2" {
+        return Err(unsupported(&encryption.key_derivation));
+    }
+
+    let start_key = match encryption.start_key_algorithm.to_lowercase() {
+        name if name.ends_with("sha256") => Sha256::digest(password).to_vec(),
+        name if name.ends_with("sha1") => Sha1::digest(password).to_vec(),
+        _ => return Err(unsupported(&encryption.start_key_algorithm)),
+    };
+    let mut key = vec![0; encryption.key_size];
+    pbkdf2::pbkdf2_hmac::<Sha1>(
+        &start_key,
+        &encryption.salt,
+        encryption.iterations,
+        &mut key,
+    );
+
+    let mut plain = data.to_vec();
+    match encryption.algorithm.as_str() {
+        AES256_CBC => {
+            let length = cbc::Decryptor::<Aes256>::new_from_slices(&key, &encryption.iv)
+                .map_err(|_| invalid("bad key or initialisation vector size"))?
+                .decrypt_padded_mut::<NoPadding>(&mut plain)
+                .map_err(|_| invalid("encrypted data is not a whole number of blocks"))?
+                .len();
+            // The last byte gives the length of the padding
+            let padding = plain.last().copied().unwrap_or(0) as usize;
+            if !(1..=16).contains(&padding) || padding > length {
+                return Err(wrong_password());
+            }
+            plain.truncate(length - padding);
+        }
+        BLOWFISH_CFB => {
+            cfb_mode::Decryptor::<Blowfish>::new_from_slices(&key, &encryption.iv)
+                .map_err(|_| invalid("bad key or initialisation vector size"))?
+                .decrypt(&mut plain);
+        }
+        algorithm => return Err(unsupported(algorithm)),
+    }
+
+    // The checksum covers the first kilobyte of the decrypted data
+    let checked = &plain[..plain.len().min(1024)];
+    let checksum = match encryption.checksum_type.to_lowercase() {
+        name if name.contains("sha256") => Sha256::digest(checked).to_vec(),
+        _ => Sha1::digest(checked).to_vec(),
+    };
+    if checksum != encryption.checksum {
+        return Err(wrong_password());
+    }
+
+    let mut content = Vec::new();
+    SizeLimit::new(
+        DeflateDecoder::new(plain.as_slice()),
+        "content.xml",
+        max_size,
+    )
+    .read_to_end(&mut content)?;
+
+    Ok(content)
+}
+
+fn attributes(element: &BytesStart) -> Result<Vec<(String, String)>> {
+    element
+        .attributes()
+        .map(|attr| {
+            let attr = attr?;
+            let key = String::from_utf8(attr.key.as_ref().to_vec())?;
+            Ok((key, attr.unescape_value()?.into_owned()))
+        })
+        .collect()
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+    BASE64
+        .decode(value)
+        .map_err(|e| invalid(&format!("bad base64 value in manifest: {}", e)))
+}
+
+fn number<T: std::str::FromStr>(value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid(&format!("bad number '{}' in manifest", value)))
+}
+
+fn invalid(message: &str) -> SentencerError {
+    SentencerError::InvalidDocument(message.to_string())
+}
+
+fn unsupported(algorithm: &str) -> SentencerError {
+    SentencerError::Decrypt(format!("unsupported encryption '{}'", algorithm))
+}
+
+fn wrong_password() -> SentencerError {
+    SentencerError::Decrypt("wrong password".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cbc::cipher::BlockEncryptMut;
+    use cbc::cipher::block_padding::Pkcs7;
+    use flate2::Compression;
+    use flate2::write::DeflateEncoder;
+    use std::io::Write;
+
+    #[test]
+    fn test_decrypt_aes_entry() -> Result<()> {
+        let manifest = r#"<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0">
+ <manifest:file-entry manifest:full-path="/" manifest:media-type="application/vnd.oasis.opendocument.text"/>
+ <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml" manifest:size="27">
+  <manifest:encryption-data manifest:checksum-type="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0#sha256-1k" manifest:checksum="AAAA">
+   <manifest:algorithm manifest:algorithm-name="http://www.w3.org/2001/04/xmlenc#aes256-cbc" manifest:initialisation-vector="AAAAAAAAAAAAAAAAAAAAAA=="/>
+   <manifest:start-key-generation manifest:start-key-generation-name="http://www.w3.org/2000/09/xmldsig#sha256" manifest:key-size="32"/>
+   <manifest:key-derivation manifest:key-derivation-name="PBKDF2" manifest:key-size="32" manifest:iteration-count="100" manifest:salt="AQIDBAUGBwgJCgsMDQ4PEA=="/>
+  </manifest:encryption-data>
+ </manifest:file-entry>
+ <manifest:file-entry manifest:full-path="mimetype" manifest:media-type=""/>
+</manifest:manifest>"#;
+
+        let mut encryption = manifest_encryption_data(manifest, "content.xml")?.unwrap();
+        assert_eq!(manifest_encryption_data(manifest, "mimetype")?, None);
+        assert_eq!(encryption.algorithm, AES256_CBC);
+        assert_eq!(encryption.iterations, 100);
+        assert_eq!(encryption.salt, (1..=16).collect::<Vec<u8>>());
+
+        // Encrypt the way an office suite would
+        let mut compressor = DeflateEncoder::new(Vec::new(), Compression::default());
+        compressor.write_all(b"<text:p>Secret text.</text:p>")?;
+        let compressed = compressor.finish()?;
+        encryption.checksum = Sha256::digest(&compressed).to_vec();
+        let mut key = [0; 32];
+        pbkdf2::pbkdf2_hmac::<Sha1>(&Sha256::digest("hunter2"), &encryption.salt, 100, &mut key);
+        let mut encrypted = compressed.clone();
+        encrypted.resize(compressed.len() + 16 - compressed.len() % 16, 0);
+        cbc::Encryptor::<Aes256>::new_from_slices(&key, &encryption.iv)
+            .unwrap()
+            .encrypt_padded_mut::<Pkcs7>(&mut encrypted, compressed.len())
+            .unwrap();
+
+        assert_eq!(
+            decrypt(&encrypted, &encryption, "hunter2", 1024)?,
+            b"<text:p>Secret text.</text:p>"
+        );
+        assert!(matches!(
+            decrypt(&encrypted, &encryption, "letmein", 1024),
+            Err(SentencerError::Decrypt(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+use super::{DocumentParser, Limits, Paragraph, Source, open_zip_entry, split_paragraphs};
+use crate::error::Result;
+use crate::sentence::{Sentence, SentenceKind};
+use crate::splitter::Splitter;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::io::BufRead;
+
+#[derive(Debug)]
+pub struct DocxParser {
+    splitter: Splitter,
+}
+
+impl DocxParser {
+    pub fn new() -> Result<Self> {
+        let splitter = Splitter::regex()?;
+
+        Ok(DocxParser { splitter })
+    }
+
+    pub fn with_splitter(splitter: Splitter) -> Self {
+        DocxParser { splitter }
+    }
+
+    #[cfg(feature = "native")]
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::File(file_path))
+    }
+
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::Bytes(data))
+    }
+
+    fn parse_source(&self, source: Source) -> Result<Vec<Sentence>> {
+        let content = self.document_xml(source)?;
+
+        let paragraphs = self.extract_paragraphs(content)?;
+
+        let sentences = split_paragraphs(&self.splitter, &paragraphs);
+
+        Ok(sentences)
+    }
+
+    // word/document.xml, decompressed as it is read
+    pub(crate) fn document_xml<'a>(&self, source: Source<'a>) -> Result<Box<dyn BufRead + 'a>> {
+        let max_size = Limits::default().max_entry_size;
+        open_zip_entry(source.open()?, source.name(), "word/document.xml", max_size)
+    }
+
+    pub fn extract_text_from_xml(&self, xml_content: &str) -> Result<String> {
+        self.extract_text(xml_content.as_bytes())
+    }
+
+    pub fn extract_text<R: BufRead>(&self, content: R) -> Result<String> {
+        let paragraphs = self.extract_paragraphs(content)?;
+
+        let mut text_content = String::new();
+        for paragraph in paragraphs {
+            text_content.push_str(&paragraph.text);
+            text_content.push(' ');
+        }
+
+        Ok(text_content)
+    }
+
+    // Paragraphs styled "Heading N" become standalone headings, and numbered
+    // paragraphs list items
+    fn extract_paragraphs<R: BufRead>(&self, content: R) -> Result<Vec<Paragraph>> {
+        let mut reader = Reader::from_reader(content);
+
+        let mut paragraphs = Vec::new();
+        let mut current = Paragraph::default();
+        let mut buf = Vec::new();
+        let mut in_text_element = false;
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => match e.name().as_ref() {
+                    b"w:t" => in_text_element = true,
+                    b"w:numPr" if current.kind != SentenceKind::Heading => {
+                        current.kind = SentenceKind::ListItem;
+                        current.list_level.get_or_insert(1);
+                    }
+                    _ => {}
+                },
+                Ok(Event::End(ref e)) => match e.name().as_ref() {
+                    b"w:t" => {
+                        in_text_element = false;
+                    }
+                    b"w:p" => {
+                        paragraphs.push(std::mem::take(&mut current));
+                    }
+                    _ => {}
+                },
+                Ok(Event::Empty(ref e)) => match e.name().as_ref() {
+                    b"w:tab" | b"w:br" | b"w:cr" => {
+                        current.text.push(' ');
+                    }
+                    b"w:pStyle" => {
+                        if let Some(level) = heading_level(e)? {
+                            current = Paragraph::heading(std::mem::take(&mut current.text), level);
+                        } else if let Some(kind) = style_kind(e)? {
+                            // Captions stand apart from the text around them
+                            current.standalone = kind == SentenceKind::Caption;
+                            current.kind = kind;
+                        }
+                    }
+                    // Levels of numbering count from 0
+                    b"w:ilvl" if current.kind == SentenceKind::ListItem => {
+                        let level = e.try_get_attribute("w:val")?.and_then(|value| {
+                            std::str::from_utf8(&value.value)
+                                .ok()?
+                                .parse::<usize>()
+                                .ok()
+                        });
+                        current.list_level = level.map(|level| level + 1).or(current.list_level);
+                    }
+                    _ => {}
+                },
+                Ok(Event::Text(e)) if in_text_element => {
+                    let text = e.unescape()?;
+                    current.text.push_str(&text);
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(e.into()),
+                _ => {}
+            }
+            buf.clear();
+        }
+        if !current.text.is_empty() {
+            paragraphs.push(current);
+        }
+
+        Ok(paragraphs)
+    }
+}
+
+fn heading_level(style: &BytesStart) -> Result<Option<usize>> {
+    let Some(value) = style.try_get_attribute("w:val")? else {
+        return Ok(None);
+    };
+
+    let level = std::str::from_utf8(&value.value)?
+        .strip_prefix("Heading")
+        .and_then(|level| level.parse().ok());
+
+    Ok(level)
+}
+
+// Word's built-in styles for quotations, captions and list paragraphs
+fn style_kind(style: &BytesStart) -> Result<Option<SentenceKind>> {
+    let Some(value) = style.try_get_attribute("w:val")? else {
+        return Ok(None);
+    };
+
+    let kind = match value.value.as_ref() {
+        b"Quote" | b"IntenseQuote" => Some(SentenceKind::Quote),
+        b"Caption" => Some(SentenceKind::Caption),
+        b"ListParagraph" | b"ListBullet" | b"ListNumber" => Some(SentenceKind::ListItem),
+        _ => None,
+    };
+    Ok(kind)
+}
+
+impl DocumentParser for DocxParser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        DocxParser::parse_file(self, file_path)
+    }
+
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        DocxParser::parse_bytes(self, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sentence::texts;
+    use std::fs;
+    use std::fs::File;
+    use std::io::Write;
+    use zip::{ZipWriter, write::FileOptions};
+
+    // Helper function to create a test DOCX file
+    fn create_test_docx_file(file_path: &str, body: &str) -> Result<()> {
+        let file = File::create(file_path)?;
+        let mut zip = ZipWriter::new(file);
+
+        zip.start_file("[Content_Types].xml", FileOptions::default())?;
+        zip.write_all(br#"<?xml version="1.0" encoding="UTF-8"?><Types/>"#)?;
+
+        zip.start_file("word/document.xml", FileOptions::default())?;
+        let document_xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+    <w:body>
+        {}
+    </w:body>
+</w:document>"#,
+            body
+        );
+        zip.write_all(document_xml.as_bytes())?;
+
+        zip.finish()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_docx_file_parsing() -> Result<()> {
+        let test_file = "test_document.docx";
+
+        let body = r#"
+            <w:p><w:r><w:t>The first paragraph. It has </w:t></w:r><w:r><w:t>two runs.</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">Second paragraph &amp; more!</w:t></w:r></w:p>
+        "#;
+        create_test_docx_file(test_file, body)?;
+
+        let parser = DocxParser::new()?;
+        let sentences = parser.parse_file(test_file);
+
+        fs::remove_file(test_file).ok();
+
+        let sentences = sentences?;
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "The first paragraph.",
+                "It has two runs.",
+                "Second paragraph & more!"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_docx_kinds() -> Result<()> {
+        let test_file = "test_kinds.docx";
+
+        let body = r#"
+            <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Loomings</w:t></w:r></w:p>
+            <w:p><w:pPr><w:pStyle w:val="Quote"/></w:pPr><w:r><w:t>Call me Ishmael.</w:t></w:r></w:p>
+            <w:p><w:pPr><w:pStyle w:val="ListParagraph"/><w:numPr><w:ilvl w:val="1"/><w:numId w:val="3"/></w:numPr></w:pPr><w:r><w:t>Some years ago.</w:t></w:r></w:p>
+            <w:p><w:r><w:t>Never mind how long.</w:t></w:r></w:p>
+        "#;
+        create_test_docx_file(test_file, body)?;
+
+        let sentences = DocxParser::new()?.parse_file(test_file);
+
+        fs::remove_file(test_file).ok();
+
+        let sentences = sentences?;
+        let kinds: Vec<_> = sentences
+            .iter()
+            .map(|sentence| (sentence.kind, sentence.list_level))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (SentenceKind::Heading, None),
+                (SentenceKind::Quote, None),
+                (SentenceKind::ListItem, Some(2)),
+                (SentenceKind::Body, None)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_document_xml() -> Result<()> {
+        let test_file = "invalid.docx";
+
+        let file = File::create(test_file)?;
+        let mut zip = ZipWriter::new(file);
+        zip.start_file("dummy.txt", FileOptions::default())?;
+        zip.write_all(b"This is not a DOCX file")?;
+        zip.finish()?;
+
+        let parser = DocxParser::new()?;
+        let result = parser.parse_file(test_file);
+
+        fs::remove_file(test_file).ok();
+
+        assert!(result.is_err(), "Should return error for invalid DOCX file");
+        Ok(())
+    }
+}
@@ -0,0 +1,385 @@
+use super::{DocumentParser, Limits, Paragraph, SizeLimit, Source, split_paragraphs};
+use crate::error::{Result, SentencerError};
+use crate::sentence::{Sentence, SentenceKind};
+use crate::splitter::Splitter;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashMap;
+use std::io::Read;
+use zip::ZipArchive;
+
+#[derive(Debug)]
+pub struct EpubParser {
+    splitter: Splitter,
+}
+
+impl EpubParser {
+    pub fn new() -> Result<Self> {
+        let splitter = Splitter::regex()?;
+
+        Ok(EpubParser { splitter })
+    }
+
+    pub fn with_splitter(splitter: Splitter) -> Self {
+        EpubParser { splitter }
+    }
+
+    #[cfg(feature = "native")]
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::File(file_path))
+    }
+
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::Bytes(data))
+    }
+
+    pub(crate) fn parse_source(&self, source: Source) -> Result<Vec<Sentence>> {
+        let mut archive = ZipArchive::new(source.open()?)?;
+
+        let (package_path, package) = self.read_package(&mut archive)?;
+
+        let base_dir = match package_path.rfind('/') {
+            Some(index) => &package_path[..=index],
+            None => "",
+        };
+
+        let mut paragraphs = Vec::new();
+        for href in self.spine_documents(&package)? {
+            let chapter = read_entry(&mut archive, &format!("{}{}", base_dir, href))?;
+            paragraphs.extend(self.extract_paragraphs_from_xhtml(&chapter)?);
+        }
+
+        Ok(split_paragraphs(&self.splitter, &paragraphs))
+    }
+
+    // The OPF package document lists metadata, the manifest and the spine
+    fn read_package<R: Read + std::io::Seek>(
+        &self,
+        archive: &mut ZipArchive<R>,
+    ) -> Result<(String, String)> {
+        let container = read_entry(archive, "META-INF/container.xml")?;
+        let package_path = self.find_package_path(&container)?;
+        let package = read_entry(archive, &package_path)?;
+
+        Ok((package_path, package))
+    }
+
+    pub(crate) fn package_xml(&self, source: Source) -> Result<String> {
+        let mut archive = ZipArchive::new(source.open()?)?;
+
+        Ok(self.read_package(&mut archive)?.1)
+    }
+
+    fn find_package_path(&self, container_xml: &str) -> Result<String> {
+        let mut reader = Reader::from_str(container_xml);
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"rootfile" => {
+                    if let Some(path) = attribute(&e, b"full-path")? {
+                        return Ok(path);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Err(SentencerError::InvalidDocument(
+            "EPUB container.xml does not reference a package document".to_string(),
+        ))
+    }
+
+    // Returns the hrefs of the reading-order documents listed in the spine
+    fn spine_documents(&self, package_xml: &str) -> Result<Vec<String>> {
+        let mut reader = Reader::from_str(package_xml);
+        let mut manifest = HashMap::new();
+        let mut spine = Vec::new();
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                    b"item" => {
+                        if let (Some(id), Some(href)) =
+                            (attribute(&e, b"id")?, attribute(&e, b"href")?)
+                        {
+                            manifest.insert(id, percent_decode(&href));
+                        }
+                    }
+                    b"itemref" => {
+                        if let Some(idref) = attribute(&e, b"idref")? {
+                            spine.push(idref);
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        spine
+            .iter()
+            .map(|idref| {
+                manifest.get(idref).cloned().ok_or_else(|| {
+                    SentencerError::InvalidDocument(format!(
+                        "EPUB spine references unknown item '{}'",
+                        idref
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    fn extract_paragraphs_from_xhtml(&self, xhtml: &str) -> Result<Vec<Paragraph>> {
+        let mut reader = Reader::from_str(xhtml);
+
+        let mut paragraphs = Vec::new();
+        let mut current = Paragraph::default();
+        let mut skip_depth = 0;
+        let mut in_body = false;
+        // Kinds of the list items, quotes and captions being read, innermost last
+        let mut kinds = Vec::new();
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => {
+                    let name = e.local_name();
+                    if skip_depth > 0 || matches!(name.as_ref(), b"script" | b"style") {
+                        skip_depth += 1;
+                    } else if name.as_ref() == b"body" {
+                        in_body = true;
+                    } else if is_block(name.as_ref()) {
+                        flush(&mut current, &mut paragraphs);
+                        kinds.extend(block_kind(name.as_ref()));
+                        if let Some(level) = heading_level(name.as_ref()) {
+                            current = Paragraph::heading(String::new(), level);
+                        } else {
+                            current.kind = kinds.last().copied().unwrap_or_default();
+                        }
+                    }
+                }
+                Event::End(e) => {
+                    let name = e.local_name();
+                    if skip_depth > 0 {
+                        skip_depth -= 1;
+                    } else if is_block(name.as_ref()) || name.as_ref() == b"body" {
+                        flush(&mut current, &mut paragraphs);
+                        if block_kind(name.as_ref()).is_some() {
+                            kinds.pop();
+                        }
+                        current.kind = kinds.last().copied().unwrap_or_default();
+                    }
+                }
+                Event::Empty(e) if e.local_name().as_ref() == b"br" => current.text.push(' '),
+                Event::Text(e) if in_body && skip_depth == 0 => {
+                    current
+                        .text
+                        .push_str(&e.unescape_with(resolve_html_entity)?);
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        flush(&mut current, &mut paragraphs);
+
+        Ok(paragraphs)
+    }
+}
+
+impl DocumentParser for EpubParser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        EpubParser::parse_file(self, file_path)
+    }
+
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        EpubParser::parse_bytes(self, data)
+    }
+}
+
+fn read_entry<R: Read + std::io::Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<String> {
+    let entry = archive.by_name(name)?;
+    let mut content = String::new();
+    SizeLimit::new(entry, name, Limits::default().max_entry_size).read_to_string(&mut content)?;
+    Ok(content)
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>> {
+    for attr in element.attributes() {
+        let attr = attr?;
+        if attr.key.local_name().as_ref() == name {
+            return Ok(Some(attr.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn flush(current: &mut Paragraph, paragraphs: &mut Vec<Paragraph>) {
+    let paragraph = std::mem::take(current);
+    if !paragraph.text.trim().is_empty() {
+        paragraphs.push(paragraph);
+    }
+}
+
+pub(super) fn is_block(name: &[u8]) -> bool {
+    matches!(
+        name,
+        b"p" | b"div"
+            | b"li"
+            | b"blockquote"
+            | b"pre"
+            | b"td"
+            | b"th"
+            | b"dt"
+            | b"dd"
+            | b"figcaption"
+            | b"section"
+            | b"article"
+    ) || heading_level(name).is_some()
+}
+
+// The kind of the paragraphs inside blocks other than plain paragraphs
+pub(super) fn block_kind(name: &[u8]) -> Option<SentenceKind> {
+    match name {
+        b"li" => Some(SentenceKind::ListItem),
+        b"blockquote" => Some(SentenceKind::Quote),
+        b"figcaption" => Some(SentenceKind::Caption),
+        _ => None,
+    }
+}
+
+pub(super) fn heading_level(name: &[u8]) -> Option<usize> {
+    match name {
+        [b'h', level @ b'1'..=b'6'] => Some((level - b'0') as usize),
+        _ => None,
+    }
+}
+
+// XHTML chapters commonly use HTML entities that XML does not predefine
+pub(crate) fn resolve_html_entity(entity: &str) -> Option<&'static str> {
+    let resolved = match entity {
+        "nbsp" => "\u{a0}",
+        "ndash" => "–",
+        "mdash" => "—",
+        "hellip" => "…",
+        "lsquo" => "‘",
+        "rsquo" => "’",
+        "ldquo" => "“",
+        "rdquo" => "”",
+        "laquo" => "«",
+        "raquo" => "»",
+        "copy" => "©",
+        "reg" => "®",
+        "trade" => "™",
+        "shy" => "",
+        _ => return None,
+    };
+    Some(resolved)
+}
+
+pub(super) fn percent_decode(href: &str) -> String {
+    let bytes = href.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = href
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sentence::texts;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use zip::{ZipWriter, write::FileOptions};
+
+    fn create_test_epub_file(file_path: &str) -> Result<()> {
+        let file = File::create(file_path)?;
+        let mut zip = ZipWriter::new(file);
+
+        let files = [
+            ("mimetype", "application/epub+zip"),
+            (
+                "META-INF/container.xml",
+                r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+    <rootfiles>
+        <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+    </rootfiles>
+</container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+    <manifest>
+        <item id="ch2" href="text/chapter%202.xhtml" media-type="application/xhtml+xml"/>
+        <item id="ch1" href="text/chapter1.xhtml" media-type="application/xhtml+xml"/>
+    </manifest>
+    <spine>
+        <itemref idref="ch1"/>
+        <itemref idref="ch2"/>
+    </spine>
+</package>"#,
+            ),
+            (
+                "OEBPS/text/chapter1.xhtml",
+                r#"<?xml version="1.0"?>
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head><title>Ignored title</title><style>p { color: red; }</style></head>
+<body>
+    <h1>Chapter One</h1>
+    <p>Call me <em>Ishmael</em>. Some years ago&nbsp;&mdash; never mind how long.</p>
+</body>
+</html>"#,
+            ),
+            (
+                "OEBPS/text/chapter 2.xhtml",
+                r#"<?xml version="1.0"?>
+<html xmlns="http://www.w3.org/1999/xhtml"><body><p>The second chapter.</p></body></html>"#,
+            ),
+        ];
+        for (name, content) in files {
+            zip.start_file(name, FileOptions::default())?;
+            zip.write_all(content.as_bytes())?;
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_epub_file_parsing() -> Result<()> {
+        let test_file = "test_book.epub";
+        create_test_epub_file(test_file)?;
+
+        let sentences = EpubParser::new()?.parse_file(test_file);
+        fs::remove_file(test_file).ok();
+
+        assert_eq!(
+            texts(&sentences?),
+            vec![
+                "Chapter One",
+                "Call me Ishmael.",
+                "Some years ago — never mind how long.",
+                "The second chapter."
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+use super::{DocumentParser, NotesMode, Paragraph, ParseOptions, Source, split_paragraphs};
+use crate::error::Result;
+use crate::sentence::{Sentence, SentenceKind};
+use crate::splitter::Splitter;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use std::io::{BufRead, BufReader};
+
+// FictionBook ebooks are one XML file. Sections nest, and the title of each
+// becomes a heading one level below the title of the section around it.
+#[derive(Debug)]
+pub struct Fb2Parser {
+    splitter: Splitter,
+    options: ParseOptions,
+}
+
+impl Fb2Parser {
+    pub fn new() -> Result<Self> {
+        let splitter = Splitter::regex()?;
+
+        Ok(Fb2Parser::with_splitter(splitter))
+    }
+
+    pub fn with_splitter(splitter: Splitter) -> Self {
+        Fb2Parser {
+            splitter,
+            options: ParseOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    #[cfg(feature = "native")]
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::File(file_path))
+    }
+
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::Bytes(data))
+    }
+
+    pub(crate) fn parse_source(&self, source: Source) -> Result<Vec<Sentence>> {
+        let paragraphs = self.extract_paragraphs(BufReader::new(source.open()?))?;
+
+        Ok(split_paragraphs(&self.splitter, &paragraphs))
+    }
+
+    pub fn extract_paragraphs_from_xml(&self, xml_content: &str) -> Result<Vec<String>> {
+        let paragraphs = self.extract_paragraphs(xml_content.as_bytes())?;
+
+        Ok(paragraphs.into_iter().map(|p| p.text).collect())
+    }
+
+    // The notes of the book are kept in a body of their own, after the main
+    // one, where the title of each note section is its label
+    fn extract_paragraphs<R: BufRead>(&self, content: R) -> Result<Vec<Paragraph>> {
+        let mut reader = Reader::from_reader(content);
+        let notes_mode = self.options.notes;
+
+        let mut paragraphs = Vec::new();
+        let mut in_notes = false;
+        let mut section_depth = 0;
+        // Quotations and the epigraphs at the start of sections
+        let mut quote_depth = 0;
+        let mut title: Option<String> = None;
+        let mut note_label: Option<String> = None;
+        let mut current: Option<Paragraph> = None;
+        let mut buf = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(ref e) => match e.local_name().as_ref() {
+                    // Metadata and embedded images
+                    b"description" | b"binary" => {
+                        let end = e.to_end().into_owned();
+                        reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                    }
+                    b"body" => {
+                        let name = e.try_get_attribute("name")?;
+                        in_notes = name.is_some_and(|name| {
+                            matches!(name.value.as_ref(), b"notes" | b"comments")
+                        });
+                        if in_notes && notes_mode == NotesMode::Exclude {
+                            let end = e.to_end().into_owned();
+                            reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                            in_notes = false;
+                        }
+                    }
+                    b"section" => {
+                        section_depth += 1;
+                        note_label = None;
+                    }
+                    b"title" => title = Some(String::new()),
+                    b"cite" | b"epigraph" => quote_depth += 1,
+                    b"p" | b"v" | b"subtitle" | b"text-author" => match &mut title {
+                        Some(text) if !text.is_empty() => text.push(' '),
+                        Some(_) => {}
+                        None => {
+                            let kind = match (in_notes, quote_depth > 0) {
+                                (true, _) => SentenceKind::Footnote,
+                                (false, true) => SentenceKind::Quote,
+                                (false, false) => SentenceKind::Body,
+                            };
+                            current = Some(Paragraph {
+                                kind,
+                                ..Paragraph::default()
+                            });
+                        }
+                    },
+                    _ => {}
+                },
+                Event::Text(e) => {
+                    let text = e.unescape()?;
+                    if let Some(title) = &mut title {
+                        title.push_str(&text);
+                    } else if let Some(paragraph) = &mut current {
+                        paragraph.text.push_str(&text);
+                    }
+                }
+                Event::End(ref e) => match e.local_name().as_ref() {
+                    b"body" => {
+                        in_notes = false;
+                        section_depth = 0;
+                    }
+                    b"section" => section_depth -= 1,
+                    b"cite" | b"epigraph" => quote_depth -= 1,
+                    b"title" => {
+                        let text = title.take().unwrap_or_default();
+                        if in_notes {
+                            note_label = Some(text.trim().to_string());
+                        } else if !text.trim().is_empty() {
+                            paragraphs.push(Paragraph::heading(text, section_depth + 1));
+                        }
+                    }
+                    b"p" | b"v" | b"subtitle" | b"text-author" => {
+                        if let Some(mut paragraph) = current.take() {
+                            // Only the first paragraph of a note carries its label
+                            if let (Some(label), NotesMode::Separate) =
+                                (note_label.take(), notes_mode)
+                            {
+                                paragraph.text = format!("[Footnote {}] {}", label, paragraph.text);
+                            }
+                            paragraphs.push(paragraph);
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(paragraphs)
+    }
+}
+
+impl DocumentParser for Fb2Parser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        Fb2Parser::parse_file(self, file_path)
+    }
+
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        Fb2Parser::parse_bytes(self, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::DocumentFormat;
+    use crate::sentence::texts;
+
+    const BOOK: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0" xmlns:l="http://www.w3.org/1999/xlink">
+  <description><title-info><book-title>Not read</book-title><lang>en</lang></title-info></description>
+  <body>
+    <title><p>The Book</p></title>
+    <section>
+      <title><p>Chapter One</p><p>The Start</p></title>
+      <epigraph><p>Call me Ishmael.</p></epigraph>
+      <p>It was <emphasis>late</emphasis>.<a l:href="#n1" type="note">1</a> Nobody came.</p>
+      <empty-line/>
+      <section>
+        <title><p>A Scene</p></title>
+        <poem><stanza><v>Roses are red,</v><v>violets are blue.</v></stanza></poem>
+      </section>
+    </section>
+  </body>
+  <body name="notes">
+    <section id="n1"><title><p>1</p></title><p>A note.</p></section>
+  </body>
+  <binary id="cover.jpg" content-type="image/jpeg">AAAA</binary>
+</FictionBook>"##;
+
+    #[test]
+    fn test_fb2_sections() -> Result<()> {
+        let data = BOOK.as_bytes();
+        let sentences = Fb2Parser::new()?.parse_bytes(data)?;
+
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "The Book",
+                "Chapter One The Start",
+                "Call me Ishmael.",
+                "It was late.1 Nobody came.",
+                "A Scene",
+                "Roses are red, violets are blue.",
+                "A note."
+            ]
+        );
+        let levels: Vec<_> = sentences.iter().map(|s| s.heading_level).collect();
+        assert_eq!(
+            levels,
+            vec![Some(1), Some(2), None, None, Some(3), None, None]
+        );
+        assert_eq!(
+            sentences[5].heading_path,
+            vec!["The Book", "Chapter One The Start", "A Scene"]
+        );
+        assert_eq!(sentences[2].kind, SentenceKind::Quote);
+        assert_eq!(sentences[3].kind, SentenceKind::Body);
+        assert_eq!(sentences[6].kind, SentenceKind::Footnote);
+        assert_eq!(DocumentFormat::detect_bytes("", data)?, DocumentFormat::Fb2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fb2_notes() -> Result<()> {
+        let parse = |notes| {
+            let parser = Fb2Parser::new()?.with_options(ParseOptions {
+                notes,
+                ..ParseOptions::default()
+            });
+            let sentences = parser.parse_bytes(BOOK.as_bytes())?;
+            Ok::<_, crate::error::SentencerError>(sentences.last().map(|s| s.text.clone()))
+        };
+
+        assert_eq!(
+            parse(NotesMode::Separate)?.as_deref(),
+            Some("[Footnote 1] A note.")
+        );
+        assert_eq!(
+            parse(NotesMode::Exclude)?.as_deref(),
+            Some("Roses are red, violets are blue.")
+        );
+        Ok(())
+    }
+}
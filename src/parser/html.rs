@@ -0,0 +1,203 @@
+use super::epub::{block_kind, heading_level, is_block};
+use super::{DocumentParser, Paragraph, split_paragraphs};
+use crate::error::Result;
+use crate::sentence::{Sentence, SentenceKind};
+use crate::splitter::Splitter;
+use ego_tree::NodeRef;
+use scraper::{Html, Node};
+#[cfg(feature = "native")]
+use std::fs;
+
+// Web pages are parsed the way browsers do, so unclosed tags and entities
+// are handled, and only the visible text is read
+#[derive(Debug)]
+pub struct HtmlParser {
+    splitter: Splitter,
+}
+
+impl HtmlParser {
+    pub fn new() -> Result<Self> {
+        let splitter = Splitter::regex()?;
+
+        Ok(HtmlParser { splitter })
+    }
+
+    pub fn with_splitter(splitter: Splitter) -> Self {
+        HtmlParser { splitter }
+    }
+
+    #[cfg(feature = "native")]
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        let content = fs::read_to_string(file_path)?;
+
+        Ok(self.parse_str(&content))
+    }
+
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        Ok(self.parse_str(std::str::from_utf8(data)?))
+    }
+
+    pub fn parse_str(&self, content: &str) -> Vec<Sentence> {
+        let document = Html::parse_document(content);
+
+        let mut paragraphs = Vec::new();
+        let mut current = Paragraph::default();
+        collect_paragraphs(
+            document.tree.root(),
+            SentenceKind::Body,
+            &mut current,
+            &mut paragraphs,
+        );
+        flush(&mut current, &mut paragraphs);
+
+        split_paragraphs(&self.splitter, &paragraphs)
+    }
+}
+
+impl DocumentParser for HtmlParser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        HtmlParser::parse_file(self, file_path)
+    }
+
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        HtmlParser::parse_bytes(self, data)
+    }
+}
+
+// The lang attribute of the html element
+pub(crate) fn declared_language(content: &str) -> Option<String> {
+    Html::parse_document(content)
+        .root_element()
+        .attr("lang")
+        .map(|lang| lang.to_string())
+}
+
+// `kind` is that of the list item, quote or caption the node is in, if any
+fn collect_paragraphs(
+    node: NodeRef<Node>,
+    kind: SentenceKind,
+    current: &mut Paragraph,
+    paragraphs: &mut Vec<Paragraph>,
+) {
+    match node.value() {
+        Node::Text(text) => current.text.push_str(text),
+        Node::Element(element) => {
+            let name = element.name();
+            if is_hidden(name) {
+                return;
+            }
+            if name == "br" {
+                current.text.push(' ');
+                return;
+            }
+
+            let block = is_block(name.as_bytes()) || is_container(name);
+            let inner_kind = block_kind(name.as_bytes()).unwrap_or(kind);
+            if block {
+                flush(current, paragraphs);
+                match heading_level(name.as_bytes()) {
+                    Some(level) => *current = Paragraph::heading(String::new(), level),
+                    None => current.kind = inner_kind,
+                }
+            }
+            for child in node.children() {
+                collect_paragraphs(child, inner_kind, current, paragraphs);
+            }
+            if block {
+                flush(current, paragraphs);
+                current.kind = kind;
+            }
+        }
+        Node::Document | Node::Fragment => {
+            for child in node.children() {
+                collect_paragraphs(child, kind, current, paragraphs);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn flush(current: &mut Paragraph, paragraphs: &mut Vec<Paragraph>) {
+    let paragraph = std::mem::take(current);
+    if !paragraph.text.trim().is_empty() {
+        paragraphs.push(paragraph);
+    }
+}
+
+fn is_hidden(name: &str) -> bool {
+    matches!(
+        name,
+        "head" | "script" | "style" | "noscript" | "template" | "svg" | "iframe"
+    )
+}
+
+// Elements that hold blocks rather than text, but still end a paragraph
+// when text is put directly inside them
+fn is_container(name: &str) -> bool {
+    matches!(
+        name,
+        "ul" | "ol"
+            | "dl"
+            | "table"
+            | "tr"
+            | "header"
+            | "footer"
+            | "nav"
+            | "main"
+            | "aside"
+            | "figure"
+            | "address"
+            | "hr"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sentence::texts;
+
+    #[test]
+    fn test_html_parsing() -> Result<()> {
+        let html = r#"<!DOCTYPE html>
+<html lang="en">
+<head><title>Not read</title><style>p { color: red; }</style></head>
+<body>
+  <h1>Caf&eacute; notes</h1>
+  <p>First paragraph with <b>bold</b> text<br>and a break. Second sentence
+  <p>Unclosed paragraph &amp; more.</p>
+  <script>document.write("Not text.");</script>
+  <ul><li>Item one</li><li>Item two</li></ul>
+  <blockquote><p>Call me Ishmael.</p></blockquote>
+</body>
+</html>"#;
+
+        let sentences = HtmlParser::new()?.parse_str(html);
+
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "Café notes",
+                "First paragraph with bold text and a break.",
+                "Second sentence Unclosed paragraph & more.",
+                "Item one",
+                "Item two",
+                "Call me Ishmael."
+            ]
+        );
+        let kinds: Vec<_> = sentences.iter().map(|sentence| sentence.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SentenceKind::Heading,
+                SentenceKind::Body,
+                SentenceKind::Body,
+                SentenceKind::ListItem,
+                SentenceKind::ListItem,
+                SentenceKind::Quote
+            ]
+        );
+        assert_eq!(declared_language(html).as_deref(), Some("en"));
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use super::{
+    DocumentFormat, DocxParser, EpubParser, Fb2Parser, HtmlParser, Limits, OdpParser, OdsParser,
+    OdtParser, RtfParser, Source, html, is_flat_xml, open_zip_entry, read_zip_entry,
+};
+use crate::error::Result;
+use crate::language::Language;
+use crate::splitter::Splitter;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use std::io::{BufRead, BufReader, Read};
+
+// The language declared in the document's metadata wins; otherwise it is
+// guessed from the text
+pub(crate) fn detect_language(source: Source, format: DocumentFormat) -> Option<Language> {
+    declared_language(source, format)
+        .ok()
+        .flatten()
+        .or_else(|| Language::detect(&document_text(source, format).ok()?))
+}
+
+fn declared_language(source: Source, format: DocumentFormat) -> Result<Option<Language>> {
+    let code = match format {
+        DocumentFormat::Odt | DocumentFormat::Ods | DocumentFormat::Odp => {
+            let meta = odt_part(source, "meta.xml").unwrap_or_default();
+            match element_text(&meta, b"language")? {
+                Some(code) => Some(code),
+                // Otherwise the default paragraph style carries the language
+                None => element_attribute(
+                    &odt_part(source, "styles.xml")?,
+                    b"text-properties",
+                    b"language",
+                )?,
+            }
+        }
+        DocumentFormat::Docx => {
+            element_attribute(&read_zip_entry(source, "word/styles.xml")?, b"lang", b"val")?
+        }
+        DocumentFormat::Epub => {
+            let package = EpubParser::with_splitter(Splitter::unicode()).package_xml(source)?;
+            element_text(&package, b"language")?
+        }
+        DocumentFormat::Html => html::declared_language(&source.read_to_string()?),
+        DocumentFormat::Fb2 => element_text(&source.read_to_string()?, b"lang")?,
+        DocumentFormat::Text | DocumentFormat::Markdown | DocumentFormat::Rtf => None,
+    };
+
+    Ok(code.as_deref().and_then(Language::from_code))
+}
+
+// A flat document holds every part in the one file
+pub(super) fn odt_part(source: Source, entry_name: &str) -> Result<String> {
+    match is_flat_xml(source)? {
+        true => source.read_to_string(),
+        false => read_zip_entry(source, entry_name),
+    }
+}
+
+// The text is gathered as the XML is read, without holding the XML itself
+pub(crate) fn document_text(source: Source, format: DocumentFormat) -> Result<String> {
+    match format {
+        DocumentFormat::Odt => {
+            let content: Box<dyn BufRead> = match is_flat_xml(source)? {
+                true => Box::new(BufReader::new(source.open()?)),
+                false => {
+                    let max_size = Limits::default().max_entry_size;
+                    open_zip_entry(source.open()?, source.name(), "content.xml", max_size)?
+                }
+            };
+            OdtParser::new()?.extract_text(content)
+        }
+        DocumentFormat::Ods => {
+            let sentences = OdsParser::with_splitter(Splitter::unicode()).parse_source(source)?;
+            Ok(crate::sentence::texts(&sentences).join(" "))
+        }
+        DocumentFormat::Odp => {
+            let sentences = OdpParser::with_splitter(Splitter::unicode()).parse_source(source)?;
+            Ok(crate::sentence::texts(&sentences).join(" "))
+        }
+        DocumentFormat::Docx => {
+            let parser = DocxParser::new()?;
+            parser.extract_text(parser.document_xml(source)?)
+        }
+        DocumentFormat::Epub => {
+            let sentences = EpubParser::with_splitter(Splitter::unicode()).parse_source(source)?;
+            Ok(crate::sentence::texts(&sentences).join(" "))
+        }
+        DocumentFormat::Html => {
+            let sentences =
+                HtmlParser::with_splitter(Splitter::unicode()).parse_str(&source.read_to_string()?);
+            Ok(crate::sentence::texts(&sentences).join(" "))
+        }
+        DocumentFormat::Fb2 => {
+            let sentences = Fb2Parser::with_splitter(Splitter::unicode()).parse_source(source)?;
+            Ok(crate::sentence::texts(&sentences).join(" "))
+        }
+        DocumentFormat::Rtf => {
+            let mut data = Vec::new();
+            source.open()?.read_to_end(&mut data)?;
+            RtfParser::new()?.extract_text(&data)
+        }
+        DocumentFormat::Text | DocumentFormat::Markdown => source.read_to_string(),
+    }
+}
+
+// Text of the first element with the given local name, such as <dc:language>
+fn element_text(xml: &str, name: &[u8]) -> Result<Option<String>> {
+    let mut reader = Reader::from_str(xml);
+    let mut inside = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.local_name().as_ref() == name => inside = true,
+            Event::Text(e) if inside => return Ok(Some(e.unescape()?.trim().to_string())),
+            Event::End(e) if e.local_name().as_ref() == name => inside = false,
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+// First real language code set on an element, skipping "zxx" (no language)
+fn element_attribute(xml: &str, element: &[u8], attribute: &[u8]) -> Result<Option<String>> {
+    let mut reader = Reader::from_str(xml);
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == element => {
+                for attr in e.attributes() {
+                    let attr = attr?;
+                    let value = attr.unescape_value()?;
+                    if attr.key.local_name().as_ref() == attribute
+                        && !matches!(value.as_ref(), "zxx" | "none")
+                    {
+                        return Ok(Some(value.into_owned()));
+                    }
+                }
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_metadata() -> Result<()> {
+        let meta = r#"<office:document-meta><office:meta><dc:language>es-ES</dc:language></office:meta></office:document-meta>"#;
+        let styles = r#"<office:styles><style:default-style><style:text-properties fo:language="zxx"/></style:default-style>
+            <style:style><style:text-properties fo:language="de" fo:country="DE"/></style:style></office:styles>"#;
+
+        assert_eq!(element_text(meta, b"language")?.as_deref(), Some("es-ES"));
+        assert_eq!(
+            element_attribute(styles, b"text-properties", b"language")?.as_deref(),
+            Some("de")
+        );
+        assert_eq!(element_text(styles, b"language")?, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use super::{DocumentParser, Paragraph, split_paragraphs, text_paragraphs};
+use crate::error::Result;
+use crate::sentence::{Sentence, SentenceKind};
+use crate::splitter::Splitter;
+use regex::Regex;
+#[cfg(feature = "native")]
+use std::fs;
+
+#[derive(Debug)]
+pub struct MarkdownParser {
+    splitter: Splitter,
+    heading_regex: Regex,
+    block_prefix_regex: Regex,
+    rule_regex: Regex,
+    reference_regex: Regex,
+    image_regex: Regex,
+    link_regex: Regex,
+    emphasis_regex: Regex,
+    code_regex: Regex,
+    html_regex: Regex,
+}
+
+impl MarkdownParser {
+    pub fn new() -> Result<Self> {
+        MarkdownParser::with_splitter(Splitter::regex()?)
+    }
+
+    pub fn with_splitter(splitter: Splitter) -> Result<Self> {
+        Ok(MarkdownParser {
+            splitter,
+            heading_regex: Regex::new(r"^\s{0,3}#{1,6}\s+(.*?)(\s+#+)?\s*$")?,
+            block_prefix_regex: Regex::new(r"^\s*(>\s?)*([-*+]\s+|\d+[.)]\s+)?")?,
+            rule_regex: Regex::new(r"^\s*([-*_=]\s*){3,}$")?,
+            reference_regex: Regex::new(r"^\s{0,3}\[[^\]]+\]:\s")?,
+            image_regex: Regex::new(r"!\[([^\]]*)\]\([^)]*\)")?,
+            link_regex: Regex::new(r"\[([^\]]+)\](\([^)]*\)|\[[^\]]*\])")?,
+            emphasis_regex: Regex::new(r"(\*{1,3}|_{1,3}|~~)(\S(?:.*?\S)?)(\*{1,3}|_{1,3}|~~)")?,
+            code_regex: Regex::new(r"`+([^`]*)`+")?,
+            html_regex: Regex::new(r"<[^>]+>")?,
+        })
+    }
+
+    #[cfg(feature = "native")]
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        let content = fs::read_to_string(file_path)?;
+
+        Ok(self.parse_str(&content))
+    }
+
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        Ok(self.parse_str(std::str::from_utf8(data)?))
+    }
+
+    pub fn parse_str(&self, content: &str) -> Vec<Sentence> {
+        let paragraphs = self.extract_paragraphs(content);
+
+        split_paragraphs(&self.splitter, &paragraphs)
+    }
+
+    // Headings become standalone paragraphs; fenced code blocks, rules and
+    // link reference definitions are dropped entirely. List items and block
+    // quotes start paragraphs of their own kind.
+    fn extract_paragraphs(&self, content: &str) -> Vec<Paragraph> {
+        let mut paragraphs = Vec::new();
+        let mut block = String::new();
+        let mut kind = SentenceKind::Body;
+        let mut in_fence = false;
+
+        for line in content.lines() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                self.flush_block(&mut block, kind, &mut paragraphs);
+                continue;
+            }
+            if in_fence || self.reference_regex.is_match(line) {
+                continue;
+            }
+            if self.rule_regex.is_match(line) {
+                self.flush_block(&mut block, kind, &mut paragraphs);
+                continue;
+            }
+
+            if let Some(captures) = self.heading_regex.captures(line) {
+                self.flush_block(&mut block, kind, &mut paragraphs);
+                let level = line.trim_start().chars().take_while(|&c| c == '#').count();
+                paragraphs.push(Paragraph::heading(self.strip_inline(&captures[1]), level));
+                continue;
+            }
+
+            // List items start a new paragraph even without a blank line, and
+            // so does the start or end of a quote
+            let (quoted, is_list_item) = self
+                .block_prefix_regex
+                .captures(line)
+                .map_or((false, false), |captures| {
+                    (captures.get(1).is_some(), captures.get(2).is_some())
+                });
+            let line = self.block_prefix_regex.replace(line, "");
+            if line.trim().is_empty() || is_list_item || (kind == SentenceKind::Quote) != quoted {
+                self.flush_block(&mut block, kind, &mut paragraphs);
+            }
+            if block.trim().is_empty() {
+                kind = match (is_list_item, quoted) {
+                    (true, _) => SentenceKind::ListItem,
+                    (false, true) => SentenceKind::Quote,
+                    (false, false) => SentenceKind::Body,
+                };
+            }
+            block.push('\n');
+            block.push_str(&line);
+        }
+        self.flush_block(&mut block, kind, &mut paragraphs);
+
+        paragraphs
+    }
+
+    fn flush_block(&self, block: &mut String, kind: SentenceKind, paragraphs: &mut Vec<Paragraph>) {
+        for text in text_paragraphs(block) {
+            paragraphs.push(Paragraph {
+                kind,
+                ..Paragraph::new(self.strip_inline(&text))
+            });
+        }
+        block.clear();
+    }
+
+    fn strip_inline(&self, text: &str) -> String {
+        let text = self.code_regex.replace_all(text, "$1");
+        let text = self.image_regex.replace_all(&text, "$1");
+        let text = self.link_regex.replace_all(&text, "$1");
+        let text = self.html_regex.replace_all(&text, "");
+        let text = self.emphasis_regex.replace_all(&text, "$2");
+
+        text.trim().to_string()
+    }
+}
+
+impl DocumentParser for MarkdownParser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        MarkdownParser::parse_file(self, file_path)
+    }
+
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        MarkdownParser::parse_bytes(self, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sentence::texts;
+
+    #[test]
+    fn test_markdown_parsing() -> Result<()> {
+        let parser = MarkdownParser::new()?;
+        let content = r#"# Getting *started*
+
+This is **bold** and _italic_ text with a [link](https://example.com).
+See `cargo run` for details!
+
+```rust
+fn main() { println!("Not prose."); }
+```
+
+Items:
+- A list item
+- Another item.
+> A quoted line.
+
+[link]: https://example.com
+"#;
+
+        let sentences = parser.parse_str(content);
+
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "Getting started",
+                "This is bold and italic text with a link.",
+                "See cargo run for details!",
+                "Items:",
+                "A list item",
+                "Another item.",
+                "A quoted line."
+            ]
+        );
+        let kinds: Vec<_> = sentences.iter().map(|sentence| sentence.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SentenceKind::Heading,
+                SentenceKind::Body,
+                SentenceKind::Body,
+                SentenceKind::Body,
+                SentenceKind::ListItem,
+                SentenceKind::ListItem,
+                SentenceKind::Quote
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use super::language::odt_part;
+use super::odt::attribute;
+use super::{DocumentFormat, Source};
+use crate::error::Result;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use serde::Serialize;
+
+// What an OpenDocument file records about itself in meta.xml. Dates are
+// kept as written there, such as "2024-03-01T09:30:00".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DocumentMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    // As counted by the application that saved the document
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub word_count: Option<usize>,
+}
+
+impl DocumentMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == DocumentMetadata::default()
+    }
+
+    // Title and author on one line, such as "Moby-Dick — Herman Melville"
+    pub fn heading(&self) -> Option<String> {
+        match (&self.title, &self.author) {
+            (Some(title), Some(author)) => Some(format!("{} — {}", title, author)),
+            (Some(text), None) | (None, Some(text)) => Some(text.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+// Formats other than OpenDocument have no meta.xml, and nothing is read of them
+pub(super) fn document_metadata(
+    source: Source,
+    format: DocumentFormat,
+) -> Result<DocumentMetadata> {
+    match format {
+        DocumentFormat::Odt | DocumentFormat::Ods | DocumentFormat::Odp => {
+            // meta.xml is optional in a package
+            parse_meta(&odt_part(source, "meta.xml").unwrap_or_default())
+        }
+        DocumentFormat::Docx
+        | DocumentFormat::Text
+        | DocumentFormat::Markdown
+        | DocumentFormat::Epub
+        | DocumentFormat::Html
+        | DocumentFormat::Rtf
+        | DocumentFormat::Fb2 => Ok(DocumentMetadata::default()),
+    }
+}
+
+// Only <office:meta> is read, so fields such as <text:title> in the body of a
+// flat document are not taken for the metadata. The initial creator is the
+// author; dc:creator, who saved the document last, stands in without one.
+fn parse_meta(xml: &str) -> Result<DocumentMetadata> {
+    let mut reader = Reader::from_str(xml);
+    let mut metadata = DocumentMetadata::default();
+    let mut creator = None;
+    let mut in_meta = false;
+    let mut element = Vec::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.name().as_ref() == b"office:meta" => in_meta = true,
+            Event::End(e) if e.name().as_ref() == b"office:meta" => break,
+            Event::Start(e) if in_meta => element = e.name().as_ref().to_vec(),
+            Event::Empty(e) if in_meta && e.name().as_ref() == b"meta:document-statistic" => {
+                metadata.word_count =
+                    attribute(&e, "meta:word-count")?.and_then(|count| count.parse().ok());
+            }
+            Event::Text(e) if in_meta => {
+                let field = match element.as_slice() {
+                    b"dc:title" => &mut metadata.title,
+                    b"meta:initial-creator" => &mut metadata.author,
+                    b"dc:creator" => &mut creator,
+                    b"meta:creation-date" => &mut metadata.created,
+                    b"dc:date" => &mut metadata.modified,
+                    _ => continue,
+                };
+                let text = e.unescape()?.trim().to_string();
+                if !text.is_empty() {
+                    *field = Some(text);
+                }
+            }
+            Event::End(_) => element.clear(),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    metadata.author = metadata.author.or(creator);
+
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::read_metadata_bytes;
+
+    #[test]
+    fn test_meta_xml() -> Result<()> {
+        let flat = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document office:mimetype="application/vnd.oasis.opendocument.text">
+  <office:meta>
+    <dc:title>Moby-Dick</dc:title>
+    <dc:creator>An Editor</dc:creator>
+    <meta:initial-creator>Herman Melville</meta:initial-creator>
+    <meta:creation-date>1851-10-18T09:00:00</meta:creation-date>
+    <dc:date>1851-11-14T12:00:00</dc:date>
+    <meta:document-statistic meta:paragraph-count="2" meta:word-count="206052"/>
+  </office:meta>
+  <office:body><office:text><text:p><text:title>Not the title</text:title></text:p></office:text></office:body>
+</office:document>"#;
+
+        let metadata = read_metadata_bytes("", flat.as_bytes())?;
+        assert_eq!(
+            metadata,
+            DocumentMetadata {
+                title: Some("Moby-Dick".to_string()),
+                author: Some("Herman Melville".to_string()),
+                created: Some("1851-10-18T09:00:00".to_string()),
+                modified: Some("1851-11-14T12:00:00".to_string()),
+                word_count: Some(206052),
+            }
+        );
+        assert_eq!(
+            metadata.heading().as_deref(),
+            Some("Moby-Dick — Herman Melville")
+        );
+
+        let saved_once = parse_meta(
+            "<office:document-meta><office:meta><dc:creator>Ishmael</dc:creator></office:meta></office:document-meta>",
+        )?;
+        assert_eq!(saved_once.author.as_deref(), Some("Ishmael"));
+        assert!(read_metadata_bytes("notes.txt", b"Plain text.")?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,1134 @@
+mod crypto;
+mod docx;
+mod epub;
+mod fb2;
+mod html;
+mod language;
+mod markdown;
+mod metadata;
+mod odp;
+mod ods;
+mod odt;
+#[cfg(feature = "native")]
+mod pandoc;
+mod rtf;
+mod text;
+
+pub use docx::DocxParser;
+pub use epub::EpubParser;
+pub use fb2::Fb2Parser;
+pub use html::HtmlParser;
+pub use markdown::MarkdownParser;
+pub use metadata::DocumentMetadata;
+pub use odp::OdpParser;
+pub use ods::OdsParser;
+pub use odt::OdtParser;
+#[cfg(feature = "native")]
+pub use pandoc::PandocTarget;
+pub use rtf::RtfParser;
+pub use text::TextParser;
+
+#[cfg(feature = "cli")]
+pub(crate) use language::document_text;
+#[cfg(feature = "native")]
+use pandoc::PandocParser;
+
+use crate::error::{Result, SentencerError};
+use crate::language::Language;
+use crate::normalize::normalize_whitespace;
+use crate::sentence::{CellPosition, Link, Sentence, SentenceKind};
+use crate::splitter::{SentenceSplitter, Splitter};
+use flate2::read::DeflateDecoder;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+use zip::{CompressionMethod, ZipArchive};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotesMode {
+    // Notes follow the paragraph that cites them
+    #[default]
+    Include,
+    Exclude,
+    // Notes are collected at the end of the document, tagged with their citation
+    Separate,
+}
+
+impl NotesMode {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "include" => Ok(NotesMode::Include),
+            "exclude" => Ok(NotesMode::Exclude),
+            "separate" => Ok(NotesMode::Separate),
+            _ => Err(SentencerError::InvalidArgument(format!(
+                "Unknown notes mode '{}' (expected include, exclude or separate)",
+                name
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommentsMode {
+    // Reviewer comments never reach the text
+    #[default]
+    Exclude,
+    // Comments follow the paragraph they are attached to
+    Include,
+    // Comments are collected at the end of the document
+    Separate,
+}
+
+impl CommentsMode {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "exclude" => Ok(CommentsMode::Exclude),
+            "include" => Ok(CommentsMode::Include),
+            "separate" => Ok(CommentsMode::Separate),
+            _ => Err(SentencerError::InvalidArgument(format!(
+                "Unknown comments mode '{}' (expected exclude, include or separate)",
+                name
+            ))),
+        }
+    }
+}
+
+// Which version of a document with tracked changes is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChangesView {
+    // As if every change were accepted: insertions kept, deletions dropped
+    #[default]
+    Accepted,
+    // As if every change were rejected: insertions dropped, deletions kept
+    Rejected,
+    // Both, marked up as [-deleted-] and {+inserted+}
+    Annotated,
+}
+
+impl ChangesView {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "accepted" | "accept" => Ok(ChangesView::Accepted),
+            "rejected" | "reject" => Ok(ChangesView::Rejected),
+            "annotated" | "both" => Ok(ChangesView::Annotated),
+            _ => Err(SentencerError::InvalidArgument(format!(
+                "Unknown changes view '{}' (expected accepted, rejected or annotated)",
+                name
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    pub notes: NotesMode,
+    pub comments: CommentsMode,
+    pub changes: ChangesView,
+    // Emit each table cell as its own sentence, tagged with its position
+    pub table_cells_as_sentences: bool,
+    // Keep each list item whole as one sentence, even when it holds several
+    pub list_items_as_sentences: bool,
+    // Join the cells of each spreadsheet row into one sentence, instead of
+    // emitting every cell on its own
+    pub spreadsheet_rows: bool,
+    // Language for the splitting rules; detected from the document when unset
+    pub language: Option<Language>,
+    // Password of an encrypted ODT document
+    pub password: Option<String>,
+    pub limits: Limits,
+    // Map ODT files into memory rather than reading them through a buffer,
+    // for documents that are large next to the memory available
+    pub mmap: bool,
+    // Convert files in formats no parser reads with pandoc first
+    #[cfg(feature = "native")]
+    pub via_pandoc: Option<PandocTarget>,
+    // Read the page headers of ODT documents before the body text and the
+    // footers after it
+    pub headers_footers: bool,
+    // Read the titles and descriptions of images in ODT documents as
+    // sentences of their own; otherwise they are left out
+    pub alt_text: bool,
+    // Read the generated tables of contents and indexes of ODT documents,
+    // which are left out by default
+    pub include_toc: bool,
+    // Read the sections of ODT master documents that link to other files
+    // from the copy kept in the master, without opening the files
+    pub skip_linked_files: bool,
+    // Put Markdown markers around bold (**), italic (*) and underlined (_)
+    // text of ODT documents, so exports keep the emphasis
+    pub emphasis_markers: bool,
+}
+
+// Guards against documents built to exhaust memory, such as zip bombs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    // Largest size of one archive entry once decompressed, in bytes
+    pub max_entry_size: u64,
+    // Deepest nesting of XML elements in ODT content
+    pub max_xml_depth: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_entry_size: 256 * 1024 * 1024,
+            max_xml_depth: 256,
+        }
+    }
+}
+
+// Sentences as they are produced, for consumers that write them out one by one
+pub type SentenceStream<'a> = Box<dyn Iterator<Item = Result<Sentence>> + 'a>;
+
+// Reading from paths needs the native feature
+pub trait DocumentParser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>>;
+
+    // The whole document already in memory, as where there is no file system
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>>;
+
+    // Parsers that can produce sentences incrementally override this; the
+    // default parses the whole document first
+    #[cfg(feature = "native")]
+    fn sentences<'a>(&'a self, file_path: &str) -> Result<SentenceStream<'a>> {
+        Ok(Box::new(self.parse_file(file_path)?.into_iter().map(Ok)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Odt,
+    Ods,
+    Odp,
+    Docx,
+    Text,
+    Markdown,
+    Epub,
+    Html,
+    Rtf,
+    Fb2,
+}
+
+impl DocumentFormat {
+    // The extension in lower case, without the dot
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            // Master documents are read like the text documents they link
+            "odt" | "fodt" | "odm" => Some(DocumentFormat::Odt),
+            "ods" | "fods" => Some(DocumentFormat::Ods),
+            "odp" | "fodp" => Some(DocumentFormat::Odp),
+            "docx" => Some(DocumentFormat::Docx),
+            "txt" | "text" => Some(DocumentFormat::Text),
+            "md" | "markdown" => Some(DocumentFormat::Markdown),
+            "epub" => Some(DocumentFormat::Epub),
+            "html" | "htm" | "xhtml" => Some(DocumentFormat::Html),
+            "rtf" => Some(DocumentFormat::Rtf),
+            "fb2" => Some(DocumentFormat::Fb2),
+            _ => None,
+        }
+    }
+
+    pub fn detect(file_path: &str) -> Result<Self> {
+        DocumentFormat::detect_source(file_path, Source::File(file_path))
+    }
+
+    // `file_name` is only looked at for its extension, and may be empty
+    pub fn detect_bytes(file_name: &str, data: &[u8]) -> Result<Self> {
+        DocumentFormat::detect_source(file_name, Source::Bytes(data))
+    }
+
+    // The content decides where it has a signature, so renamed documents are
+    // read as what they are; text without one goes by the extension
+    fn detect_source(file_name: &str, source: Source) -> Result<Self> {
+        let extension = Path::new(file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+        let by_extension = extension
+            .as_deref()
+            .and_then(DocumentFormat::from_extension);
+
+        let mut start = Vec::new();
+        source.open()?.take(8192).read_to_end(&mut start)?;
+
+        if start.starts_with(b"PK\x03\x04") {
+            return DocumentFormat::detect_archive(file_name, source, by_extension);
+        }
+        if start.starts_with(b"%PDF") {
+            return Err(SentencerError::UnsupportedFormat(format!(
+                "{} (PDF documents cannot be read)",
+                file_name
+            )));
+        }
+        // RTF is text, opened by the group of the whole document
+        if start.starts_with(b"{\\rtf") {
+            return Ok(DocumentFormat::Rtf);
+        }
+
+        if let Some(format) = by_extension {
+            return Ok(format);
+        }
+
+        let text = String::from_utf8_lossy(&start);
+        if is_flat_xml(source)? {
+            if text.contains("application/vnd.oasis.opendocument.text") {
+                return Ok(DocumentFormat::Odt);
+            }
+            if text.contains("application/vnd.oasis.opendocument.spreadsheet") {
+                return Ok(DocumentFormat::Ods);
+            }
+            if text.contains("application/vnd.oasis.opendocument.presentation") {
+                return Ok(DocumentFormat::Odp);
+            }
+            if text.contains("<FictionBook") {
+                return Ok(DocumentFormat::Fb2);
+            }
+            if text.to_lowercase().contains("<html") {
+                return Ok(DocumentFormat::Html);
+            }
+        }
+
+        // Files without an extension are read as plain text when they look
+        // like it; the sample may end in the middle of a character
+        let utf8 = match std::str::from_utf8(&start) {
+            Ok(_) => true,
+            Err(e) => e.error_len().is_none(),
+        };
+        if extension.is_none() && !start.is_empty() && utf8 && !start.contains(&0) {
+            return Ok(DocumentFormat::Text);
+        }
+
+        Err(SentencerError::UnsupportedFormat(file_name.to_string()))
+    }
+
+    // Zip archives are told apart by the entries they hold
+    fn detect_archive(
+        file_name: &str,
+        source: Source,
+        by_extension: Option<DocumentFormat>,
+    ) -> Result<Self> {
+        let mut archive = ZipArchive::new(source.open()?)?;
+
+        if let Ok(mut mimetype) = archive.by_name("mimetype") {
+            let mut content = String::new();
+            mimetype.read_to_string(&mut content)?;
+            if let Some(format) = DocumentFormat::from_media_type(content.trim()) {
+                return Ok(format);
+            }
+        }
+
+        if archive.by_name("word/document.xml").is_ok() {
+            return Ok(DocumentFormat::Docx);
+        }
+
+        if archive.by_name("META-INF/container.xml").is_ok() {
+            return Ok(DocumentFormat::Epub);
+        }
+
+        // OpenDocument packages without a mimetype entry still name their
+        // type in the manifest
+        if let Ok(mut manifest) = archive.by_name("META-INF/manifest.xml") {
+            let mut content = String::new();
+            manifest.read_to_string(&mut content)?;
+            let media_type = content.split('"').find_map(DocumentFormat::from_media_type);
+            if let Some(format) = media_type {
+                return Ok(format);
+            }
+        }
+
+        by_extension.ok_or_else(|| SentencerError::UnsupportedFormat(file_name.to_string()))
+    }
+
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            "application/vnd.oasis.opendocument.text"
+            | "application/vnd.oasis.opendocument.text-master" => Some(DocumentFormat::Odt),
+            "application/vnd.oasis.opendocument.spreadsheet" => Some(DocumentFormat::Ods),
+            "application/vnd.oasis.opendocument.presentation" => Some(DocumentFormat::Odp),
+            "application/epub+zip" => Some(DocumentFormat::Epub),
+            _ => None,
+        }
+    }
+}
+
+// Where a document is read from: a file, or bytes already in memory, such as
+// a document handed to a browser viewer
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Source<'a> {
+    File(&'a str),
+    Bytes(&'a [u8]),
+}
+
+pub(crate) trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+impl<'a> Source<'a> {
+    pub(crate) fn open(self) -> Result<Box<dyn ReadSeek + 'a>> {
+        match self {
+            Source::File(file_path) => Ok(Box::new(BufReader::new(File::open(file_path)?))),
+            Source::Bytes(data) => Ok(Box::new(Cursor::new(data))),
+        }
+    }
+
+    pub(crate) fn read_to_string(self) -> Result<String> {
+        match self {
+            Source::File(file_path) => Ok(fs::read_to_string(file_path)?),
+            Source::Bytes(data) => Ok(std::str::from_utf8(data)?.to_string()),
+        }
+    }
+
+    // How the document is called in messages
+    pub(crate) fn name(self) -> &'a str {
+        match self {
+            Source::File(file_path) => file_path,
+            Source::Bytes(_) => "document",
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+pub fn parser_for(
+    file_path: &str,
+    splitter: Splitter,
+    options: ParseOptions,
+) -> Result<Box<dyn DocumentParser>> {
+    parser_for_source(file_path, Source::File(file_path), splitter, options)
+}
+
+// For parsing `data` with `parse_bytes`; `file_name` is only looked at for
+// its extension
+pub fn parser_for_bytes(
+    file_name: &str,
+    data: &[u8],
+    splitter: Splitter,
+    options: ParseOptions,
+) -> Result<Box<dyn DocumentParser>> {
+    parser_for_source(file_name, Source::Bytes(data), splitter, options)
+}
+
+// The title, author, dates and word count a document records about itself,
+// empty for formats that keep none
+#[cfg(feature = "native")]
+pub fn read_metadata(file_path: &str) -> Result<DocumentMetadata> {
+    let source = Source::File(file_path);
+    metadata::document_metadata(source, DocumentFormat::detect_source(file_path, source)?)
+}
+
+pub fn read_metadata_bytes(file_name: &str, data: &[u8]) -> Result<DocumentMetadata> {
+    let source = Source::Bytes(data);
+    metadata::document_metadata(source, DocumentFormat::detect_source(file_name, source)?)
+}
+
+fn parser_for_source(
+    file_name: &str,
+    source: Source,
+    splitter: Splitter,
+    options: ParseOptions,
+) -> Result<Box<dyn DocumentParser>> {
+    // Files no parser reads go to pandoc when asked to
+    let detected = DocumentFormat::detect_source(file_name, source);
+    #[cfg(feature = "native")]
+    let detected = match (detected, source, options.via_pandoc) {
+        (
+            Err(SentencerError::UnsupportedFormat(_) | SentencerError::Zip(_)),
+            Source::File(_),
+            Some(target),
+        ) => return Ok(Box::new(PandocParser::new(target, splitter, options))),
+        (detected, _, _) => detected,
+    };
+    let format = detected?;
+
+    // Detection costs a read of the document, so it is skipped when the
+    // splitter has no language rules
+    let splitter = match splitter.has_language_rules() {
+        true => {
+            let language = options
+                .language
+                .or_else(|| language::detect_language(source, format));
+            splitter.with_language(language)
+        }
+        false => splitter,
+    };
+
+    let parser: Box<dyn DocumentParser> = match format {
+        DocumentFormat::Odt => Box::new(OdtParser::with_splitter(splitter).with_options(options)),
+        DocumentFormat::Ods => Box::new(OdsParser::with_splitter(splitter).with_options(options)),
+        DocumentFormat::Odp => Box::new(OdpParser::with_splitter(splitter).with_options(options)),
+        DocumentFormat::Docx => Box::new(DocxParser::with_splitter(splitter)),
+        DocumentFormat::Text => Box::new(TextParser::with_splitter(splitter)),
+        DocumentFormat::Markdown => Box::new(MarkdownParser::with_splitter(splitter)?),
+        DocumentFormat::Epub => Box::new(EpubParser::with_splitter(splitter)),
+        DocumentFormat::Html => Box::new(HtmlParser::with_splitter(splitter)),
+        DocumentFormat::Rtf => Box::new(RtfParser::with_splitter(splitter).with_options(options)),
+        DocumentFormat::Fb2 => Box::new(Fb2Parser::with_splitter(splitter).with_options(options)),
+    };
+
+    Ok(parser)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Paragraph {
+    pub(crate) text: String,
+    pub(crate) standalone: bool,
+    pub(crate) kind: SentenceKind,
+    pub(crate) heading_level: Option<usize>,
+    pub(crate) list_level: Option<usize>,
+    pub(crate) links: Vec<Link>,
+    pub(crate) cell: Option<CellPosition>,
+    pub(crate) slide: Option<usize>,
+}
+
+impl Paragraph {
+    pub(crate) fn new(text: String) -> Self {
+        Paragraph {
+            text,
+            ..Paragraph::default()
+        }
+    }
+
+    pub(crate) fn heading(text: String, level: usize) -> Self {
+        Paragraph {
+            text,
+            standalone: true,
+            kind: SentenceKind::Heading,
+            heading_level: Some(level.max(1)),
+            list_level: None,
+            links: Vec::new(),
+            cell: None,
+            slide: None,
+        }
+    }
+}
+
+pub(crate) fn split_paragraphs(splitter: &Splitter, paragraphs: &[Paragraph]) -> Vec<Sentence> {
+    // Paragraphs already in memory cannot fail to be read
+    Sentences::new(splitter, paragraphs.iter().cloned().map(Ok))
+        .filter_map(Result::ok)
+        .collect()
+}
+
+// Running text is split as one stream so sentences may continue across
+// paragraphs, while standalone paragraphs always form their own sentence.
+// Paragraphs are pulled only as far as needed: the last sentence of the
+// running text is held back until the next paragraph shows whether it is
+// complete. A paragraph that fails to be read is passed on as the error.
+pub(crate) struct Sentences<'a, I: Iterator<Item = Result<Paragraph>>> {
+    splitter: &'a Splitter,
+    paragraphs: std::iter::Fuse<std::iter::Enumerate<I>>,
+    ready: VecDeque<Sentence>,
+    heading_path: Vec<String>,
+    // Characters of normalized document text before `pending`
+    offset: usize,
+    // Running text that has not been emitted yet
+    pending: String,
+    // Where each paragraph of `pending` starts
+    pending_starts: Vec<PendingStart>,
+    // Links in `pending` with the byte position where they start
+    pending_links: Vec<(usize, Link)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PendingStart {
+    // Byte position in `pending`
+    byte: usize,
+    paragraph_index: usize,
+    kind: SentenceKind,
+    list_level: Option<usize>,
+    slide: Option<usize>,
+}
+
+impl<'a, I: Iterator<Item = Result<Paragraph>>> Sentences<'a, I> {
+    pub(crate) fn new(splitter: &'a Splitter, paragraphs: I) -> Self {
+        Sentences {
+            splitter,
+            paragraphs: paragraphs.enumerate().fuse(),
+            ready: VecDeque::new(),
+            heading_path: Vec::new(),
+            offset: 0,
+            pending: String::new(),
+            pending_starts: Vec::new(),
+            pending_links: Vec::new(),
+        }
+    }
+
+    fn push_running(&mut self, index: usize, paragraph: &Paragraph) {
+        let normalized = normalize_whitespace(&paragraph.text);
+        if normalized.is_empty() {
+            return;
+        }
+        // Text never runs on into text of another kind, such as a quote or
+        // the notes of a slide, nor into the next slide or list item
+        let last = self.pending_starts.last();
+        if last.is_some_and(|last| {
+            (last.slide, last.kind) != (paragraph.slide, paragraph.kind)
+                || paragraph.kind == SentenceKind::ListItem
+        }) {
+            self.split_pending(true);
+        }
+        if !self.pending.is_empty() {
+            self.pending.push(' ');
+        }
+        for (position, link) in link_positions(&normalized, &paragraph.links) {
+            self.pending_links
+                .push((self.pending.len() + position, link.clone()));
+        }
+        self.pending_starts.push(PendingStart {
+            byte: self.pending.len(),
+            paragraph_index: index,
+            kind: paragraph.kind,
+            list_level: paragraph.list_level,
+            slide: paragraph.slide,
+        });
+        self.pending.push_str(&normalized);
+
+        self.split_pending(false);
+    }
+
+    // Emits the sentences of the pending text, keeping the last one pending
+    // unless the running text has ended
+    fn split_pending(&mut self, at_end: bool) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let sentences = self.splitter.split(&self.pending);
+        let count = sentences.len();
+        let mut cursor = 0;
+        let mut consumed = self.pending.len();
+        for (i, sentence) in sentences.into_iter().enumerate() {
+            let start = self.pending[cursor..]
+                .find(&sentence)
+                .map_or(cursor, |found| cursor + found);
+            if !at_end && i + 1 == count {
+                consumed = start;
+                break;
+            }
+            cursor = (start + sentence.len()).min(self.pending.len());
+
+            let paragraph = self
+                .pending_starts
+                .iter()
+                .rev()
+                .find(|paragraph| paragraph.byte <= start)
+                .copied()
+                .unwrap_or(self.pending_starts[0]);
+            let char_start = self.offset + self.pending[..start].chars().count();
+            let link_count = match at_end && i + 1 == count {
+                true => self.pending_links.len(),
+                false => self
+                    .pending_links
+                    .partition_point(|(position, _)| *position < cursor),
+            };
+            let links = self
+                .pending_links
+                .drain(..link_count)
+                .map(|(_, link)| link)
+                .collect();
+
+            self.ready.push_back(Sentence {
+                char_range: char_start..char_start + sentence.chars().count(),
+                text: sentence,
+                paragraph_index: paragraph.paragraph_index,
+                heading_path: self.heading_path.clone(),
+                kind: paragraph.kind,
+                heading_level: None,
+                list_level: paragraph.list_level,
+                links,
+                cell: None,
+                slide: paragraph.slide,
+            });
+        }
+
+        if at_end {
+            self.offset += self.pending.chars().count() + 1;
+            self.pending.clear();
+            self.pending_starts.clear();
+            self.pending_links.clear();
+            return;
+        }
+
+        self.offset += self.pending[..consumed].chars().count();
+        self.pending.drain(..consumed);
+        let first = self
+            .pending_starts
+            .iter()
+            .rposition(|paragraph| paragraph.byte <= consumed)
+            .unwrap_or(0);
+        self.pending_starts.drain(..first);
+        for paragraph in &mut self.pending_starts {
+            paragraph.byte = paragraph.byte.saturating_sub(consumed);
+        }
+        for (position, _) in &mut self.pending_links {
+            *position = position.saturating_sub(consumed);
+        }
+    }
+
+    // Entries of a reference list are each kept whole, as their initials,
+    // years and abbreviations would split them into fragments
+    fn in_bibliography(&self) -> bool {
+        self.heading_path
+            .last()
+            .is_some_and(|heading| is_bibliography_heading(heading))
+    }
+
+    fn push_standalone(&mut self, index: usize, paragraph: &Paragraph) {
+        let text = normalize_whitespace(&paragraph.text);
+        if text.is_empty() {
+            return;
+        }
+
+        if let Some(level) = paragraph.heading_level {
+            self.heading_path.truncate(level - 1);
+            self.heading_path.push(text.clone());
+        }
+
+        let length = text.chars().count();
+        self.ready.push_back(Sentence {
+            text,
+            paragraph_index: index,
+            char_range: self.offset..self.offset + length,
+            heading_path: self.heading_path.clone(),
+            kind: paragraph.kind,
+            heading_level: paragraph.heading_level,
+            list_level: paragraph.list_level,
+            links: paragraph.links.clone(),
+            cell: paragraph.cell.clone(),
+            slide: paragraph.slide,
+        });
+        self.offset += length + 1;
+    }
+}
+
+// Headings of reference lists, compared without numbering or a final colon
+const BIBLIOGRAPHY_HEADINGS: &[&str] = &[
+    "references",
+    "reference list",
+    "bibliography",
+    "works cited",
+    "literature cited",
+    "literaturverzeichnis",
+    "bibliographie",
+    "références",
+    "referencias",
+    "bibliografía",
+    "bibliografia",
+];
+
+fn is_bibliography_heading(heading: &str) -> bool {
+    let title = heading
+        .trim_start_matches(|c: char| c.is_ascii_digit() || matches!(c, '.' | ' '))
+        .trim_end_matches([':', ' '])
+        .to_lowercase();
+    BIBLIOGRAPHY_HEADINGS.contains(&title.as_str())
+}
+
+impl<I: Iterator<Item = Result<Paragraph>>> Iterator for Sentences<'_, I> {
+    type Item = Result<Sentence>;
+
+    fn next(&mut self) -> Option<Result<Sentence>> {
+        loop {
+            if let Some(sentence) = self.ready.pop_front() {
+                return Some(Ok(sentence));
+            }
+
+            match self.paragraphs.next() {
+                Some((_, Err(e))) => return Some(Err(e)),
+                Some((index, Ok(paragraph))) if paragraph.standalone || self.in_bibliography() => {
+                    self.split_pending(true);
+                    self.push_standalone(index, &paragraph);
+                }
+                Some((index, Ok(paragraph))) => self.push_running(index, &paragraph),
+                None if self.pending.is_empty() => return None,
+                None => self.split_pending(true),
+            }
+        }
+    }
+}
+
+// Links are looked up by their text, in order; one whose text cannot be found
+// is placed where the previous one ended
+fn link_positions<'l>(text: &str, links: &'l [Link]) -> Vec<(usize, &'l Link)> {
+    let mut cursor = 0;
+    links
+        .iter()
+        .map(|link| {
+            let link_text = normalize_whitespace(&link.text);
+            let position = match text[cursor..].find(&link_text) {
+                Some(found) if !link_text.is_empty() => {
+                    cursor += found + link_text.len();
+                    cursor - link_text.len()
+                }
+                _ => cursor,
+            };
+            (position, link)
+        })
+        .collect()
+}
+
+// Paragraphs in plain text are separated by blank lines
+pub(crate) fn text_paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(line.trim());
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+
+    paragraphs
+}
+
+// Decompresses the entry while it is read instead of up front. The reader owns
+// the archive's data, so it can outlive the archive it was found in.
+// `document` names the archive in messages.
+pub(crate) fn open_zip_entry<'a>(
+    data: Box<dyn ReadSeek + 'a>,
+    document: &str,
+    entry_name: &str,
+    max_size: u64,
+) -> Result<Box<dyn BufRead + 'a>> {
+    let mut archive = ZipArchive::new(data)?;
+
+    let (data_start, size, compression) = {
+        let entry = archive.by_name(entry_name)?;
+        (
+            entry.data_start(),
+            entry.compressed_size(),
+            entry.compression(),
+        )
+    };
+    let mut reader = archive.into_inner();
+    reader.seek(SeekFrom::Start(data_start))?;
+    let data = reader.take(size);
+
+    match compression {
+        CompressionMethod::Stored => Ok(Box::new(BufReader::new(data))),
+        CompressionMethod::Deflated => Ok(Box::new(BufReader::new(SizeLimit::new(
+            DeflateDecoder::new(data),
+            entry_name,
+            max_size,
+        )))),
+        method => Err(SentencerError::InvalidDocument(format!(
+            "'{}' in {} uses unsupported compression {}",
+            entry_name, document, method
+        ))),
+    }
+}
+
+// Flat ODF documents (.fodt) are a single XML file rather than a zip archive
+pub(crate) fn is_flat_xml(source: Source) -> Result<bool> {
+    let mut start = Vec::new();
+    source.open()?.take(64).read_to_end(&mut start)?;
+    let start = String::from_utf8_lossy(&start);
+
+    Ok(start
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .starts_with('<'))
+}
+
+pub(crate) fn read_zip_entry(source: Source, entry_name: &str) -> Result<String> {
+    let mut archive = ZipArchive::new(source.open()?)?;
+
+    let entry = archive.by_name(entry_name)?;
+    let mut content = String::new();
+    SizeLimit::new(entry, entry_name, Limits::default().max_entry_size)
+        .read_to_string(&mut content)?;
+
+    Ok(content)
+}
+
+// Fails once more than `max_size` bytes are read, so a small compressed entry
+// cannot expand without bound
+pub(crate) struct SizeLimit<R> {
+    inner: R,
+    entry_name: String,
+    max_size: u64,
+    read: u64,
+}
+
+impl<R: Read> SizeLimit<R> {
+    pub(crate) fn new(inner: R, entry_name: &str, max_size: u64) -> Self {
+        SizeLimit {
+            inner,
+            entry_name: entry_name.to_string(),
+            max_size,
+            read: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for SizeLimit<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.read += read as u64;
+        if self.read > self.max_size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::FileTooLarge,
+                format!(
+                    "'{}' expands to more than the limit of {} bytes",
+                    self.entry_name, self.max_size
+                ),
+            ));
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sentence::texts;
+    use std::fs;
+    use std::io::Write;
+    use zip::{ZipWriter, write::FileOptions};
+
+    fn create_zip(file_path: &str, entries: &[(&str, &str)]) -> Result<()> {
+        let file = File::create(file_path)?;
+        let mut zip = ZipWriter::new(file);
+        for (name, content) in entries {
+            zip.start_file(*name, FileOptions::default())?;
+            zip.write_all(content.as_bytes())?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_by_extension() -> Result<()> {
+        // Text without a signature of its own goes by the extension
+        let text = b"Some text.";
+        let detect = |name| DocumentFormat::detect_bytes(name, text);
+        assert_eq!(detect("report.ODT")?, DocumentFormat::Odt);
+        assert_eq!(detect("report.docx")?, DocumentFormat::Docx);
+        assert_eq!(detect("notes.md")?, DocumentFormat::Markdown);
+        assert_eq!(detect("draft.txt")?, DocumentFormat::Text);
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_by_content() -> Result<()> {
+        let renamed = "detect_renamed.txt";
+        let manifest = "detect_manifest";
+        create_zip(renamed, &[("word/document.xml", "<w:document/>")])?;
+        create_zip(
+            manifest,
+            &[(
+                "META-INF/manifest.xml",
+                r#"<manifest:file-entry manifest:full-path="/" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/>"#,
+            )],
+        )?;
+
+        let renamed_format = DocumentFormat::detect(renamed);
+        let manifest_format = DocumentFormat::detect(manifest);
+
+        fs::remove_file(renamed).ok();
+        fs::remove_file(manifest).ok();
+
+        assert_eq!(renamed_format?, DocumentFormat::Docx);
+        assert_eq!(manifest_format?, DocumentFormat::Ods);
+        assert_eq!(
+            DocumentFormat::detect_bytes("letter.doc", b"{\\rtf1 Hello.}")?,
+            DocumentFormat::Rtf
+        );
+        assert!(matches!(
+            DocumentFormat::detect_bytes("paper", b"%PDF-1.7"),
+            Err(SentencerError::UnsupportedFormat(message)) if message.contains("PDF")
+        ));
+        assert_eq!(
+            DocumentFormat::detect_bytes("README", "Plain text. Héllo.".as_bytes())?,
+            DocumentFormat::Text
+        );
+        assert!(DocumentFormat::detect_bytes("image", b"\x89PNG\r\n\x1a\n\0\0").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_by_archive_contents() -> Result<()> {
+        let odt_file = "detect_odt.bin";
+        let docx_file = "detect_docx.bin";
+        create_zip(
+            odt_file,
+            &[("mimetype", "application/vnd.oasis.opendocument.text")],
+        )?;
+        create_zip(docx_file, &[("word/document.xml", "<w:document/>")])?;
+
+        let odt_format = DocumentFormat::detect(odt_file);
+        let docx_format = DocumentFormat::detect(docx_file);
+
+        fs::remove_file(odt_file).ok();
+        fs::remove_file(docx_file).ok();
+
+        assert_eq!(odt_format?, DocumentFormat::Odt);
+        assert_eq!(docx_format?, DocumentFormat::Docx);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_bytes() -> Result<()> {
+        let odt_file = "parse_bytes.odt";
+        crate::test_utils::create_test_odt_file(
+            odt_file,
+            "<text:h text:outline-level=\"1\">Title</text:h><text:p>One. Two.</text:p>",
+        )?;
+        let data = fs::read(odt_file);
+        let from_file = parser_for(odt_file, Splitter::regex()?, ParseOptions::default())
+            .and_then(|parser| parser.parse_file(odt_file));
+        fs::remove_file(odt_file).ok();
+
+        // Without a name the archive is looked into
+        let data = data?;
+        let parser = parser_for_bytes("", &data, Splitter::regex()?, ParseOptions::default())?;
+        assert_eq!(parser.parse_bytes(&data)?, from_file?);
+
+        let text = "First line\nwraps. Second.".as_bytes();
+        let parser = parser_for_bytes(
+            "notes.txt",
+            text,
+            Splitter::regex()?,
+            ParseOptions::default(),
+        )?;
+        assert_eq!(
+            texts(&parser.parse_bytes(text)?),
+            vec!["First line wraps.", "Second."]
+        );
+        assert_eq!(
+            DocumentFormat::detect_bytes("", text)?,
+            DocumentFormat::Text
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_paragraphs_metadata() -> Result<()> {
+        let paragraphs = vec![
+            Paragraph::heading("Intro".to_string(), 1),
+            Paragraph::new("One sentence. Another".to_string()),
+            Paragraph::new("continues here.".to_string()),
+            Paragraph::heading("Details".to_string(), 2),
+            Paragraph {
+                kind: SentenceKind::Footnote,
+                ..Paragraph::new("A note.".to_string())
+            },
+            Paragraph::new("Items:".to_string()),
+            Paragraph {
+                kind: SentenceKind::ListItem,
+                ..Paragraph::new("First item".to_string())
+            },
+            Paragraph {
+                kind: SentenceKind::ListItem,
+                ..Paragraph::new("second item".to_string())
+            },
+        ];
+
+        let sentences = split_paragraphs(&Splitter::regex()?, &paragraphs);
+
+        let summary: Vec<_> = sentences
+            .iter()
+            .map(|s| {
+                (
+                    s.text.as_str(),
+                    s.paragraph_index,
+                    s.char_range.clone(),
+                    s.kind,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Intro", 0, 0..5, SentenceKind::Heading),
+                ("One sentence.", 1, 6..19, SentenceKind::Body),
+                ("Another continues here.", 1, 20..43, SentenceKind::Body),
+                ("Details", 3, 44..51, SentenceKind::Heading),
+                ("A note.", 4, 52..59, SentenceKind::Footnote),
+                ("Items:", 5, 60..66, SentenceKind::Body),
+                ("First item", 6, 67..77, SentenceKind::ListItem),
+                ("second item", 7, 78..89, SentenceKind::ListItem),
+            ]
+        );
+        assert_eq!(sentences[2].heading_path, vec!["Intro"]);
+        assert_eq!(sentences[4].heading_path, vec!["Intro", "Details"]);
+        assert_eq!(sentences[3].heading_level, Some(2));
+        assert_eq!(sentences[4].heading_level, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sentences_are_split_lazily() -> Result<()> {
+        let splitter = Splitter::regex()?;
+        let pulled = std::cell::Cell::new(0);
+        let paragraphs = ["One. Two", "continues. Three.", "Four.", "Five."]
+            .into_iter()
+            .inspect(|_| pulled.set(pulled.get() + 1))
+            .map(|text| Ok(Paragraph::new(text.to_string())));
+
+        let mut sentences = Sentences::new(&splitter, paragraphs);
+
+        assert_eq!(
+            sentences.next().transpose()?.map(|s| s.text),
+            Some("One.".to_string())
+        );
+        assert_eq!(pulled.get(), 1);
+        let second = sentences.next().transpose()?;
+        assert_eq!(pulled.get(), 2);
+        assert_eq!(
+            second.map(|s| (s.text, s.paragraph_index, s.char_range)),
+            Some(("Two continues.".to_string(), 0, 5..19))
+        );
+        let rest: Vec<_> = sentences
+            .map(|s| s.map(|s| (s.text, s.paragraph_index)))
+            .collect::<Result<_>>()?;
+        assert_eq!(
+            rest,
+            vec![
+                ("Three.".to_string(), 1),
+                ("Four.".to_string(), 2),
+                ("Five.".to_string(), 3)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bibliography_entries() -> Result<()> {
+        let splitter = Splitter::regex()?;
+        let paragraphs = vec![
+            Paragraph::new("Whales sing. They always have.".to_string()),
+            Paragraph::heading("7. References".to_string(), 1),
+            Paragraph::new("Payne, R. S. (1971). Songs of humpback whales. Science.".to_string()),
+            Paragraph::new("Smith, J. (2020). Whales. Oxford.".to_string()),
+            Paragraph::heading("Appendix".to_string(), 1),
+            Paragraph::new("More text. Here.".to_string()),
+        ];
+
+        assert_eq!(
+            texts(&split_paragraphs(&splitter, &paragraphs)),
+            vec![
+                "Whales sing.",
+                "They always have.",
+                "7. References",
+                "Payne, R. S. (1971). Songs of humpback whales. Science.",
+                "Smith, J. (2020). Whales. Oxford.",
+                "Appendix",
+                "More text.",
+                "Here."
+            ]
+        );
+        Ok(())
+    }
+}
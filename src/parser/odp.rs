@@ -0,0 +1,259 @@
+use super::odt::{attribute, space_count};
+use super::{
+    DocumentParser, Paragraph, ParseOptions, Source, is_flat_xml, open_zip_entry, split_paragraphs,
+};
+use crate::error::Result;
+use crate::sentence::{Sentence, SentenceKind};
+use crate::splitter::Splitter;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use std::io::{BufRead, BufReader};
+
+// Presentations are read slide by slide: the title as a heading, then the
+// text on the slide, then its speaker notes, all tagged with the slide number
+#[derive(Debug)]
+pub struct OdpParser {
+    splitter: Splitter,
+    options: ParseOptions,
+}
+
+impl OdpParser {
+    pub fn new() -> Result<Self> {
+        let splitter = Splitter::regex()?;
+
+        Ok(OdpParser::with_splitter(splitter))
+    }
+
+    pub fn with_splitter(splitter: Splitter) -> Self {
+        OdpParser {
+            splitter,
+            options: ParseOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    #[cfg(feature = "native")]
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::File(file_path))
+    }
+
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::Bytes(data))
+    }
+
+    pub(crate) fn parse_source(&self, source: Source) -> Result<Vec<Sentence>> {
+        // A flat presentation (.fodp) holds the content.xml elements itself
+        let content: Box<dyn BufRead> = match is_flat_xml(source)? {
+            true => Box::new(BufReader::new(source.open()?)),
+            false => {
+                let max_size = self.options.limits.max_entry_size;
+                open_zip_entry(source.open()?, source.name(), "content.xml", max_size)?
+            }
+        };
+
+        let paragraphs = self.extract_paragraphs(content)?;
+
+        Ok(split_paragraphs(&self.splitter, &paragraphs))
+    }
+
+    pub fn extract_paragraphs_from_xml(&self, xml_content: &str) -> Result<Vec<String>> {
+        let paragraphs = self.extract_paragraphs(xml_content.as_bytes())?;
+
+        Ok(paragraphs.into_iter().map(|p| p.text).collect())
+    }
+
+    // All paragraphs of a title frame make up one heading
+    fn extract_paragraphs<R: BufRead>(&self, content: R) -> Result<Vec<Paragraph>> {
+        let mut reader = Reader::from_reader(content);
+
+        let mut paragraphs = Vec::new();
+        let mut slide = 0;
+        let mut in_notes = false;
+        let mut title: Option<String> = None;
+        let mut current: Option<Paragraph> = None;
+        let mut list_depth = 0;
+        let mut buf = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(ref e) => match e.name().as_ref() {
+                    b"draw:page" => slide += 1,
+                    b"presentation:notes" => in_notes = true,
+                    b"draw:frame" => {
+                        match attribute(e, "presentation:class")?.as_deref() {
+                            Some("title") if !in_notes => title = Some(String::new()),
+                            // Placeholders repeated from the master slide
+                            Some("header" | "footer" | "date-time" | "page-number") => {
+                                let end = e.to_end().into_owned();
+                                reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                            }
+                            _ => {}
+                        }
+                    }
+                    b"text:p" | b"text:h" => match &mut title {
+                        Some(text) if !text.is_empty() => text.push(' '),
+                        Some(_) => {}
+                        None => {
+                            let kind = match (in_notes, list_depth > 0) {
+                                (true, _) => SentenceKind::SpeakerNote,
+                                (false, true) => SentenceKind::ListItem,
+                                (false, false) => SentenceKind::Body,
+                            };
+                            let items_as_sentences = self.options.list_items_as_sentences;
+                            current = Some(Paragraph {
+                                standalone: list_depth > 0 && items_as_sentences,
+                                kind,
+                                list_level: (list_depth > 0).then_some(list_depth),
+                                slide: Some(slide),
+                                ..Paragraph::default()
+                            });
+                        }
+                    },
+                    b"text:list" => list_depth += 1,
+                    // Reviewer comments are not part of the slide
+                    b"office:annotation" => {
+                        let end = e.to_end().into_owned();
+                        reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                    }
+                    _ => {}
+                },
+                Event::Empty(ref e) => {
+                    let text = match e.name().as_ref() {
+                        b"text:s" => " ".repeat(space_count(e)?),
+                        b"text:tab" | b"text:line-break" => " ".to_string(),
+                        _ => String::new(),
+                    };
+                    push_text(&mut title, &mut current, &text);
+                }
+                Event::Text(e) => push_text(&mut title, &mut current, &e.unescape()?),
+                Event::End(ref e) => match e.name().as_ref() {
+                    b"presentation:notes" => in_notes = false,
+                    b"draw:frame" => {
+                        if let Some(text) = title.take() {
+                            paragraphs.push(Paragraph {
+                                slide: Some(slide),
+                                ..Paragraph::heading(text, 1)
+                            });
+                        }
+                    }
+                    b"text:p" | b"text:h" => paragraphs.extend(current.take()),
+                    b"text:list" => list_depth -= 1,
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(paragraphs)
+    }
+}
+
+// Text outside any paragraph, such as the whitespace between elements, is dropped
+fn push_text(title: &mut Option<String>, current: &mut Option<Paragraph>, text: &str) {
+    if let Some(title) = title {
+        title.push_str(text);
+    } else if let Some(paragraph) = current {
+        paragraph.text.push_str(text);
+    }
+}
+
+impl DocumentParser for OdpParser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        OdpParser::parse_file(self, file_path)
+    }
+
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        OdpParser::parse_bytes(self, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::DocumentFormat;
+    use crate::sentence::texts;
+    use crate::test_utils::create_test_package;
+    use std::fs;
+
+    fn create_test_odp_file(file_path: &str, pages: &str) -> Result<()> {
+        let content_xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:draw="urn:oasis:names:tc:opendocument:xmlns:drawing:1.0"
+    xmlns:presentation="urn:oasis:names:tc:opendocument:xmlns:presentation:1.0"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+    <office:body><office:presentation>{}</office:presentation></office:body>
+</office:document-content>"#,
+            pages
+        );
+
+        create_test_package(
+            file_path,
+            "application/vnd.oasis.opendocument.presentation",
+            &content_xml,
+        )
+    }
+
+    #[test]
+    fn test_odp_slides_and_notes() -> Result<()> {
+        let test_file = "test_slides.odp";
+        create_test_odp_file(
+            test_file,
+            r#"<draw:page draw:name="page1">
+                <draw:frame presentation:class="title"><draw:text-box><text:p>Welcome</text:p><text:p>to the talk</text:p></draw:text-box></draw:frame>
+                <draw:frame presentation:class="outline"><draw:text-box>
+                    <text:list><text:list-item><text:p>First point</text:p></text:list-item></text:list>
+                </draw:text-box></draw:frame>
+                <draw:frame presentation:class="page-number"><draw:text-box><text:p>1</text:p></draw:text-box></draw:frame>
+                <presentation:notes>
+                    <draw:page-thumbnail/>
+                    <draw:frame presentation:class="notes"><draw:text-box><text:p>Greet everyone. Then start.</text:p></draw:text-box></draw:frame>
+                </presentation:notes>
+            </draw:page>
+            <draw:page draw:name="page2">
+                <draw:frame><draw:text-box><text:p>No title here</text:p></draw:text-box></draw:frame>
+            </draw:page>"#,
+        )?;
+
+        let format = DocumentFormat::detect(test_file);
+        let sentences = OdpParser::new()?.parse_file(test_file);
+        fs::remove_file(test_file).ok();
+
+        assert_eq!(format?, DocumentFormat::Odp);
+        let sentences = sentences?;
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "Welcome to the talk",
+                "First point",
+                "Greet everyone.",
+                "Then start.",
+                "No title here"
+            ]
+        );
+        let tags: Vec<_> = sentences
+            .iter()
+            .map(|sentence| (sentence.kind, sentence.slide))
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                (SentenceKind::Heading, Some(1)),
+                (SentenceKind::ListItem, Some(1)),
+                (SentenceKind::SpeakerNote, Some(1)),
+                (SentenceKind::SpeakerNote, Some(1)),
+                (SentenceKind::Body, Some(2))
+            ]
+        );
+        assert_eq!(sentences[2].heading_path, vec!["Welcome to the talk"]);
+        Ok(())
+    }
+}
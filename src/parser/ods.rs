@@ -0,0 +1,328 @@
+use super::odt::{attribute, space_count};
+use super::{
+    DocumentParser, Paragraph, ParseOptions, Source, is_flat_xml, open_zip_entry, split_paragraphs,
+};
+use crate::error::Result;
+use crate::sentence::{CellPosition, Sentence, SentenceKind};
+use crate::splitter::Splitter;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::io::{BufRead, BufReader};
+
+// Spreadsheets are read cell by cell, each cell with text becoming one
+// sentence that knows its sheet, row and column
+#[derive(Debug)]
+pub struct OdsParser {
+    splitter: Splitter,
+    options: ParseOptions,
+}
+
+impl OdsParser {
+    pub fn new() -> Result<Self> {
+        let splitter = Splitter::regex()?;
+
+        Ok(OdsParser::with_splitter(splitter))
+    }
+
+    pub fn with_splitter(splitter: Splitter) -> Self {
+        OdsParser {
+            splitter,
+            options: ParseOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    #[cfg(feature = "native")]
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::File(file_path))
+    }
+
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::Bytes(data))
+    }
+
+    pub(crate) fn parse_source(&self, source: Source) -> Result<Vec<Sentence>> {
+        // A flat spreadsheet (.fods) holds the content.xml elements itself
+        let content: Box<dyn BufRead> = match is_flat_xml(source)? {
+            true => Box::new(BufReader::new(source.open()?)),
+            false => {
+                let max_size = self.options.limits.max_entry_size;
+                open_zip_entry(source.open()?, source.name(), "content.xml", max_size)?
+            }
+        };
+
+        let paragraphs = self.extract_paragraphs(content)?;
+
+        Ok(split_paragraphs(&self.splitter, &paragraphs))
+    }
+
+    pub fn extract_paragraphs_from_xml(&self, xml_content: &str) -> Result<Vec<String>> {
+        let paragraphs = self.extract_paragraphs(xml_content.as_bytes())?;
+
+        Ok(paragraphs.into_iter().map(|p| p.text).collect())
+    }
+
+    // One paragraph per cell with text, or per row with
+    // ParseOptions::spreadsheet_rows, in sheet order
+    fn extract_paragraphs<R: BufRead>(&self, content: R) -> Result<Vec<Paragraph>> {
+        let mut reader = Reader::from_reader(content);
+        let rows = self.options.spreadsheet_rows;
+
+        let mut paragraphs = Vec::new();
+        let mut sheet = String::new();
+        let mut sheets_seen = 0;
+        let mut row = 0;
+        let mut column = 0;
+        // Cells are counted as they start; repeated cells and rows take the
+        // space of all their copies once they end
+        let mut repeated_columns = 1;
+        let mut repeated_rows = 1;
+        let mut cell: Option<String> = None;
+        let mut row_text = String::new();
+        let mut buf = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(ref e) => match e.name().as_ref() {
+                    b"table:table" => {
+                        sheets_seen += 1;
+                        sheet = attribute(e, "table:name")?
+                            .unwrap_or_else(|| format!("Sheet{}", sheets_seen));
+                        row = 0;
+                    }
+                    b"table:table-row" => {
+                        row += 1;
+                        column = 0;
+                        repeated_rows = repeat_count(e, "table:number-rows-repeated")?;
+                    }
+                    b"table:table-cell" | b"table:covered-table-cell" => {
+                        column += 1;
+                        repeated_columns = repeat_count(e, "table:number-columns-repeated")?;
+                        cell = Some(String::new());
+                    }
+                    b"text:p" | b"text:h" => {
+                        if let Some(text) = cell.as_mut().filter(|text| !text.is_empty()) {
+                            text.push(' ');
+                        }
+                    }
+                    // Comments on a cell are not part of its text
+                    b"office:annotation" => {
+                        let end = e.to_end().into_owned();
+                        reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                    }
+                    _ => {}
+                },
+                Event::Empty(ref e) => match e.name().as_ref() {
+                    b"table:table-cell" | b"table:covered-table-cell" => {
+                        column += repeat_count(e, "table:number-columns-repeated")?;
+                    }
+                    b"text:s" => {
+                        if let Some(text) = &mut cell {
+                            text.push_str(&" ".repeat(space_count(e)?));
+                        }
+                    }
+                    b"text:tab" | b"text:line-break" => {
+                        if let Some(text) = &mut cell {
+                            text.push(' ');
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Text(e) => {
+                    if let Some(text) = &mut cell {
+                        text.push_str(&e.unescape()?);
+                    }
+                }
+                Event::End(ref e) => match e.name().as_ref() {
+                    // A cell repeated across columns is read once, at its first
+                    b"table:table-cell" | b"table:covered-table-cell" => {
+                        let text = cell.take().unwrap_or_default();
+                        if !text.trim().is_empty() {
+                            if rows {
+                                if !row_text.is_empty() {
+                                    row_text.push(' ');
+                                }
+                                row_text.push_str(&text);
+                            } else {
+                                paragraphs.push(cell_paragraph(text, &sheet, row, Some(column)));
+                            }
+                        }
+                        column += repeated_columns - 1;
+                    }
+                    b"table:table-row" => {
+                        if !row_text.is_empty() {
+                            let text = std::mem::take(&mut row_text);
+                            paragraphs.push(cell_paragraph(text, &sheet, row, None));
+                        }
+                        row += repeated_rows - 1;
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(paragraphs)
+    }
+}
+
+fn cell_paragraph(text: String, sheet: &str, row: usize, column: Option<usize>) -> Paragraph {
+    Paragraph {
+        standalone: true,
+        kind: SentenceKind::TableCell,
+        cell: Some(CellPosition {
+            sheet: sheet.to_string(),
+            row,
+            column,
+        }),
+        ..Paragraph::new(text)
+    }
+}
+
+// How many copies of a row or cell the element stands for
+fn repeat_count(element: &BytesStart, name: &str) -> Result<usize> {
+    let count = attribute(element, name)?
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(1);
+
+    Ok(count.max(1))
+}
+
+impl DocumentParser for OdsParser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        OdsParser::parse_file(self, file_path)
+    }
+
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        OdsParser::parse_bytes(self, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::DocumentFormat;
+    use crate::sentence::texts;
+    use crate::test_utils::create_test_package;
+    use std::fs;
+
+    fn create_test_ods_file(file_path: &str, tables: &str) -> Result<()> {
+        let content_xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+    <office:body><office:spreadsheet>{}</office:spreadsheet></office:body>
+</office:document-content>"#,
+            tables
+        );
+
+        create_test_package(
+            file_path,
+            "application/vnd.oasis.opendocument.spreadsheet",
+            &content_xml,
+        )
+    }
+
+    const TABLES: &str = r#"
+        <table:table table:name="Terms">
+            <table:table-row>
+                <table:table-cell><text:p>Term</text:p></table:table-cell>
+                <table:table-cell table:number-columns-repeated="2"/>
+                <table:table-cell><text:p>Meaning</text:p></table:table-cell>
+            </table:table-row>
+            <table:table-row table:number-rows-repeated="3">
+                <table:table-cell table:number-columns-repeated="4"/>
+            </table:table-row>
+            <table:table-row>
+                <table:table-cell><text:p>Sentence</text:p></table:table-cell>
+                <table:table-cell table:number-columns-repeated="3">
+                    <text:p>A unit of text.<office:annotation><text:p>Check this</text:p></office:annotation></text:p>
+                    <text:p>It ends  here.</text:p>
+                </table:table-cell>
+            </table:table-row>
+        </table:table>
+        <table:table table:name="Notes">
+            <table:table-row><table:table-cell><text:p>Last one</text:p></table:table-cell></table:table-row>
+        </table:table>"#;
+
+    #[test]
+    fn test_ods_cells() -> Result<()> {
+        let test_file = "test_cells.ods";
+        create_test_ods_file(test_file, TABLES)?;
+
+        let format = DocumentFormat::detect(test_file);
+        let sentences = OdsParser::new()?.parse_file(test_file);
+        fs::remove_file(test_file).ok();
+
+        assert_eq!(format?, DocumentFormat::Ods);
+        let sentences = sentences?;
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "Term",
+                "Meaning",
+                "Sentence",
+                "A unit of text. It ends here.",
+                "Last one"
+            ]
+        );
+        let references: Vec<_> = sentences
+            .iter()
+            .filter_map(|sentence| sentence.cell.as_ref().map(CellPosition::reference))
+            .collect();
+        assert_eq!(
+            references,
+            vec!["Terms.A1", "Terms.D1", "Terms.A5", "Terms.B5", "Notes.A1"]
+        );
+        assert!(
+            sentences
+                .iter()
+                .all(|sentence| sentence.kind == SentenceKind::TableCell)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ods_rows() -> Result<()> {
+        let test_file = "test_rows.ods";
+        create_test_ods_file(test_file, TABLES)?;
+
+        let sentences = OdsParser::new()?
+            .with_options(ParseOptions {
+                spreadsheet_rows: true,
+                ..ParseOptions::default()
+            })
+            .parse_file(test_file);
+        fs::remove_file(test_file).ok();
+
+        let sentences = sentences?;
+        let rows: Vec<_> = sentences
+            .iter()
+            .map(|sentence| {
+                let reference = sentence.cell.as_ref().map(CellPosition::reference);
+                (sentence.text.as_str(), reference.unwrap_or_default())
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("Term Meaning", "Terms.1".to_string()),
+                (
+                    "Sentence A unit of text. It ends here.",
+                    "Terms.5".to_string()
+                ),
+                ("Last one", "Notes.1".to_string())
+            ]
+        );
+        Ok(())
+    }
+}
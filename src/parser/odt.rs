@@ -0,0 +1,2112 @@
+use super::crypto;
+use super::epub::percent_decode;
+use super::{
+    ChangesView, CommentsMode, DocumentFormat, DocumentParser, NotesMode, Paragraph, ParseOptions,
+    ReadSeek, SentenceStream, Sentences, Source, is_flat_xml, open_zip_entry, split_paragraphs,
+};
+use crate::error::{Result, SentencerError};
+use crate::sentence::{Link, Sentence, SentenceKind};
+use crate::splitter::Splitter;
+#[cfg(feature = "native")]
+use memmap2::Mmap;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::QName;
+use std::collections::{HashMap, HashSet, VecDeque};
+#[cfg(feature = "native")]
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use zip::result::ZipError;
+
+#[derive(Debug)]
+pub struct OdtParser {
+    splitter: Splitter,
+    options: ParseOptions,
+}
+
+impl OdtParser {
+    pub fn new() -> Result<Self> {
+        let splitter = Splitter::regex()?;
+
+        Ok(OdtParser::with_splitter(splitter))
+    }
+
+    pub fn with_splitter(splitter: Splitter) -> Self {
+        OdtParser {
+            splitter,
+            options: ParseOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    // Whether the document can only be read with a password
+    pub fn is_encrypted(file_path: &str) -> Result<bool> {
+        let source = Source::File(file_path);
+        if DocumentFormat::detect(file_path)? != DocumentFormat::Odt || is_flat_xml(source)? {
+            return Ok(false);
+        }
+
+        Ok(crypto::encryption_data(source, "content.xml")?.is_some())
+    }
+
+    #[cfg(feature = "native")]
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        self.sentences(file_path)?.collect()
+    }
+
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        let source = Source::Bytes(data);
+        let content = self.part(source, Box::new(Cursor::new(data)), "content.xml")?;
+        let mut paragraphs = self.paragraph_reader(content);
+        if self.options.emphasis_markers {
+            paragraphs.text_styles = Some(self.text_styles(source)?);
+        }
+
+        self.with_pages(source, paragraphs)?.collect()
+    }
+
+    // Sentences are split as the XML is read, so only the paragraphs around
+    // the current sentence are kept in memory
+    #[cfg(feature = "native")]
+    pub fn sentences(
+        &self,
+        file_path: &str,
+    ) -> Result<impl Iterator<Item = Result<Sentence>> + '_> {
+        let file: Box<dyn ReadSeek> = match self.options.mmap {
+            true => Box::new(Cursor::new(map_file(file_path)?)),
+            false => Box::new(BufReader::new(File::open(file_path)?)),
+        };
+        let source = Source::File(file_path);
+        let content = self.part(source, file, "content.xml")?;
+        let mut paragraphs = self.paragraph_reader(content);
+        if !self.options.skip_linked_files {
+            paragraphs.linked = Some(LinkedSections::new(Path::new(file_path), &self.options));
+        }
+        if self.options.emphasis_markers {
+            paragraphs.text_styles = Some(self.text_styles(source)?);
+        }
+
+        self.with_pages(source, paragraphs)
+    }
+
+    // Only the page headers and footers, such as for listing them
+    pub fn page_sentences(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        let (headers, footers) = self.page_paragraphs(Source::File(file_path))?;
+
+        Ok(split_paragraphs(
+            &self.splitter,
+            &[headers, footers].concat(),
+        ))
+    }
+
+    // The sentences of the body paragraphs, between those of the page headers
+    // and footers when they are read
+    fn with_pages<'a, I>(
+        &'a self,
+        source: Source,
+        body: I,
+    ) -> Result<Sentences<'a, impl Iterator<Item = Result<Paragraph>> + 'a>>
+    where
+        I: Iterator<Item = Result<Paragraph>> + 'a,
+    {
+        let (headers, footers) = match self.options.headers_footers {
+            true => self.page_paragraphs(source)?,
+            false => (Vec::new(), Vec::new()),
+        };
+        let paragraphs = headers
+            .into_iter()
+            .map(Ok)
+            .chain(body)
+            .chain(footers.into_iter().map(Ok));
+
+        Ok(Sentences::new(&self.splitter, paragraphs))
+    }
+
+    // Page headers and footers are part of the page styles in styles.xml,
+    // which a package may leave out
+    fn page_paragraphs(&self, source: Source) -> Result<(Vec<Paragraph>, Vec<Paragraph>)> {
+        match self.part(source, source.open()?, "styles.xml") {
+            Ok(styles) => read_page_paragraphs(styles),
+            Err(SentencerError::Zip(ZipError::FileNotFound)) => Ok((Vec::new(), Vec::new())),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Named text styles such as "Emphasis" are in styles.xml, while the
+    // automatic styles of content.xml are read along with the paragraphs
+    fn text_styles(&self, source: Source) -> Result<TextStyles> {
+        match self.part(source, source.open()?, "styles.xml") {
+            Ok(styles) => TextStyles::read(styles),
+            Err(SentencerError::Zip(ZipError::FileNotFound)) => Ok(TextStyles::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    // The XML of a part of the document, such as content.xml, read from
+    // `data`, which holds the document `source` names
+    fn part<'a>(
+        &self,
+        source: Source,
+        data: Box<dyn ReadSeek + 'a>,
+        entry_name: &str,
+    ) -> Result<Box<dyn BufRead + 'a>> {
+        let max_size = self.options.limits.max_entry_size;
+        // A flat document holds the elements of every part in the file itself
+        if is_flat_xml(source)? {
+            return Ok(Box::new(BufReader::new(data)));
+        }
+
+        let name = source.name();
+        match crypto::encryption_data(source, entry_name)? {
+            Some(encryption) => {
+                let password = self
+                    .options
+                    .password
+                    .as_deref()
+                    .ok_or_else(|| SentencerError::PasswordRequired(name.to_string()))?;
+                let mut encrypted = Vec::new();
+                open_zip_entry(data, name, entry_name, max_size)?.read_to_end(&mut encrypted)?;
+                let content = crypto::decrypt(&encrypted, &encryption, password, max_size)?;
+                Ok(Box::new(Cursor::new(content)))
+            }
+            None => open_zip_entry(data, name, entry_name, max_size),
+        }
+    }
+
+    pub fn extract_text_from_xml(&self, xml_content: &str) -> Result<String> {
+        self.extract_text(xml_content.as_bytes())
+    }
+
+    // The text of content.xml as it is read, one paragraph at a time
+    pub fn extract_text<R: BufRead>(&self, content: R) -> Result<String> {
+        let mut text_content = String::new();
+        for paragraph in self.paragraph_reader(content) {
+            text_content.push_str(&paragraph?.text);
+            text_content.push(' ');
+        }
+
+        Ok(text_content)
+    }
+
+    // Paragraphs are returned in document order, except that notes are moved
+    // to the end when NotesMode::Separate is selected
+    pub fn extract_paragraphs_from_xml(&self, xml_content: &str) -> Result<Vec<String>> {
+        self.paragraph_reader(xml_content.as_bytes())
+            .map(|paragraph| paragraph.map(|p| p.text))
+            .collect()
+    }
+
+    fn paragraph_reader<R: BufRead>(&self, content: R) -> ParagraphReader<R> {
+        ParagraphReader {
+            reader: Reader::from_reader(content),
+            buf: Vec::new(),
+            notes_mode: self.options.notes,
+            comments_mode: self.options.comments,
+            changes_view: self.options.changes,
+            changes: TrackedChanges::default(),
+            cells_as_sentences: self.options.table_cells_as_sentences,
+            items_as_sentences: self.options.list_items_as_sentences,
+            alt_text: self.options.alt_text,
+            include_toc: self.options.include_toc,
+            list_depth: 0,
+            in_bibliography: false,
+            quote_styles: HashSet::new(),
+            text_styles: self.options.emphasis_markers.then(TextStyles::default),
+            spans: Vec::new(),
+            ready: VecDeque::new(),
+            open: Vec::new(),
+            open_paragraphs: Vec::new(),
+            note_depth: 0,
+            note_label: String::new(),
+            note_kind: SentenceKind::Footnote,
+            in_citation: false,
+            notes: Vec::new(),
+            comment: None,
+            link: None,
+            comments: Vec::new(),
+            previous_label: None,
+            table: TablePosition::default(),
+            cell_paragraph: None,
+            linked: None,
+            depth: 0,
+            max_depth: self.options.limits.max_xml_depth,
+            done: false,
+        }
+    }
+}
+
+// The pages of the file are read by the system as the archive is read from
+// them, and can be dropped again under memory pressure
+#[cfg(feature = "native")]
+fn map_file(file_path: &str) -> Result<Mmap> {
+    let file = File::open(file_path)?;
+    // Safety: the map is only read. A document truncated on disk while it is
+    // parsed ends the process, which the --mmap flag documents.
+    Ok(unsafe { Mmap::map(&file)? })
+}
+
+const QUOTATION_STYLE: &str = "Quotations";
+
+// Reads content.xml event by event. A paragraph is handed out once every
+// paragraph, note and table cell it is part of has been closed; notes kept
+// for NotesMode::Separate are handed out at the end.
+struct ParagraphReader<R: BufRead> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    notes_mode: NotesMode,
+    comments_mode: CommentsMode,
+    changes_view: ChangesView,
+    changes: TrackedChanges,
+    cells_as_sentences: bool,
+    items_as_sentences: bool,
+    alt_text: bool,
+    include_toc: bool,
+    list_depth: usize,
+    in_bibliography: bool,
+    // Automatic styles of the document based on the quotation style
+    quote_styles: HashSet<String>,
+    // Set when emphasis is kept as Markdown markers
+    text_styles: Option<TextStyles>,
+    // The text:span elements being read, with the paragraph and position
+    // where the text of those with emphasis starts
+    spans: Vec<Option<(usize, usize, Emphasis)>>,
+    ready: VecDeque<Paragraph>,
+    // Paragraphs that are still being read, with the label of their note
+    open: Vec<(Paragraph, Option<String>)>,
+    // Stack of the paragraphs being read, innermost last, so text inside any
+    // inline element goes to the paragraph that contains it. Excluded notes
+    // have no paragraph.
+    open_paragraphs: Vec<Option<usize>>,
+    note_depth: usize,
+    note_label: String,
+    note_kind: SentenceKind,
+    in_citation: bool,
+    notes: Vec<Paragraph>,
+    comment: Option<OpenComment>,
+    // The text:a being read; its text is collected along with the paragraph's
+    link: Option<Link>,
+    comments: Vec<Paragraph>,
+    previous_label: Option<String>,
+    table: TablePosition,
+    cell_paragraph: Option<usize>,
+    // Set when the document is a file, whose linked sections can be found
+    linked: Option<LinkedSections>,
+    // Nesting of the element being read
+    depth: usize,
+    max_depth: usize,
+    done: bool,
+}
+
+impl<R: BufRead> ParagraphReader<R> {
+    fn read_event(&mut self) -> Result<()> {
+        let notes_mode = self.notes_mode;
+        // Events borrow the buffer, which is taken out while they are handled
+        // so their text goes into the paragraphs without being copied first
+        let mut buf = std::mem::take(&mut self.buf);
+
+        match self.reader.read_event_into(&mut buf)? {
+            Event::Start(_) if self.depth >= self.max_depth => {
+                return Err(SentencerError::InvalidDocument(format!(
+                    "XML elements are nested more than {} levels deep",
+                    self.max_depth
+                )));
+            }
+            // Entities declared in the document could expand without bound
+            Event::DocType(ref e) if String::from_utf8_lossy(e).contains("<!ENTITY") => {
+                return Err(SentencerError::InvalidDocument(
+                    "XML entity declarations are not supported".to_string(),
+                ));
+            }
+            Event::Start(ref e) => {
+                self.depth += 1;
+                match e.name().as_ref() {
+                    // Parts of a flat document that are not in content.xml, and
+                    // embedded images, which hold no text
+                    b"office:meta"
+                    | b"office:settings"
+                    | b"office:styles"
+                    | b"office:master-styles"
+                    | b"office:binary-data" => {
+                        let end = e.to_end().into_owned();
+                        self.reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                        self.depth -= 1;
+                    }
+                    // Generated tables of contents and indexes, which repeat
+                    // headings and terms as fragments
+                    b"text:table-of-content"
+                    | b"text:alphabetical-index"
+                    | b"text:illustration-index"
+                    | b"text:table-index"
+                    | b"text:object-index"
+                    | b"text:user-index"
+                        if !self.include_toc =>
+                    {
+                        let end = e.to_end().into_owned();
+                        self.reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                        self.depth -= 1;
+                    }
+                    name @ (b"text:p" | b"text:h") => {
+                        if self.changes.reading {
+                            // Deleted paragraphs are kept with their change
+                            self.changes.separate_paragraph();
+                            self.open_paragraphs.push(None);
+                        } else if self.note_depth > 0 && notes_mode == NotesMode::Exclude {
+                            self.open_paragraphs.push(None);
+                        } else if let Some(comment) = &mut self.comment {
+                            if self.comments_mode == CommentsMode::Exclude {
+                                self.open_paragraphs.push(None);
+                            } else {
+                                // Only the first paragraph of a comment is tagged
+                                let text = match comment.paragraphs {
+                                    0 => format!("[{}] ", comment.label()),
+                                    _ => String::new(),
+                                };
+                                comment.paragraphs += 1;
+                                let paragraph = Paragraph {
+                                    standalone: true,
+                                    kind: SentenceKind::Comment,
+                                    ..Paragraph::new(text)
+                                };
+                                self.open_paragraphs.push(Some(self.open.len()));
+                                self.open.push((paragraph, None));
+                            }
+                        } else if let Some(index) = self.cell_paragraph {
+                            // Every paragraph of the cell feeds one sentence
+                            let cell = &mut self.open[index].0;
+                            if !cell.text.is_empty() {
+                                cell.text.push(' ');
+                            }
+                            self.open_paragraphs.push(Some(index));
+                        } else {
+                            let paragraph = if name == b"text:h" {
+                                Paragraph::heading(String::new(), outline_level(e)?)
+                            } else if self.note_depth > 0 {
+                                Paragraph {
+                                    kind: self.note_kind,
+                                    ..Paragraph::default()
+                                }
+                            } else if self.in_bibliography {
+                                // Each entry of a generated bibliography is
+                                // a paragraph of its own
+                                Paragraph {
+                                    standalone: true,
+                                    ..Paragraph::default()
+                                }
+                            } else if self.list_depth > 0 {
+                                Paragraph {
+                                    standalone: self.items_as_sentences,
+                                    kind: SentenceKind::ListItem,
+                                    list_level: Some(self.list_depth),
+                                    ..Paragraph::default()
+                                }
+                            } else if self.is_quote(e)? {
+                                Paragraph {
+                                    kind: SentenceKind::Quote,
+                                    ..Paragraph::default()
+                                }
+                            } else {
+                                Paragraph::default()
+                            };
+                            let label = (self.note_depth > 0).then(|| self.note_label.clone());
+                            self.open_paragraphs.push(Some(self.open.len()));
+                            self.open.push((paragraph, label));
+                        }
+                    }
+                    b"text:list" => self.list_depth += 1,
+                    b"text:bibliography" => self.in_bibliography = true,
+                    b"style:style" | b"style:text-properties" => self.read_style(e)?,
+                    b"text:span" => {
+                        if let Some(styles) = &self.text_styles {
+                            let emphasis = match attribute(e, "text:style-name")? {
+                                Some(name) => styles.emphasis(&name),
+                                None => Emphasis::default(),
+                            };
+                            let span = match self.open_paragraphs.last() {
+                                Some(&Some(index))
+                                    if !emphasis.is_plain() && !self.changes.reading =>
+                                {
+                                    Some((index, self.open[index].0.text.len(), emphasis))
+                                }
+                                _ => None,
+                            };
+                            self.spans.push(span);
+                        }
+                    }
+                    // The alternative text of an image sits in the frame around
+                    // it, inside the paragraph the image is anchored to
+                    b"svg:title" | b"svg:desc" => {
+                        if self.alt_text && !self.changes.reading && self.comment.is_none() {
+                            let paragraph = Paragraph {
+                                standalone: true,
+                                kind: SentenceKind::AltText,
+                                ..Paragraph::default()
+                            };
+                            self.open_paragraphs.push(Some(self.open.len()));
+                            self.open.push((paragraph, None));
+                        } else {
+                            self.open_paragraphs.push(None);
+                        }
+                    }
+                    // Numbered captions such as "Figure 2: ..." count their
+                    // figure with a sequence field
+                    b"text:sequence" => {
+                        if let Some(&Some(index)) = self.open_paragraphs.last() {
+                            let paragraph = &mut self.open[index].0;
+                            if paragraph.kind == SentenceKind::Body {
+                                paragraph.kind = SentenceKind::Caption;
+                                paragraph.standalone = true;
+                            }
+                        }
+                    }
+                    b"text:a" => {
+                        self.link = Some(Link {
+                            href: attribute(e, "xlink:href")?.unwrap_or_default(),
+                            ..Link::default()
+                        });
+                    }
+                    b"text:note" => {
+                        self.note_depth += 1;
+                        self.note_kind = match e.try_get_attribute("text:note-class")? {
+                            Some(class) if class.value.as_ref() == b"endnote" => {
+                                SentenceKind::Endnote
+                            }
+                            _ => SentenceKind::Footnote,
+                        };
+                        self.note_label = match self.note_kind {
+                            SentenceKind::Endnote => "Endnote",
+                            _ => "Footnote",
+                        }
+                        .to_string();
+                    }
+                    b"text:note-citation" => {
+                        self.in_citation = true;
+                    }
+                    b"text:tracked-changes" => self.changes.reading = true,
+                    b"text:changed-region" => {
+                        self.changes.region = attribute(e, "text:id")?;
+                    }
+                    b"text:deletion" => self.changes.start_deletion(),
+                    b"text:insertion" => self.changes.add_insertion(),
+                    b"office:change-info" => self.changes.in_change_info = true,
+                    b"office:annotation" => {
+                        self.comment = Some(OpenComment {
+                            depth: self.open_paragraphs.len(),
+                            ..OpenComment::default()
+                        });
+                    }
+                    b"dc:creator" => {
+                        if let Some(comment) = &mut self.comment {
+                            comment.in_creator = true;
+                        }
+                    }
+                    b"table:table" => self.table.start_table(),
+                    b"table:table-row" => self.table.start_row(),
+                    b"table:table-cell" => {
+                        self.table.start_cell();
+                        if self.cells_as_sentences && self.note_depth == 0 {
+                            self.cell_paragraph = Some(self.open.len());
+                            let cell = Paragraph {
+                                standalone: true,
+                                kind: SentenceKind::TableCell,
+                                ..Paragraph::default()
+                            };
+                            self.open.push((cell, None));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Empty(ref e) => match e.name().as_ref() {
+                b"style:style" => {
+                    self.read_style(e)?;
+                    if let Some(styles) = &mut self.text_styles {
+                        styles.end();
+                    }
+                }
+                b"style:text-properties" => self.read_style(e)?,
+                b"table:table-cell" | b"table:covered-table-cell" => self.table.start_cell(),
+                // Runs of spaces, tabs and line breaks are stored as elements
+                b"text:s" => {
+                    let count = space_count(e)?;
+                    self.push_text(&" ".repeat(count));
+                }
+                b"text:tab" => self.push_text("\t"),
+                b"text:line-break" => self.push_text("\n"),
+                // A section of a master document linked to another file, which
+                // is read in place of the copy the section holds
+                b"text:section-source" => {
+                    let linked = match (&self.linked, attribute(e, "xlink:href")?) {
+                        (Some(linked), Some(href)) => linked.paragraphs(&href)?,
+                        _ => None,
+                    };
+                    if let Some(paragraphs) = linked {
+                        self.release_open();
+                        self.ready.extend(paragraphs);
+                        let end = QName(b"text:section");
+                        self.reader.read_to_end_into(end, &mut Vec::new())?;
+                        self.depth = self.depth.saturating_sub(1);
+                    }
+                }
+                // Where tracked deletions were and insertions are
+                b"text:change" => {
+                    let deleted = attribute(e, "text:change-id")?
+                        .and_then(|id| self.changes.deletions.get(&id).cloned());
+                    match (deleted, self.changes_view) {
+                        (Some(deleted), ChangesView::Rejected) => self.push_text(&deleted),
+                        (Some(deleted), ChangesView::Annotated) => {
+                            self.push_text(&format!("[-{}-]", deleted))
+                        }
+                        _ => {}
+                    }
+                }
+                b"text:change-start" => {
+                    let id = attribute(e, "text:change-id")?;
+                    match id.filter(|id| self.changes.insertions.contains(id)) {
+                        Some(id) if self.changes_view == ChangesView::Rejected => {
+                            self.changes.hidden_insertion = Some(id);
+                        }
+                        Some(_) if self.changes_view == ChangesView::Annotated => {
+                            self.push_text("{+")
+                        }
+                        _ => {}
+                    }
+                }
+                b"text:change-end" => {
+                    let id = attribute(e, "text:change-id")?;
+                    if id.is_some() && id == self.changes.hidden_insertion {
+                        self.changes.hidden_insertion = None;
+                    } else if self.changes_view == ChangesView::Annotated
+                        && id.is_some_and(|id| self.changes.insertions.contains(&id))
+                    {
+                        self.push_text("+}");
+                    }
+                }
+                _ => {}
+            },
+            Event::End(ref e) => {
+                self.depth = self.depth.saturating_sub(1);
+                match e.name().as_ref() {
+                    b"text:p" | b"text:h" | b"svg:title" | b"svg:desc" => {
+                        self.open_paragraphs.pop();
+                    }
+                    b"text:list" => self.list_depth -= 1,
+                    b"text:bibliography" => self.in_bibliography = false,
+                    b"style:style" => {
+                        if let Some(styles) = &mut self.text_styles {
+                            styles.end();
+                        }
+                    }
+                    b"text:span" => {
+                        if let Some(Some((index, start, emphasis))) = self.spans.pop() {
+                            emphasis.mark(&mut self.open[index].0.text, start);
+                        }
+                    }
+                    b"text:a" => {
+                        if let (Some(link), Some(&Some(index))) =
+                            (self.link.take(), self.open_paragraphs.last())
+                        {
+                            self.open[index].0.links.push(link);
+                        }
+                    }
+                    b"text:note" => {
+                        self.note_depth -= 1;
+                    }
+                    b"text:note-citation" => {
+                        self.in_citation = false;
+                    }
+                    b"text:tracked-changes" => self.changes.reading = false,
+                    b"text:deletion" => self.changes.in_deletion = false,
+                    b"office:change-info" => self.changes.in_change_info = false,
+                    b"office:annotation" => self.comment = None,
+                    b"dc:creator" => {
+                        if let Some(comment) = &mut self.comment {
+                            comment.in_creator = false;
+                        }
+                    }
+                    b"table:table-cell" => {
+                        if let Some(index) = self.cell_paragraph.take() {
+                            let cell = &mut self.open[index].0;
+                            if !cell.text.trim().is_empty() {
+                                cell.text = format!("[{}] {}", self.table.label(), cell.text);
+                            }
+                        }
+                    }
+                    b"table:table" => self.table.end_table(),
+                    _ => {}
+                }
+                if self.open_paragraphs.is_empty() && self.cell_paragraph.is_none() {
+                    self.release_open();
+                }
+            }
+            Event::Text(e) if self.in_citation => {
+                self.note_label.push(' ');
+                self.note_label.push_str(&e.unescape()?);
+            }
+            Event::Text(e) => {
+                let text = e.unescape()?;
+                match &mut self.comment {
+                    Some(comment) if comment.in_creator => comment.author.push_str(&text),
+                    _ => self.push_text(&text),
+                }
+            }
+            Event::Eof => {
+                self.release_open();
+                self.ready.extend(self.notes.drain(..));
+                self.ready.extend(self.comments.drain(..));
+                self.done = true;
+            }
+            _ => {}
+        }
+        buf.clear();
+        self.buf = buf;
+
+        Ok(())
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if self.changes.reading {
+            // Whitespace between the elements of a change is not text
+            if !self.open_paragraphs.is_empty() {
+                self.changes.push_deleted_text(text);
+            }
+            return;
+        }
+        if self.changes.hidden_insertion.is_some() {
+            return;
+        }
+        // Comment metadata such as the date sits outside the comment's own
+        // paragraphs and is not part of any text
+        if self
+            .comment
+            .as_ref()
+            .is_some_and(|comment| self.open_paragraphs.len() <= comment.depth)
+        {
+            return;
+        }
+        if let Some(&Some(index)) = self.open_paragraphs.last() {
+            self.open[index].0.text.push_str(text);
+            if let Some(link) = &mut self.link {
+                link.text.push_str(text);
+            }
+        }
+    }
+
+    // LibreOffice sets block quotations in the "Quotations" style, which
+    // paragraphs use directly or through an automatic style based on it.
+    // Text styles are read for their emphasis as well.
+    fn read_style(&mut self, style: &BytesStart) -> Result<()> {
+        if let Some(styles) = &mut self.text_styles {
+            styles.read_element(style)?;
+        }
+        if style.name().as_ref() == b"style:style"
+            && attribute(style, "style:parent-style-name")?.as_deref() == Some(QUOTATION_STYLE)
+            && let Some(name) = attribute(style, "style:name")?
+        {
+            self.quote_styles.insert(name);
+        }
+        Ok(())
+    }
+
+    fn is_quote(&self, paragraph: &BytesStart) -> Result<bool> {
+        Ok(attribute(paragraph, "text:style-name")?
+            .is_some_and(|name| name == QUOTATION_STYLE || self.quote_styles.contains(&name)))
+    }
+
+    fn release_open(&mut self) {
+        for (mut paragraph, label) in self.open.drain(..) {
+            if paragraph.kind == SentenceKind::Comment
+                && self.comments_mode == CommentsMode::Separate
+            {
+                self.comments.push(paragraph);
+                continue;
+            }
+            if self.notes_mode != NotesMode::Separate {
+                self.ready.push_back(paragraph);
+                continue;
+            }
+
+            match &label {
+                // Only the first paragraph of each note carries the tag
+                Some(tag) if self.previous_label.as_ref() != Some(tag) => {
+                    paragraph.text = format!("[{}] {}", tag, paragraph.text);
+                    self.notes.push(paragraph);
+                }
+                Some(_) => self.notes.push(paragraph),
+                None => self.ready.push_back(paragraph),
+            }
+            self.previous_label = label;
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for ParagraphReader<R> {
+    type Item = Result<Paragraph>;
+
+    fn next(&mut self) -> Option<Result<Paragraph>> {
+        loop {
+            if let Some(paragraph) = self.ready.pop_front() {
+                return Some(Ok(paragraph));
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.read_event() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+// The paragraphs of the headers and footers of every page style, each its own
+// sentence. Left and first pages often repeat the text of the others, which
+// is read once.
+fn read_page_paragraphs<R: BufRead>(styles: R) -> Result<(Vec<Paragraph>, Vec<Paragraph>)> {
+    let mut reader = Reader::from_reader(styles);
+    let mut headers: Vec<Paragraph> = Vec::new();
+    let mut footers: Vec<Paragraph> = Vec::new();
+    let mut kind = None;
+    let mut current: Option<Paragraph> = None;
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(ref e) => match e.name().as_ref() {
+                b"style:header" | b"style:header-left" | b"style:header-first" => {
+                    kind = Some(SentenceKind::Header);
+                }
+                b"style:footer" | b"style:footer-left" | b"style:footer-first" => {
+                    kind = Some(SentenceKind::Footer);
+                }
+                b"text:p" | b"text:h" if current.is_none() => {
+                    current = kind.map(|kind| Paragraph {
+                        standalone: true,
+                        kind,
+                        ..Paragraph::default()
+                    });
+                }
+                _ => {}
+            },
+            Event::Empty(ref e) => {
+                let text = match e.name().as_ref() {
+                    b"text:s" => " ".repeat(space_count(e)?),
+                    b"text:tab" | b"text:line-break" => " ".to_string(),
+                    _ => String::new(),
+                };
+                if let Some(paragraph) = &mut current {
+                    paragraph.text.push_str(&text);
+                }
+            }
+            Event::Text(e) => {
+                if let Some(paragraph) = &mut current {
+                    paragraph.text.push_str(&e.unescape()?);
+                }
+            }
+            Event::End(ref e) => match e.name().as_ref() {
+                b"style:header"
+                | b"style:header-left"
+                | b"style:header-first"
+                | b"style:footer"
+                | b"style:footer-left"
+                | b"style:footer-first" => kind = None,
+                b"text:p" | b"text:h" => {
+                    if let Some(paragraph) = current.take() {
+                        let read = match paragraph.kind {
+                            SentenceKind::Header => &mut headers,
+                            _ => &mut footers,
+                        };
+                        if !paragraph.text.trim().is_empty() && !read.contains(&paragraph) {
+                            read.push(paragraph);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok((headers, footers))
+}
+
+// Which of bold, italic and underline a text style sets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Emphasis {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Emphasis {
+    fn is_plain(self) -> bool {
+        self == Emphasis::default()
+    }
+
+    // Puts the markers around the text from `start` on. Whitespace at either
+    // end stays outside them, and a span with no text gets none.
+    fn mark(self, text: &mut String, start: usize) {
+        let span = &text[start..];
+        let content = span.trim();
+        if content.is_empty() {
+            return;
+        }
+        let content_start = start + span.len() - span.trim_start().len();
+        let content_end = content_start + content.len();
+
+        let mut opening = String::new();
+        if self.bold {
+            opening.push_str("**");
+        }
+        if self.italic {
+            opening.push('*');
+        }
+        if self.underline {
+            opening.push('_');
+        }
+        let closing: String = opening.chars().rev().collect();
+        text.insert_str(content_end, &closing);
+        text.insert_str(content_start, &opening);
+    }
+}
+
+// The emphasis of each text style by name. A style has the properties of the
+// style it is based on, except those it sets itself.
+#[derive(Debug, Default)]
+struct TextStyles {
+    styles: HashMap<String, Emphasis>,
+    // The text style being read, with its emphasis so far
+    reading: Option<(String, Emphasis)>,
+}
+
+impl TextStyles {
+    // The styles of styles.xml, or of a flat document up to its body
+    fn read<R: BufRead>(xml: R) -> Result<Self> {
+        let mut reader = Reader::from_reader(xml);
+        let mut styles = TextStyles::default();
+        let mut buf = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(ref e) if e.name().as_ref() == b"office:body" => break,
+                Event::Start(ref e) => styles.read_element(e)?,
+                Event::Empty(ref e) => {
+                    styles.read_element(e)?;
+                    if e.name().as_ref() == b"style:style" {
+                        styles.end();
+                    }
+                }
+                Event::End(ref e) if e.name().as_ref() == b"style:style" => styles.end(),
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(styles)
+    }
+
+    fn read_element(&mut self, element: &BytesStart) -> Result<()> {
+        match element.name().as_ref() {
+            b"style:style" => {
+                if attribute(element, "style:family")?.as_deref() != Some("text") {
+                    return Ok(());
+                }
+                let Some(name) = attribute(element, "style:name")? else {
+                    return Ok(());
+                };
+                let inherited = match attribute(element, "style:parent-style-name")? {
+                    Some(parent) => self.emphasis(&parent),
+                    None => Emphasis::default(),
+                };
+                self.reading = Some((name, inherited));
+            }
+            b"style:text-properties" => {
+                let Some((_, emphasis)) = &mut self.reading else {
+                    return Ok(());
+                };
+                if let Some(weight) = attribute(element, "fo:font-weight")? {
+                    emphasis.bold =
+                        weight == "bold" || weight.parse::<u32>().is_ok_and(|weight| weight >= 600);
+                }
+                if let Some(style) = attribute(element, "fo:font-style")? {
+                    emphasis.italic = matches!(style.as_str(), "italic" | "oblique");
+                }
+                if let Some(underline) = attribute(element, "style:text-underline-style")? {
+                    emphasis.underline = underline != "none";
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn end(&mut self) {
+        if let Some((name, emphasis)) = self.reading.take() {
+            self.styles.insert(name, emphasis);
+        }
+    }
+
+    fn emphasis(&self, name: &str) -> Emphasis {
+        self.styles.get(name).copied().unwrap_or_default()
+    }
+}
+
+// The files the sections of a master document link to are found from the
+// master's path. The documents being read are kept so a link back to one of
+// them is not followed again.
+#[derive(Debug)]
+struct LinkedSections {
+    master: PathBuf,
+    options: ParseOptions,
+    reading: Vec<PathBuf>,
+}
+
+impl LinkedSections {
+    fn new(master: &Path, options: &ParseOptions) -> Self {
+        LinkedSections {
+            master: master.to_path_buf(),
+            options: options.clone(),
+            reading: vec![master.canonicalize().unwrap_or(master.to_path_buf())],
+        }
+    }
+
+    // None where the copy in the master is read instead: links to one section
+    // of a file, links back to a document being read, and missing files
+    fn paragraphs(&self, href: &str) -> Result<Option<Vec<Paragraph>>> {
+        if href.contains('#') {
+            return Ok(None);
+        }
+        let path = linked_path(&self.master, href);
+        let canonical = path.canonicalize().unwrap_or(path.clone());
+        if !path.is_file() || self.reading.contains(&canonical) {
+            return Ok(None);
+        }
+
+        let file_path = path.to_string_lossy();
+        let source = Source::File(&file_path);
+        let parser =
+            OdtParser::with_splitter(Splitter::unicode()).with_options(self.options.clone());
+        let mut paragraphs =
+            parser.paragraph_reader(parser.part(source, source.open()?, "content.xml")?);
+        let mut linked = LinkedSections::new(&path, &self.options);
+        linked.reading.extend(self.reading.iter().cloned());
+        paragraphs.linked = Some(linked);
+
+        paragraphs.collect::<Result<Vec<_>>>().map(Some)
+    }
+}
+
+// Links in a package are relative to the package as if it were a folder, so
+// "../chapter1.odt" names a file next to the master document
+fn linked_path(master: &Path, href: &str) -> PathBuf {
+    let href = percent_decode(href.strip_prefix("file://").unwrap_or(href));
+    let href = Path::new(&href);
+    if href.is_absolute() {
+        return href.to_path_buf();
+    }
+
+    let mut path = master.to_path_buf();
+    for component in href.components() {
+        match component {
+            Component::ParentDir => {
+                path.pop();
+            }
+            Component::CurDir => {}
+            component => path.push(component),
+        }
+    }
+    path
+}
+
+fn outline_level(element: &BytesStart) -> Result<usize> {
+    let level = element
+        .try_get_attribute("text:outline-level")?
+        .and_then(|attr| std::str::from_utf8(&attr.value).ok()?.parse().ok())
+        .unwrap_or(1);
+
+    Ok(level)
+}
+
+// The changes listed in text:tracked-changes, which precedes the body text
+// that refers to them by id
+#[derive(Debug, Default)]
+struct TrackedChanges {
+    reading: bool,
+    region: Option<String>,
+    in_deletion: bool,
+    in_change_info: bool,
+    deletions: HashMap<String, String>,
+    insertions: HashSet<String>,
+    // Insertion whose text is left out of the rejected view
+    hidden_insertion: Option<String>,
+}
+
+impl TrackedChanges {
+    fn start_deletion(&mut self) {
+        if let Some(region) = &self.region {
+            self.deletions.insert(region.clone(), String::new());
+            self.in_deletion = true;
+        }
+    }
+
+    fn add_insertion(&mut self) {
+        if let Some(region) = &self.region {
+            self.insertions.insert(region.clone());
+        }
+    }
+
+    fn separate_paragraph(&mut self) {
+        if let Some(deleted) = self.deleted_text()
+            && !deleted.is_empty()
+        {
+            deleted.push(' ');
+        }
+    }
+
+    // Author and date of the change are not part of the deleted text
+    fn push_deleted_text(&mut self, text: &str) {
+        if !self.in_change_info
+            && let Some(deleted) = self.deleted_text()
+        {
+            deleted.push_str(text);
+        }
+    }
+
+    fn deleted_text(&mut self) -> Option<&mut String> {
+        if !self.in_deletion {
+            return None;
+        }
+        self.deletions.get_mut(self.region.as_ref()?)
+    }
+}
+
+// An office:annotation being read
+#[derive(Debug, Default)]
+struct OpenComment {
+    // Paragraphs open around the annotation
+    depth: usize,
+    author: String,
+    in_creator: bool,
+    paragraphs: usize,
+}
+
+impl OpenComment {
+    fn label(&self) -> String {
+        match self.author.trim() {
+            "" => "Comment".to_string(),
+            author => format!("Comment by {}", author),
+        }
+    }
+}
+
+pub(super) fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>> {
+    let value = element
+        .try_get_attribute(name)?
+        .map(|attr| attr.unescape_value())
+        .transpose()?
+        .map(|value| value.into_owned());
+
+    Ok(value)
+}
+
+// `<text:s text:c="3"/>` stands for three spaces
+pub(super) fn space_count(element: &BytesStart) -> Result<usize> {
+    let count = element
+        .try_get_attribute("text:c")?
+        .and_then(|attr| std::str::from_utf8(&attr.value).ok()?.parse().ok())
+        .unwrap_or(1);
+
+    Ok(count)
+}
+
+// Tracks the 1-based position of the current cell, stacking outer tables
+// while a nested table is being read
+#[derive(Debug, Default)]
+struct TablePosition {
+    tables_seen: usize,
+    stack: Vec<(usize, usize, usize)>,
+}
+
+impl TablePosition {
+    fn start_table(&mut self) {
+        self.tables_seen += 1;
+        self.stack.push((self.tables_seen, 0, 0));
+    }
+
+    fn end_table(&mut self) {
+        self.stack.pop();
+    }
+
+    fn start_row(&mut self) {
+        if let Some((_, row, column)) = self.stack.last_mut() {
+            *row += 1;
+            *column = 0;
+        }
+    }
+
+    fn start_cell(&mut self) {
+        if let Some((_, _, column)) = self.stack.last_mut() {
+            *column += 1;
+        }
+    }
+
+    fn label(&self) -> String {
+        let (table, row, column) = self.stack.last().copied().unwrap_or_default();
+        format!("Table {}, row {}, column {}", table, row, column)
+    }
+}
+
+impl DocumentParser for OdtParser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        OdtParser::parse_file(self, file_path)
+    }
+
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        OdtParser::parse_bytes(self, data)
+    }
+
+    #[cfg(feature = "native")]
+    fn sentences<'a>(&'a self, file_path: &str) -> Result<SentenceStream<'a>> {
+        Ok(Box::new(OdtParser::sentences(self, file_path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SentencerError;
+    use crate::parser::{DocumentFormat, Limits};
+    use crate::sentence::texts;
+    use crate::test_utils::create_test_odt_file;
+    use std::fs;
+    use std::io::Write;
+    use zip::{ZipWriter, write::FileOptions};
+
+    #[test]
+    fn test_odt_file_parsing() -> Result<()> {
+        let test_file = "test_document.odt";
+
+        // Create test content with multiple paragraphs and sentences
+        let test_content = r#"
+            <text:p>This is the first paragraph with two sentences. Here is the second sentence!</text:p>
+            <text:p>This is a second paragraph. It also has multiple sentences? Yes, it does.</text:p>
+            <text:h>This is a heading.</text:h>
+            <text:p>Final paragraph after the heading.</text:p>
+        "#;
+
+        // Create the test ODT file
+        create_test_odt_file(test_file, test_content)?;
+
+        // Parse the file
+        let parser = OdtParser::new()?;
+        let sentences = parser.parse_file(test_file)?;
+
+        // Clean up test file
+        fs::remove_file(test_file).ok();
+
+        // Verify results
+        assert!(!sentences.is_empty(), "Should have parsed some sentences");
+
+        // Check that we got the expected sentences
+        let expected_phrases = vec![
+            "This is the first paragraph with two sentences",
+            "Here is the second sentence",
+            "This is a second paragraph",
+            "It also has multiple sentences",
+            "Yes, it does",
+            "This is a heading",
+            "Final paragraph after the heading",
+        ];
+
+        println!("Parsed sentences:");
+        for (i, sentence) in sentences.iter().enumerate() {
+            println!("  {}: {}", i + 1, sentence.text);
+        }
+
+        // Verify we have at least the expected number of sentences
+        assert!(
+            sentences.len() >= 6,
+            "Should have at least 6 sentences, got {}",
+            sentences.len()
+        );
+
+        // Check that some expected content is present
+        let all_text = texts(&sentences).join(" ");
+        for phrase in &expected_phrases {
+            println!("  expected '{}': {}", phrase, all_text.contains(phrase));
+        }
+        assert!(
+            all_text.contains("first paragraph"),
+            "Should contain 'first paragraph'"
+        );
+        assert!(
+            all_text.contains("second sentence"),
+            "Should contain 'second sentence'"
+        );
+        assert!(all_text.contains("heading"), "Should contain 'heading'");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sentences_iterator() -> Result<()> {
+        let test_file = "test_sentences_iterator.odt";
+        create_test_odt_file(
+            test_file,
+            r#"<text:h text:outline-level="1">Title</text:h>
+            <text:p>First sentence. Second<text:note text:note-class="footnote"><text:note-citation>1</text:note-citation><text:note-body><text:p>The note.</text:p></text:note-body></text:note></text:p>
+            <text:p>sentence ends here.</text:p>"#,
+        )?;
+
+        let parser = OdtParser::new()?.with_options(ParseOptions {
+            notes: NotesMode::Separate,
+            ..ParseOptions::default()
+        });
+        let streamed: Result<Vec<Sentence>> = parser
+            .sentences(test_file)
+            .and_then(|sentences| sentences.collect());
+        let parsed = parser.parse_file(test_file);
+        let missing = parser.sentences("missing.odt").map(|_| ());
+
+        fs::remove_file(test_file).ok();
+
+        let streamed = streamed?;
+        assert_eq!(
+            texts(&streamed),
+            vec![
+                "Title",
+                "First sentence.",
+                "Second sentence ends here.",
+                "[Footnote 1] The note."
+            ]
+        );
+        assert_eq!(streamed[1].heading_path, vec!["Title"]);
+        assert_eq!(streamed, parsed?);
+        assert!(matches!(missing, Err(SentencerError::Io(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_not_found() {
+        let parser = OdtParser::new().unwrap();
+        let result = parser.parse_file("nonexistent.odt");
+        assert!(result.is_err(), "Should return error for nonexistent file");
+        assert!(matches!(result, Err(SentencerError::Io(_))));
+    }
+
+    #[test]
+    fn test_invalid_odt_file() -> Result<()> {
+        let test_file = "invalid.odt";
+
+        // Create a file that's not a valid ODT (missing content.xml)
+        let file = File::create(test_file)?;
+        let mut zip = ZipWriter::new(file);
+        zip.start_file("dummy.txt", FileOptions::default())?;
+        zip.write_all(b"This is not an ODT file")?;
+        zip.finish()?;
+
+        let parser = OdtParser::new()?;
+        let result = parser.parse_file(test_file);
+
+        // Clean up
+        fs::remove_file(test_file).ok();
+
+        assert!(result.is_err(), "Should return error for invalid ODT file");
+        assert!(matches!(result, Err(SentencerError::Zip(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_xml_extraction() -> Result<()> {
+        let parser = OdtParser::new()?;
+
+        // Test XML content extraction directly
+        let xml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content 
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+    <office:body>
+        <office:text>
+            <text:p>First paragraph.</text:p>
+            <text:p>Second paragraph with <text:span>inline text</text:span>.</text:p>
+        </office:text>
+    </office:body>
+</office:document-content>"#;
+
+        let extracted_text = parser.extract_text_from_xml(xml_content)?;
+
+        assert!(
+            extracted_text.contains("First paragraph"),
+            "Should extract first paragraph"
+        );
+        assert!(
+            extracted_text.contains("Second paragraph"),
+            "Should extract second paragraph"
+        );
+        assert!(
+            extracted_text.contains("inline text"),
+            "Should extract span text"
+        );
+
+        println!("Extracted text: '{}'", extracted_text);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_inline_elements() -> Result<()> {
+        let xml_content = r#"<office:document-content
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+    <office:body>
+        <office:text>
+            <text:p>Some <text:span>bold <text:span>and italic</text:span> words</text:span> and <text:a xlink:href="x">a <text:span>link</text:span></text:a> after.</text:p>
+            <text:h text:outline-level="1">A <text:span>styled</text:span> heading</text:h>
+        </office:text>
+    </office:body>
+</office:document-content>"#;
+
+        let paragraphs = OdtParser::new()?.extract_paragraphs_from_xml(xml_content)?;
+
+        assert_eq!(
+            paragraphs,
+            vec![
+                "Some bold and italic words and a link after.",
+                "A styled heading"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_whitespace_elements() -> Result<()> {
+        let xml_content = r#"<office:document-content
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+    <office:body>
+        <office:text>
+            <text:p>Wide<text:s text:c="3"/>gap<text:s/>and<text:tab/>tab<text:line-break/>break.</text:p>
+        </office:text>
+    </office:body>
+</office:document-content>"#;
+
+        let parser = OdtParser::new()?;
+        let paragraphs = parser.extract_paragraphs_from_xml(xml_content)?;
+
+        assert_eq!(paragraphs, vec!["Wide   gap and\ttab\nbreak."]);
+        assert_eq!(
+            parser.extract_text_from_xml(xml_content)?,
+            "Wide   gap and\ttab\nbreak. "
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_notes_modes() -> Result<()> {
+        let xml_content = r#"<office:document-content
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+    <office:body>
+        <office:text>
+            <text:p>Main text continues.<text:note text:id="ftn1" text:note-class="footnote"><text:note-citation>1</text:note-citation><text:note-body><text:p>A footnote.</text:p></text:note-body></text:note></text:p>
+            <text:p>Last paragraph.</text:p>
+        </office:text>
+    </office:body>
+</office:document-content>"#;
+
+        let paragraphs = |notes| {
+            OdtParser::new()?
+                .with_options(ParseOptions {
+                    notes,
+                    ..ParseOptions::default()
+                })
+                .extract_paragraphs_from_xml(xml_content)
+        };
+
+        assert_eq!(
+            paragraphs(NotesMode::Include)?,
+            vec!["Main text continues.", "A footnote.", "Last paragraph."]
+        );
+        assert_eq!(
+            paragraphs(NotesMode::Exclude)?,
+            vec!["Main text continues.", "Last paragraph."]
+        );
+        assert_eq!(
+            paragraphs(NotesMode::Separate)?,
+            vec![
+                "Main text continues.",
+                "Last paragraph.",
+                "[Footnote 1] A footnote."
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_comments_modes() -> Result<()> {
+        let xml_content = r#"<office:document-content
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+    <office:body>
+        <office:text>
+            <text:p>Commented <office:annotation office:name="c1"><dc:creator>Ann Lee</dc:creator><dc:date>2024-01-01T10:00:00</dc:date><text:p>Reword this.</text:p><text:p>Please.</text:p></office:annotation>text<office:annotation-end office:name="c1"/> here.</text:p>
+            <text:p>Last paragraph.</text:p>
+        </office:text>
+    </office:body>
+</office:document-content>"#;
+
+        let paragraphs = |comments| {
+            OdtParser::new()?
+                .with_options(ParseOptions {
+                    comments,
+                    ..ParseOptions::default()
+                })
+                .extract_paragraphs_from_xml(xml_content)
+        };
+
+        assert_eq!(
+            paragraphs(CommentsMode::Exclude)?,
+            vec!["Commented text here.", "Last paragraph."]
+        );
+        assert_eq!(
+            paragraphs(CommentsMode::Include)?,
+            vec![
+                "Commented text here.",
+                "[Comment by Ann Lee] Reword this.",
+                "Please.",
+                "Last paragraph."
+            ]
+        );
+        assert_eq!(
+            paragraphs(CommentsMode::Separate)?,
+            vec![
+                "Commented text here.",
+                "Last paragraph.",
+                "[Comment by Ann Lee] Reword this.",
+                "Please."
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tracked_changes_views() -> Result<()> {
+        let xml_content = r#"<office:document-content
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+    <office:body>
+        <office:text>
+            <text:tracked-changes>
+                <text:changed-region text:id="ct1">
+                    <text:deletion>
+                        <office:change-info><dc:creator>Ann</dc:creator><dc:date>2024-01-01T10:00:00</dc:date></office:change-info>
+                        <text:p>old</text:p>
+                    </text:deletion>
+                </text:changed-region>
+                <text:changed-region text:id="ct2">
+                    <text:insertion>
+                        <office:change-info><dc:creator>Ann</dc:creator><dc:date>2024-01-01T10:00:00</dc:date></office:change-info>
+                    </text:insertion>
+                </text:changed-region>
+            </text:tracked-changes>
+            <text:p>The <text:change text:change-id="ct1"/><text:change-start text:change-id="ct2"/>new<text:change-end text:change-id="ct2"/> text.</text:p>
+        </office:text>
+    </office:body>
+</office:document-content>"#;
+
+        let paragraphs = |changes| {
+            OdtParser::new()?
+                .with_options(ParseOptions {
+                    changes,
+                    ..ParseOptions::default()
+                })
+                .extract_paragraphs_from_xml(xml_content)
+        };
+
+        assert_eq!(paragraphs(ChangesView::Accepted)?, vec!["The new text."]);
+        assert_eq!(paragraphs(ChangesView::Rejected)?, vec!["The old text."]);
+        assert_eq!(
+            paragraphs(ChangesView::Annotated)?,
+            vec!["The [-old-]{+new+} text."]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_items() -> Result<()> {
+        let test_file = "test_lists.odt";
+        create_test_odt_file(
+            test_file,
+            r#"<text:p>Shopping:</text:p>
+            <text:list>
+                <text:list-item><text:p>apples</text:p></text:list-item>
+                <text:list-item>
+                    <text:p>pears. Green ones</text:p>
+                    <text:list>
+                        <text:list-item><text:p>ripe ones</text:p></text:list-item>
+                    </text:list>
+                </text:list-item>
+            </text:list>
+            <text:p>That is all.</text:p>"#,
+        )?;
+
+        let inline = OdtParser::new()?.parse_file(test_file);
+        let items = OdtParser::new()?
+            .with_options(ParseOptions {
+                list_items_as_sentences: true,
+                ..ParseOptions::default()
+            })
+            .parse_file(test_file);
+
+        fs::remove_file(test_file).ok();
+
+        assert_eq!(
+            texts(&inline?),
+            vec![
+                "Shopping:",
+                "apples",
+                "pears.",
+                "Green ones",
+                "ripe ones",
+                "That is all."
+            ]
+        );
+        let items = items?;
+        assert_eq!(
+            texts(&items),
+            vec![
+                "Shopping:",
+                "apples",
+                "pears. Green ones",
+                "ripe ones",
+                "That is all."
+            ]
+        );
+        let levels: Vec<_> = items.iter().map(|sentence| sentence.list_level).collect();
+        assert_eq!(levels, vec![None, Some(1), Some(1), Some(2), None]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sentence_kinds() -> Result<()> {
+        let xml = r#"<office:document-content>
+            <office:automatic-styles>
+                <style:style style:name="P1" style:family="paragraph" style:parent-style-name="Quotations"/>
+                <style:style style:name="P2" style:family="paragraph" style:parent-style-name="Standard">
+                    <style:text-properties fo:font-weight="bold"/>
+                </style:style>
+            </office:automatic-styles>
+            <office:body><office:text>
+                <text:h text:outline-level="1">Loomings</text:h>
+                <text:p text:style-name="P2">Call me Ishmael.</text:p>
+                <text:p text:style-name="P1">Some years ago.</text:p>
+                <text:p text:style-name="Quotations">Never mind how long.</text:p>
+                <text:list><text:list-item><text:p>Whenever I find myself grim.</text:p></text:list-item></text:list>
+            </office:text></office:body>
+        </office:document-content>"#;
+
+        let parser = OdtParser::new()?;
+        let paragraphs = parser.paragraph_reader(xml.as_bytes());
+        let sentences = Sentences::new(&parser.splitter, paragraphs).collect::<Result<Vec<_>>>()?;
+        let kinds: Vec<_> = sentences
+            .iter()
+            .map(|sentence| (sentence.text.as_str(), sentence.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("Loomings", SentenceKind::Heading),
+                ("Call me Ishmael.", SentenceKind::Body),
+                ("Some years ago.", SentenceKind::Quote),
+                ("Never mind how long.", SentenceKind::Quote),
+                ("Whenever I find myself grim.", SentenceKind::ListItem)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_emphasis_markers() -> Result<()> {
+        let flat = r##"<office:document>
+            <office:styles>
+                <style:style style:name="Emphasis" style:family="text">
+                    <style:text-properties fo:font-style="italic"/>
+                </style:style>
+                <style:style style:name="Strong_20_Emphasis" style:family="text">
+                    <style:text-properties fo:font-weight="bold"/>
+                </style:style>
+            </office:styles>
+            <office:automatic-styles>
+                <style:style style:name="T1" style:family="text" style:parent-style-name="Emphasis">
+                    <style:text-properties style:text-underline-style="solid"/>
+                </style:style>
+                <style:style style:name="T2" style:family="text">
+                    <style:text-properties fo:font-weight="700" fo:font-style="italic"/>
+                </style:style>
+                <style:style style:name="T3" style:family="text">
+                    <style:text-properties fo:color="#ff0000"/>
+                </style:style>
+            </office:automatic-styles>
+            <office:body><office:text>
+                <text:p>Call me <text:span text:style-name="Strong_20_Emphasis">Ishmael </text:span>today.</text:p>
+                <text:p><text:span text:style-name="Emphasis">Some years ago</text:span> I sailed<text:span text:style-name="T1"> </text:span>about.</text:p>
+                <text:p><text:span text:style-name="T2">Never <text:span text:style-name="T1">mind</text:span></text:span> how <text:span text:style-name="T3">long</text:span>.</text:p>
+            </office:text></office:body>
+        </office:document>"##;
+
+        let marked = OdtParser::new()?
+            .with_options(ParseOptions {
+                emphasis_markers: true,
+                ..ParseOptions::default()
+            })
+            .parse_bytes(flat.as_bytes())?;
+        let plain = OdtParser::new()?.parse_bytes(flat.as_bytes())?;
+
+        assert_eq!(
+            texts(&marked),
+            vec![
+                "Call me **Ishmael** today.",
+                "*Some years ago* I sailed about.",
+                "***Never *_mind_**** how long."
+            ]
+        );
+        assert_eq!(texts(&plain)[2], "Never mind how long.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hyperlinks() -> Result<()> {
+        let paragraphs = [
+            r#"<text:p>See <text:a xlink:href="https://example.com/a">the <text:span>first</text:span> site</text:a>. Nothing here.</text:p>"#,
+            r#"<text:p>Then <text:a xlink:href="https://example.com/b">another</text:a> and <text:a xlink:href="https://example.com/c">more</text:a>.</text:p>"#,
+        ];
+        let test_file = "test_links.odt";
+        create_test_odt_file(test_file, &paragraphs.join("\n"))?;
+
+        let sentences = OdtParser::new()?.parse_file(test_file);
+        fs::remove_file(test_file).ok();
+
+        let sentences = sentences?;
+        let links: Vec<Vec<(&str, &str)>> = sentences
+            .iter()
+            .map(|sentence| {
+                sentence
+                    .links
+                    .iter()
+                    .map(|link| (link.text.as_str(), link.href.as_str()))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                vec![("the first site", "https://example.com/a")],
+                vec![],
+                vec![
+                    ("another", "https://example.com/b"),
+                    ("more", "https://example.com/c")
+                ],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_flat_odt() -> Result<()> {
+        let test_file = "test_flat.fodt";
+        fs::write(
+            test_file,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document office:mimetype="application/vnd.oasis.opendocument.text"
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+    <office:master-styles>
+        <style:master-page style:name="Standard">
+            <style:header><text:p>Page header</text:p></style:header>
+        </style:master-page>
+    </office:master-styles>
+    <office:body>
+        <office:text>
+            <text:p>A flat document. It is not zipped.</text:p>
+        </office:text>
+    </office:body>
+</office:document>"#,
+        )?;
+
+        let format = DocumentFormat::detect(test_file);
+        let sentences = OdtParser::new()?.parse_file(test_file);
+        fs::remove_file(test_file).ok();
+
+        assert_eq!(format?, DocumentFormat::Odt);
+        assert_eq!(
+            texts(&sentences?),
+            vec!["A flat document.", "It is not zipped."]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_headers_footers() -> Result<()> {
+        let test_file = "test_headers_footers.odt";
+        let mut zip = ZipWriter::new(File::create(test_file)?);
+        zip.start_file("content.xml", FileOptions::default())?;
+        zip.write_all(
+            br#"<office:document-content><office:body><office:text>
+                <text:p>The body. It comes between.</text:p>
+            </office:text></office:body></office:document-content>"#,
+        )?;
+        zip.start_file("styles.xml", FileOptions::default())?;
+        zip.write_all(
+            br#"<office:document-styles><office:master-styles>
+                <style:master-page style:name="Standard">
+                    <style:header><text:p>Moby-Dick</text:p></style:header>
+                    <style:header-left><text:p>Moby-Dick</text:p></style:header-left>
+                    <style:footer><text:p>Page <text:page-number>1</text:page-number></text:p></style:footer>
+                </style:master-page>
+            </office:master-styles></office:document-styles>"#,
+        )?;
+        zip.finish()?;
+        let no_styles = "test_no_styles.odt";
+        create_test_odt_file(no_styles, "<text:p>Only a body.</text:p>")?;
+
+        let options = ParseOptions {
+            headers_footers: true,
+            ..ParseOptions::default()
+        };
+        let with_pages = OdtParser::new()?
+            .with_options(options.clone())
+            .parse_file(test_file);
+        let without = OdtParser::new()?.parse_file(test_file);
+        let pages = OdtParser::new()?.page_sentences(test_file);
+        let no_styles_result = OdtParser::new()?
+            .with_options(options)
+            .parse_file(no_styles);
+        fs::remove_file(test_file).ok();
+        fs::remove_file(no_styles).ok();
+
+        let with_pages = with_pages?;
+        assert_eq!(
+            texts(&with_pages),
+            vec!["Moby-Dick", "The body.", "It comes between.", "Page 1"]
+        );
+        let kinds: Vec<_> = with_pages.iter().map(|sentence| sentence.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SentenceKind::Header,
+                SentenceKind::Body,
+                SentenceKind::Body,
+                SentenceKind::Footer
+            ]
+        );
+        assert_eq!(texts(&without?), vec!["The body.", "It comes between."]);
+        assert_eq!(texts(&pages?), vec!["Moby-Dick", "Page 1"]);
+        assert_eq!(texts(&no_styles_result?), vec!["Only a body."]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_images() -> Result<()> {
+        let xml = r#"<office:text>
+            <text:p>Before the figure.</text:p>
+            <text:p><draw:frame draw:name="Frame1"><draw:text-box>
+                <text:p text:style-name="Figure"><draw:frame draw:name="Image1">
+                    <draw:image xlink:href="Pictures/whale.png"/>
+                    <svg:title>A whale</svg:title>
+                    <svg:desc>A sperm whale breaching off Nantucket</svg:desc>
+                </draw:frame>Figure <text:sequence text:name="Figure">1</text:sequence>: The white whale</text:p>
+            </draw:text-box></draw:frame></text:p>
+            <text:p>After the figure.</text:p>
+        </office:text>"#;
+
+        let parse = |alt_text| {
+            let parser = OdtParser::new()?.with_options(ParseOptions {
+                alt_text,
+                ..ParseOptions::default()
+            });
+            let paragraphs = parser.paragraph_reader(xml.as_bytes());
+            Sentences::new(&parser.splitter, paragraphs).collect::<Result<Vec<_>>>()
+        };
+
+        let with_alt_text = parse(true)?;
+        assert_eq!(
+            texts(&with_alt_text),
+            vec![
+                "Before the figure.",
+                "Figure 1: The white whale",
+                "A whale",
+                "A sperm whale breaching off Nantucket",
+                "After the figure."
+            ]
+        );
+        let kinds: Vec<_> = with_alt_text.iter().map(|sentence| sentence.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SentenceKind::Body,
+                SentenceKind::Caption,
+                SentenceKind::AltText,
+                SentenceKind::AltText,
+                SentenceKind::Body
+            ]
+        );
+        assert_eq!(
+            texts(&parse(false)?),
+            vec![
+                "Before the figure.",
+                "Figure 1: The white whale",
+                "After the figure."
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_of_contents() -> Result<()> {
+        let xml_content = r#"<office:text>
+            <text:table-of-content text:name="Table of Contents1">
+                <text:table-of-content-source text:outline-level="2">
+                    <text:index-title-template>Contents</text:index-title-template>
+                </text:table-of-content-source>
+                <text:index-body>
+                    <text:index-title><text:p>Contents</text:p></text:index-title>
+                    <text:p>Loomings<text:tab/>1</text:p>
+                </text:index-body>
+            </text:table-of-content>
+            <text:h text:outline-level="1">Loomings</text:h>
+            <text:p>Call me Ishmael.</text:p>
+            <text:alphabetical-index><text:index-body><text:p>Ishmael, 1</text:p></text:index-body></text:alphabetical-index>
+        </office:text>"#;
+
+        let paragraphs = |include_toc| {
+            OdtParser::new()?
+                .with_options(ParseOptions {
+                    include_toc,
+                    ..ParseOptions::default()
+                })
+                .extract_paragraphs_from_xml(xml_content)
+        };
+
+        assert_eq!(paragraphs(false)?, vec!["Loomings", "Call me Ishmael."]);
+        assert_eq!(
+            paragraphs(true)?,
+            vec![
+                "Contents",
+                "Loomings\t1",
+                "Loomings",
+                "Call me Ishmael.",
+                "Ishmael, 1"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bibliography() -> Result<()> {
+        let xml = r#"<office:text>
+            <text:p>Whales sing (Payne et al. 1971, p. 587). They <text:bibliography-mark text:identifier="Payne1971" text:bibliography-type="article">[Payne1971]</text:bibliography-mark> agree</text:p>
+            <text:bibliography text:name="Bibliography1">
+                <text:bibliography-source/>
+                <text:index-body>
+                    <text:p>Payne, R. S. and McVay, S. Songs of humpback whales. Science, 1971.</text:p>
+                    <text:p>Smith, J. A. Whales. Oxford U. Press, 2020.</text:p>
+                </text:index-body>
+            </text:bibliography>
+        </office:text>"#;
+
+        let parser = OdtParser::new()?;
+        let paragraphs = parser.paragraph_reader(xml.as_bytes());
+        let sentences = Sentences::new(&parser.splitter, paragraphs).collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "Whales sing (Payne et al. 1971, p. 587).",
+                "They [Payne1971] agree",
+                "Payne, R. S. and McVay, S. Songs of humpback whales. Science, 1971.",
+                "Smith, J. A. Whales. Oxford U. Press, 2020."
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_master_document() -> Result<()> {
+        let master = "test_master.odm";
+        let chapter = "test_master_chapter.odt";
+        create_test_odt_file(chapter, "<text:p>Fresh chapter text.</text:p>")?;
+        let mut zip = ZipWriter::new(File::create(master)?);
+        zip.start_file(
+            "mimetype",
+            FileOptions::default().compression_method(zip::CompressionMethod::Stored),
+        )?;
+        zip.write_all(b"application/vnd.oasis.opendocument.text-master")?;
+        zip.start_file("content.xml", FileOptions::default())?;
+        zip.write_all(
+            br#"<office:document-content><office:body><office:text>
+                <text:p>Before.</text:p>
+                <text:section text:name="test_master_chapter.odt">
+                    <text:section-source xlink:href="../test_master_chapter.odt" text:filter-name="writer8"/>
+                    <text:p>Old copy.</text:p>
+                </text:section>
+                <text:section text:name="missing.odt">
+                    <text:section-source xlink:href="../missing.odt"/>
+                    <text:p>Kept copy.</text:p>
+                </text:section>
+                <text:p>After.</text:p>
+            </office:text></office:body></office:document-content>"#,
+        )?;
+        zip.finish()?;
+
+        let format = DocumentFormat::detect(master);
+        let linked = OdtParser::new()?.parse_file(master);
+        let copies = OdtParser::new()?
+            .with_options(ParseOptions {
+                skip_linked_files: true,
+                ..ParseOptions::default()
+            })
+            .parse_file(master);
+        fs::remove_file(master).ok();
+        fs::remove_file(chapter).ok();
+
+        assert_eq!(format?, DocumentFormat::Odt);
+        assert_eq!(
+            texts(&linked?),
+            vec!["Before.", "Fresh chapter text.", "Kept copy.", "After."]
+        );
+        assert_eq!(
+            texts(&copies?),
+            vec!["Before.", "Old copy.", "Kept copy.", "After."]
+        );
+        assert_eq!(
+            linked_path(Path::new("books/master.odm"), "../part%201.odt"),
+            Path::new("books/part 1.odt")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mmap() -> Result<()> {
+        let test_file = "test_mmap.odt";
+        create_test_odt_file(
+            test_file,
+            "<text:p>Mapped into memory. Read the same.</text:p>",
+        )?;
+
+        let buffered = OdtParser::new()?.parse_file(test_file);
+        let mapped = OdtParser::new()?
+            .with_options(ParseOptions {
+                mmap: true,
+                ..ParseOptions::default()
+            })
+            .parse_file(test_file);
+        fs::remove_file(test_file).ok();
+
+        assert_eq!(texts(&mapped?), texts(&buffered?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_limits() -> Result<()> {
+        let test_file = "test_limits.odt";
+        let nested = format!(
+            "<text:p>{}Deep.{}</text:p>",
+            "<text:span>".repeat(20),
+            "</text:span>".repeat(20)
+        );
+        create_test_odt_file(test_file, &nested)?;
+
+        let parse = |limits| {
+            OdtParser::new()?
+                .with_options(ParseOptions {
+                    limits,
+                    ..ParseOptions::default()
+                })
+                .parse_file(test_file)
+        };
+        let default = parse(Limits::default());
+        let shallow = parse(Limits {
+            max_xml_depth: 10,
+            ..Limits::default()
+        });
+        let small = parse(Limits {
+            max_entry_size: 100,
+            ..Limits::default()
+        });
+        fs::remove_file(test_file).ok();
+
+        assert_eq!(texts(&default?), vec!["Deep."]);
+        assert!(matches!(shallow, Err(SentencerError::InvalidDocument(_))));
+        assert!(matches!(
+            small,
+            Err(SentencerError::Xml(_) | SentencerError::Io(_))
+        ));
+
+        let bomb = r#"<?xml version="1.0"?>
+<!DOCTYPE lolz [<!ENTITY lol "lol"><!ENTITY lol2 "&lol;&lol;&lol;&lol;">]>
+<office:document-content><text:p>&lol2;</text:p></office:document-content>"#;
+        assert!(matches!(
+            OdtParser::new()?.extract_paragraphs_from_xml(bomb),
+            Err(SentencerError::InvalidDocument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_cells() -> Result<()> {
+        let test_file = "test_tables.odt";
+        create_test_odt_file(
+            test_file,
+            r#"<text:p>Before the table.</text:p>
+            <table:table table:name="Table1">
+                <table:table-row>
+                    <table:table-cell><text:p>Name</text:p></table:table-cell>
+                    <table:table-cell/>
+                    <table:table-cell><text:p>Notes here</text:p><text:p>and more</text:p></table:table-cell>
+                </table:table-row>
+            </table:table>
+            <text:p>After the table.</text:p>"#,
+        )?;
+
+        let inline = OdtParser::new()?.parse_file(test_file);
+        let cells = OdtParser::new()?
+            .with_options(ParseOptions {
+                table_cells_as_sentences: true,
+                ..ParseOptions::default()
+            })
+            .parse_file(test_file);
+
+        fs::remove_file(test_file).ok();
+
+        assert_eq!(
+            texts(&inline?),
+            vec![
+                "Before the table.",
+                "Name Notes here and more After the table."
+            ]
+        );
+        let cells = cells?;
+        assert_eq!(cells[1].kind, SentenceKind::TableCell);
+        assert_eq!(cells[3].kind, SentenceKind::Body);
+        assert_eq!(
+            texts(&cells),
+            vec![
+                "Before the table.",
+                "[Table 1, row 1, column 1] Name",
+                "[Table 1, row 1, column 3] Notes here and more",
+                "After the table."
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use super::{DocumentParser, ParseOptions, parser_for};
+use crate::error::{Result, SentencerError};
+use crate::sentence::Sentence;
+use crate::splitter::Splitter;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Tells conversions of documents with the same name apart
+static CONVERSIONS: AtomicUsize = AtomicUsize::new(0);
+
+// What pandoc converts a document to before it is parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PandocTarget {
+    // Paragraphs only, separated by blank lines
+    Text,
+    // Keeps headings, lists, tables and notes
+    Odt,
+}
+
+impl PandocTarget {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "text" | "plain" => Ok(PandocTarget::Text),
+            "odt" => Ok(PandocTarget::Odt),
+            _ => Err(SentencerError::InvalidArgument(format!(
+                "Unknown pandoc target '{}' (expected text or odt)",
+                name
+            ))),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            PandocTarget::Text => "txt",
+            PandocTarget::Odt => "odt",
+        }
+    }
+}
+
+// Reads documents in formats without a parser of their own by having pandoc
+// convert them first. The converted copy lives in the temporary directory
+// only while it is parsed.
+#[derive(Debug)]
+pub(crate) struct PandocParser {
+    target: PandocTarget,
+    splitter: Splitter,
+    options: ParseOptions,
+    program: String,
+}
+
+impl PandocParser {
+    pub(crate) fn new(target: PandocTarget, splitter: Splitter, options: ParseOptions) -> Self {
+        PandocParser {
+            target,
+            splitter,
+            // The converted copy is read natively
+            options: ParseOptions {
+                via_pandoc: None,
+                ..options
+            },
+            program: "pandoc".to_string(),
+        }
+    }
+
+    fn convert(&self, file_path: &str) -> Result<Conversion> {
+        let stem = Path::new(file_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("document");
+        let conversion = Conversion {
+            path: env::temp_dir().join(format!(
+                "sentencer-{}-{}-{}.{}",
+                std::process::id(),
+                CONVERSIONS.fetch_add(1, Ordering::Relaxed),
+                stem,
+                self.target.extension()
+            )),
+        };
+
+        let mut command = Command::new(&self.program);
+        command.arg(file_path).arg("--output").arg(&conversion.path);
+        match self.target {
+            PandocTarget::Text => command.args(["--to", "plain", "--wrap", "none"]),
+            PandocTarget::Odt => command.args(["--to", "odt"]),
+        };
+        let output = command.output().map_err(|e| match e.kind() {
+            ErrorKind::NotFound => SentencerError::Pandoc(format!(
+                "'{}' was not found; install pandoc from https://pandoc.org to read {}",
+                self.program, file_path
+            )),
+            _ => SentencerError::Pandoc(format!("could not run '{}': {}", self.program, e)),
+        })?;
+        if !output.status.success() {
+            return Err(SentencerError::Pandoc(format!(
+                "converting {} failed: {}",
+                file_path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(conversion)
+    }
+}
+
+impl DocumentParser for PandocParser {
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        let conversion = self.convert(file_path)?;
+        let path = conversion.path.to_str().unwrap_or_default();
+
+        parser_for(path, self.splitter.clone(), self.options.clone())?.parse_file(path)
+    }
+
+    // pandoc is told the format by the file name, which bytes do not have
+    fn parse_bytes(&self, _data: &[u8]) -> Result<Vec<Sentence>> {
+        Err(SentencerError::UnsupportedFormat(
+            "documents in memory cannot be converted with pandoc".to_string(),
+        ))
+    }
+}
+
+// A converted document, removed again when dropped
+struct Conversion {
+    path: PathBuf,
+}
+
+impl Drop for Conversion {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pandoc_missing() -> Result<()> {
+        let test_file = "test_pandoc.rst";
+        fs::write(test_file, "Title\n=====\n\nSome text.\n")?;
+
+        let parser = PandocParser {
+            program: "sentencer-missing-pandoc".to_string(),
+            ..PandocParser::new(
+                PandocTarget::Text,
+                Splitter::regex()?,
+                ParseOptions::default(),
+            )
+        };
+        let result = parser.parse_file(test_file);
+        let unconverted = parser_for(test_file, Splitter::regex()?, ParseOptions::default())
+            .and_then(|parser| parser.parse_file(test_file));
+        fs::remove_file(test_file).ok();
+
+        assert!(matches!(
+            result,
+            Err(SentencerError::Pandoc(message)) if message.contains("install pandoc")
+        ));
+        assert!(unconverted.is_err());
+        assert_eq!(PandocTarget::from_name("plain")?, PandocTarget::Text);
+        Ok(())
+    }
+}
@@ -0,0 +1,619 @@
+use super::{DocumentParser, NotesMode, Paragraph, ParseOptions, Source, split_paragraphs};
+use crate::error::{Result, SentencerError};
+use crate::sentence::{Sentence, SentenceKind};
+use crate::splitter::Splitter;
+use encoding_rs::{Encoding, WINDOWS_1252};
+use std::collections::HashMap;
+use std::io::Read;
+
+// Groups whose text is never part of the document
+const SKIPPED_DESTINATIONS: &[&str] = &[
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "object",
+    "header",
+    "headerl",
+    "headerr",
+    "headerf",
+    "footer",
+    "footerl",
+    "footerr",
+    "footerf",
+    "fldinst",
+    "listtable",
+    "listoverridetable",
+    "listtext",
+    "pntext",
+    "revtbl",
+    "rsidtbl",
+    "generator",
+    "xmlnstbl",
+    "themedata",
+    "colorschememapping",
+    "datastore",
+    "latentstyles",
+];
+
+#[derive(Debug)]
+pub struct RtfParser {
+    splitter: Splitter,
+    options: ParseOptions,
+}
+
+impl RtfParser {
+    pub fn new() -> Result<Self> {
+        let splitter = Splitter::regex()?;
+
+        Ok(RtfParser::with_splitter(splitter))
+    }
+
+    pub fn with_splitter(splitter: Splitter) -> Self {
+        RtfParser {
+            splitter,
+            options: ParseOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    #[cfg(feature = "native")]
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::File(file_path))
+    }
+
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        self.parse_source(Source::Bytes(data))
+    }
+
+    fn parse_source(&self, source: Source) -> Result<Vec<Sentence>> {
+        let mut data = Vec::new();
+        source.open()?.read_to_end(&mut data)?;
+
+        let paragraphs = self.extract_paragraphs(&data)?;
+
+        Ok(split_paragraphs(&self.splitter, &paragraphs))
+    }
+
+    pub fn extract_text(&self, data: &[u8]) -> Result<String> {
+        let mut text_content = String::new();
+        for paragraph in self.extract_paragraphs(data)? {
+            text_content.push_str(&paragraph.text);
+            text_content.push(' ');
+        }
+
+        Ok(text_content)
+    }
+
+    fn extract_paragraphs(&self, data: &[u8]) -> Result<Vec<Paragraph>> {
+        if !data.starts_with(b"{\\rtf") {
+            return Err(SentencerError::InvalidDocument(
+                "RTF documents start with {\\rtf".to_string(),
+            ));
+        }
+
+        let mut reader = RtfReader::new(self.options.notes);
+        for token in Tokens::new(data) {
+            reader.handle(token);
+        }
+
+        Ok(reader.finish())
+    }
+}
+
+impl DocumentParser for RtfParser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        RtfParser::parse_file(self, file_path)
+    }
+
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        RtfParser::parse_bytes(self, data)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    GroupStart,
+    GroupEnd,
+    // A control word such as \par or \u8364, with its numeric parameter
+    Control(&'a str, Option<i32>),
+    // A control symbol such as \~ or \{
+    Symbol(u8),
+    // A byte written as \'hh
+    Hex(u8),
+    Text(&'a [u8]),
+}
+
+struct Tokens<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Tokens { data, position: 0 }
+    }
+
+    fn control(&mut self) -> Token<'a> {
+        let data = self.data;
+        let start = self.position;
+        while self.position < data.len() && data[self.position].is_ascii_alphabetic() {
+            self.position += 1;
+        }
+        if self.position == start {
+            let symbol = data.get(start).copied().unwrap_or(b'\\');
+            self.position += 1;
+            if symbol == b'\'' {
+                let hex = data.get(start + 1..start + 3).unwrap_or_default();
+                let byte = std::str::from_utf8(hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(byte) = byte {
+                    self.position += 2;
+                    return Token::Hex(byte);
+                }
+            }
+            return Token::Symbol(symbol);
+        }
+        let word = std::str::from_utf8(&data[start..self.position]).unwrap_or_default();
+
+        let digits = self.position;
+        if data.get(self.position) == Some(&b'-') {
+            self.position += 1;
+        }
+        while self.position < data.len() && data[self.position].is_ascii_digit() {
+            self.position += 1;
+        }
+        let parameter = std::str::from_utf8(&data[digits..self.position])
+            .ok()
+            .and_then(|digits| digits.parse().ok());
+        // One space ends the control word and belongs to it
+        if data.get(self.position) == Some(&b' ') {
+            self.position += 1;
+        }
+
+        // Binary data follows \binN and may hold any byte, braces included
+        if word == "bin" {
+            self.position += parameter.unwrap_or(0).max(0) as usize;
+            self.position = self.position.min(data.len());
+        }
+
+        Token::Control(word, parameter)
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let byte = *self.data.get(self.position)?;
+        self.position += 1;
+
+        match byte {
+            b'{' => Some(Token::GroupStart),
+            b'}' => Some(Token::GroupEnd),
+            b'\\' => Some(self.control()),
+            _ => {
+                let start = self.position - 1;
+                while self.position < self.data.len()
+                    && !matches!(self.data[self.position], b'{' | b'}' | b'\\')
+                {
+                    self.position += 1;
+                }
+                Some(Token::Text(&self.data[start..self.position]))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Destination {
+    Text,
+    Skip,
+    FontTable,
+    Note(SentenceKind),
+}
+
+// What a group inherits from the one around it
+#[derive(Debug, Clone, Copy)]
+struct Group {
+    destination: Destination,
+    // Characters standing in for each \uN, for readers without Unicode
+    unicode_skip: usize,
+    font: Option<i32>,
+}
+
+struct RtfReader {
+    notes_mode: NotesMode,
+    group: Group,
+    stack: Vec<Group>,
+    // Whether nothing but the opening brace of the group has been read
+    at_group_start: bool,
+    ansi: &'static Encoding,
+    fonts: HashMap<i32, &'static Encoding>,
+    table_font: i32,
+    // Bytes not yet decoded, with the encoding they were written in
+    bytes: Vec<u8>,
+    bytes_encoding: &'static Encoding,
+    skip: usize,
+    high_surrogate: Option<u16>,
+    paragraph: Paragraph,
+    outline_level: Option<usize>,
+    list_level: Option<usize>,
+    note: Paragraph,
+    notes_seen: usize,
+    // Notes wait for the end of the paragraph that cites them
+    waiting_notes: Vec<Paragraph>,
+    separate_notes: Vec<Paragraph>,
+    paragraphs: Vec<Paragraph>,
+}
+
+impl RtfReader {
+    fn new(notes_mode: NotesMode) -> Self {
+        RtfReader {
+            notes_mode,
+            group: Group {
+                destination: Destination::Text,
+                unicode_skip: 1,
+                font: None,
+            },
+            stack: Vec::new(),
+            at_group_start: false,
+            ansi: WINDOWS_1252,
+            fonts: HashMap::new(),
+            table_font: 0,
+            bytes: Vec::new(),
+            bytes_encoding: WINDOWS_1252,
+            skip: 0,
+            high_surrogate: None,
+            paragraph: Paragraph::default(),
+            outline_level: None,
+            list_level: None,
+            note: Paragraph::default(),
+            notes_seen: 0,
+            waiting_notes: Vec::new(),
+            separate_notes: Vec::new(),
+            paragraphs: Vec::new(),
+        }
+    }
+
+    fn handle(&mut self, token: Token) {
+        let at_group_start = std::mem::replace(&mut self.at_group_start, false);
+        if !matches!(token, Token::Text(_) | Token::Hex(_)) {
+            self.decode_bytes();
+        }
+
+        match token {
+            Token::GroupStart => {
+                self.skip = 0;
+                self.stack.push(self.group);
+                self.at_group_start = true;
+            }
+            Token::GroupEnd => {
+                self.skip = 0;
+                let closed = self.group.destination;
+                if let Some(group) = self.stack.pop() {
+                    self.group = group;
+                }
+                if let (Destination::Note(_), false) = (
+                    closed,
+                    matches!(self.group.destination, Destination::Note(_)),
+                ) {
+                    self.end_note();
+                }
+            }
+            _ if self.skip > 0 => self.skip_fallback(token),
+            Token::Control(word, parameter) => self.control(word, parameter, at_group_start),
+            // \* marks a destination that readers not knowing it leave out
+            Token::Symbol(b'*') => self.group.destination = Destination::Skip,
+            Token::Symbol(b'~') => self.push_str("\u{a0}"),
+            Token::Symbol(b'_') => self.push_str("-"),
+            Token::Symbol(b'\n' | b'\r') => self.end_paragraph(),
+            Token::Symbol(symbol @ (b'\\' | b'{' | b'}')) => self.push_bytes(&[symbol]),
+            Token::Symbol(_) => {}
+            Token::Hex(byte) => self.push_bytes(&[byte]),
+            Token::Text(text) => {
+                let text: Vec<u8> = text
+                    .iter()
+                    .copied()
+                    .filter(|&byte| byte != b'\r' && byte != b'\n')
+                    .collect();
+                self.push_bytes(&text);
+            }
+        }
+    }
+
+    // Drops the characters written after \uN for readers without Unicode
+    fn skip_fallback(&mut self, token: Token) {
+        match token {
+            Token::Text(text) => {
+                let skipped = self.skip.min(text.len());
+                self.skip -= skipped;
+                self.handle(Token::Text(&text[skipped..]));
+            }
+            _ => self.skip -= 1,
+        }
+    }
+
+    fn control(&mut self, word: &str, parameter: Option<i32>, at_group_start: bool) {
+        if at_group_start && SKIPPED_DESTINATIONS.contains(&word) {
+            self.group.destination = Destination::Skip;
+            return;
+        }
+
+        match (word, self.group.destination) {
+            ("fonttbl", _) => self.group.destination = Destination::FontTable,
+            ("f", Destination::FontTable) => self.table_font = parameter.unwrap_or(0),
+            ("fcharset", Destination::FontTable) => {
+                if let Some(encoding) = parameter.and_then(charset_encoding) {
+                    self.fonts.insert(self.table_font, encoding);
+                }
+            }
+            (_, Destination::Skip | Destination::FontTable) => {}
+            ("footnote", _) if self.notes_mode == NotesMode::Exclude => {
+                self.group.destination = Destination::Skip;
+            }
+            ("footnote", _) => {
+                self.notes_seen += 1;
+                self.group.destination = Destination::Note(SentenceKind::Footnote);
+                self.note = Paragraph {
+                    kind: SentenceKind::Footnote,
+                    ..Paragraph::default()
+                };
+            }
+            // Endnotes are footnotes marked as the alternative kind
+            ("ftnalt", Destination::Note(_)) => {
+                self.group.destination = Destination::Note(SentenceKind::Endnote);
+                self.note.kind = SentenceKind::Endnote;
+            }
+            ("ansicpg", _) => {
+                if let Some(encoding) = parameter.and_then(code_page_encoding) {
+                    self.ansi = encoding;
+                }
+            }
+            ("f", _) => self.group.font = parameter,
+            ("uc", _) => self.group.unicode_skip = parameter.unwrap_or(1).max(0) as usize,
+            ("u", _) => {
+                // Parameters are signed 16-bit, so characters above 32767 are negative
+                let unit = parameter.unwrap_or(0) as u16;
+                self.push_unicode(unit);
+                self.skip = self.group.unicode_skip;
+            }
+            ("par" | "sect" | "row", _) => self.end_paragraph(),
+            ("line" | "tab" | "cell", _) => self.push_str(" "),
+            ("emdash", _) => self.push_str("\u{2014}"),
+            ("endash", _) => self.push_str("\u{2013}"),
+            ("bullet", _) => self.push_str("\u{2022}"),
+            ("lquote", _) => self.push_str("\u{2018}"),
+            ("rquote", _) => self.push_str("\u{2019}"),
+            ("ldblquote", _) => self.push_str("\u{201c}"),
+            ("rdblquote", _) => self.push_str("\u{201d}"),
+            ("emspace" | "enspace" | "qmspace", _) => self.push_str(" "),
+            ("pard", Destination::Text) => {
+                self.outline_level = None;
+                self.list_level = None;
+            }
+            ("outlinelevel", Destination::Text) => {
+                self.outline_level = parameter.map(|level| level.max(0) as usize + 1);
+            }
+            ("ilvl", Destination::Text) => {
+                self.list_level = parameter.map(|level| level.max(0) as usize + 1);
+            }
+            ("ls", Destination::Text) => {
+                self.list_level.get_or_insert(1);
+            }
+            _ => {}
+        }
+    }
+
+    // The encoding of the current font, or the document's code page
+    fn encoding(&self) -> &'static Encoding {
+        self.group
+            .font
+            .and_then(|font| self.fonts.get(&font).copied())
+            .unwrap_or(self.ansi)
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        if !self.writes_text() {
+            return;
+        }
+        let encoding = self.encoding();
+        if encoding != self.bytes_encoding {
+            self.decode_bytes();
+            self.bytes_encoding = encoding;
+        }
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    // Bytes are decoded together, so characters of several bytes, as in
+    // Shift JIS, come out whole
+    fn decode_bytes(&mut self) {
+        if self.bytes.is_empty() {
+            return;
+        }
+        let bytes = std::mem::take(&mut self.bytes);
+        let (text, _, _) = self.bytes_encoding.decode(&bytes);
+        self.push_str(&text);
+    }
+
+    fn push_unicode(&mut self, unit: u16) {
+        let character = match (self.high_surrogate.take(), unit) {
+            (_, 0xd800..=0xdbff) => {
+                self.high_surrogate = Some(unit);
+                None
+            }
+            (Some(high), 0xdc00..=0xdfff) => char::decode_utf16([high, unit])
+                .next()
+                .and_then(|decoded| decoded.ok()),
+            (_, unit) => char::from_u32(unit as u32),
+        };
+        if let Some(character) = character {
+            self.push_str(character.encode_utf8(&mut [0; 4]));
+        }
+    }
+
+    fn writes_text(&self) -> bool {
+        matches!(
+            self.group.destination,
+            Destination::Text | Destination::Note(_)
+        )
+    }
+
+    fn push_str(&mut self, text: &str) {
+        match self.group.destination {
+            Destination::Text => self.paragraph.text.push_str(text),
+            Destination::Note(_) => self.note.text.push_str(text),
+            Destination::Skip | Destination::FontTable => {}
+        }
+    }
+
+    fn end_paragraph(&mut self) {
+        match self.group.destination {
+            // Paragraphs inside a note are read as one
+            Destination::Note(_) => self.note.text.push(' '),
+            Destination::Text => {
+                let text = std::mem::take(&mut self.paragraph.text);
+                let paragraph = match self.outline_level {
+                    Some(level) => Paragraph::heading(text, level),
+                    None => Paragraph {
+                        kind: match self.list_level {
+                            Some(_) => SentenceKind::ListItem,
+                            None => SentenceKind::Body,
+                        },
+                        list_level: self.list_level,
+                        ..Paragraph::new(text)
+                    },
+                };
+                self.paragraphs.push(paragraph);
+                self.paragraphs.append(&mut self.waiting_notes);
+            }
+            Destination::Skip | Destination::FontTable => {}
+        }
+    }
+
+    fn end_note(&mut self) {
+        let mut note = std::mem::take(&mut self.note);
+        if note.text.trim().is_empty() {
+            return;
+        }
+        if self.notes_mode == NotesMode::Separate {
+            let label = match note.kind {
+                SentenceKind::Endnote => "Endnote",
+                _ => "Footnote",
+            };
+            note.text = format!("[{} {}] {}", label, self.notes_seen, note.text.trim());
+            self.separate_notes.push(note);
+        } else {
+            self.waiting_notes.push(note);
+        }
+    }
+
+    fn finish(mut self) -> Vec<Paragraph> {
+        self.decode_bytes();
+        if !self.paragraph.text.trim().is_empty() {
+            self.end_paragraph();
+        }
+        self.paragraphs.append(&mut self.waiting_notes);
+        self.paragraphs.append(&mut self.separate_notes);
+        self.paragraphs
+    }
+}
+
+// Code pages named by \ansicpgN
+fn code_page_encoding(code_page: i32) -> Option<&'static Encoding> {
+    let label = match code_page {
+        866 => "ibm866".to_string(),
+        874 => "windows-874".to_string(),
+        932 => "shift_jis".to_string(),
+        936 => "gbk".to_string(),
+        949 => "euc-kr".to_string(),
+        950 => "big5".to_string(),
+        1250..=1258 => format!("windows-{}", code_page),
+        10000 => "macintosh".to_string(),
+        _ => return None,
+    };
+
+    Encoding::for_label(label.as_bytes())
+}
+
+// Character sets named by \fcharsetN in the font table
+fn charset_encoding(charset: i32) -> Option<&'static Encoding> {
+    let code_page = match charset {
+        77 => 10000,
+        128 => 932,
+        129 => 949,
+        134 => 936,
+        136 => 950,
+        161 => 1253,
+        162 => 1254,
+        163 => 1258,
+        177 => 1255,
+        178 => 1256,
+        186 => 1257,
+        204 => 1251,
+        222 => 874,
+        238 => 1250,
+        _ => return None,
+    };
+
+    code_page_encoding(code_page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::DocumentFormat;
+    use crate::sentence::texts;
+
+    #[test]
+    fn test_rtf_parsing() -> Result<()> {
+        let rtf =
+            br"{\rtf1\ansi\ansicpg1252\deff0{\fonttbl{\f0\fswiss Arial;}{\f1\fcharset204 Times;}}
+{\colortbl;\red0\green0\blue0;}{\info{\title Not text}}
+{\*\generator Writer}\pard\outlinelevel0 Caf\'e9 Notes\par
+\pard\plain First sentence\emdash with a dash. Unicode \u8364? sign\u-10179?\u-8704? here.\par
+{\f1 \'cf\'f0\'e8\'e2\'e5\'f2.}\par
+Escaped \{braces\} and a back\\slash.{\footnote\pard A note.}\par
+{\pict\bin2 }{}\par}";
+
+        let sentences = RtfParser::new()?.parse_bytes(rtf)?;
+
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "Café Notes",
+                "First sentence\u{2014}with a dash.",
+                "Unicode \u{20ac} sign\u{1f600} here.",
+                "\u{41f}\u{440}\u{438}\u{432}\u{435}\u{442}.",
+                "Escaped {braces} and a back\\slash.",
+                "A note."
+            ]
+        );
+        assert_eq!(sentences[0].kind, SentenceKind::Heading);
+        assert_eq!(sentences[5].kind, SentenceKind::Footnote);
+        assert!(RtfParser::new()?.parse_bytes(b"plain text").is_err());
+        assert_eq!(DocumentFormat::detect_bytes("", rtf)?, DocumentFormat::Rtf);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rtf_separate_notes() -> Result<()> {
+        let rtf = br"{\rtf1\ansi Body one.{\footnote A note.} Body two.\par Last.\par}";
+        let parser = RtfParser::new()?.with_options(ParseOptions {
+            notes: NotesMode::Separate,
+            ..ParseOptions::default()
+        });
+
+        assert_eq!(
+            texts(&parser.parse_bytes(rtf)?),
+            vec!["Body one.", "Body two.", "Last.", "[Footnote 1] A note."]
+        );
+        Ok(())
+    }
+}
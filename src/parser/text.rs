@@ -0,0 +1,77 @@
+use super::{DocumentParser, Paragraph, split_paragraphs, text_paragraphs};
+use crate::error::Result;
+use crate::sentence::Sentence;
+use crate::splitter::Splitter;
+#[cfg(feature = "native")]
+use std::fs;
+
+#[derive(Debug)]
+pub struct TextParser {
+    splitter: Splitter,
+}
+
+impl TextParser {
+    pub fn new() -> Result<Self> {
+        let splitter = Splitter::regex()?;
+
+        Ok(TextParser { splitter })
+    }
+
+    pub fn with_splitter(splitter: Splitter) -> Self {
+        TextParser { splitter }
+    }
+
+    #[cfg(feature = "native")]
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        let content = fs::read_to_string(file_path)?;
+
+        Ok(self.parse_str(&content))
+    }
+
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        Ok(self.parse_str(std::str::from_utf8(data)?))
+    }
+
+    pub fn parse_str(&self, content: &str) -> Vec<Sentence> {
+        let paragraphs: Vec<Paragraph> = text_paragraphs(content)
+            .into_iter()
+            .map(Paragraph::new)
+            .collect();
+
+        split_paragraphs(&self.splitter, &paragraphs)
+    }
+}
+
+impl DocumentParser for TextParser {
+    #[cfg(feature = "native")]
+    fn parse_file(&self, file_path: &str) -> Result<Vec<Sentence>> {
+        TextParser::parse_file(self, file_path)
+    }
+
+    fn parse_bytes(&self, data: &[u8]) -> Result<Vec<Sentence>> {
+        TextParser::parse_bytes(self, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sentence::texts;
+
+    #[test]
+    fn test_text_parsing() -> Result<()> {
+        let parser = TextParser::new()?;
+        let sentences =
+            parser.parse_str("First line wraps\nonto the next. Second!\n\n\nNew paragraph.");
+
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "First line wraps onto the next.",
+                "Second!",
+                "New paragraph."
+            ]
+        );
+        Ok(())
+    }
+}
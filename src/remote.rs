@@ -0,0 +1,172 @@
+use crate::error::{Result, SentencerError};
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Tells downloads of documents with the same name apart
+static DOWNLOADS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn is_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+// A document downloaded to the temporary directory, removed again when dropped
+#[derive(Debug)]
+pub struct Download {
+    path: PathBuf,
+    file_name: String,
+}
+
+impl Download {
+    pub fn path(&self) -> &str {
+        self.path.to_str().unwrap_or_default()
+    }
+
+    // The document's name in the URL
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+}
+
+impl Drop for Download {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+// The last segment of the URL's path, which keeps the extension the format
+// is detected from
+pub fn file_name(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let without_query = without_scheme.split(['?', '#']).next().unwrap_or_default();
+
+    without_query
+        .split_once('/')
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("document")
+        .to_string()
+}
+
+// `progress` is called with the bytes received so far and the expected total,
+// when the server gives one
+pub fn download(
+    url: &str,
+    max_size: u64,
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> Result<Download> {
+    let failed = |message: String| SentencerError::Download(format!("{}: {}", url, message));
+    let too_large = || failed(format!("larger than the limit of {} bytes", max_size));
+
+    // ureq's errors already name the URL
+    let response = ureq::get(url)
+        .call()
+        .map_err(|e| SentencerError::Download(e.to_string()))?;
+    let total = response
+        .header("Content-Length")
+        .and_then(|length| length.parse::<u64>().ok());
+    if total.is_some_and(|total| total > max_size) {
+        return Err(too_large());
+    }
+
+    let file_name = file_name(url);
+    let download = Download {
+        path: env::temp_dir().join(format!(
+            "sentencer-{}-{}-{}",
+            std::process::id(),
+            DOWNLOADS.fetch_add(1, Ordering::Relaxed),
+            file_name
+        )),
+        file_name,
+    };
+    let mut file = File::create(&download.path)?;
+
+    // The size is checked as the body arrives, since the header may be missing
+    // or wrong
+    let mut reader = response.into_reader();
+    let mut buf = vec![0; 64 * 1024];
+    let mut received = 0;
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        received += read as u64;
+        if received > max_size {
+            return Err(too_large());
+        }
+        file.write_all(&buf[..read])?;
+        progress(received, total);
+    }
+    file.flush()?;
+
+    Ok(download)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Answers one request with `body`
+    fn serve_once(body: &'static str) -> Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut request_line = String::new();
+                BufReader::new(&stream).read_line(&mut request_line).ok();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).ok();
+            }
+        });
+        Ok(format!("http://{}/files/notes.txt?version=2", address))
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("https://example.com/a/report.odt"), "report.odt");
+        assert_eq!(
+            file_name("https://example.com/report.odt?x=1#top"),
+            "report.odt"
+        );
+        assert_eq!(file_name("https://example.com"), "document");
+        assert_eq!(file_name("https://example.com/dir/"), "document");
+    }
+
+    #[test]
+    fn test_download() -> Result<()> {
+        let url = serve_once("Fetched text. Two sentences.")?;
+        let mut updates = Vec::new();
+        let download = download(&url, 1024, |received, total| {
+            updates.push((received, total))
+        })?;
+
+        assert_eq!(download.file_name(), "notes.txt");
+        assert!(download.path().ends_with("notes.txt"));
+        assert_eq!(
+            fs::read_to_string(download.path())?,
+            "Fetched text. Two sentences."
+        );
+        assert_eq!(updates.last(), Some(&(28, Some(28))));
+
+        let path = download.path().to_string();
+        drop(download);
+        assert!(fs::metadata(path).is_err());
+
+        let url = serve_once("Too long for the limit.")?;
+        assert!(matches!(
+            super::download(&url, 10, |_, _| {}),
+            Err(SentencerError::Download(_))
+        ));
+        Ok(())
+    }
+}
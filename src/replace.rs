@@ -0,0 +1,157 @@
+use crate::error::{Result, SentencerError};
+use crate::normalize::normalize_whitespace;
+use regex::{Regex, RegexBuilder};
+
+// A regex substitution applied to every sentence of a document, written like
+// sed's `s/pattern/replacement/flags`. The replacement may refer to groups as
+// `$1` or `\1`; the flags are `g` to replace every match in a sentence rather
+// than the first, and `i` to ignore case.
+#[derive(Debug, Clone)]
+pub struct Substitution {
+    regex: Regex,
+    replacement: String,
+    global: bool,
+}
+
+impl Substitution {
+    pub fn new(pattern: &str, replacement: &str, global: bool, ignore_case: bool) -> Result<Self> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()?;
+
+        Ok(Substitution {
+            regex,
+            replacement: group_references(replacement),
+            global,
+        })
+    }
+
+    // Any character may stand in for the slashes, as in `s|/usr|/opt|`, and is
+    // escaped with a backslash to appear in the pattern or replacement
+    pub fn parse(command: &str) -> Result<Self> {
+        let invalid = || {
+            SentencerError::InvalidArgument(format!(
+                "Expected s/pattern/replacement/flags, got '{}'",
+                command
+            ))
+        };
+        let command = command.trim().trim_start_matches(':');
+        let rest = command.strip_prefix('s').ok_or_else(invalid)?;
+        let mut chars = rest.chars();
+        let delimiter = chars
+            .next()
+            .filter(|c| !c.is_alphanumeric() && *c != '\\')
+            .ok_or_else(invalid)?;
+
+        let mut parts = vec![String::new()];
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if chars.clone().next() == Some(delimiter) => {
+                    parts.last_mut().ok_or_else(invalid)?.push(delimiter);
+                    chars.next();
+                }
+                c if c == delimiter && parts.len() < 3 => parts.push(String::new()),
+                c => parts.last_mut().ok_or_else(invalid)?.push(c),
+            }
+        }
+        let [pattern, replacement, flags] = parts.as_slice() else {
+            // The closing delimiter may be left out when there are no flags
+            return match parts.as_slice() {
+                [pattern, replacement] => Substitution::new(pattern, replacement, false, false),
+                _ => Err(invalid()),
+            };
+        };
+        if let Some(flag) = flags.chars().find(|flag| !matches!(flag, 'g' | 'i')) {
+            return Err(SentencerError::InvalidArgument(format!(
+                "Unknown substitution flag '{}' (expected g or i)",
+                flag
+            )));
+        }
+
+        Substitution::new(
+            pattern,
+            replacement,
+            flags.contains('g'),
+            flags.contains('i'),
+        )
+    }
+
+    pub fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    // The new text, or None when the sentence does not change
+    pub fn apply(&self, text: &str) -> Option<String> {
+        let limit = if self.global { 0 } else { 1 };
+        let replaced = self.regex.replacen(text, limit, self.replacement.as_str());
+        let replaced = normalize_whitespace(&replaced);
+        (!replaced.is_empty() && replaced != text).then_some(replaced)
+    }
+
+    // Index and new text of every sentence the substitution changes
+    pub fn changes<S: AsRef<str>>(&self, sentences: &[S]) -> Vec<(usize, String)> {
+        sentences
+            .iter()
+            .enumerate()
+            .filter_map(|(i, sentence)| self.apply(sentence.as_ref()).map(|text| (i, text)))
+            .collect()
+    }
+}
+
+// sed writes groups as \1; the regex crate expects ${1}
+fn group_references(replacement: &str) -> String {
+    let mut converted = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(digit)) if digit.is_ascii_digit() => {
+                converted.push_str(&format!("${{{}}}", digit));
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                converted.push('\\');
+                chars.next();
+            }
+            (c, _) => converted.push(c),
+        }
+    }
+    converted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitution() -> Result<()> {
+        let sentences = ["Colour and colour.", "No match here.", "COLOUR me."];
+
+        let first = Substitution::parse(":s/colour/color/")?;
+        assert_eq!(
+            first.changes(&sentences),
+            vec![(0, "Colour and color.".to_string())]
+        );
+
+        let every = Substitution::parse("s/colo(u)r/<\\1>/gi")?;
+        assert_eq!(
+            every.changes(&sentences),
+            vec![(0, "<u> and <u>.".to_string()), (2, "<U> me.".to_string()),]
+        );
+
+        let paths = Substitution::parse("s#/usr/#/opt/#")?;
+        assert_eq!(
+            paths.apply("See /usr/bin."),
+            Some("See /opt/bin.".to_string())
+        );
+        assert_eq!(Substitution::parse(r"s/a\/b/c/")?.pattern(), "a/b");
+        assert_eq!(
+            Substitution::parse("s/here/")?.apply("Words here."),
+            Some("Words .".to_string())
+        );
+
+        assert!(Substitution::parse("x/a/b/").is_err());
+        assert!(Substitution::parse("s/a/b/q").is_err());
+        assert!(Substitution::parse("s/(/b/").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::{Result, SentencerError};
+use crate::normalize::normalize_whitespace;
+use serde::Deserialize;
+
+pub const DEFAULT_REWRITE_URL: &str = "https://api.openai.com/v1";
+pub const DEFAULT_REWRITE_MODEL: &str = "gpt-4o-mini";
+
+const SYSTEM_PROMPT: &str = "You edit single sentences of a document. Rewrite the \
+sentence as the instruction asks, keeping its meaning and its language. Reply with \
+the rewritten sentence only, without quotes or explanations.";
+
+#[derive(Debug, Deserialize)]
+struct CompletionResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    content: String,
+}
+
+// Asks a language model to rewrite sentences, through any server speaking
+// the OpenAI chat completions API, such as OpenAI itself, Ollama
+// (http://localhost:11434/v1) or llama.cpp's server
+#[derive(Debug, Clone)]
+pub struct Rewriter {
+    url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl Default for Rewriter {
+    fn default() -> Self {
+        Rewriter::new(DEFAULT_REWRITE_URL)
+    }
+}
+
+impl Rewriter {
+    // The API's base URL, the part before /chat/completions
+    pub fn new(url: &str) -> Self {
+        Rewriter {
+            url: url.trim_end_matches('/').to_string(),
+            model: DEFAULT_REWRITE_MODEL.to_string(),
+            api_key: None,
+        }
+    }
+
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+
+    // Local servers usually need no key
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    // The suggestion is a single line, without the quotes models like to
+    // put around it
+    pub fn rewrite(&self, sentence: &str, instruction: &str) -> Result<String> {
+        let endpoint = format!("{}/chat/completions", self.url);
+        let request = serde_json::json!({
+            "model": self.model,
+            "temperature": 0.2,
+            "messages": [
+                {"role": "system", "content": SYSTEM_PROMPT},
+                {
+                    "role": "user",
+                    "content": format!("Instruction: {}\nSentence: {}", instruction, sentence),
+                },
+            ],
+        });
+
+        let failed = |message: String| SentencerError::Rewrite(message);
+        let mut call = ureq::post(&endpoint).set("Content-Type", "application/json");
+        if let Some(api_key) = &self.api_key {
+            call = call.set("Authorization", &format!("Bearer {}", api_key));
+        }
+        let response = call
+            .send_string(&request.to_string())
+            .map_err(|e| failed(e.to_string()))?
+            .into_string()?;
+        let response: CompletionResponse = serde_json::from_str(&response)
+            .map_err(|e| failed(format!("{}: unexpected response: {}", endpoint, e)))?;
+
+        let content = response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .unwrap_or_default();
+        let suggestion = normalize_whitespace(&content)
+            .trim_matches(['"', '“', '”'])
+            .to_string();
+        if suggestion.is_empty() {
+            return Err(failed(format!(
+                "{}: the model gave no suggestion",
+                endpoint
+            )));
+        }
+        Ok(suggestion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::serve_once;
+
+    #[test]
+    fn test_rewrite() -> Result<()> {
+        let (url, request) = serve_once(
+            r#"{"choices": [{"index": 0, "message": {"role": "assistant",
+                "content": "\"The meeting was short.\"\n"}}]}"#,
+        )?;
+
+        let rewriter = Rewriter::new(&format!("{}v1", url))
+            .with_model("local")
+            .with_api_key("secret");
+        assert_eq!(
+            rewriter.rewrite("The meeting, which was held, was not long.", "make concise")?,
+            "The meeting was short."
+        );
+
+        let request: serde_json::Value = serde_json::from_str(&request.recv().unwrap_or_default())?;
+        assert_eq!(request["model"], "local");
+        assert_eq!(
+            request["messages"][1]["content"],
+            "Instruction: make concise\nSentence: The meeting, which was held, was not long."
+        );
+        Ok(())
+    }
+}
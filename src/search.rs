@@ -0,0 +1,176 @@
+use crate::error::Result;
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+// A match shown in its context: up to the given number of characters of the
+// sentence on either side of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcordanceLine {
+    pub sentence: usize,
+    pub left: String,
+    pub keyword: String,
+    pub right: String,
+}
+
+// Case-insensitive search over sentences, either for a literal substring or
+// for a regular expression
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pattern: String,
+    is_regex: bool,
+    regex: Regex,
+}
+
+impl SearchQuery {
+    pub fn new(pattern: &str, is_regex: bool) -> Result<Self> {
+        let source = if is_regex {
+            pattern.to_string()
+        } else {
+            regex::escape(pattern)
+        };
+        let regex = RegexBuilder::new(&source).case_insensitive(true).build()?;
+
+        Ok(SearchQuery {
+            pattern: pattern.to_string(),
+            is_regex,
+            regex,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn is_regex(&self) -> bool {
+        self.is_regex
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    // Byte ranges of every non-empty match, for highlighting
+    pub fn find_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        self.regex
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect()
+    }
+
+    pub fn matching_indices<S: AsRef<str>>(&self, sentences: &[S]) -> Vec<usize> {
+        sentences
+            .iter()
+            .enumerate()
+            .filter(|(_, sentence)| self.is_match(sentence.as_ref()))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // Every match in every sentence, in document order, for a
+    // keyword-in-context listing
+    pub fn concordance<S: AsRef<str>>(
+        &self,
+        sentences: &[S],
+        context: usize,
+    ) -> Vec<ConcordanceLine> {
+        let mut lines = Vec::new();
+        for (i, sentence) in sentences.iter().enumerate() {
+            let text = sentence.as_ref();
+            for range in self.find_ranges(text) {
+                let before: Vec<char> = text[..range.start].chars().collect();
+                lines.push(ConcordanceLine {
+                    sentence: i,
+                    left: before[before.len().saturating_sub(context)..]
+                        .iter()
+                        .collect(),
+                    keyword: text[range.clone()].to_string(),
+                    right: text[range.end..].chars().take(context).collect(),
+                });
+            }
+        }
+        lines
+    }
+
+    // The next matching sentence after `from` in the given direction,
+    // wrapping around the document
+    pub fn next_match<S: AsRef<str>>(
+        &self,
+        sentences: &[S],
+        from: usize,
+        forward: bool,
+    ) -> Option<usize> {
+        let total = sentences.len();
+        (1..=total)
+            .map(|step| {
+                if forward {
+                    (from + step) % total
+                } else {
+                    (from + total * step - step) % total
+                }
+            })
+            .find(|&i| self.is_match(sentences[i].as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENTENCES: [&str; 4] = [
+        "Call me Ishmael.",
+        "Some years ago.",
+        "Never mind.",
+        "ISHMAEL again.",
+    ];
+
+    #[test]
+    fn test_literal_search() -> Result<()> {
+        let query = SearchQuery::new("ishmael.", false)?;
+
+        assert_eq!(query.matching_indices(&SENTENCES), vec![0]);
+        assert_eq!(query.find_ranges(SENTENCES[0]), vec![8..16]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_search_wraps() -> Result<()> {
+        let query = SearchQuery::new(r"ishmael\b", true)?;
+
+        assert_eq!(query.next_match(&SENTENCES, 0, true), Some(3));
+        assert_eq!(query.next_match(&SENTENCES, 3, true), Some(0));
+        assert_eq!(query.next_match(&SENTENCES, 0, false), Some(3));
+        assert_eq!(query.next_match(&SENTENCES, 3, false), Some(0));
+        assert!(SearchQuery::new("(", true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_concordance() -> Result<()> {
+        let query = SearchQuery::new("e", false)?;
+        let lines = query.concordance(&SENTENCES[1..3], 4);
+
+        let columns: Vec<(usize, &str, &str, &str)> = lines
+            .iter()
+            .map(|line| {
+                let ConcordanceLine {
+                    sentence,
+                    left,
+                    keyword,
+                    right,
+                } = line;
+                (*sentence, left.as_str(), keyword.as_str(), right.as_str())
+            })
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                (0, "Som", "e", " yea"),
+                (0, "me y", "e", "ars "),
+                (1, "N", "e", "ver "),
+                (1, "Nev", "e", "r mi"),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use crate::error::{Result, SentencerError};
+use serde::Serialize;
+use std::ops::Range;
+
+// A hyperlink in the text of a sentence
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Link {
+    pub text: String,
+    pub href: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SentenceKind {
+    #[default]
+    Body,
+    Heading,
+    Footnote,
+    Endnote,
+    TableCell,
+    // A reviewer comment attached to the text
+    Comment,
+    // The speaker notes of a presentation slide
+    SpeakerNote,
+    // Text repeated at the top or bottom of every page
+    Header,
+    Footer,
+    // The numbered caption of a figure or table
+    Caption,
+    // The title or description given to an image for readers who cannot see it
+    AltText,
+    ListItem,
+    // A block quotation set apart from the text around it
+    Quote,
+}
+
+impl SentenceKind {
+    pub const ALL: [SentenceKind; 13] = [
+        SentenceKind::Body,
+        SentenceKind::Heading,
+        SentenceKind::Footnote,
+        SentenceKind::Endnote,
+        SentenceKind::TableCell,
+        SentenceKind::Comment,
+        SentenceKind::SpeakerNote,
+        SentenceKind::Header,
+        SentenceKind::Footer,
+        SentenceKind::Caption,
+        SentenceKind::AltText,
+        SentenceKind::ListItem,
+        SentenceKind::Quote,
+    ];
+
+    // As in exports, such as "table_cell"
+    pub fn name(self) -> &'static str {
+        match self {
+            SentenceKind::Body => "body",
+            SentenceKind::Heading => "heading",
+            SentenceKind::Footnote => "footnote",
+            SentenceKind::Endnote => "endnote",
+            SentenceKind::TableCell => "table_cell",
+            SentenceKind::Comment => "comment",
+            SentenceKind::SpeakerNote => "speaker_note",
+            SentenceKind::Header => "header",
+            SentenceKind::Footer => "footer",
+            SentenceKind::Caption => "caption",
+            SentenceKind::AltText => "alt_text",
+            SentenceKind::ListItem => "list_item",
+            SentenceKind::Quote => "quote",
+        }
+    }
+
+    // Hyphens may stand for the underscores, as in "list-item"
+    pub fn from_name(name: &str) -> Result<Self> {
+        let normalized = name.replace('-', "_");
+        SentenceKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == normalized)
+            .ok_or_else(|| {
+                let names: Vec<_> = SentenceKind::ALL.iter().map(|kind| kind.name()).collect();
+                SentencerError::InvalidArgument(format!(
+                    "Unknown sentence kind '{}' (expected {})",
+                    name,
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+// Where a spreadsheet cell is, with rows and columns counted from 1
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CellPosition {
+    pub sheet: String,
+    pub row: usize,
+    // Unset when the cells of a row are read as one sentence
+    pub column: Option<usize>,
+}
+
+impl CellPosition {
+    // The cell as spreadsheets name it, such as Sheet1.B3, or Sheet1.3 for a row
+    pub fn reference(&self) -> String {
+        match self.column {
+            Some(column) => format!("{}.{}{}", self.sheet, column_name(column), self.row),
+            None => format!("{}.{}", self.sheet, self.row),
+        }
+    }
+}
+
+// Columns are lettered A to Z, then AA, AB and so on
+fn column_name(column: usize) -> String {
+    let mut name = Vec::new();
+    let mut column = column;
+    while column > 0 {
+        column -= 1;
+        name.push(b'A' + (column % 26) as u8);
+        column /= 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+// A sentence together with where it came from in the parsed document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sentence {
+    pub text: String,
+    // Index of the paragraph the sentence starts in, in parser order
+    pub paragraph_index: usize,
+    // Character offsets into the whitespace-normalized document text
+    pub char_range: Range<usize>,
+    // Titles of the enclosing headings, outermost first
+    pub heading_path: Vec<String>,
+    pub kind: SentenceKind,
+    // Outline level (1 for top-level headings), only set for headings
+    pub heading_level: Option<usize>,
+    // List nesting level (1 for top-level items), only set inside lists
+    pub list_level: Option<usize>,
+    pub links: Vec<Link>,
+    // The spreadsheet cell or row the sentence was read from
+    pub cell: Option<CellPosition>,
+    // Number of the presentation slide the sentence is on, counted from 1
+    pub slide: Option<usize>,
+}
+
+impl Sentence {
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let length = text.chars().count();
+
+        Sentence {
+            text,
+            paragraph_index: 0,
+            char_range: 0..length,
+            heading_path: Vec::new(),
+            kind: SentenceKind::Body,
+            heading_level: None,
+            list_level: None,
+            links: Vec::new(),
+            cell: None,
+            slide: None,
+        }
+    }
+}
+
+impl AsRef<str> for Sentence {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+pub fn texts(sentences: &[Sentence]) -> Vec<&str> {
+    sentences
+        .iter()
+        .map(|sentence| sentence.text.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_reference() {
+        let cell = |row, column| CellPosition {
+            sheet: "Sheet1".to_string(),
+            row,
+            column,
+        };
+        assert_eq!(cell(3, Some(2)).reference(), "Sheet1.B3");
+        assert_eq!(cell(1, Some(28)).reference(), "Sheet1.AB1");
+        assert_eq!(cell(7, Some(702)).reference(), "Sheet1.ZZ7");
+        assert_eq!(cell(4, None).reference(), "Sheet1.4");
+    }
+}
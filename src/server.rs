@@ -0,0 +1,487 @@
+use crate::error::{Result, SentencerError};
+use crate::export::exported_sentences;
+use crate::normalize::normalize_whitespace;
+use crate::sentence::Sentence;
+use crate::stats::DocumentStats;
+use crate::ui::ReaderEvent;
+use crate::writer::{self, SaveOptions};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::fmt;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use tiny_http::{Header, Method, ReadWrite, Request, Response, Server, StatusCode};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+// Shows the sentence the reader is on, following along over /live
+const COMPANION_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>sentencer</title>
+<style>
+  body { font: 1.6em/1.5 Georgia, serif; max-width: 40em; margin: 3em auto; color: #222; }
+  #position { font: 0.6em sans-serif; color: #888; }
+</style>
+</head>
+<body>
+<p id="position">Connecting…</p>
+<p id="sentence"></p>
+<script>
+  let sentences = [];
+  let position = 0;
+  const show = () => {
+    document.getElementById("position").textContent =
+      `Sentence ${position + 1} of ${sentences.length}`;
+    document.getElementById("sentence").textContent = sentences[position] ?? "";
+  };
+  const socket = new WebSocket(`ws://${location.host}/live`);
+  socket.onmessage = (event) => {
+    const message = JSON.parse(event.data);
+    if (message.type === "document") sentences = message.sentences;
+    if (message.type === "sentence") sentences[message.index - 1] = message.text;
+    if ("position" in message) position = message.position - 1;
+    show();
+  };
+  socket.onclose = () => {
+    document.getElementById("position").textContent = "Disconnected";
+  };
+</script>
+</body>
+</html>
+"#;
+
+#[derive(Debug, Deserialize)]
+struct SentenceUpdate {
+    text: String,
+}
+
+// A status code and the JSON body to answer with
+type Reply = (u16, Value);
+
+// One document behind a JSON API, so web frontends and scripts can read and
+// edit it the way the navigator does:
+//
+//   GET   /sentences       every sentence, as `export --format json` lists them
+//   GET   /sentences/{n}   sentence n, counting from 1
+//   PATCH /sentences/{n}   {"text": "..."} replaces the text of sentence n
+//   POST  /save            writes the edits into the document
+//   GET   /stats           counts of the document as edited
+//
+// Browsers following the edits connect a WebSocket to /live; see `LiveSync`.
+#[derive(Debug)]
+pub struct DocumentServer {
+    sentences: Vec<Sentence>,
+    // Text of each sentence as it reads in the source document
+    saved: Vec<String>,
+    source_path: String,
+    output_path: Option<String>,
+    save_options: SaveOptions,
+    live: LiveSync,
+}
+
+impl DocumentServer {
+    pub fn new(sentences: Vec<Sentence>, source_path: &str) -> Self {
+        let texts: Vec<String> = sentences
+            .iter()
+            .map(|sentence| sentence.text.clone())
+            .collect();
+        DocumentServer {
+            live: LiveSync::new(texts.clone()),
+            saved: texts,
+            sentences,
+            source_path: source_path.to_string(),
+            output_path: None,
+            save_options: SaveOptions::default(),
+        }
+    }
+
+    // Save edits to this file instead of the source document
+    pub fn with_output_path(mut self, output_path: Option<&str>) -> Self {
+        self.output_path = output_path.map(|path| path.to_string());
+        self
+    }
+
+    pub fn with_save_options(mut self, options: SaveOptions) -> Self {
+        self.save_options = options;
+        self
+    }
+
+    // Answers requests one at a time until the process is stopped
+    pub fn serve(&mut self, address: &str) -> Result<()> {
+        let server = Server::http(address).map_err(|e| {
+            SentencerError::InvalidArgument(format!("Could not listen on {}: {}", address, e))
+        })?;
+        for mut request in server.incoming_requests() {
+            if is_live(&request) {
+                self.live.accept(request);
+                continue;
+            }
+            let mut body = String::new();
+            let (status, reply) = match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => self.handle(request.method(), request.url(), &body),
+                Err(e) => (400, error_body(e)),
+            };
+            // A client that went away only loses its own answer
+            if let Err(e) = respond(request, status, &reply) {
+                eprintln!("Could not answer a request: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn handle(&mut self, method: &Method, url: &str, body: &str) -> Reply {
+        let path = url
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('/');
+        let segments: Vec<&str> = path.split('/').skip(1).collect();
+
+        match (method, segments.as_slice()) {
+            // Browsers ask before sending a PATCH from another origin
+            (Method::Options, _) => (204, Value::Null),
+            (Method::Get, ["sentences"]) => (200, json!(exported_sentences(&self.sentences))),
+            (Method::Get, ["sentences", number]) => match self.index(number) {
+                Ok(index) => (200, self.sentence(index)),
+                Err(reply) => reply,
+            },
+            (Method::Patch, ["sentences", number]) => match self.index(number) {
+                Ok(index) => self.update(index, body),
+                Err(reply) => reply,
+            },
+            (Method::Post, ["save"]) => self.save(),
+            (Method::Get, ["stats"]) => {
+                let edited = self.edited().count();
+                let mut stats = json!(DocumentStats::from_sentences(&self.sentences));
+                stats["edited"] = json!(edited);
+                (200, stats)
+            }
+            (_, ["sentences"] | ["sentences", _] | ["save"] | ["stats"]) => {
+                (405, error_body("method not allowed"))
+            }
+            _ => (404, error_body(format!("no such resource: {}", path))),
+        }
+    }
+
+    fn index(&self, number: &str) -> std::result::Result<usize, Reply> {
+        match number.parse::<usize>() {
+            Ok(number) if number >= 1 && number <= self.sentences.len() => Ok(number - 1),
+            _ => Err((
+                404,
+                error_body(format!(
+                    "no sentence {}; sentences are numbered 1 to {}",
+                    number,
+                    self.sentences.len()
+                )),
+            )),
+        }
+    }
+
+    fn sentence(&self, index: usize) -> Value {
+        let exported = exported_sentences(&self.sentences[index..=index]);
+        let mut sentence = json!(exported[0]);
+        sentence["index"] = json!(index + 1);
+        sentence
+    }
+
+    fn update(&mut self, index: usize, body: &str) -> Reply {
+        let text = match serde_json::from_str::<SentenceUpdate>(body) {
+            Ok(update) => normalize_whitespace(&update.text),
+            Err(e) => {
+                return (
+                    400,
+                    error_body(format!("expected {{\"text\": ...}}: {}", e)),
+                );
+            }
+        };
+        if text.is_empty() {
+            return (400, error_body("a sentence cannot be empty"));
+        }
+        self.sentences[index].text = text.clone();
+        self.live.update_sentence(index, text);
+        (200, self.sentence(index))
+    }
+
+    fn edited(&self) -> impl Iterator<Item = (usize, &Sentence)> {
+        self.sentences
+            .iter()
+            .enumerate()
+            .filter(|(index, sentence)| sentence.text != self.saved[*index])
+    }
+
+    fn save(&mut self) -> Reply {
+        let output_path = self.output_path.as_deref().unwrap_or(&self.source_path);
+        let (original, edited): (Vec<String>, Vec<String>) = self
+            .edited()
+            .map(|(index, sentence)| (self.saved[index].clone(), sentence.text.clone()))
+            .unzip();
+        if let Err(e) = writer::save_odt_with(
+            &self.source_path,
+            output_path,
+            &original,
+            &edited,
+            &self.save_options,
+        ) {
+            return (500, error_body(e));
+        }
+
+        let saved = edited.len();
+        let path = output_path.to_string();
+        if self.save_options.dry_run {
+            return (200, json!({"saved": saved, "path": path, "dry_run": true}));
+        }
+        // Saved over the source, the edits are what the document reads now
+        if self.output_path.is_none() {
+            self.saved = self
+                .sentences
+                .iter()
+                .map(|sentence| sentence.text.clone())
+                .collect();
+        }
+        (200, json!({"saved": saved, "path": path}))
+    }
+}
+
+#[derive(Default)]
+struct LiveState {
+    sentences: Vec<String>,
+    position: usize,
+    clients: Vec<WebSocket<Box<dyn ReadWrite + Send>>>,
+}
+
+// Sends the document and every change to it to the browsers connected over
+// WebSockets, as JSON messages:
+//
+//   {"type": "document", "sentences": [...], "position": n}  on connecting and after edits
+//   {"type": "position", "position": n}                      when the reader moves
+//   {"type": "sentence", "index": n, "text": "..."}          when one sentence is edited
+//
+// Positions and indices count from 1. Clients only listen; what they send is ignored.
+#[derive(Clone, Default)]
+pub struct LiveSync {
+    state: Arc<Mutex<LiveState>>,
+}
+
+impl fmt::Debug for LiveSync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LiveSync").finish_non_exhaustive()
+    }
+}
+
+impl LiveSync {
+    pub fn new(sentences: Vec<String>) -> Self {
+        let live = LiveSync::default();
+        if let Ok(mut state) = live.state.lock() {
+            state.sentences = sentences;
+        }
+        live
+    }
+
+    // Serves the companion page at / and the WebSocket at /live in the
+    // background
+    pub fn listen(&self, address: &str) -> Result<JoinHandle<()>> {
+        let server = Server::http(address).map_err(|e| {
+            SentencerError::InvalidArgument(format!("Could not listen on {}: {}", address, e))
+        })?;
+        let live = self.clone();
+        Ok(thread::spawn(move || {
+            for request in server.incoming_requests() {
+                if is_live(&request) {
+                    live.accept(request);
+                    continue;
+                }
+                let mut response = match request.url() {
+                    "/" => Response::from_string(COMPANION_PAGE),
+                    _ => Response::from_string("Not found").with_status_code(404),
+                };
+                add_headers(&mut response, &["Content-Type: text/html; charset=utf-8"]);
+                request.respond(response).ok();
+            }
+        }))
+    }
+
+    // Passes on what a navigator reports until it is gone
+    pub fn follow(&self, events: Receiver<ReaderEvent>) -> JoinHandle<()> {
+        let live = self.clone();
+        thread::spawn(move || {
+            for event in events {
+                live.update(event);
+            }
+        })
+    }
+
+    pub fn update(&self, event: ReaderEvent) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        match event {
+            ReaderEvent::Moved(position) => {
+                state.position = position;
+                state.broadcast(&json!({"type": "position", "position": position + 1}));
+            }
+            ReaderEvent::Edited(sentences, position) => {
+                state.sentences = sentences;
+                state.position = position;
+                let document = state.document();
+                state.broadcast(&document);
+            }
+        }
+    }
+
+    fn update_sentence(&self, index: usize, text: String) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let message = json!({"type": "sentence", "index": index + 1, "text": text});
+        if let Some(sentence) = state.sentences.get_mut(index) {
+            *sentence = text;
+        }
+        state.broadcast(&message);
+    }
+
+    fn accept(&self, request: Request) {
+        let key = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Sec-WebSocket-Key"))
+            .map(|header| derive_accept_key(header.value.as_bytes()));
+        let Some(accept) = key else {
+            let response = Response::from_string("Expected a WebSocket").with_status_code(400);
+            request.respond(response).ok();
+            return;
+        };
+
+        let mut response = Response::empty(StatusCode(101));
+        add_headers(
+            &mut response,
+            &[
+                "Upgrade: websocket",
+                "Connection: Upgrade",
+                &format!("Sec-WebSocket-Accept: {}", accept),
+            ],
+        );
+        let stream = request.upgrade("websocket", response);
+        let mut client = WebSocket::from_raw_socket(stream, Role::Server, None);
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if client
+            .send(Message::text(state.document().to_string()))
+            .is_ok()
+        {
+            state.clients.push(client);
+        }
+    }
+}
+
+impl LiveState {
+    fn document(&self) -> Value {
+        json!({
+            "type": "document",
+            "sentences": self.sentences,
+            "position": self.position + 1,
+        })
+    }
+
+    // Clients that cannot be written to have disconnected
+    fn broadcast(&mut self, message: &Value) {
+        let text = message.to_string();
+        self.clients
+            .retain_mut(|client| client.send(Message::text(text.clone())).is_ok());
+    }
+}
+
+fn is_live(request: &Request) -> bool {
+    request.url().split('?').next() == Some("/live")
+}
+
+fn add_headers<R: std::io::Read>(response: &mut Response<R>, headers: &[&str]) {
+    for header in headers {
+        if let Ok(header) = header.parse::<Header>() {
+            response.add_header(header);
+        }
+    }
+}
+
+fn error_body(message: impl ToString) -> Value {
+    json!({"error": message.to_string()})
+}
+
+// Any page may call the API
+fn respond(request: Request, status: u16, body: &Value) -> Result<()> {
+    let body = match body {
+        Value::Null => String::new(),
+        body => body.to_string(),
+    };
+    let mut response = Response::from_string(body).with_status_code(status);
+    add_headers(
+        &mut response,
+        &[
+            "Content-Type: application/json",
+            "Access-Control-Allow-Origin: *",
+            "Access-Control-Allow-Methods: GET, PATCH, POST",
+            "Access-Control-Allow-Headers: Content-Type",
+        ],
+    );
+    request.respond(response)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> DocumentServer {
+        let sentences = ["One two.", "Three."]
+            .into_iter()
+            .map(Sentence::new)
+            .collect();
+        DocumentServer::new(sentences, "missing.odt")
+    }
+
+    #[test]
+    fn test_read_and_edit_sentences() {
+        let mut server = server();
+
+        let (status, sentences) = server.handle(&Method::Get, "/sentences", "");
+        assert_eq!(status, 200);
+        assert_eq!(sentences[1]["text"], "Three.");
+
+        let (status, sentence) = server.handle(
+            &Method::Patch,
+            "/sentences/2",
+            r#"{"text": " Three  and four. "}"#,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(sentence["index"], 2);
+        assert_eq!(sentence["text"], "Three and four.");
+        assert_eq!(server.handle(&Method::Get, "/sentences/2/", "").1, sentence);
+
+        let (_, stats) = server.handle(&Method::Get, "/stats?x=1", "");
+        assert_eq!(
+            (stats["words"].clone(), stats["edited"].clone()),
+            (json!(5), json!(1))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut server = server();
+        assert_eq!(server.handle(&Method::Get, "/sentences/3", "").0, 404);
+        assert_eq!(server.handle(&Method::Get, "/sentences/zero", "").0, 404);
+        assert_eq!(server.handle(&Method::Patch, "/sentences/1", "text").0, 400);
+        assert_eq!(
+            server
+                .handle(&Method::Patch, "/sentences/1", r#"{"text": " "}"#)
+                .0,
+            400
+        );
+        assert_eq!(server.handle(&Method::Delete, "/sentences/1", "").0, 405);
+        assert_eq!(server.handle(&Method::Get, "/other", "").0, 404);
+        assert_eq!(server.handle(&Method::Post, "/save", "").0, 500);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::annotation::Annotation;
+use crate::error::{Result, SentencerError};
+use crate::remote;
+use crate::sentence::Sentence;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// Unsaved edits replacing a run of the sentences as parsed from the document,
+// indices start..end, with one or more sentences. Splits and joins are edits
+// like any other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionEdit {
+    pub start: usize,
+    pub end: usize,
+    // The parsed sentences joined with spaces, to tell whether the document
+    // still reads the same
+    pub original: String,
+    pub sentences: Vec<String>,
+}
+
+// Everything about a proofreading job on one document, kept in a file of its
+// own (such as project.sentencer) so it can be resumed exactly or applied to
+// the document in one go. Sentence indices count edited sentences, as in the
+// reader.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    // Absolute, so the session can be opened from any directory, unless it is a URL
+    pub document: String,
+    #[serde(default)]
+    pub position: usize,
+    #[serde(default)]
+    pub bookmarks: BTreeMap<char, usize>,
+    #[serde(default)]
+    pub annotations: BTreeMap<usize, Annotation>,
+    #[serde(default)]
+    pub edits: Vec<SessionEdit>,
+}
+
+impl Session {
+    pub fn new(document: &str) -> Result<Self> {
+        Ok(Session {
+            document: document_path(document)?,
+            ..Session::default()
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    // An existing session, which has to be for `document` when one is given,
+    // or a new one for `document`
+    pub fn open(path: &str, document: Option<&str>) -> Result<Self> {
+        if !Path::new(path).exists() {
+            return match document {
+                Some(document) => Session::new(document),
+                None => Err(SentencerError::InvalidArgument(format!(
+                    "Session '{}' does not exist yet; name the document to start it with",
+                    path
+                ))),
+            };
+        }
+
+        let session = Session::load(path)?;
+        if let Some(document) = document
+            && document_path(document)? != session.document
+        {
+            return Err(SentencerError::InvalidArgument(format!(
+                "Session '{}' is for {}, not {}",
+                path, session.document, document
+            )));
+        }
+        Ok(session)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // The original and edited text of every edit, in document order, for
+    // writing into the document or a diff. Fails when the document no
+    // longer has the sentences an edit was made to.
+    pub fn changes(&self, sentences: &[Sentence]) -> Result<(Vec<String>, Vec<String>)> {
+        let mut edits: Vec<&SessionEdit> = self.edits.iter().collect();
+        edits.sort_by_key(|edit| edit.start);
+
+        let mut original = Vec::new();
+        let mut edited = Vec::new();
+        for edit in edits {
+            let text = sentences
+                .get(edit.start..edit.end)
+                .map(|sentences| {
+                    sentences
+                        .iter()
+                        .map(|sentence| sentence.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .filter(|text| *text == edit.original)
+                .ok_or_else(|| {
+                    SentencerError::InvalidArgument(format!(
+                        "The document changed since the session was saved; sentence {} no longer reads: {}",
+                        edit.start + 1,
+                        edit.original
+                    ))
+                })?;
+            original.push(text);
+            edited.push(edit.sentences.join(" "));
+        }
+        Ok((original, edited))
+    }
+}
+
+fn document_path(document: &str) -> Result<String> {
+    if remote::is_url(document) {
+        return Ok(document.to_string());
+    }
+    Ok(fs::canonicalize(document)?.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip_and_changes() -> Result<()> {
+        let document = "test_session.txt";
+        let session_path = "test_session.sentencer";
+        fs::write(document, "One. Two. Three.")?;
+
+        let result = (|| -> Result<()> {
+            let mut session = Session::open(session_path, Some(document))?;
+            assert!(session.document.ends_with("test_session.txt"));
+            session.position = 2;
+            session.edits = vec![
+                SessionEdit {
+                    start: 2,
+                    end: 3,
+                    original: "Three.".to_string(),
+                    sentences: vec!["Three!".to_string()],
+                },
+                SessionEdit {
+                    start: 0,
+                    end: 2,
+                    original: "One. Two.".to_string(),
+                    sentences: vec!["One and two.".to_string()],
+                },
+            ];
+            session.save(session_path)?;
+
+            let loaded = Session::open(session_path, None)?;
+            assert_eq!(loaded, session);
+            assert!(Session::open(session_path, Some(session_path)).is_err());
+
+            let sentences: Vec<Sentence> = ["One.", "Two.", "Three."]
+                .into_iter()
+                .map(Sentence::new)
+                .collect();
+            let (original, edited) = loaded.changes(&sentences)?;
+            assert_eq!(original, vec!["One. Two.", "Three."]);
+            assert_eq!(edited, vec!["One and two.", "Three!"]);
+            assert!(loaded.changes(&sentences[..2]).is_err());
+            Ok(())
+        })();
+
+        fs::remove_file(document).ok();
+        fs::remove_file(session_path).ok();
+        result?;
+        assert!(Session::open(session_path, None).is_err());
+        Ok(())
+    }
+}
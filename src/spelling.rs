@@ -0,0 +1,157 @@
+use crate::error::{Result, SentencerError};
+use crate::language::Language;
+use spellbook::Dictionary;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Where distributions and macOS install Hunspell dictionaries, searched
+// after the directories in $DICPATH
+const DICTIONARY_DIRS: [&str; 4] = [
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/Library/Spelling",
+];
+
+// The dictionary used when none is configured; Japanese and Chinese have none
+pub fn default_dictionary(language: Language) -> Option<&'static str> {
+    match language {
+        Language::English => Some("en_US"),
+        Language::German => Some("de_DE"),
+        Language::Spanish => Some("es_ES"),
+        Language::Japanese | Language::Chinese => None,
+    }
+}
+
+// A misspelled word, by its byte range in the sentence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    pub range: Range<usize>,
+    pub word: String,
+}
+
+// Checks words against a Hunspell dictionary (a .aff and a .dic file)
+pub struct SpellChecker {
+    dictionary: Dictionary,
+}
+
+impl fmt::Debug for SpellChecker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpellChecker").finish_non_exhaustive()
+    }
+}
+
+impl SpellChecker {
+    pub fn new(aff: &str, dic: &str) -> Result<Self> {
+        let dictionary =
+            Dictionary::new(aff, dic).map_err(|e| SentencerError::Dictionary(e.to_string()))?;
+        Ok(SpellChecker { dictionary })
+    }
+
+    // A dictionary name such as "en_US", looked up in the usual directories,
+    // or the path of its .dic or .aff file with or without the extension
+    pub fn open(dictionary: &str) -> Result<Self> {
+        let base = find_dictionary(dictionary).ok_or_else(|| {
+            SentencerError::Dictionary(format!(
+                "'{}' not found in $DICPATH or {}",
+                dictionary,
+                DICTIONARY_DIRS.join(", ")
+            ))
+        })?;
+        let aff = read_dictionary_file(&base.with_extension("aff"))?;
+        let dic = read_dictionary_file(&base.with_extension("dic"))?;
+        SpellChecker::new(&aff, &dic)
+            .map_err(|e| SentencerError::Dictionary(format!("{}: {}", base.display(), e)))
+    }
+
+    // Numbers and words with digits in them, such as "3rd", are not checked
+    pub fn misspellings(&self, text: &str) -> Vec<Misspelling> {
+        text.unicode_word_indices()
+            .filter(|(_, word)| {
+                word.chars()
+                    .all(|c| c.is_alphabetic() || c == '\'' || c == '’')
+                    && !self.dictionary.check(word)
+            })
+            .map(|(start, word)| Misspelling {
+                range: start..start + word.len(),
+                word: word.to_string(),
+            })
+            .collect()
+    }
+
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        self.dictionary.suggest(word, &mut suggestions);
+        suggestions
+    }
+}
+
+fn find_dictionary(dictionary: &str) -> Option<PathBuf> {
+    let path = Path::new(dictionary);
+    let is_path = path.components().count() > 1
+        || matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some("aff" | "dic")
+        );
+    if is_path {
+        return Some(path.with_extension("dic"));
+    }
+
+    let search_path = env::var_os("DICPATH").unwrap_or_default();
+    env::split_paths(&search_path)
+        .chain(DICTIONARY_DIRS.iter().map(PathBuf::from))
+        .chain(dirs::home_dir().map(|home| home.join("Library").join("Spelling")))
+        .map(|dir| dir.join(format!("{}.dic", dictionary)))
+        .find(|candidate| candidate.is_file())
+}
+
+// Older dictionaries are often in ISO 8859-1 rather than UTF-8
+fn read_dictionary_file(path: &Path) -> Result<String> {
+    let bytes = fs::read(path)?;
+    Ok(String::from_utf8(bytes)
+        .unwrap_or_else(|e| e.into_bytes().iter().map(|&byte| byte as char).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "SET UTF-8\nTRY esianrtolcdugmphbyfvkwz'\n";
+    const DIC: &str = "4\nhello\nworld\nsentence\nthe\n";
+
+    #[test]
+    fn test_misspellings_and_suggestions() -> Result<()> {
+        let checker = SpellChecker::new(AFF, DIC)?;
+
+        let text = "Hello wrold, the 3rd sentense.";
+        let misspellings = checker.misspellings(text);
+        let words: Vec<&str> = misspellings
+            .iter()
+            .map(|misspelling| &text[misspelling.range.clone()])
+            .collect();
+        assert_eq!(words, vec!["wrold", "sentense"]);
+        assert_eq!(misspellings[1].word, "sentense");
+        assert!(checker.suggestions("wrold").contains(&"world".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_dictionary_by_path() -> Result<()> {
+        fs::write("test_xx_XX.aff", AFF)?;
+        fs::write("test_xx_XX.dic", DIC)?;
+
+        let checker = SpellChecker::open("test_xx_XX.dic");
+        fs::remove_file("test_xx_XX.aff").ok();
+        fs::remove_file("test_xx_XX.dic").ok();
+        assert!(checker?.misspellings("Hello world.").is_empty());
+        assert!(matches!(
+            SpellChecker::open("no_such_dictionary"),
+            Err(SentencerError::Dictionary(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,990 @@
+use crate::abbreviations::Abbreviations;
+use crate::error::{Result, SentencerError};
+use crate::language::Language;
+use crate::normalize::{is_invisible, normalize_whitespace};
+use crate::srx::SrxSplitter;
+use regex::Regex;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Splits the text of one paragraph into sentences. Implementations are shared
+// between the threads that parse documents in parallel.
+pub trait SentenceSplitter: fmt::Debug + Send + Sync {
+    fn split(&self, text: &str) -> Vec<String>;
+}
+
+#[derive(Debug, Clone)]
+pub enum Splitter {
+    Regex(RegexSplitter),
+    Unicode(UnicodeSplitter),
+    Rules(RuleSplitter),
+    Srx(SrxSplitter),
+    Custom(Arc<dyn SentenceSplitter>),
+    Adjusted(Box<Splitter>, SplitOptions),
+}
+
+// Adjustments made to the sentences of any splitter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitOptions {
+    // Sentences with fewer characters are joined with the next one, or with
+    // the one before at the end of the text
+    pub min_length: usize,
+    pub keep_punctuation: bool,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        SplitOptions {
+            min_length: 0,
+            keep_punctuation: true,
+        }
+    }
+}
+
+impl SplitOptions {
+    fn apply(&self, sentences: Vec<String>) -> Vec<String> {
+        let is_short = |sentence: &str| sentence.chars().count() < self.min_length;
+
+        let mut adjusted: Vec<String> = Vec::new();
+        for sentence in sentences {
+            match adjusted.last_mut() {
+                Some(last) if is_short(last) => {
+                    last.push(' ');
+                    last.push_str(&sentence);
+                }
+                _ => adjusted.push(sentence),
+            }
+        }
+        if adjusted.len() > 1 && adjusted.last().is_some_and(|last| is_short(last)) {
+            let last = adjusted.pop().unwrap_or_default();
+            if let Some(previous) = adjusted.last_mut() {
+                previous.push(' ');
+                previous.push_str(&last);
+            }
+        }
+
+        if !self.keep_punctuation {
+            for sentence in &mut adjusted {
+                let stripped = sentence.trim_end_matches(['.', '!', '?', '。', '｡', '！', '？']);
+                if !stripped.is_empty() {
+                    sentence.truncate(stripped.len());
+                }
+            }
+        }
+        adjusted
+    }
+}
+
+impl Splitter {
+    pub fn regex() -> Result<Self> {
+        Ok(Splitter::Regex(RegexSplitter::new()?))
+    }
+
+    pub fn unicode() -> Self {
+        Splitter::Unicode(UnicodeSplitter)
+    }
+
+    pub fn rules(abbreviations: Abbreviations) -> Result<Self> {
+        Ok(Splitter::Rules(RuleSplitter::new(abbreviations)?))
+    }
+
+    pub fn srx(file_path: &str) -> Result<Self> {
+        Ok(Splitter::Srx(SrxSplitter::from_file(file_path)?))
+    }
+
+    // A splitter from outside this crate, usable wherever the built-in ones are
+    pub fn custom(splitter: impl SentenceSplitter + 'static) -> Self {
+        Splitter::Custom(Arc::new(splitter))
+    }
+
+    pub fn with_options(self, options: SplitOptions) -> Self {
+        match (self, options == SplitOptions::default()) {
+            (splitter, true) => splitter,
+            (Splitter::Adjusted(splitter, _), false) => Splitter::Adjusted(splitter, options),
+            (splitter, false) => Splitter::Adjusted(Box::new(splitter), options),
+        }
+    }
+
+    // Makes the regex and rules splitters end sentences at matches of
+    // `pattern` instead; the matched text stays with the sentence before
+    pub fn with_boundary(self, pattern: &str) -> Result<Self> {
+        match self {
+            Splitter::Regex(_) => Ok(Splitter::Regex(RegexSplitter::with_boundary(pattern)?)),
+            Splitter::Rules(mut splitter) => {
+                splitter.base = RegexSplitter::with_boundary(pattern)?;
+                Ok(Splitter::Rules(splitter))
+            }
+            Splitter::Adjusted(splitter, options) => Ok(Splitter::Adjusted(
+                Box::new(splitter.with_boundary(pattern)?),
+                options,
+            )),
+            _ => Err(SentencerError::InvalidArgument(
+                "A boundary regex can only be used with the regex and rules splitters".to_string(),
+            )),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "regex" => Splitter::regex(),
+            "unicode" | "uax29" => Ok(Splitter::unicode()),
+            "rules" => Splitter::rules(Abbreviations::english()),
+            _ => Err(SentencerError::InvalidArgument(format!(
+                "Unknown splitter '{}' (expected regex, unicode or rules)",
+                name
+            ))),
+        }
+    }
+
+    // Only the rules and SRX splitters have language-specific rules
+    pub fn with_language(self, language: Option<Language>) -> Self {
+        match self {
+            Splitter::Rules(splitter) => Splitter::Rules(splitter.with_language(language)),
+            Splitter::Srx(splitter) => Splitter::Srx(splitter.with_language(language)),
+            Splitter::Adjusted(splitter, options) => {
+                Splitter::Adjusted(Box::new(splitter.with_language(language)), options)
+            }
+            splitter => splitter,
+        }
+    }
+
+    pub fn has_language_rules(&self) -> bool {
+        match self {
+            Splitter::Rules(_) | Splitter::Srx(_) => true,
+            Splitter::Adjusted(splitter, _) => splitter.has_language_rules(),
+            _ => false,
+        }
+    }
+}
+
+impl SentenceSplitter for Splitter {
+    fn split(&self, text: &str) -> Vec<String> {
+        match self {
+            Splitter::Regex(splitter) => splitter.split(text),
+            Splitter::Unicode(splitter) => splitter.split(text),
+            Splitter::Rules(splitter) => splitter.split(text),
+            Splitter::Srx(splitter) => splitter.split(text),
+            Splitter::Custom(splitter) => splitter.split(text),
+            Splitter::Adjusted(splitter, options) => options.apply(splitter.split(text)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RegexSplitter {
+    sentence_regex: Regex,
+}
+
+impl RegexSplitter {
+    pub fn new() -> Result<Self> {
+        // Full-width terminators used in Chinese and Japanese need no trailing
+        // space, and closing quotes and brackets after any terminator stay
+        // with the sentence
+        RegexSplitter::with_boundary(r#"[.!?]+["'”’»)\]}]*\s+|[。｡！？]+[」』）〕】]*\s*"#)
+    }
+
+    pub fn with_boundary(pattern: &str) -> Result<Self> {
+        Ok(RegexSplitter {
+            sentence_regex: Regex::new(pattern)?,
+        })
+    }
+
+    // Each sentence with the bytes of `text` it was taken from. The text
+    // between boundaries forms a sentence, which keeps the terminators of the
+    // boundary but not the whitespace before them.
+    pub fn split_ranges(&self, text: &str) -> Vec<(String, Range<usize>)> {
+        let (cleaned, positions) = clean(text);
+
+        let mut sentences = Vec::new();
+        let mut start = 0;
+        let boundaries = self
+            .sentence_regex
+            .find_iter(&cleaned)
+            .map(|boundary| boundary.range())
+            .chain(std::iter::once(cleaned.len()..cleaned.len()));
+        for boundary in boundaries {
+            let part = &cleaned[start..boundary.start];
+            let trimmed = part.trim();
+            if !trimmed.is_empty() {
+                let matched = &cleaned[boundary.clone()];
+                let terminator = matched.trim();
+                let first = start + part.len() - part.trim_start().len();
+                let last = match terminator.is_empty() {
+                    true => first + trimmed.len(),
+                    false => {
+                        boundary.start + matched.len() - matched.trim_start().len()
+                            + terminator.len()
+                    }
+                };
+                sentences.push((
+                    restore(format!("{}{}", trimmed, terminator)),
+                    positions[first]..positions[last],
+                ));
+            }
+            start = boundary.end;
+        }
+
+        join_nested_ranges(sentences)
+    }
+}
+
+// The text as the boundaries are looked for in: protected, trimmed and with
+// every run of whitespace as one space. Along with it, the position in `text`
+// of each of its bytes and of its end; a space stands where its run starts.
+fn clean(text: &str) -> (String, Vec<usize>) {
+    let protected = protect(text);
+    let mut cleaned = String::with_capacity(protected.len());
+    let mut positions = Vec::with_capacity(protected.len() + 1);
+    let mut space = None;
+    let mut end = 0;
+
+    // Protection swaps characters one for one, so they line up with `text`
+    for ((position, original), c) in text.char_indices().zip(protected.chars()) {
+        if is_invisible(c) {
+            continue;
+        }
+        if c.is_whitespace() {
+            space = space.or(Some(position));
+            continue;
+        }
+        if let (Some(space), false) = (space.take(), cleaned.is_empty()) {
+            cleaned.push(' ');
+            positions.push(space);
+        }
+        cleaned.push(c);
+        positions.extend(std::iter::repeat_n(position, c.len_utf8()));
+        end = position + original.len_utf8();
+    }
+    positions.push(end);
+    (cleaned, positions)
+}
+
+impl SentenceSplitter for RegexSplitter {
+    fn split(&self, text: &str) -> Vec<String> {
+        self.split_ranges(text)
+            .into_iter()
+            .map(|(sentence, _)| sentence)
+            .collect()
+    }
+}
+
+// Sentence boundaries as defined by Unicode UAX #29, which also covers
+// terminators such as '。' that are not followed by whitespace
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnicodeSplitter;
+
+impl SentenceSplitter for UnicodeSplitter {
+    fn split(&self, text: &str) -> Vec<String> {
+        let text = protect(text);
+        let cleaned_text = normalize_whitespace(&text);
+
+        let sentences = cleaned_text
+            .unicode_sentences()
+            .map(|sentence| sentence.trim())
+            .filter(|sentence| !sentence.is_empty())
+            .map(|sentence| restore(sentence.to_string()))
+            .collect();
+
+        join_nested(sentences)
+    }
+}
+
+// Regex splitting that re-joins fragments whose final period belongs to an
+// abbreviation, so "e.g." or "Mr." never end a sentence on their own. When the
+// language is known its own boundary rules are applied as well.
+#[derive(Debug, Clone)]
+pub struct RuleSplitter {
+    base: RegexSplitter,
+    abbreviations: Abbreviations,
+    language: Option<Language>,
+}
+
+impl RuleSplitter {
+    pub fn new(abbreviations: Abbreviations) -> Result<Self> {
+        Ok(RuleSplitter {
+            base: RegexSplitter::new()?,
+            abbreviations,
+            language: None,
+        })
+    }
+
+    pub fn with_language(mut self, language: Option<Language>) -> Self {
+        self.language = language;
+        self
+    }
+
+    pub fn abbreviations(&self) -> &Abbreviations {
+        &self.abbreviations
+    }
+
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    fn continues_sentence(&self, previous: &str, next: &str) -> bool {
+        match self.language {
+            // Ordinal numbers take a period, as in "am 3. Oktober"
+            Some(Language::German) => previous
+                .rsplit(' ')
+                .next()
+                .and_then(|word| word.strip_suffix('.'))
+                .is_some_and(|number| {
+                    (1..=2).contains(&number.len()) && number.chars().all(|c| c.is_ascii_digit())
+                }),
+            // A question or exclamation can be followed by the rest of the
+            // sentence, as in "¿Vienes? preguntó ella."
+            Some(Language::Spanish) => {
+                previous.ends_with(['?', '!'])
+                    && next.chars().next().is_some_and(|c| c.is_lowercase())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl SentenceSplitter for RuleSplitter {
+    fn split(&self, text: &str) -> Vec<String> {
+        let mut sentences: Vec<String> = Vec::new();
+
+        for part in self.base.split(text) {
+            match sentences.last_mut() {
+                Some(last)
+                    if self.abbreviations.ends_with_abbreviation(last, &part)
+                        || self.continues_sentence(last, &part) =>
+                {
+                    last.push(' ');
+                    last.push_str(&part);
+                }
+                _ => sentences.push(part),
+            }
+        }
+
+        sentences
+    }
+}
+
+// Characters that stand in for terminators which cannot end a sentence while
+// the text is split, from Unicode's private use area
+const MASKS: [(char, char); 3] = [('.', '\u{E000}'), ('?', '\u{E001}'), ('!', '\u{E002}')];
+const OPENING: &[char] = &['(', '[', '{', '"', '\'', '<', '«', '“', '‘'];
+const CLOSING: &[char] = &[
+    '.', ',', ';', ':', '!', '?', ')', ']', '}', '"', '\'', '>', '»', '”', '’',
+];
+
+// Masks the periods inside numbers, file names and domains ("2.5",
+// "notes.txt"), every terminator inside URLs, email addresses and paths, and
+// those inside citations, so they are never taken for sentence boundaries.
+// Punctuation around a word is left alone, as it may still end the sentence.
+pub(crate) fn protect(text: &str) -> String {
+    // Text that already holds the stand-ins could not be restored faithfully
+    if text.contains(|c| MASKS.iter().any(|(_, mask)| *mask == c)) {
+        return text.to_string();
+    }
+
+    // Citations are masked first, as their periods would otherwise be taken
+    // for punctuation around a word
+    let text = &mask_citations(text);
+    let mut protected = String::with_capacity(text.len());
+    for token in text.split_inclusive(char::is_whitespace) {
+        let start = token
+            .find(|c: char| !OPENING.contains(&c))
+            .unwrap_or(token.len());
+        let end = token
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !c.is_whitespace() && !CLOSING.contains(c))
+            .map_or(start, |(i, c)| i + c.len_utf8())
+            .max(start);
+        let word: Vec<char> = token[start..end].chars().collect();
+        let link = token[start..end].contains("://")
+            || token[start..end].starts_with("www.")
+            || word.iter().any(|c| matches!(c, '@' | '/' | '\\'));
+
+        protected.push_str(&token[..start]);
+        for (i, &c) in word.iter().enumerate() {
+            let between_alphanumerics = i > 0
+                && i + 1 < word.len()
+                && word[i - 1].is_alphanumeric()
+                && word[i + 1].is_alphanumeric();
+            let mask = MASKS
+                .iter()
+                .find(|(terminator, _)| *terminator == c)
+                .filter(|(terminator, _)| link || (*terminator == '.' && between_alphanumerics));
+            protected.push(mask.map_or(c, |(_, mask)| *mask));
+        }
+        protected.push_str(&token[end..]);
+    }
+    protected
+}
+
+// Longest parenthetical, in bytes, that is looked at as a citation
+const MAX_CITATION_LENGTH: usize = 300;
+
+// Masks the terminators inside parenthetical citations, as in "(Smith et al.
+// 2020, p. 14)", and the period of an "et al." right before one, as in "Smith
+// et al. (2020)". A terminator just before the closing parenthesis is left
+// alone, since a whole sentence in parentheses ends there.
+fn mask_citations(text: &str) -> String {
+    let mut positions = Vec::new();
+    let mut open = None;
+
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => open = Some(i),
+            ')' => {
+                let Some(start) = open.take() else {
+                    continue;
+                };
+                let inside = &text[start + 1..i];
+                if inside.len() > MAX_CITATION_LENGTH {
+                    continue;
+                }
+                if is_citation(inside) {
+                    let body = inside.trim_end().trim_end_matches(['.', '!', '?']);
+                    positions.extend(
+                        body.match_indices(['.', '!', '?'])
+                            .map(|(j, _)| start + 1 + j),
+                    );
+                }
+                // The authors of a narrative citation come before the year
+                let before = text[..start].trim_end();
+                if before.ends_with("et al.") && is_dated(inside) {
+                    positions.push(before.len() - 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    if positions.is_empty() {
+        return text.to_string();
+    }
+
+    text.char_indices()
+        .map(|(i, c)| {
+            MASKS
+                .iter()
+                .find(|(terminator, _)| *terminator == c && positions.contains(&i))
+                .map_or(c, |(_, mask)| *mask)
+        })
+        .collect()
+}
+
+// A year along with a capitalized name, or a reference back such as
+// "ibid., p. 3"
+fn is_citation(inside: &str) -> bool {
+    let named = inside
+        .split_whitespace()
+        .any(|word| word.starts_with(char::is_uppercase));
+
+    (is_dated(inside) && named) || inside.trim_start().to_lowercase().starts_with("ibid.")
+}
+
+// A year such as "2020" or "2020a", or "n.d." for an undated work
+fn is_dated(inside: &str) -> bool {
+    inside.contains("n.d.")
+        || inside.split(|c: char| !c.is_alphanumeric()).any(|word| {
+            let year = word
+                .strip_suffix(|c: char| c.is_ascii_lowercase())
+                .unwrap_or(word);
+            year.len() == 4
+                && year
+                    .parse::<u32>()
+                    .is_ok_and(|year| (1500..2100).contains(&year))
+        })
+}
+
+// Quotes and brackets a sentence does not end inside of
+const PAIRS: [(char, char); 8] = [
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('“', '”'),
+    ('‘', '’'),
+    ('«', '»'),
+    ('"', '"'),
+    ('\'', '\''),
+];
+// How many split parts an unclosed quote or bracket may join, so that a stray
+// one does not swallow the rest of the text
+const MAX_NESTED_PARTS: usize = 4;
+
+// Joins parts that were split inside quotes or brackets, and parts that
+// continue after a quoted sentence, as in "\"Is it over?\" she asked."
+fn join_nested(parts: Vec<String>) -> Vec<String> {
+    let parts = parts.into_iter().map(|part| (part, 0..0)).collect();
+    join_nested_ranges(parts)
+        .into_iter()
+        .map(|(sentence, _)| sentence)
+        .collect()
+}
+
+// As join_nested, for parts with the range of the text they were taken from
+fn join_nested_ranges(parts: Vec<(String, Range<usize>)>) -> Vec<(String, Range<usize>)> {
+    let mut sentences: Vec<(String, Range<usize>)> = Vec::new();
+    let mut joined = 0;
+
+    for (part, range) in parts {
+        match sentences.last_mut() {
+            Some((last, last_range))
+                if joined < MAX_NESTED_PARTS
+                    && (open_quotes(last) > 0 || continues_after_quote(last, &part)) =>
+            {
+                last.push(' ');
+                last.push_str(&part);
+                last_range.end = range.end;
+                joined += 1;
+            }
+            _ => {
+                sentences.push((part, range));
+                joined = 1;
+            }
+        }
+    }
+
+    sentences
+}
+
+// The number of quotes and brackets left open at the end of the text.
+// Apostrophes within or after a word ("don't", "the dogs' bowls") are not
+// taken for quotes.
+fn open_quotes(text: &str) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let mut expected: Vec<char> = Vec::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        let before = i.checked_sub(1).map(|j| chars[j]);
+        let after = chars.get(i + 1).copied();
+        let apostrophe = matches!(c, '\'' | '’');
+
+        if expected.last() == Some(&c) && !(apostrophe && after.is_some_and(char::is_alphanumeric))
+        {
+            expected.pop();
+            continue;
+        }
+        if apostrophe && before.is_some_and(char::is_alphanumeric) {
+            continue;
+        }
+        if let Some((_, closer)) = PAIRS.iter().find(|(opener, _)| *opener == c) {
+            expected.push(*closer);
+        }
+    }
+
+    expected.len()
+}
+
+// A quoted sentence ending in its own terminator, followed by text that
+// starts in lowercase
+fn continues_after_quote(previous: &str, next: &str) -> bool {
+    let quote_closed = previous.ends_with(|c| PAIRS.iter().any(|(_, closer)| *closer == c));
+    let unquoted = previous.trim_end_matches(|c| PAIRS.iter().any(|(_, closer)| *closer == c));
+
+    quote_closed
+        && unquoted.ends_with(['.', '!', '?'])
+        && next.chars().next().is_some_and(char::is_lowercase)
+}
+
+pub(crate) fn restore(sentence: String) -> String {
+    if !sentence.contains(|c| MASKS.iter().any(|(_, mask)| *mask == c)) {
+        return sentence;
+    }
+    sentence
+        .chars()
+        .map(|c| {
+            MASKS
+                .iter()
+                .find(|(_, mask)| *mask == c)
+                .map_or(c, |(terminator, _)| *terminator)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_splitting() {
+        let splitter = RegexSplitter::new().unwrap();
+        let text = "This is sentence one. This is sentence two! And this is sentence three?";
+        let sentences = splitter.split(text);
+
+        assert_eq!(sentences.len(), 3);
+        assert_eq!(sentences[0], "This is sentence one.");
+        assert_eq!(sentences[1], "This is sentence two!");
+        assert_eq!(sentences[2], "And this is sentence three?");
+    }
+
+    #[test]
+    fn test_empty_text() {
+        let splitter = RegexSplitter::new().unwrap();
+        let sentences = splitter.split("");
+        assert_eq!(sentences.len(), 0);
+    }
+
+    #[test]
+    fn test_complex_punctuation() {
+        let splitter = RegexSplitter::new().unwrap();
+        let text = "What?! Really... Yes. No doubt about it!!! Absolutely.";
+        let sentences = splitter.split(text);
+
+        println!("Complex punctuation sentences:");
+        for (i, sentence) in sentences.iter().enumerate() {
+            println!("  {}: {}", i + 1, sentence);
+        }
+
+        assert!(sentences.len() >= 3, "Should handle complex punctuation");
+    }
+
+    #[test]
+    fn test_full_width_terminators() {
+        let splitter = RegexSplitter::new().unwrap();
+        let sentences =
+            splitter.split("今日は晴れです。「本当？」明日は雨です！ Then English. 最後。");
+
+        assert_eq!(
+            sentences,
+            vec![
+                "今日は晴れです。",
+                "「本当？」",
+                "明日は雨です！",
+                "Then English.",
+                "最後。"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unicode_splitting() {
+        let splitter = Splitter::unicode();
+        let sentences = splitter.split("This is one.  This is two! 今日は晴れです。明日は雨です。");
+
+        assert_eq!(
+            sentences,
+            vec![
+                "This is one.",
+                "This is two!",
+                "今日は晴れです。",
+                "明日は雨です。"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_splitter_from_name() {
+        assert!(matches!(
+            Splitter::from_name("regex"),
+            Ok(Splitter::Regex(_))
+        ));
+        assert!(matches!(
+            Splitter::from_name("unicode"),
+            Ok(Splitter::Unicode(_))
+        ));
+        assert!(Splitter::from_name("bogus").is_err());
+    }
+
+    #[test]
+    fn test_protected_terminators() -> Result<()> {
+        let text = "Read version 2.5 of the spec. Visit example.com. Then mail bob@mail.co.uk! \
+                    See https://example.com/a.b?x=1. Open ~/notes.v2.txt?! Done.";
+        let expected = vec![
+            "Read version 2.5 of the spec.",
+            "Visit example.com.",
+            "Then mail bob@mail.co.uk!",
+            "See https://example.com/a.b?x=1.",
+            "Open ~/notes.v2.txt?!",
+            "Done.",
+        ];
+
+        assert_eq!(Splitter::regex()?.split(text), expected);
+        assert_eq!(Splitter::unicode().split(text), expected);
+        assert_eq!(
+            Splitter::rules(Abbreviations::english())?.split(text),
+            expected
+        );
+        assert_eq!(
+            restore(protect("Costs $3.50 (see www.x.org/?a=b).")),
+            "Costs $3.50 (see www.x.org/?a=b)."
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_quotes_and_brackets() -> Result<()> {
+        let text = "He said 'stop!' and left. \"Is it over?\" she asked. He shouted \"Run!\" \
+                    Then he ran. (This is an aside.) Next one. She wrote (see fig. 4!) and \
+                    so on. Don't worry about the dogs' bowls. Done.";
+        let expected = vec![
+            "He said 'stop!' and left.",
+            "\"Is it over?\" she asked.",
+            "He shouted \"Run!\"",
+            "Then he ran.",
+            "(This is an aside.)",
+            "Next one.",
+            "She wrote (see fig. 4!) and so on.",
+            "Don't worry about the dogs' bowls.",
+            "Done.",
+        ];
+
+        assert_eq!(Splitter::regex()?.split(text), expected);
+        assert_eq!(Splitter::unicode().split(text), expected);
+        assert_eq!(
+            Splitter::rules(Abbreviations::english())?.split(text),
+            expected
+        );
+
+        // A stray quote joins only a few sentences
+        let stray = Splitter::regex()?.split("A \"b. C. D. E. F. G.");
+        assert_eq!(stray, vec!["A \"b. C. D. E.", "F.", "G."]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_citations() -> Result<()> {
+        let text = "Whales sing (Smith et al. 2020, pp. 14-20; Jones 2019, p. 3). \
+                    Payne et al. (1971) agreed. So did others (ibid., p. 9). \
+                    (This was new in 1971.) Done.";
+        let expected = vec![
+            "Whales sing (Smith et al. 2020, pp. 14-20; Jones 2019, p. 3).",
+            "Payne et al. (1971) agreed.",
+            "So did others (ibid., p. 9).",
+            "(This was new in 1971.)",
+            "Done.",
+        ];
+
+        assert_eq!(Splitter::regex()?.split(text), expected);
+        assert_eq!(Splitter::unicode().split(text), expected);
+        assert_eq!(
+            Splitter::rules(Abbreviations::english())?.split(text),
+            expected
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_options() -> Result<()> {
+        let splitter = Splitter::regex()?.with_options(SplitOptions {
+            min_length: 8,
+            keep_punctuation: false,
+        });
+        assert_eq!(
+            splitter.split("Hi. Yes! This is long enough. Sure? Ok."),
+            vec!["Hi. Yes", "This is long enough", "Sure? Ok"]
+        );
+
+        let splitter = Splitter::rules(Abbreviations::english())?.with_boundary(r"[.;]\s+")?;
+        assert_eq!(
+            splitter.split("Dr. Who came; he left. Why? No."),
+            vec!["Dr. Who came;", "he left.", "Why? No."]
+        );
+        assert!(Splitter::unicode().with_boundary(r"\.").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_splitter() {
+        // One sentence per line, whatever the punctuation
+        #[derive(Debug)]
+        struct LineSplitter;
+
+        impl SentenceSplitter for LineSplitter {
+            fn split(&self, text: &str) -> Vec<String> {
+                text.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            }
+        }
+
+        let splitter = Splitter::custom(LineSplitter).with_language(Some(Language::German));
+        assert_eq!(
+            splitter.split("First line. Still first\nSecond line"),
+            vec!["First line. Still first", "Second line"]
+        );
+    }
+
+    #[test]
+    fn test_rule_splitting_keeps_abbreviations() -> Result<()> {
+        let splitter = Splitter::rules(Abbreviations::english())?;
+        let text =
+            "Mr. Smith met Dr. Jones, e.g. at noon. The Hobbit is by J. R. R. Tolkien. Done!";
+        let sentences = splitter.split(text);
+
+        assert_eq!(
+            sentences,
+            vec![
+                "Mr. Smith met Dr. Jones, e.g. at noon.",
+                "The Hobbit is by J. R. R. Tolkien.",
+                "Done!"
+            ]
+        );
+        assert_eq!(
+            splitter.split(
+                "She said no. He left anyway. We chose plan B. Then came Dec. 25, etc. Done."
+            ),
+            vec![
+                "She said no.",
+                "He left anyway.",
+                "We chose plan B.",
+                "Then came Dec. 25, etc.",
+                "Done."
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_language_rules() -> Result<()> {
+        let splitter = |language| {
+            Splitter::rules(Abbreviations::english()).map(|s| s.with_language(Some(language)))
+        };
+
+        assert_eq!(
+            splitter(Language::German)?.split("Am 3. Oktober ist Feiertag. Wir feiern."),
+            vec!["Am 3. Oktober ist Feiertag.", "Wir feiern."]
+        );
+        assert_eq!(
+            splitter(Language::Spanish)?.split("¿Vienes? preguntó ella. ¡Sí! Ahora voy."),
+            vec!["¿Vienes? preguntó ella.", "¡Sí!", "Ahora voy."]
+        );
+        assert_eq!(
+            splitter(Language::Japanese)?.split("今日は晴れです。「本当？」明日は雨です。"),
+            vec!["今日は晴れです。", "「本当？」", "明日は雨です。"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_ranges() -> Result<()> {
+        let splitter = RegexSplitter::new()?;
+        let text = "  First  one .\n\n\"Is it 2.5?\" she asked. Last";
+        let sentences = splitter.split_ranges(text);
+
+        let found: Vec<_> = sentences
+            .iter()
+            .map(|(sentence, range)| (sentence.as_str(), &text[range.clone()]))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("First one.", "First  one ."),
+                ("\"Is it 2.5?\" she asked.", "\"Is it 2.5?\" she asked."),
+                ("Last", "Last"),
+            ]
+        );
+        Ok(())
+    }
+
+    // The splitting as it was before it worked with ranges, which recovered
+    // the terminators by the position of each part in the cleaned text
+    fn split_as_before(splitter: &RegexSplitter, text: &str) -> Vec<String> {
+        let text = protect(text);
+        let cleaned_text = text.trim().replace('\n', " ");
+        let cleaned_text = Regex::new(r"\s+")
+            .map(|whitespace| whitespace.replace_all(&cleaned_text, " ").into_owned())
+            .unwrap_or_default();
+
+        let parts: Vec<&str> = splitter.sentence_regex.split(&cleaned_text).collect();
+        let mut sentences = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            let trimmed = part.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let next_start = part.as_ptr() as usize + part.len() - cleaned_text.as_ptr() as usize;
+            match splitter.sentence_regex.find_at(&cleaned_text, next_start) {
+                Some(punct) if i < parts.len() - 1 => {
+                    sentences.push(format!("{}{}", trimmed, punct.as_str().trim()))
+                }
+                _ => sentences.push(trimmed.to_string()),
+            }
+        }
+        join_nested(sentences.into_iter().map(restore).collect())
+    }
+
+    #[test]
+    fn test_split_matches_previous_splitting() -> Result<()> {
+        let fragments = [
+            "word",
+            "Mr.",
+            "e.g.",
+            "2.5",
+            "notes.txt",
+            "https://example.com/a.b?c=d",
+            ".",
+            "!",
+            "?",
+            "...",
+            "?!",
+            "\"",
+            "'",
+            "(",
+            ")",
+            "“",
+            "”",
+            "。",
+            "！",
+            "」",
+            "今日",
+            " ",
+            "  ",
+            "\n",
+            "\n\n",
+            "\t",
+            "don't",
+            "«",
+            "»",
+            "Über",
+            "ça",
+        ];
+        let splitters = [
+            RegexSplitter::new()?,
+            RegexSplitter::with_boundary(r";\s*")?,
+            RegexSplitter::with_boundary(r"\s+-\s+")?,
+        ];
+
+        // A fixed xorshift sequence, so failures can be reproduced
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+        for _ in 0..500 {
+            let length = next(24);
+            let text: String = (0..length)
+                .map(|_| fragments[next(fragments.len())])
+                .collect::<Vec<_>>()
+                .join(match next(3) {
+                    0 => "",
+                    _ => " ",
+                });
+            for splitter in &splitters {
+                let sentences = splitter.split_ranges(&text);
+                assert_eq!(
+                    sentences
+                        .iter()
+                        .map(|(sentence, _)| sentence.clone())
+                        .collect::<Vec<_>>(),
+                    split_as_before(splitter, &text),
+                    "splitting {:?}",
+                    text
+                );
+                // Each range starts and ends with its sentence and holds all of
+                // it, but for whitespace and the terminators of boundaries
+                // that end no sentence, which are dropped
+                for (sentence, range) in &sentences {
+                    let taken = text[range.clone()].trim();
+                    let mut chars = taken.chars();
+                    assert!(sentence.chars().all(|c| c == ' ' || chars.any(|t| t == c)));
+                    assert_eq!(taken.chars().next(), sentence.chars().next());
+                    assert_eq!(taken.chars().last(), sentence.chars().last());
+                }
+            }
+        }
+        Ok(())
+    }
+}
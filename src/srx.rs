@@ -0,0 +1,289 @@
+use crate::error::{Result, SentencerError};
+use crate::language::Language;
+use crate::normalize::normalize_whitespace;
+use crate::splitter::{SentenceSplitter, protect, restore};
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use regex::Regex;
+use std::fs;
+
+#[derive(Debug, Clone)]
+struct SrxRule {
+    breaks: bool,
+    // Anchored at the end and the start of the text around a position
+    before: Regex,
+    after: Regex,
+}
+
+#[derive(Debug, Clone)]
+struct LanguageMap {
+    pattern: Regex,
+    rule_name: String,
+}
+
+// Segmentation rules in the SRX 2.0 format that translation tools exchange.
+// At each position the rules of the language are tried in order and the
+// first whose beforebreak and afterbreak patterns match decides whether the
+// text breaks there; positions no rule matches do not break.
+#[derive(Debug, Clone)]
+pub struct SrxSplitter {
+    cascade: bool,
+    language_rules: Vec<(String, Vec<SrxRule>)>,
+    maps: Vec<LanguageMap>,
+    language: Option<Language>,
+    rules: Vec<SrxRule>,
+}
+
+impl SrxSplitter {
+    pub fn from_file(file_path: &str) -> Result<Self> {
+        SrxSplitter::parse(&fs::read_to_string(file_path)?)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut reader = Reader::from_str(content);
+        let mut cascade = false;
+        let mut language_rules: Vec<(String, Vec<SrxRule>)> = Vec::new();
+        let mut maps = Vec::new();
+
+        let mut breaks = true;
+        let mut before = String::new();
+        let mut after = String::new();
+        let mut reading: Option<bool> = None;
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                    b"header" => cascade = attribute(&e, b"cascade")?.as_deref() == Some("yes"),
+                    b"languagerule" => {
+                        let name = attribute(&e, b"languagerulename")?.unwrap_or_default();
+                        language_rules.push((name, Vec::new()));
+                    }
+                    b"rule" => {
+                        breaks = attribute(&e, b"break")?.as_deref() != Some("no");
+                        before.clear();
+                        after.clear();
+                    }
+                    b"beforebreak" => reading = Some(true),
+                    b"afterbreak" => reading = Some(false),
+                    b"languagemap" => {
+                        let pattern = attribute(&e, b"languagepattern")?.unwrap_or_default();
+                        maps.push(LanguageMap {
+                            pattern: Regex::new(&format!("(?i)^(?:{})$", pattern))?,
+                            rule_name: attribute(&e, b"languagerulename")?.unwrap_or_default(),
+                        });
+                    }
+                    _ => {}
+                },
+                Event::Text(e) => match reading {
+                    Some(true) => before.push_str(&e.unescape()?),
+                    Some(false) => after.push_str(&e.unescape()?),
+                    None => {}
+                },
+                Event::CData(e) => {
+                    let text = std::str::from_utf8(&e)?;
+                    match reading {
+                        Some(true) => before.push_str(text),
+                        Some(false) => after.push_str(text),
+                        None => {}
+                    }
+                }
+                Event::End(e) => match e.local_name().as_ref() {
+                    b"beforebreak" | b"afterbreak" => reading = None,
+                    b"rule" => {
+                        let Some((_, rules)) = language_rules.last_mut() else {
+                            return Err(invalid("rule outside of a languagerule"));
+                        };
+                        rules.push(SrxRule {
+                            breaks,
+                            before: Regex::new(&format!("(?:{})$", before))?,
+                            after: Regex::new(&format!("^(?:{})", after))?,
+                        });
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        if language_rules.is_empty() {
+            return Err(invalid("no language rules"));
+        }
+        if let Some(map) = maps.iter().find(|map| {
+            !language_rules
+                .iter()
+                .any(|(name, _)| *name == map.rule_name)
+        }) {
+            return Err(invalid(&format!(
+                "unknown language rule '{}' in language map",
+                map.rule_name
+            )));
+        }
+
+        let splitter = SrxSplitter {
+            cascade,
+            language_rules,
+            maps,
+            language: None,
+            rules: Vec::new(),
+        };
+        Ok(splitter.with_language(None))
+    }
+
+    // Picks the rules whose language pattern matches the language's code; an
+    // unknown language only matches patterns such as ".*"
+    pub fn with_language(mut self, language: Option<Language>) -> Self {
+        let code = language.map_or("", |language| language.code());
+
+        self.language = language;
+        self.rules.clear();
+        for map in self.maps.iter().filter(|map| map.pattern.is_match(code)) {
+            if let Some((_, rules)) = self
+                .language_rules
+                .iter()
+                .find(|(name, _)| *name == map.rule_name)
+            {
+                self.rules.extend(rules.iter().cloned());
+            }
+            if !self.cascade {
+                break;
+            }
+        }
+        self
+    }
+
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    fn breaks_at(&self, text: &str, position: usize) -> bool {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.after.is_match(&text[position..]) && rule.before.is_match(&text[..position])
+            })
+            .is_some_and(|rule| rule.breaks)
+    }
+}
+
+impl SentenceSplitter for SrxSplitter {
+    fn split(&self, text: &str) -> Vec<String> {
+        let text = protect(text);
+        let cleaned_text = normalize_whitespace(&text);
+
+        // Only positions where a break rule matches need all rules tried
+        let break_rules: Vec<&SrxRule> = self.rules.iter().filter(|rule| rule.breaks).collect();
+        let mut sentences = Vec::new();
+        let mut start = 0;
+        for (position, _) in cleaned_text.char_indices().skip(1) {
+            let candidate = break_rules.iter().any(|rule| {
+                rule.after.is_match(&cleaned_text[position..])
+                    && rule.before.is_match(&cleaned_text[..position])
+            });
+            if candidate && self.breaks_at(&cleaned_text, position) {
+                sentences.push(&cleaned_text[start..position]);
+                start = position;
+            }
+        }
+        sentences.push(&cleaned_text[start..]);
+
+        sentences
+            .into_iter()
+            .map(str::trim)
+            .filter(|sentence| !sentence.is_empty())
+            .map(|sentence| restore(sentence.to_string()))
+            .collect()
+    }
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>> {
+    for attr in element.attributes() {
+        let attr = attr?;
+        if attr.key.local_name().as_ref() == name {
+            return Ok(Some(attr.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn invalid(message: &str) -> SentencerError {
+    SentencerError::InvalidArgument(format!("Invalid SRX file: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<srx xmlns="http://www.lisa.org/srx20" version="2.0">
+  <header segmentsubflows="yes" cascade="yes"/>
+  <body>
+    <languagerules>
+      <languagerule languagerulename="English">
+        <rule break="no">
+          <beforebreak>\b(?:Mr|Dr)\.</beforebreak>
+          <afterbreak>\s</afterbreak>
+        </rule>
+      </languagerule>
+      <languagerule languagerulename="German">
+        <rule break="no">
+          <beforebreak>\b\d{1,2}\.</beforebreak>
+          <afterbreak>\s</afterbreak>
+        </rule>
+      </languagerule>
+      <languagerule languagerulename="Default">
+        <rule break="yes">
+          <beforebreak>[.?!]+</beforebreak>
+          <afterbreak>\s</afterbreak>
+        </rule>
+        <rule break="yes">
+          <beforebreak>&#x3002;</beforebreak>
+          <afterbreak></afterbreak>
+        </rule>
+      </languagerule>
+    </languagerules>
+    <maprules>
+      <languagemap languagepattern="EN.*" languagerulename="English"/>
+      <languagemap languagepattern="DE.*" languagerulename="German"/>
+      <languagemap languagepattern=".*" languagerulename="Default"/>
+    </maprules>
+  </body>
+</srx>"#;
+
+    #[test]
+    fn test_srx_rules_per_language() -> Result<()> {
+        let splitter = SrxSplitter::parse(RULES)?;
+        let text = "Mr. Smith left on 3. May. Was it late? Yes!";
+
+        assert_eq!(
+            splitter.split(text),
+            vec!["Mr.", "Smith left on 3.", "May.", "Was it late?", "Yes!"]
+        );
+        assert_eq!(
+            splitter
+                .clone()
+                .with_language(Some(Language::English))
+                .split(text),
+            vec!["Mr. Smith left on 3.", "May.", "Was it late?", "Yes!"]
+        );
+        assert_eq!(
+            splitter
+                .with_language(Some(Language::German))
+                .split("Am 3. Mai. 今日は晴れです。明日も。"),
+            vec!["Am 3. Mai.", "今日は晴れです。", "明日も。"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_srx() {
+        assert!(SrxSplitter::parse("<srx><body/></srx>").is_err());
+        assert!(
+            SrxSplitter::parse(&RULES.replace(
+                "languagerulename=\"German\"/>",
+                "languagerulename=\"French\"/>"
+            ))
+            .is_err()
+        );
+    }
+}
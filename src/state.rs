@@ -0,0 +1,180 @@
+use crate::annotation::Annotation;
+use crate::error::Result;
+use crate::stats::ReadingStats;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentState {
+    pub position: usize,
+    // Sentence index of each bookmark, by the key it was set with
+    #[serde(default)]
+    pub bookmarks: BTreeMap<char, usize>,
+    // Notes and tags by sentence index
+    #[serde(default)]
+    pub annotations: BTreeMap<usize, Annotation>,
+    // Added up over every session the document was read in
+    #[serde(default)]
+    pub reading: ReadingStats,
+}
+
+// Per-document reading state, stored as JSON under the XDG data directory
+// (e.g. ~/.local/share/sentencer/state.json)
+#[derive(Debug)]
+pub struct StateStore {
+    path: PathBuf,
+    documents: HashMap<String, DocumentState>,
+}
+
+impl StateStore {
+    pub fn open_default() -> Result<Self> {
+        let data_dir = dirs::data_dir().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "Could not determine the user data directory",
+            )
+        })?;
+
+        StateStore::open(data_dir.join("sentencer").join("state.json"))
+    }
+
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        let documents = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(StateStore { path, documents })
+    }
+
+    pub fn document(&self, key: &str) -> Option<&DocumentState> {
+        self.documents.get(key)
+    }
+
+    pub fn document_mut(&mut self, key: &str) -> &mut DocumentState {
+        self.documents.entry(key.to_string()).or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.documents)?)?;
+        Ok(())
+    }
+}
+
+// Identifies a document by its absolute path plus a hash of its contents, so
+// a file that was modified since the last session starts from scratch
+pub fn document_key(file_path: &str) -> Result<String> {
+    let path = fs::canonicalize(file_path)?;
+    let content = fs::read(&path)?;
+
+    Ok(format!(
+        "{}#{:016x}",
+        display_path(&path),
+        fnv1a_hash(&content)
+    ))
+}
+
+fn display_path(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+// FNV-1a, chosen because its output is stable across Rust releases
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip() -> Result<()> {
+        let state_dir = "test_state";
+        let state_file = "test_state/state.json";
+
+        let mut store = StateStore::open(state_file)?;
+        let state = store.document_mut("/tmp/book.odt#1");
+        state.position = 41;
+        state.bookmarks.insert('a', 7);
+        state.annotations.insert(
+            2,
+            Annotation {
+                sentence: "Three.".to_string(),
+                note: "Check.".to_string(),
+                tags: Vec::new(),
+            },
+        );
+        let saved = store.save();
+        let reopened = StateStore::open(state_file);
+
+        fs::remove_dir_all(state_dir).ok();
+
+        saved?;
+        let reopened = reopened?;
+        assert_eq!(
+            reopened.document("/tmp/book.odt#1"),
+            Some(&DocumentState {
+                position: 41,
+                bookmarks: BTreeMap::from([('a', 7)]),
+                annotations: BTreeMap::from([(
+                    2,
+                    Annotation {
+                        sentence: "Three.".to_string(),
+                        note: "Check.".to_string(),
+                        tags: Vec::new(),
+                    }
+                )]),
+                reading: ReadingStats::default(),
+            })
+        );
+        assert_eq!(reopened.document("/tmp/book.odt#2"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_without_bookmarks_loads() -> Result<()> {
+        let documents: HashMap<String, DocumentState> =
+            serde_json::from_str(r#"{"/tmp/book.odt#1": {"position": 3}}"#)?;
+
+        assert_eq!(documents["/tmp/book.odt#1"].position, 3);
+        assert!(documents["/tmp/book.odt#1"].bookmarks.is_empty());
+        assert!(documents["/tmp/book.odt#1"].annotations.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_document_key_tracks_content() -> Result<()> {
+        let document = "test_state_document.txt";
+
+        fs::write(document, "Some text.")?;
+        let first_key = document_key(document);
+        fs::write(document, "Changed text.")?;
+        let second_key = document_key(document);
+
+        fs::remove_file(document).ok();
+
+        let (first_key, second_key) = (first_key?, second_key?);
+        assert!(first_key.contains("test_state_document.txt#"));
+        assert_ne!(first_key, second_key);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fnv1a_hash_is_stable() {
+        assert_eq!(fnv1a_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a_hash(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::sentence::Sentence;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DocumentStats {
+    pub sentences: usize,
+    pub words: usize,
+    pub characters: usize,
+    pub paragraphs: usize,
+    // Word count of the longest sentence
+    pub longest_sentence: usize,
+}
+
+impl DocumentStats {
+    pub fn from_sentences(sentences: &[Sentence]) -> Self {
+        let mut stats = DocumentStats {
+            sentences: sentences.len(),
+            ..DocumentStats::default()
+        };
+
+        let mut paragraphs = HashSet::new();
+        for sentence in sentences {
+            let words = sentence.text.split_whitespace().count();
+            stats.words += words;
+            stats.characters += sentence.text.chars().count();
+            stats.longest_sentence = stats.longest_sentence.max(words);
+            paragraphs.insert(sentence.paragraph_index);
+        }
+        stats.paragraphs = paragraphs.len();
+
+        stats
+    }
+
+    // Counts are added up; the longest sentence is the longest of either
+    pub fn add(&mut self, other: &DocumentStats) {
+        self.sentences += other.sentences;
+        self.words += other.words;
+        self.characters += other.characters;
+        self.paragraphs += other.paragraphs;
+        self.longest_sentence = self.longest_sentence.max(other.longest_sentence);
+    }
+
+    pub fn average_words(&self) -> f64 {
+        if self.sentences == 0 {
+            return 0.0;
+        }
+        self.words as f64 / self.sentences as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileStats {
+    pub file: String,
+    #[serde(flatten)]
+    pub stats: DocumentStats,
+}
+
+// Statistics of several documents together with their totals
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StatsReport {
+    pub files: Vec<FileStats>,
+    pub total: DocumentStats,
+}
+
+impl StatsReport {
+    pub fn new(files: Vec<FileStats>) -> Self {
+        let mut total = DocumentStats::default();
+        for file in &files {
+            total.add(&file.stats);
+        }
+
+        StatsReport { files, total }
+    }
+}
+
+// Time spent reading a document and what was read in it, for one session or
+// added up over all of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadingStats {
+    pub seconds: u64,
+    pub words: usize,
+    pub sentences: usize,
+    #[serde(default)]
+    pub sessions: usize,
+}
+
+impl ReadingStats {
+    pub fn add(&mut self, other: &ReadingStats) {
+        self.seconds += other.seconds;
+        self.words += other.words;
+        self.sentences += other.sentences;
+        self.sessions += other.sessions;
+    }
+
+    // None until there has been some reading to go by
+    pub fn words_per_minute(&self) -> Option<f64> {
+        (self.seconds > 0 && self.words > 0).then(|| self.words as f64 * 60.0 / self.seconds as f64)
+    }
+}
+
+impl fmt::Display for ReadingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} sentences ({} words) in {}",
+            self.sentences,
+            self.words,
+            format_duration(Duration::from_secs(self.seconds))
+        )?;
+        if let Some(wpm) = self.words_per_minute() {
+            write!(f, ", {:.0} words per minute", wpm)?;
+        }
+        Ok(())
+    }
+}
+
+// Rounded to what matters at the scale, as in "1 h 5 min" or "40 s"
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match (seconds / 3600, seconds % 3600 / 60, seconds % 60) {
+        (0, 0, seconds) => format!("{} s", seconds),
+        (0, minutes, 0) => format!("{} min", minutes),
+        (0, minutes, seconds) if minutes < 10 => format!("{} min {} s", minutes, seconds),
+        (0, minutes, _) => format!("{} min", minutes),
+        (hours, minutes, _) => format!("{} h {} min", hours, minutes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_stats() {
+        let sentences = vec![
+            Sentence::new("One two three."),
+            Sentence::new("Four."),
+            Sentence {
+                paragraph_index: 1,
+                ..Sentence::new("Five six.")
+            },
+        ];
+
+        let stats = DocumentStats::from_sentences(&sentences);
+
+        assert_eq!(
+            stats,
+            DocumentStats {
+                sentences: 3,
+                words: 6,
+                characters: 28,
+                paragraphs: 2,
+                longest_sentence: 3,
+            }
+        );
+        assert_eq!(stats.average_words(), 2.0);
+        assert_eq!(DocumentStats::default().average_words(), 0.0);
+    }
+
+    #[test]
+    fn test_reading_stats() {
+        let mut reading = ReadingStats {
+            seconds: 125,
+            words: 500,
+            sentences: 30,
+            sessions: 1,
+        };
+        reading.add(&ReadingStats {
+            seconds: 55,
+            words: 100,
+            sentences: 6,
+            sessions: 1,
+        });
+
+        assert_eq!(reading.words_per_minute(), Some(200.0));
+        assert_eq!(
+            reading.to_string(),
+            "36 sentences (600 words) in 3 min, 200 words per minute"
+        );
+        assert_eq!(format_duration(Duration::from_secs(4000)), "1 h 6 min");
+        assert_eq!(format_duration(Duration::from_secs(130)), "2 min 10 s");
+        assert_eq!(ReadingStats::default().words_per_minute(), None);
+    }
+
+    #[test]
+    fn test_stats_report_totals() -> Result<(), serde_json::Error> {
+        let file = |name: &str, sentences, words, longest_sentence| FileStats {
+            file: name.to_string(),
+            stats: DocumentStats {
+                sentences,
+                words,
+                characters: words * 5,
+                paragraphs: 1,
+                longest_sentence,
+            },
+        };
+
+        let report = StatsReport::new(vec![file("a.md", 2, 10, 6), file("b.md", 3, 9, 4)]);
+
+        assert_eq!(
+            report.total,
+            DocumentStats {
+                sentences: 5,
+                words: 19,
+                characters: 95,
+                paragraphs: 2,
+                longest_sentence: 6,
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&report)?["files"][1],
+            serde_json::json!({
+                "file": "b.md", "sentences": 3, "words": 9, "characters": 45,
+                "paragraphs": 1, "longest_sentence": 4
+            })
+        );
+        Ok(())
+    }
+}
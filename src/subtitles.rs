@@ -0,0 +1,114 @@
+use crate::error::Result;
+use crate::export::ExportFormat;
+use crate::sentence::Sentence;
+use std::io::Write;
+
+// A comfortable narration speed
+pub const DEFAULT_WPM: u32 = 160;
+// Short sentences stay on screen long enough to be read
+const MIN_CUE_MILLIS: u64 = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue<'a> {
+    // Milliseconds from the start
+    pub start: u64,
+    pub end: u64,
+    pub text: &'a str,
+}
+
+// One cue per sentence, back to back, each lasting as long as its words take
+// to speak at `wpm`
+pub fn cues<'a, I>(sentences: I, wpm: u32) -> Vec<Cue<'a>>
+where
+    I: IntoIterator<Item = &'a Sentence>,
+{
+    let mut start = 0;
+    sentences
+        .into_iter()
+        .map(|sentence| {
+            let words = sentence.text.split_whitespace().count() as u64;
+            let duration = (words * 60_000 / u64::from(wpm.max(1))).max(MIN_CUE_MILLIS);
+            let cue = Cue {
+                start,
+                end: start + duration,
+                text: &sentence.text,
+            };
+            start = cue.end;
+            cue
+        })
+        .collect()
+}
+
+// SubRip numbers its cues and uses a comma before the milliseconds; WebVTT
+// starts with a header and uses a dot
+pub fn write_subtitles<W: Write>(writer: &mut W, cues: &[Cue], format: ExportFormat) -> Result<()> {
+    let vtt = format == ExportFormat::Vtt;
+    if vtt {
+        writeln!(writer, "WEBVTT")?;
+        writeln!(writer)?;
+    }
+
+    for (i, cue) in cues.iter().enumerate() {
+        if !vtt {
+            writeln!(writer, "{}", i + 1)?;
+        }
+        writeln!(
+            writer,
+            "{} --> {}",
+            timestamp(cue.start, vtt),
+            timestamp(cue.end, vtt)
+        )?;
+        writeln!(writer, "{}", cue.text)?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+fn timestamp(millis: u64, vtt: bool) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        if vtt { '.' } else { ',' },
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subtitle_cues() -> Result<()> {
+        let sentences = vec![
+            Sentence::new("One two three four."),
+            Sentence::new("Five."),
+            Sentence::new("Six seven eight nine ten eleven twelve thirteen."),
+        ];
+
+        let cues = cues(&sentences, 160);
+        let times: Vec<(u64, u64)> = cues.iter().map(|cue| (cue.start, cue.end)).collect();
+        assert_eq!(times, vec![(0, 1500), (1500, 2500), (2500, 5500)]);
+
+        let mut srt = Vec::new();
+        write_subtitles(&mut srt, &cues[..2], ExportFormat::Srt)?;
+        assert_eq!(
+            String::from_utf8(srt)?,
+            "1\n00:00:00,000 --> 00:00:01,500\nOne two three four.\n\n\
+             2\n00:00:01,500 --> 00:00:02,500\nFive.\n\n"
+        );
+
+        let mut vtt = Vec::new();
+        write_subtitles(&mut vtt, &cues[2..], ExportFormat::Vtt)?;
+        assert_eq!(
+            String::from_utf8(vtt)?,
+            "WEBVTT\n\n00:00:02.500 --> 00:00:05.500\n\
+             Six seven eight nine ten eleven twelve thirteen.\n\n"
+        );
+
+        assert_eq!(timestamp(3_725_042, false), "01:02:05,042");
+        Ok(())
+    }
+}
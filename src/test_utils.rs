@@ -0,0 +1,99 @@
+use crate::error::Result;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use zip::{ZipWriter, write::FileOptions};
+
+// Helper function to create a test OpenDocument package of the given
+// mimetype, holding `content_xml` as its content.xml
+pub fn create_test_package(file_path: &str, mimetype: &str, content_xml: &str) -> Result<()> {
+    let file = File::create(file_path)?;
+    let mut zip = ZipWriter::new(file);
+
+    // Create mimetype file (required for OpenDocument)
+    zip.start_file(
+        "mimetype",
+        FileOptions::default().compression_method(zip::CompressionMethod::Stored),
+    )?;
+    zip.write_all(mimetype.as_bytes())?;
+
+    // Create META-INF/manifest.xml (required for OpenDocument)
+    zip.start_file("META-INF/manifest.xml", FileOptions::default())?;
+    let manifest = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0">
+    <manifest:file-entry manifest:full-path="/" manifest:media-type="{}"/>
+    <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>"#,
+        mimetype
+    );
+    zip.write_all(manifest.as_bytes())?;
+
+    zip.start_file("content.xml", FileOptions::default())?;
+    zip.write_all(content_xml.as_bytes())?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+// Helper function to create a test ODT file
+pub fn create_test_odt_file(file_path: &str, content: &str) -> Result<()> {
+    let content_xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content 
+    xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+    xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+    <office:body>
+        <office:text>
+            {}
+        </office:text>
+    </office:body>
+</office:document-content>"#,
+        content
+    );
+
+    create_test_package(
+        file_path,
+        "application/vnd.oasis.opendocument.text",
+        &content_xml,
+    )
+}
+
+// Answers one HTTP request with the JSON `body`. Returns the server's URL and
+// a receiver for the body of the request it got. Only the HTTP clients of the
+// cli feature use it.
+#[cfg_attr(not(feature = "cli"), allow(dead_code))]
+pub fn serve_once(body: &'static str) -> Result<(String, mpsc::Receiver<String>)> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = listener.local_addr()?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let Ok((mut stream, _)) = listener.accept() else {
+            return;
+        };
+        let mut reader = BufReader::new(&stream);
+        let mut content_length = 0;
+        let mut line = String::new();
+        while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+            if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                content_length = length.trim().parse().unwrap_or(0);
+            }
+            line.clear();
+        }
+        let mut request = vec![0; content_length];
+        reader.read_exact(&mut request).ok();
+        sender
+            .send(String::from_utf8_lossy(&request).into_owned())
+            .ok();
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).ok();
+    });
+    Ok((format!("http://{}/", address), receiver))
+}
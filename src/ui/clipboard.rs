@@ -0,0 +1,26 @@
+use std::fmt;
+
+// The system clipboard is opened on first use and kept open: on X11 copied
+// text is only available while the program that copied it is running
+#[derive(Default)]
+pub(super) struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clipboard")
+            .field("open", &self.inner.is_some())
+            .finish()
+    }
+}
+
+impl Clipboard {
+    pub(super) fn copy(&mut self, text: &str) -> Result<(), arboard::Error> {
+        let clipboard = match &mut self.inner {
+            Some(clipboard) => clipboard,
+            None => self.inner.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)
+    }
+}
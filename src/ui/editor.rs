@@ -0,0 +1,74 @@
+use crate::error::{Result, SentencerError};
+use std::env;
+use std::fs;
+use std::process::Command;
+
+const INSTRUCTIONS: &str = "\n\
+# Edit the sentence above; line breaks become spaces.
+# Lines starting with '#' are ignored. Save an empty text to cancel.
+";
+
+// $VISUAL takes precedence over $EDITOR, as in most terminal programs
+pub(super) fn editor_command() -> String {
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+// Opens `text` in the editor through a temporary file. Returns None when the
+// editor fails or the text is emptied, which cancels the edit.
+pub(super) fn edit_text(editor: &str, text: &str) -> Result<Option<String>> {
+    let path = env::temp_dir().join(format!("sentencer-{}.txt", std::process::id()));
+    fs::write(&path, format!("{}\n{}", text, INSTRUCTIONS))?;
+
+    // The editor setting may carry arguments, such as "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| SentencerError::InvalidArgument("No editor configured".to_string()))?;
+    let status = Command::new(program).args(parts).arg(&path).status();
+    let edited = fs::read_to_string(&path);
+    fs::remove_file(&path).ok();
+
+    if !status?.success() {
+        return Ok(None);
+    }
+    let edited = edited_sentence(&edited?);
+    Ok((!edited.is_empty()).then_some(edited))
+}
+
+fn edited_sentence(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edited_sentence() {
+        assert_eq!(
+            edited_sentence("A long sentence\n  across lines.\n\n# ignored\n"),
+            "A long sentence across lines."
+        );
+        assert_eq!(edited_sentence(&format!("\n{}", INSTRUCTIONS)), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_text_with_editor_exit_status() -> Result<()> {
+        assert_eq!(
+            edit_text("true", "Unchanged text.")?,
+            Some("Unchanged text.".to_string())
+        );
+        assert_eq!(edit_text("false", "Unchanged text.")?, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,647 @@
+use crate::config::KeysConfig;
+use crate::error::{Result, SentencerError};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Next,
+    Previous,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+    Outline,
+    Context,
+    Unit,
+    Kind,
+    SetBookmark,
+    JumpToBookmark,
+    Bookmarks,
+    Note,
+    Tags,
+    Annotations,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Concordance,
+    Replace,
+    Check,
+    Spelling,
+    Edit,
+    ExternalEdit,
+    Yank,
+    YankReference,
+    Speak,
+    Split,
+    Join,
+    Delete,
+    InsertBefore,
+    InsertAfter,
+    Undo,
+    Redo,
+    Save,
+    Diff,
+    NextDocument,
+    PreviousDocument,
+    Documents,
+    Help,
+    Quit,
+}
+
+// Every action with its name in the config file and its help text, in the
+// order the help screen lists them
+const ACTIONS: [(Action, &str, &str); 42] = [
+    (Action::Next, "next", "Next sentence"),
+    (Action::Previous, "previous", "Previous sentence"),
+    (Action::PageDown, "page-down", "10 sentences forward"),
+    (Action::PageUp, "page-up", "10 sentences back"),
+    (Action::First, "first", "First sentence"),
+    (Action::Last, "last", "Last sentence"),
+    (Action::Outline, "outline", "Outline of headings"),
+    (
+        Action::Context,
+        "context",
+        "Show previous and next sentence",
+    ),
+    (
+        Action::Unit,
+        "unit",
+        "Move by sentence, paragraph or section",
+    ),
+    (
+        Action::Kind,
+        "kind",
+        "Step only through one kind of sentence",
+    ),
+    (
+        Action::SetBookmark,
+        "set-bookmark",
+        "Bookmark current sentence",
+    ),
+    (
+        Action::JumpToBookmark,
+        "jump-to-bookmark",
+        "Jump to bookmark",
+    ),
+    (Action::Bookmarks, "bookmarks", "List bookmarks"),
+    (Action::Note, "note", "Note on current sentence"),
+    (Action::Tags, "tags", "Tags on current sentence"),
+    (Action::Annotations, "annotations", "List annotations"),
+    (Action::Search, "search", "Search (Tab toggles regex)"),
+    (Action::NextMatch, "next-match", "Next match"),
+    (Action::PreviousMatch, "previous-match", "Previous match"),
+    (Action::Concordance, "concordance", "Matches in context"),
+    (
+        Action::Replace,
+        "replace",
+        "Replace in all sentences (s/pattern/text/g)",
+    ),
+    (Action::Check, "check", "Check grammar and style"),
+    (Action::Spelling, "spelling", "Correct a misspelled word"),
+    (Action::Edit, "edit", "Edit current sentence"),
+    (
+        Action::ExternalEdit,
+        "external-edit",
+        "Edit in $VISUAL / $EDITOR",
+    ),
+    (Action::Yank, "yank", "Copy sentence to the clipboard"),
+    (
+        Action::YankReference,
+        "yank-reference",
+        "Copy sentence with its number and file",
+    ),
+    (Action::Speak, "speak", "Read aloud sentence by sentence"),
+    (Action::Split, "split", "Split current sentence"),
+    (Action::Join, "join", "Join with next sentence"),
+    (Action::Delete, "delete", "Delete current sentence"),
+    (
+        Action::InsertBefore,
+        "insert-before",
+        "Insert a sentence before this one",
+    ),
+    (
+        Action::InsertAfter,
+        "insert-after",
+        "Insert a sentence after this one",
+    ),
+    (Action::Undo, "undo", "Undo last edit"),
+    (Action::Redo, "redo", "Redo last undone edit"),
+    (Action::Save, "save", "Save changes to the document"),
+    (Action::Diff, "diff", "Write edits as a .diff file"),
+    (Action::NextDocument, "next-document", "Next document"),
+    (
+        Action::PreviousDocument,
+        "previous-document",
+        "Previous document",
+    ),
+    (Action::Documents, "documents", "List open documents"),
+    (Action::Help, "help", "Show this help"),
+    (Action::Quit, "quit", "Quit"),
+];
+
+type Preset = &'static [(Action, &'static [&'static str])];
+
+const DEFAULT_KEYS: Preset = &[
+    (Action::Next, &["n", "Enter", "Space", "Right", "Down"]),
+    (Action::Previous, &["p", "Left", "Up"]),
+    (Action::PageDown, &["PgDn"]),
+    (Action::PageUp, &["PgUp"]),
+    (Action::First, &["f", "Home"]),
+    (Action::Last, &["l", "End"]),
+    (Action::Outline, &["O"]),
+    (Action::Context, &["c"]),
+    (Action::Unit, &["Tab"]),
+    (Action::Kind, &["K"]),
+    (Action::SetBookmark, &["m"]),
+    (Action::JumpToBookmark, &["'"]),
+    (Action::Bookmarks, &["b"]),
+    (Action::Note, &["a"]),
+    (Action::Tags, &["t"]),
+    (Action::Annotations, &["A"]),
+    (Action::Search, &["/"]),
+    (Action::NextMatch, &["n"]),
+    (Action::PreviousMatch, &["N"]),
+    (Action::Concordance, &["C"]),
+    (Action::Replace, &[":"]),
+    (Action::Check, &["g"]),
+    (Action::Spelling, &["z"]),
+    (Action::Edit, &["e"]),
+    (Action::ExternalEdit, &["E"]),
+    (Action::Yank, &["y"]),
+    (Action::YankReference, &["Y"]),
+    (Action::Speak, &["r"]),
+    (Action::Split, &["x"]),
+    (Action::Join, &["j"]),
+    (Action::Delete, &["d"]),
+    (Action::InsertBefore, &["i"]),
+    (Action::InsertAfter, &["o"]),
+    (Action::Undo, &["u"]),
+    (Action::Redo, &["C-r"]),
+    (Action::Save, &["s", "w"]),
+    (Action::Diff, &["M-d"]),
+    (Action::NextDocument, &["]"]),
+    (Action::PreviousDocument, &["["]),
+    (Action::Documents, &["D"]),
+    (Action::Help, &["h", "?"]),
+    (Action::Quit, &["q", "Esc"]),
+];
+
+// Presets only list the actions whose keys differ from the defaults
+const VIM_KEYS: Preset = &[
+    (Action::Next, &["j", "Down", "Enter", "Space"]),
+    (Action::Previous, &["k", "Up"]),
+    (Action::PageDown, &["C-d", "C-f", "PgDn"]),
+    (Action::PageUp, &["C-u", "C-b", "PgUp"]),
+    (Action::First, &["gg", "Home"]),
+    (Action::Last, &["G", "End"]),
+    (Action::Outline, &["gO"]),
+    (Action::Edit, &["i", "e"]),
+    (Action::Join, &["J"]),
+    (Action::Delete, &["dd"]),
+    (Action::InsertBefore, &["O"]),
+    (Action::InsertAfter, &["o"]),
+    (Action::Replace, &[":s"]),
+    (Action::Check, &["gl"]),
+    (Action::Spelling, &["z="]),
+    (Action::Redo, &["C-r"]),
+    (Action::Save, &[":w"]),
+    (Action::NextDocument, &[":bn", "]"]),
+    (Action::PreviousDocument, &[":bp", "["]),
+    (Action::Documents, &[":ls"]),
+    (Action::Help, &["?"]),
+    (Action::Quit, &[":q", "q"]),
+];
+
+const EMACS_KEYS: Preset = &[
+    (Action::Next, &["C-n", "Down", "Enter", "Space"]),
+    (Action::Previous, &["C-p", "Up"]),
+    (Action::PageDown, &["C-v", "PgDn"]),
+    (Action::PageUp, &["M-v", "PgUp"]),
+    (Action::First, &["M-<", "Home"]),
+    (Action::Last, &["M->", "End"]),
+    (Action::Search, &["C-s", "/"]),
+    (Action::NextMatch, &["C-s"]),
+    (Action::PreviousMatch, &["C-r"]),
+    (Action::Replace, &["M-%"]),
+    (Action::Check, &["C-c g"]),
+    (Action::Spelling, &["M-$"]),
+    (Action::Yank, &["M-w"]),
+    (Action::Undo, &["C-/", "C-_"]),
+    (Action::Redo, &["M-_"]),
+    (Action::Save, &["C-x C-s"]),
+    (Action::Documents, &["C-x b"]),
+    (Action::Help, &["C-h", "?"]),
+    (Action::Quit, &["C-x C-c"]),
+];
+
+const NAMED_KEYS: [(&str, KeyCode); 14] = [
+    ("Enter", KeyCode::Enter),
+    ("Space", KeyCode::Char(' ')),
+    ("Esc", KeyCode::Esc),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("PgUp", KeyCode::PageUp),
+    ("PgDn", KeyCode::PageDown),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+];
+
+impl Action {
+    pub fn from_name(name: &str) -> Result<Self> {
+        ACTIONS
+            .iter()
+            .find(|(_, action_name, _)| *action_name == name)
+            .map(|(action, _, _)| *action)
+            .ok_or_else(|| SentencerError::Config(format!("unknown key binding action '{}'", name)))
+    }
+
+    // Only active while there is a search, where they take precedence
+    pub(super) fn steps_through_matches(&self) -> bool {
+        matches!(self, Action::NextMatch | Action::PreviousMatch)
+    }
+
+    // Refused while the document is open read-only
+    pub(super) fn changes_text(&self) -> bool {
+        matches!(
+            self,
+            Action::Replace
+                | Action::Edit
+                | Action::ExternalEdit
+                | Action::Split
+                | Action::Join
+                | Action::Delete
+                | Action::InsertBefore
+                | Action::InsertAfter
+                | Action::Undo
+                | Action::Redo
+                | Action::Save
+        )
+    }
+}
+
+// One key press. Shift is part of the character rather than a modifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    ctrl: bool,
+    alt: bool,
+}
+
+impl Key {
+    fn new(code: KeyCode) -> Self {
+        Key {
+            code,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    pub fn from_event(event: &KeyEvent) -> Self {
+        Key {
+            code: event.code,
+            ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+            alt: event.modifiers.contains(KeyModifiers::ALT),
+        }
+    }
+
+    // A key name such as "q", "Enter", "C-r" or "Alt-x"
+    fn parse(name: &str) -> Option<Self> {
+        let mut key = Key::new(KeyCode::Null);
+        let mut rest = name;
+        loop {
+            let lowercase = rest.to_lowercase();
+            if let Some(prefix) = ["c-", "ctrl-"].iter().find(|p| lowercase.starts_with(**p)) {
+                key.ctrl = true;
+                rest = &rest[prefix.len()..];
+            } else if let Some(prefix) = ["m-", "alt-"].iter().find(|p| lowercase.starts_with(**p))
+            {
+                key.alt = true;
+                rest = &rest[prefix.len()..];
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = rest.chars();
+        key.code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => NAMED_KEYS
+                .iter()
+                .find(|(key_name, _)| key_name.eq_ignore_ascii_case(rest))
+                .map(|(_, code)| *code)?,
+        };
+        Some(key)
+    }
+
+    pub fn label(&self) -> String {
+        let name = match self.code {
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            code => NAMED_KEYS
+                .iter()
+                .find(|(_, named)| *named == code)
+                .map_or("?".to_string(), |(name, _)| name.to_string()),
+        };
+
+        match (self.ctrl, self.alt) {
+            (true, true) => format!("Ctrl-Alt-{}", name),
+            (true, false) => format!("Ctrl-{}", name),
+            (false, true) => format!("Alt-{}", name),
+            (false, false) => name,
+        }
+    }
+}
+
+// Keys are separated by spaces, and a word that is not a key name is typed
+// one character at a time, so "gg" and ":w" are sequences of two keys
+fn parse_sequence(binding: &str) -> Result<Vec<Key>> {
+    let mut keys = Vec::new();
+    for word in binding.split_whitespace() {
+        match Key::parse(word) {
+            Some(key) => keys.push(key),
+            None if !word.contains('-') || word.len() < 3 => {
+                keys.extend(word.chars().map(|c| Key::new(KeyCode::Char(c))))
+            }
+            None => {
+                return Err(SentencerError::Config(format!(
+                    "unknown key '{}' in binding '{}'",
+                    word, binding
+                )));
+            }
+        }
+    }
+
+    if keys.is_empty() {
+        return Err(SentencerError::Config("empty key binding".to_string()));
+    }
+    Ok(keys)
+}
+
+pub(super) enum Lookup {
+    // The actions bound to exactly the keys pressed
+    Actions(Vec<Action>),
+    // The keys pressed start a longer binding
+    Prefix,
+    None,
+}
+
+// Which key sequences trigger each action in normal mode. Digits always
+// build up a sentence number, and Esc always clears an active search.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Vec<Key>>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::preset("default").unwrap_or(Keymap {
+            bindings: Vec::new(),
+        })
+    }
+}
+
+impl Keymap {
+    // "default", "vim" or "emacs"
+    pub fn preset(name: &str) -> Result<Self> {
+        let overrides = match name {
+            "default" => &[][..],
+            "vim" => VIM_KEYS,
+            "emacs" => EMACS_KEYS,
+            _ => {
+                return Err(SentencerError::Config(format!(
+                    "unknown key preset '{}' (expected default, vim or emacs)",
+                    name
+                )));
+            }
+        };
+
+        let mut bindings = Vec::new();
+        for (action, _, _) in ACTIONS {
+            let keys = overrides
+                .iter()
+                .chain(DEFAULT_KEYS)
+                .find(|(bound, _)| *bound == action)
+                .map_or(&[][..], |(_, keys)| *keys);
+            let sequences = keys
+                .iter()
+                .map(|binding| parse_sequence(binding))
+                .collect::<Result<_>>()?;
+            bindings.push((action, sequences));
+        }
+
+        Ok(Keymap { bindings })
+    }
+
+    // The preset with the config file's bindings on top
+    pub fn from_config(config: &KeysConfig) -> Result<Self> {
+        let mut keymap = Keymap::preset(config.preset.as_deref().unwrap_or("default"))?;
+        for (name, keys) in &config.bindings {
+            let sequences = keys
+                .iter()
+                .map(|binding| parse_sequence(binding))
+                .collect::<Result<_>>()?;
+            keymap.bind(Action::from_name(name)?, sequences);
+        }
+        Ok(keymap)
+    }
+
+    // Replaces the keys of `action`. The keys are taken from other actions,
+    // except that the match steps share keys with ordinary actions.
+    pub fn bind(&mut self, action: Action, sequences: Vec<Vec<Key>>) {
+        for (bound, bound_sequences) in &mut self.bindings {
+            if bound.steps_through_matches() == action.steps_through_matches() {
+                bound_sequences.retain(|sequence| !sequences.contains(sequence));
+            }
+        }
+        if let Some((_, bound_sequences)) =
+            self.bindings.iter_mut().find(|(bound, _)| *bound == action)
+        {
+            *bound_sequences = sequences;
+        }
+    }
+
+    pub(super) fn lookup(&self, keys: &[Key]) -> Lookup {
+        let actions: Vec<Action> = self
+            .bindings
+            .iter()
+            .filter(|(_, sequences)| sequences.iter().any(|sequence| sequence == keys))
+            .map(|(action, _)| *action)
+            .collect();
+        if !actions.is_empty() {
+            return Lookup::Actions(actions);
+        }
+
+        let is_prefix = self.bindings.iter().any(|(_, sequences)| {
+            sequences
+                .iter()
+                .any(|sequence| sequence.len() > keys.len() && sequence.starts_with(keys))
+        });
+        match is_prefix {
+            true => Lookup::Prefix,
+            false => Lookup::None,
+        }
+    }
+
+    // How the first key of the action's first binding reads, as in "gg"
+    pub(super) fn first_label(&self, action: Action) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .and_then(|(_, sequences)| sequences.first())
+            .map(|sequence| sequence_label(sequence))
+    }
+
+    // Keys and description of every action for the help screen, including
+    // the keys that cannot be rebound
+    pub(super) fn help_lines(&self) -> Vec<(String, &'static str)> {
+        let mut lines = Vec::new();
+        for ((action, sequences), (_, _, description)) in self.bindings.iter().zip(ACTIONS) {
+            if sequences.is_empty() {
+                continue;
+            }
+            let suffix = match action {
+                Action::SetBookmark | Action::JumpToBookmark => "<key>",
+                _ => "",
+            };
+            let keys: Vec<String> = sequences
+                .iter()
+                .map(|sequence| format!("{}{}", sequence_label(sequence), suffix))
+                .collect();
+            lines.push((keys.join(" "), description));
+
+            match action {
+                Action::Last => {
+                    lines.push(("[number] Enter".to_string(), "Jump to sentence number"))
+                }
+                Action::PreviousMatch => lines.push(("Esc".to_string(), "Clear search")),
+                _ => {}
+            }
+        }
+        lines
+    }
+}
+
+// Single characters typed in a row read as a word, as in "gg" or ":w"
+fn sequence_label(sequence: &[Key]) -> String {
+    let typed = sequence
+        .iter()
+        .all(|key| !key.ctrl && !key.alt && matches!(key.code, KeyCode::Char(c) if c != ' '));
+    let labels: Vec<String> = sequence.iter().map(Key::label).collect();
+    match typed {
+        true => labels.concat(),
+        false => labels.join(" "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn keys(binding: &str) -> Vec<Key> {
+        parse_sequence(binding).unwrap()
+    }
+
+    #[test]
+    fn test_parse_keys() -> Result<()> {
+        assert_eq!(keys("gg"), vec![Key::new(KeyCode::Char('g')); 2]);
+        assert_eq!(
+            keys("C-x C-s"),
+            vec![
+                Key {
+                    code: KeyCode::Char('x'),
+                    ctrl: true,
+                    alt: false
+                },
+                Key {
+                    code: KeyCode::Char('s'),
+                    ctrl: true,
+                    alt: false
+                }
+            ]
+        );
+        assert_eq!(keys("pgdn"), vec![Key::new(KeyCode::PageDown)]);
+        assert_eq!(keys("M-<")[0].label(), "Alt-<");
+        assert!(parse_sequence("C-Bogus").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_presets_and_bindings() -> Result<()> {
+        let vim = Keymap::preset("vim")?;
+        assert!(matches!(vim.lookup(&keys("g")), Lookup::Prefix));
+        assert!(
+            matches!(vim.lookup(&keys("gg")), Lookup::Actions(actions) if actions == [Action::First])
+        );
+        assert!(matches!(vim.lookup(&keys("p")), Lookup::None));
+        assert!(
+            matches!(vim.lookup(&keys("z=")), Lookup::Actions(actions) if actions == [Action::Spelling])
+        );
+        assert!(matches!(vim.lookup(&keys("d")), Lookup::Prefix));
+        assert!(
+            matches!(vim.lookup(&keys("dd")), Lookup::Actions(actions) if actions == [Action::Delete])
+        );
+        assert!(
+            matches!(vim.lookup(&keys("gO")), Lookup::Actions(actions) if actions == [Action::Outline])
+        );
+        assert!(
+            matches!(vim.lookup(&keys("O")), Lookup::Actions(actions) if actions == [Action::InsertBefore])
+        );
+
+        // Outline and diff moved to make room for d, i and o
+        let default = Keymap::default();
+        let moved = [
+            ("d", Action::Delete),
+            ("i", Action::InsertBefore),
+            ("o", Action::InsertAfter),
+            ("O", Action::Outline),
+            ("M-d", Action::Diff),
+        ];
+        for (binding, action) in moved {
+            assert!(
+                matches!(default.lookup(&keys(binding)), Lookup::Actions(actions) if actions == [action])
+            );
+        }
+        assert_eq!(default.first_label(Action::Diff).as_deref(), Some("Alt-d"));
+        assert!(Keymap::preset("nano").is_err());
+
+        let config = KeysConfig {
+            preset: Some("emacs".to_string()),
+            bindings: BTreeMap::from([(
+                "quit".to_string(),
+                vec!["C-g".to_string(), "j".to_string()],
+            )]),
+        };
+        let keymap = Keymap::from_config(&config)?;
+        assert!(
+            matches!(keymap.lookup(&keys("j")), Lookup::Actions(actions) if actions == [Action::Quit])
+        );
+        assert!(matches!(keymap.lookup(&keys("C-x C-c")), Lookup::None));
+        assert!(matches!(
+            keymap.lookup(&keys("C-s")),
+            Lookup::Actions(actions) if actions == [Action::Search, Action::NextMatch]
+        ));
+        assert_eq!(keymap.first_label(Action::First).as_deref(), Some("Alt-<"));
+        assert!(
+            keymap
+                .help_lines()
+                .contains(&("Ctrl-g j".to_string(), "Quit"))
+        );
+
+        let config = KeysConfig {
+            preset: None,
+            bindings: BTreeMap::from([("fly".to_string(), vec!["f".to_string()])]),
+        };
+        assert!(Keymap::from_config(&config).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,3006 @@
+mod clipboard;
+mod editor;
+mod keys;
+mod progress;
+mod render;
+mod speech;
+mod watch;
+
+use crate::annotation::{self, Annotation, AnnotationFormat};
+use crate::diff;
+use crate::error::Result;
+use crate::grammar::{GrammarIssue, LanguageTool};
+use crate::parser::DocumentMetadata;
+use crate::replace::Substitution;
+use crate::rewrite::Rewriter;
+use crate::search::{ConcordanceLine, SearchQuery};
+use crate::sentence::{Sentence, SentenceKind};
+use crate::session::SessionEdit;
+#[cfg(feature = "spellcheck")]
+use crate::spelling::SpellChecker;
+use crate::stats::ReadingStats;
+use crate::writer::{self, SaveOptions};
+use clipboard::Clipboard;
+use keys::{Key, Lookup};
+use progress::ReadingTracker;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{EnterAlternateScreen, enable_raw_mode};
+use speech::Speaker;
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+use unicode_segmentation::UnicodeSegmentation;
+use watch::DocumentWatcher;
+
+pub use keys::{Action, Keymap};
+
+const PAGE_SIZE: usize = 10;
+// Suggestions listed for each misspelled word
+#[cfg(feature = "spellcheck")]
+const MAX_SUGGESTIONS: usize = 5;
+// How often the document is checked for changes, and speech for its end,
+// while waiting for a key
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Mode {
+    Normal,
+    Help,
+    Edit(String),
+    // The draft being edited and the instruction for rewriting it
+    RewriteInstruction {
+        draft: String,
+        instruction: String,
+    },
+    // Waiting for the rewrite, which happens once this has been drawn
+    Rewriting {
+        draft: String,
+        instruction: String,
+    },
+    // The suggested rewrite, or why there is none, to accept, modify or reject
+    RewriteSuggestion {
+        draft: String,
+        suggestion: std::result::Result<String, String>,
+    },
+    // Number of words that stay in the first half of the split
+    Split(usize),
+    // The text of a new sentence going before or after the current one
+    Insert {
+        text: String,
+        after: bool,
+    },
+    // Position of the selected entry in the heading list
+    Outline(usize),
+    Search {
+        input: String,
+        is_regex: bool,
+    },
+    // Position of the selected match in the list of search matches
+    Concordance(usize),
+    // The s/pattern/replacement/flags command being typed
+    Replace(String),
+    // New text of each sentence a replacement changes, and the position of
+    // the selected entry, waiting for the replacement to be confirmed
+    ConfirmReplace(Vec<(usize, String)>, usize),
+    // A suggestion for each misspelled word of the current sentence, by the
+    // word's byte range, and the position of the selected entry
+    #[cfg_attr(not(feature = "spellcheck"), allow(dead_code))]
+    Spelling(Vec<(Range<usize>, String)>, usize),
+    Note(String),
+    Tags(String),
+    // Position of the selected entry in the annotation list
+    Annotations(usize),
+    // Waiting for the key to set (m) or jump to (') a bookmark
+    SetBookmark,
+    JumpToBookmark,
+    // Position of the selected entry in the bookmark list
+    Bookmarks(usize),
+    // Position of the selected entry in the list of open documents
+    Documents(usize),
+    ConfirmQuit,
+}
+
+// What next and previous move by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NavigationUnit {
+    Sentence,
+    Paragraph,
+    // From one heading to the next
+    Section,
+}
+
+impl NavigationUnit {
+    fn name(self) -> &'static str {
+        match self {
+            NavigationUnit::Sentence => "sentence",
+            NavigationUnit::Paragraph => "paragraph",
+            NavigationUnit::Section => "section",
+        }
+    }
+}
+
+// One of several documents read one after another, with the position
+// reached in it and its number of sentences once it has been opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentEntry {
+    pub name: String,
+    pub progress: Option<(usize, usize)>,
+}
+
+impl DocumentEntry {
+    pub fn new(name: &str) -> Self {
+        DocumentEntry {
+            name: name.to_string(),
+            progress: None,
+        }
+    }
+}
+
+// What the reader does, for following along elsewhere; see `with_events`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReaderEvent {
+    // The position of the current sentence
+    Moved(usize),
+    // The text of every sentence after an edit, and the position
+    Edited(Vec<String>, usize),
+}
+
+// A sentence together with the parsed sentences it covers
+type Piece = (Sentence, Range<usize>);
+
+// Replaces the pieces starting at `index`, so edits, splits and joins can all
+// be undone and redone the same way
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Edit {
+    index: usize,
+    before: Vec<Piece>,
+    after: Vec<Piece>,
+}
+
+#[derive(Debug)]
+pub struct Navigator {
+    sentences: Vec<Sentence>,
+    // Text of each parsed sentence as it currently reads in the source document
+    original_sentences: Vec<String>,
+    // Which parsed sentences each sentence covers; splitting and joining
+    // sentences changes this but not the document text
+    units: Vec<Range<usize>>,
+    current_index: usize,
+    has_changes: bool,
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+    source_path: Option<String>,
+    output_path: Option<String>,
+    save_options: SaveOptions,
+    mode: Mode,
+    pending_number: String,
+    search: Option<SearchQuery>,
+    bookmarks: BTreeMap<char, usize>,
+    annotations: BTreeMap<usize, Annotation>,
+    message: Option<String>,
+    // Edit sentences in $VISUAL or $EDITOR instead of the input line
+    external_editor: bool,
+    editor_requested: bool,
+    // Refuses every action that changes the text
+    read_only: bool,
+    // Parses the document again when it changes on disk
+    watcher: Option<DocumentWatcher>,
+    max_width: u16,
+    // Dimmed previous and next sentences around the current one
+    show_context: bool,
+    unit: NavigationUnit,
+    // Only sentences of this kind are stepped through
+    kind_filter: Option<SentenceKind>,
+    keymap: Keymap,
+    // Keys pressed so far of a binding of several keys, such as vim's "gg"
+    pending_keys: Vec<Key>,
+    tracker: ReadingTracker,
+    clipboard: Clipboard,
+    // Reading aloud, moving on to the next sentence when one is spoken
+    speaking: bool,
+    speaker: Speaker,
+    grammar: LanguageTool,
+    rewriter: Rewriter,
+    // The last sentence checked, as it read then, and its issues
+    checked: Option<(usize, String, Vec<GrammarIssue>)>,
+    #[cfg(feature = "spellcheck")]
+    spell_checker: Option<SpellChecker>,
+    // All the documents opened together, and which of them this is
+    documents: Vec<DocumentEntry>,
+    document_index: usize,
+    // Shown above the sentence when the document records a title or author
+    metadata: DocumentMetadata,
+    // The document to go on with once this one is closed
+    next_document: Option<usize>,
+    events: Option<Sender<ReaderEvent>>,
+    // Counts changes to the sentences, so listeners hear about each one
+    revision: usize,
+    // The position and revision listeners last heard about
+    announced: Option<(usize, usize)>,
+    should_quit: bool,
+}
+
+impl Navigator {
+    pub fn new(sentences: Vec<Sentence>) -> Self {
+        Navigator {
+            original_sentences: sentence_texts(&sentences),
+            units: (0..sentences.len()).map(|index| index..index + 1).collect(),
+            sentences,
+            current_index: 0,
+            has_changes: false,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            source_path: None,
+            output_path: None,
+            save_options: SaveOptions::default(),
+            mode: Mode::Help,
+            pending_number: String::new(),
+            search: None,
+            bookmarks: BTreeMap::new(),
+            annotations: BTreeMap::new(),
+            message: None,
+            external_editor: false,
+            read_only: false,
+            editor_requested: false,
+            watcher: None,
+            max_width: render::MAX_SENTENCE_WIDTH,
+            show_context: false,
+            unit: NavigationUnit::Sentence,
+            kind_filter: None,
+            keymap: Keymap::default(),
+            pending_keys: Vec::new(),
+            tracker: ReadingTracker::new(0),
+            clipboard: Clipboard::default(),
+            speaking: false,
+            speaker: Speaker::new(speech::default_speech_command()),
+            grammar: LanguageTool::default(),
+            rewriter: Rewriter::default(),
+            checked: None,
+            #[cfg(feature = "spellcheck")]
+            spell_checker: None,
+            documents: Vec::new(),
+            document_index: 0,
+            metadata: DocumentMetadata::default(),
+            next_document: None,
+            events: None,
+            revision: 0,
+            announced: None,
+            should_quit: false,
+        }
+    }
+
+    pub fn with_save_path(mut self, source_path: &str, output_path: Option<&str>) -> Self {
+        self.source_path = Some(source_path.to_string());
+        self.output_path = output_path.map(|path| path.to_string());
+        self
+    }
+
+    pub fn with_save_options(mut self, options: SaveOptions) -> Self {
+        self.save_options = options;
+        self
+    }
+
+    pub fn with_position(mut self, index: usize) -> Self {
+        self.current_index = index.min(self.sentences.len().saturating_sub(1));
+        self.tracker.restart(self.current_index);
+        self
+    }
+
+    // Reading done in earlier sessions, which the speed is estimated from
+    // until this session has enough of its own
+    pub fn with_kind_filter(mut self, kind: Option<SentenceKind>) -> Self {
+        self.set_kind_filter(kind);
+        self
+    }
+
+    pub fn with_reading_history(mut self, history: ReadingStats) -> Self {
+        self.tracker = self.tracker.with_history(history);
+        self
+    }
+
+    // `reload` parses the document again; it runs whenever the file at the
+    // save path's source changes
+    pub fn with_watch(
+        mut self,
+        reload: impl Fn() -> Result<Vec<Sentence>> + 'static,
+    ) -> Result<Self> {
+        if let Some(source_path) = &self.source_path {
+            self.watcher = Some(DocumentWatcher::new(source_path, Box::new(reload))?);
+        }
+        Ok(self)
+    }
+
+    // Widest the sentence box grows on wide terminals
+    pub fn with_max_width(mut self, max_width: u16) -> Self {
+        self.max_width = max_width.max(1);
+        self
+    }
+
+    pub fn with_context(mut self, show_context: bool) -> Self {
+        self.show_context = show_context;
+        self
+    }
+
+    // A text-to-speech command reading the sentence from standard input,
+    // such as "espeak-ng -s 150"
+    pub fn with_speech_command(mut self, command: &str) -> Self {
+        self.speaker = Speaker::new(command);
+        self
+    }
+
+    // Start reading aloud from the start position
+    pub fn with_speaking(mut self, speaking: bool) -> Self {
+        self.speaking = speaking;
+        self
+    }
+
+    // The LanguageTool server `g` sends the current sentence to
+    pub fn with_grammar_checker(mut self, grammar: LanguageTool) -> Self {
+        self.grammar = grammar;
+        self
+    }
+
+    // The language model Tab asks for a rewrite of the sentence being edited
+    pub fn with_rewriter(mut self, rewriter: Rewriter) -> Self {
+        self.rewriter = rewriter;
+        self
+    }
+
+    // Misspelled words are underlined, and `z` lists corrections for them
+    #[cfg(feature = "spellcheck")]
+    pub fn with_spell_checker(mut self, spell_checker: SpellChecker) -> Self {
+        self.spell_checker = Some(spell_checker);
+        self
+    }
+
+    pub fn with_keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    pub fn with_external_editor(mut self, external_editor: bool) -> Self {
+        self.external_editor = external_editor;
+        self
+    }
+
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    // Shown in the footer until the first key is pressed
+    pub fn with_message(mut self, message: String) -> Self {
+        self.message = Some(message);
+        self
+    }
+
+    // Receives the sentences once the navigator runs, and then every move
+    // and edit as it happens
+    pub fn with_events(mut self, events: Sender<ReaderEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    // The documents `]`, `[` and the document list move between, `current`
+    // being this one. Moving closes this document; see `next_document`.
+    pub fn with_documents(mut self, documents: Vec<DocumentEntry>, current: usize) -> Self {
+        self.document_index = current.min(documents.len().saturating_sub(1));
+        self.documents = documents;
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: DocumentMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    // Unsaved edits of an earlier session, made to the document as parsed.
+    // Call this before setting the position, bookmarks and annotations, which
+    // count the edited sentences. Edits to sentences that read differently
+    // now are left out.
+    pub fn with_edits(mut self, edits: &[SessionEdit]) -> Self {
+        let mut edits: Vec<&SessionEdit> = edits.iter().collect();
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.start));
+
+        // From the end, so the sentences before each edit are still as parsed
+        let mut limit = self.sentences.len();
+        let mut skipped = 0;
+        for edit in edits {
+            let units = edit.start..edit.end;
+            if units.is_empty()
+                || edit.end > limit
+                || edit.sentences.is_empty()
+                || self.original_text(units.clone()) != edit.original
+            {
+                skipped += 1;
+                continue;
+            }
+
+            let template = Sentence {
+                char_range: self.sentences[edit.start].char_range.start
+                    ..self.sentences[edit.end - 1].char_range.end,
+                ..self.sentences[edit.start].clone()
+            };
+            let replacement: Vec<Sentence> = edit
+                .sentences
+                .iter()
+                .map(|text| Sentence {
+                    text: text.clone(),
+                    ..template.clone()
+                })
+                .collect();
+            self.units.splice(
+                units.clone(),
+                std::iter::repeat_n(units.clone(), replacement.len()),
+            );
+            self.sentences.splice(units, replacement);
+            self.has_changes = true;
+            limit = edit.start;
+        }
+
+        if skipped > 0 {
+            self.message = Some(format!(
+                "{} edits of the session no longer match the document and were left out.",
+                skipped
+            ));
+        }
+        self
+    }
+
+    // Bookmarks past the end of the document are dropped
+    pub fn with_bookmarks(mut self, bookmarks: BTreeMap<char, usize>) -> Self {
+        let total = self.sentences.len();
+        self.bookmarks = bookmarks
+            .into_iter()
+            .filter(|&(_, index)| index < total)
+            .collect();
+        self
+    }
+
+    // Annotations past the end of the document are dropped
+    pub fn with_annotations(mut self, annotations: BTreeMap<usize, Annotation>) -> Self {
+        let total = self.sentences.len();
+        self.annotations = annotations
+            .into_iter()
+            .filter(|&(index, _)| index < total)
+            .collect();
+        self
+    }
+
+    pub fn position(&self) -> usize {
+        self.current_index
+    }
+
+    pub fn bookmarks(&self) -> &BTreeMap<char, usize> {
+        &self.bookmarks
+    }
+
+    pub fn annotations(&self) -> &BTreeMap<usize, Annotation> {
+        &self.annotations
+    }
+
+    pub fn sentences(&self) -> &[Sentence] {
+        &self.sentences
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.has_changes
+    }
+
+    // The document asked for when this one was closed, if it was closed to
+    // move to another one rather than to quit
+    pub fn next_document(&self) -> Option<usize> {
+        self.next_document
+    }
+
+    // The edits not saved to the document yet, for storing in a session
+    // Joins are kept even though they leave the text as it was
+    pub fn edits(&self) -> Vec<SessionEdit> {
+        self.edit_groups()
+            .into_iter()
+            .filter(|(sentences, units)| {
+                sentences.len() != units.len()
+                    || self.is_group_edited(sentences.clone(), units.clone())
+            })
+            .map(|(sentences, units)| SessionEdit {
+                start: units.start,
+                end: units.end,
+                original: self.original_text(units),
+                sentences: sentence_texts(&self.sentences[sentences]),
+            })
+            .collect()
+    }
+
+    pub fn reading_session(&self) -> ReadingStats {
+        self.tracker.session()
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        if self.sentences.is_empty() {
+            println!("No sentences found in the document.");
+            return Ok(());
+        }
+
+        // try_init also installs a panic hook that restores the terminal
+        let mut terminal = ratatui::try_init()?;
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+        result?;
+
+        if self.next_document.is_none() {
+            println!("Gooooodbye...");
+        }
+        Ok(())
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.should_quit {
+            terminal.draw(|frame| render::draw(frame, self))?;
+
+            // Resize events need no handling beyond the redraw at the top.
+            // While watching or speaking, keys are polled so changes and the
+            // end of a sentence are noticed meanwhile.
+            let polling = self.watcher.is_some() || self.speaking;
+            if (!polling || event::poll(WATCH_INTERVAL)?)
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_key(key);
+            }
+
+            if self
+                .watcher
+                .as_ref()
+                .is_some_and(|watcher| watcher.changed())
+            {
+                self.reload();
+            }
+
+            if matches!(self.mode, Mode::Rewriting { .. }) {
+                terminal.draw(|frame| render::draw(frame, self))?;
+                self.rewrite();
+            }
+
+            if std::mem::take(&mut self.editor_requested) {
+                self.edit_externally(terminal)?;
+            }
+            self.update_speech();
+            self.track_reading();
+            self.announce();
+        }
+
+        Ok(())
+    }
+
+    fn track_reading(&mut self) {
+        let previous = self.tracker.current();
+        if previous != self.current_index {
+            let words = self
+                .sentences
+                .get(previous)
+                .map_or(0, |sentence| sentence.text.split_whitespace().count());
+            self.tracker
+                .moved(self.current_index, words, Instant::now());
+        }
+    }
+
+    // Listeners that have gone away are forgotten
+    fn announce(&mut self) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        let now = (self.current_index, self.revision);
+        let event = match self.announced {
+            Some(announced) if announced == now => return,
+            Some((_, revision)) if revision == self.revision => {
+                ReaderEvent::Moved(self.current_index)
+            }
+            _ => ReaderEvent::Edited(sentence_texts(&self.sentences), self.current_index),
+        };
+        if events.send(event).is_err() {
+            self.events = None;
+        }
+        self.announced = Some(now);
+    }
+
+    // Speaks the current sentence when reading aloud has just started or the
+    // reader moved, and moves on once it has been spoken
+    fn update_speech(&mut self) {
+        if !self.speaking {
+            return;
+        }
+        if self.speaker.sentence() == Some(self.current_index) {
+            if !self.speaker.finished() {
+                return;
+            }
+            if self.current_index + 1 >= self.sentences.len() {
+                self.speaking = false;
+                self.speaker.stop();
+                self.message = Some("Finished reading aloud.".to_string());
+                return;
+            }
+            self.current_index += 1;
+        }
+
+        let Some(sentence) = self.sentences.get(self.current_index) else {
+            return;
+        };
+        if let Err(e) = self.speaker.speak(self.current_index, &sentence.text) {
+            self.speaking = false;
+            self.speaker.stop();
+            self.message = Some(format!("Could not run '{}': {}", self.speaker.command(), e));
+        }
+    }
+
+    fn toggle_speaking(&mut self) {
+        self.speaking = !self.speaking;
+        if self.speaking {
+            self.message = Some("Reading aloud.".to_string());
+        } else {
+            self.speaker.stop();
+            self.message = Some("Stopped reading aloud.".to_string());
+        }
+    }
+
+    fn reload(&mut self) {
+        let Some(watcher) = &self.watcher else {
+            return;
+        };
+        // Edits are never thrown away; saving them changes the file again
+        if self.has_changes {
+            self.message = Some(
+                "The document changed on disk; it is reloaded once your edits are saved."
+                    .to_string(),
+            );
+            return;
+        }
+
+        match (watcher.reload)() {
+            Ok(sentences) if sentences.is_empty() => {
+                self.message =
+                    Some("The changed document has no sentences; not reloaded.".to_string())
+            }
+            Ok(sentences) => self.replace_document(sentences),
+            Err(e) => self.message = Some(format!("Could not reload the document: {}", e)),
+        }
+    }
+
+    // The current sentence, bookmarks and annotations are moved to where
+    // their sentences are found in the new text
+    fn replace_document(&mut self, sentences: Vec<Sentence>) {
+        // Our own saves change the file too, usually without changing the text
+        if sentence_texts(&sentences) == sentence_texts(&self.sentences) {
+            return;
+        }
+
+        let relocate = |index: usize| {
+            let text = self
+                .sentences
+                .get(index)
+                .map_or("", |sentence| sentence.text.as_str());
+            watch::relocate(text, index, &sentences)
+        };
+        self.current_index = relocate(self.current_index);
+        for index in self.bookmarks.values_mut() {
+            *index = relocate(*index);
+        }
+        let mut annotations: BTreeMap<usize, Annotation> = BTreeMap::new();
+        for (index, mut annotation) in std::mem::take(&mut self.annotations) {
+            let index = watch::relocate(&annotation.sentence, index, &sentences);
+            annotation.sentence = sentences[index].text.clone();
+            match annotations.entry(index) {
+                Entry::Vacant(entry) => {
+                    entry.insert(annotation);
+                }
+                Entry::Occupied(mut entry) => entry.get_mut().merge(annotation),
+            }
+        }
+        self.annotations = annotations;
+
+        self.original_sentences = sentence_texts(&sentences);
+        self.units = (0..sentences.len()).map(|index| index..index + 1).collect();
+        self.sentences = sentences;
+        self.revision += 1;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        // Lists hold positions in the old sentences
+        if matches!(
+            self.mode,
+            Mode::Outline(_) | Mode::Annotations(_) | Mode::Bookmarks(_)
+        ) {
+            self.mode = Mode::Normal;
+        }
+        self.message = Some("The document changed on disk and was reloaded.".to_string());
+    }
+
+    fn file_name(&self) -> &str {
+        self.source_path
+            .as_deref()
+            .and_then(|path| Path::new(path).file_name())
+            .and_then(|name| name.to_str())
+            .unwrap_or("ODT Navigator")
+    }
+
+    fn is_edited(&self, index: usize) -> bool {
+        self.edit_groups()
+            .into_iter()
+            .find(|(sentences, _)| sentences.contains(&index))
+            .is_some_and(|(sentences, units)| self.is_group_edited(sentences, units))
+    }
+
+    // Runs of sentences covering the same parsed sentences. Each run is
+    // compared as a whole, so splitting or joining alone changes nothing.
+    fn edit_groups(&self) -> Vec<(Range<usize>, Range<usize>)> {
+        let mut groups: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+        for (index, units) in self.units.iter().enumerate() {
+            match groups.last_mut() {
+                Some((sentences, group_units)) if units.start < group_units.end => {
+                    sentences.end = index + 1;
+                    group_units.end = group_units.end.max(units.end);
+                }
+                _ => groups.push((index..index + 1, units.clone())),
+            }
+        }
+        groups
+    }
+
+    fn is_group_edited(&self, sentences: Range<usize>, units: Range<usize>) -> bool {
+        self.current_text(sentences) != self.original_text(units)
+    }
+
+    fn current_text(&self, sentences: Range<usize>) -> String {
+        sentence_texts(&self.sentences[sentences]).join(" ")
+    }
+
+    // Units emptied by an earlier save have their text stored in the first
+    // unit of their group
+    fn original_text(&self, units: Range<usize>) -> String {
+        self.original_sentences[units]
+            .iter()
+            .filter(|text| !text.is_empty())
+            .map(|text| text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.next_document = None;
+            self.request_quit();
+            return;
+        }
+
+        match self.mode {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Help => self.mode = Mode::Normal,
+            Mode::Edit(_) => self.handle_edit_key(key),
+            Mode::RewriteInstruction { .. } => self.handle_rewrite_instruction_key(key),
+            Mode::Rewriting { .. } => {}
+            Mode::RewriteSuggestion { .. } => self.handle_rewrite_suggestion_key(key),
+            Mode::Split(_) => self.handle_split_key(key),
+            Mode::Insert { .. } => self.handle_insert_key(key),
+            Mode::Outline(_) => self.handle_outline_key(key),
+            Mode::Search { .. } => self.handle_search_key(key),
+            Mode::Concordance(_) => self.handle_concordance_key(key),
+            Mode::Spelling(..) => self.handle_spelling_key(key),
+            Mode::Replace(_) => self.handle_replace_key(key),
+            Mode::ConfirmReplace(..) => self.handle_confirm_replace_key(key),
+            Mode::Note(_) | Mode::Tags(_) => self.handle_annotation_key(key),
+            Mode::Annotations(_) => self.handle_annotation_list_key(key),
+            Mode::SetBookmark | Mode::JumpToBookmark => self.handle_bookmark_key(key),
+            Mode::Bookmarks(_) => self.handle_bookmark_list_key(key),
+            Mode::Documents(_) => self.handle_document_list_key(key),
+            Mode::ConfirmQuit => self.handle_confirm_quit_key(key),
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) {
+        // Digits build up a sentence number that Enter jumps to
+        if !self.pending_number.is_empty() {
+            match key.code {
+                KeyCode::Char(c) if c.is_ascii_digit() => {
+                    self.pending_number.push(c);
+                    return;
+                }
+                KeyCode::Backspace => {
+                    self.pending_number.pop();
+                    return;
+                }
+                KeyCode::Enter => {
+                    let number = std::mem::take(&mut self.pending_number);
+                    self.jump_to(number.parse().unwrap_or(0));
+                    return;
+                }
+                KeyCode::Esc => {
+                    self.pending_number.clear();
+                    return;
+                }
+                _ => self.pending_number.clear(),
+            }
+        }
+
+        if self.search.is_some() && key.code == KeyCode::Esc && self.pending_keys.is_empty() {
+            self.search = None;
+            return;
+        }
+        if let KeyCode::Char(c) = key.code
+            && c.is_ascii_digit()
+            && key.modifiers.is_empty()
+            && self.pending_keys.is_empty()
+        {
+            self.pending_number.push(c);
+            return;
+        }
+
+        self.pending_keys.push(Key::from_event(&key));
+        let actions = match self.keymap.lookup(&self.pending_keys) {
+            Lookup::Actions(actions) => actions,
+            Lookup::Prefix => return,
+            Lookup::None => {
+                // A key that breaks off a sequence counts on its own
+                let broke_off = self.pending_keys.len() > 1;
+                self.pending_keys.clear();
+                if broke_off {
+                    self.handle_normal_key(key);
+                }
+                return;
+            }
+        };
+        self.pending_keys.clear();
+
+        // While a search is active the match steps take precedence
+        let searching = self.search.is_some();
+        let action = actions
+            .iter()
+            .find(|action| action.steps_through_matches() == searching)
+            .or(actions.first());
+        if let Some(&action) = action {
+            self.perform(action);
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        if self.read_only && action.changes_text() {
+            self.message = Some("The document is open read-only.".to_string());
+            return;
+        }
+        match action {
+            Action::Next => self.step(true),
+            Action::Previous => self.step(false),
+            Action::PageDown => self.move_by(PAGE_SIZE),
+            Action::PageUp => self.move_back_by(PAGE_SIZE),
+            Action::First => self.current_index = 0,
+            Action::Last => self.current_index = self.sentences.len() - 1,
+            Action::Outline => self.open_outline(),
+            Action::Context => self.show_context = !self.show_context,
+            Action::Unit => {
+                self.unit = match self.unit {
+                    NavigationUnit::Sentence => NavigationUnit::Paragraph,
+                    NavigationUnit::Paragraph => NavigationUnit::Section,
+                    NavigationUnit::Section => NavigationUnit::Sentence,
+                };
+                self.message = Some(format!("Moving by {}.", self.unit.name()));
+            }
+            Action::Kind => self.cycle_kind_filter(),
+            Action::SetBookmark => self.mode = Mode::SetBookmark,
+            Action::JumpToBookmark => self.mode = Mode::JumpToBookmark,
+            Action::Bookmarks => self.open_bookmarks(),
+            Action::Note => {
+                let annotation = self.annotations.get(&self.current_index);
+                self.mode = Mode::Note(annotation.map(|a| a.note.clone()).unwrap_or_default());
+            }
+            Action::Tags => {
+                let annotation = self.annotations.get(&self.current_index);
+                self.mode = Mode::Tags(annotation.map(|a| a.tags.join(" ")).unwrap_or_default());
+            }
+            Action::Annotations => self.open_annotations(),
+            Action::Search => {
+                self.mode = Mode::Search {
+                    input: String::new(),
+                    is_regex: false,
+                };
+            }
+            Action::NextMatch => self.jump_to_match(true),
+            Action::PreviousMatch => self.jump_to_match(false),
+            Action::Concordance => self.open_concordance(),
+            Action::Replace => self.mode = Mode::Replace("s/".to_string()),
+            Action::Edit if self.external_editor => self.editor_requested = true,
+            Action::Edit => {
+                self.mode = Mode::Edit(self.sentences[self.current_index].text.clone());
+            }
+            Action::ExternalEdit => self.editor_requested = true,
+            Action::Check => self.check_grammar(),
+            Action::Spelling => self.open_spelling(),
+            Action::Speak => self.toggle_speaking(),
+            Action::Yank => self.yank(false),
+            Action::YankReference => self.yank(true),
+            Action::Split => self.start_split(),
+            Action::Join => self.join_with_next(),
+            Action::Delete => self.delete_current(),
+            Action::InsertBefore | Action::InsertAfter => {
+                self.mode = Mode::Insert {
+                    text: String::new(),
+                    after: action == Action::InsertAfter,
+                };
+            }
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Save => self.save(),
+            Action::Diff => self.write_diff(),
+            Action::NextDocument => self.switch_document(self.document_index + 1),
+            Action::PreviousDocument => match self.document_index.checked_sub(1) {
+                Some(index) => self.switch_document(index),
+                None if self.documents.len() > 1 => {
+                    self.message = Some("This is the first document.".to_string())
+                }
+                None => self.switch_document(0),
+            },
+            Action::Documents => self.open_documents(),
+            Action::Help => self.mode = Mode::Help,
+            Action::Quit => self.request_quit(),
+        }
+    }
+
+    fn handle_edit_key(&mut self, key: KeyEvent) {
+        let Mode::Edit(buffer) = &mut self.mode else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Char(c) => buffer.push(c),
+            KeyCode::Backspace => pop_grapheme(buffer),
+            KeyCode::Enter => {
+                let new_text = buffer.trim().to_string();
+                self.mode = Mode::Normal;
+                self.replace_current(new_text);
+            }
+            KeyCode::Tab => {
+                self.mode = Mode::RewriteInstruction {
+                    draft: buffer.clone(),
+                    instruction: String::new(),
+                }
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_rewrite_instruction_key(&mut self, key: KeyEvent) {
+        let Mode::RewriteInstruction { draft, instruction } = &mut self.mode else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Char(c) => instruction.push(c),
+            KeyCode::Backspace => pop_grapheme(instruction),
+            KeyCode::Enter if !instruction.trim().is_empty() => {
+                self.mode = Mode::Rewriting {
+                    draft: std::mem::take(draft),
+                    instruction: instruction.trim().to_string(),
+                }
+            }
+            KeyCode::Enter | KeyCode::Esc => self.mode = Mode::Edit(std::mem::take(draft)),
+            _ => {}
+        }
+    }
+
+    fn rewrite(&mut self) {
+        let Mode::Rewriting { draft, instruction } = &mut self.mode else {
+            return;
+        };
+        let suggestion = self
+            .rewriter
+            .rewrite(draft, instruction)
+            .map_err(|e| e.to_string());
+        self.mode = Mode::RewriteSuggestion {
+            draft: std::mem::take(draft),
+            suggestion,
+        };
+    }
+
+    // Accepting records the suggestion as an edit; modifying edits it further;
+    // rejecting goes back to the draft
+    fn handle_rewrite_suggestion_key(&mut self, key: KeyEvent) {
+        let Mode::RewriteSuggestion { draft, suggestion } = &mut self.mode else {
+            return;
+        };
+
+        match (key.code, suggestion) {
+            (KeyCode::Enter | KeyCode::Char('y'), Ok(suggestion)) => {
+                let new_text = std::mem::take(suggestion);
+                self.mode = Mode::Normal;
+                self.replace_current(new_text);
+            }
+            (KeyCode::Char('e'), Ok(suggestion)) => {
+                self.mode = Mode::Edit(std::mem::take(suggestion))
+            }
+            (KeyCode::Esc | KeyCode::Char('n'), _) | (KeyCode::Enter, Err(_)) => {
+                self.mode = Mode::Edit(std::mem::take(draft))
+            }
+            _ => {}
+        }
+    }
+
+    fn check_grammar(&mut self) {
+        let index = self.current_index;
+        let text = self.sentences[index].text.clone();
+        match self.grammar.check(std::slice::from_ref(&text)) {
+            Ok(mut issues) => {
+                let issues = issues.pop().unwrap_or_default();
+                self.message = Some(match issues.len() {
+                    0 => "No grammar or style issues found.".to_string(),
+                    1 => "1 grammar or style issue.".to_string(),
+                    count => format!("{} grammar or style issues.", count),
+                });
+                self.checked = Some((index, text, issues));
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    // Issues found in the current sentence, unless it was edited since
+    pub(super) fn grammar_issues(&self) -> &[GrammarIssue] {
+        match &self.checked {
+            Some((index, text, issues))
+                if *index == self.current_index && *text == self.sentences[*index].text =>
+            {
+                issues
+            }
+            _ => &[],
+        }
+    }
+
+    // Byte ranges of the misspelled words of the current sentence
+    pub(super) fn misspellings(&self) -> Vec<Range<usize>> {
+        #[cfg(feature = "spellcheck")]
+        if let Some(checker) = &self.spell_checker {
+            return checker
+                .misspellings(&self.sentences[self.current_index].text)
+                .into_iter()
+                .map(|misspelling| misspelling.range)
+                .collect();
+        }
+        Vec::new()
+    }
+
+    #[cfg(feature = "spellcheck")]
+    fn open_spelling(&mut self) {
+        let Some(checker) = &self.spell_checker else {
+            self.message = Some(
+                "No spelling dictionary found; set one under [spelling] in the config file."
+                    .to_string(),
+            );
+            return;
+        };
+
+        let misspellings = checker.misspellings(&self.sentences[self.current_index].text);
+        let fixes: Vec<(Range<usize>, String)> = misspellings
+            .iter()
+            .flat_map(|misspelling| {
+                checker
+                    .suggestions(&misspelling.word)
+                    .into_iter()
+                    .take(MAX_SUGGESTIONS)
+                    .map(|suggestion| (misspelling.range.clone(), suggestion))
+            })
+            .collect();
+
+        if misspellings.is_empty() {
+            self.message = Some("No misspelled words.".to_string());
+        } else if fixes.is_empty() {
+            let words: Vec<&str> = misspellings
+                .iter()
+                .map(|misspelling| misspelling.word.as_str())
+                .collect();
+            self.message = Some(format!("No suggestions for {}.", words.join(", ")));
+        } else {
+            self.mode = Mode::Spelling(fixes, 0);
+        }
+    }
+
+    #[cfg(not(feature = "spellcheck"))]
+    fn open_spelling(&mut self) {
+        self.message = Some("This build of sentencer has no spell checking.".to_string());
+    }
+
+    fn handle_spelling_key(&mut self, key: KeyEvent) {
+        let Mode::Spelling(fixes, selected) = &mut self.mode else {
+            return;
+        };
+        let last = fixes.len().saturating_sub(1);
+
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => *selected = (*selected + 1).min(last),
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::Enter if self.read_only => {
+                self.mode = Mode::Normal;
+                self.message = Some("The document is open read-only.".to_string());
+            }
+            KeyCode::Enter => {
+                let fix = fixes.get(*selected).cloned();
+                self.mode = Mode::Normal;
+                if let Some((range, suggestion)) = fix {
+                    let mut text = self.sentences[self.current_index].text.clone();
+                    text.replace_range(range, &suggestion);
+                    self.replace_current(text);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    // With `reference` the sentence is followed by where it comes from, for
+    // quoting it elsewhere
+    fn yank(&mut self, reference: bool) {
+        let index = self.current_index;
+        let mut text = self.sentences[index].text.clone();
+        if reference {
+            // Spreadsheet cells are referred to as spreadsheets name them
+            let sentence = &self.sentences[index];
+            let place = match (&sentence.cell, sentence.slide) {
+                (Some(cell), _) => cell.reference(),
+                (None, Some(slide)) => format!("slide {}, sentence {}", slide, index + 1),
+                (None, None) => format!("sentence {}", index + 1),
+            };
+            text = format!("{} ({}, {})", text, self.file_name(), place);
+        }
+
+        self.message = Some(match self.clipboard.copy(&text) {
+            Ok(()) if reference => format!("Copied sentence {} with its reference.", index + 1),
+            Ok(()) => format!("Copied sentence {}.", index + 1),
+            Err(e) => format!("Could not copy to the clipboard: {}", e),
+        });
+    }
+
+    fn replace_current(&mut self, new_text: String) {
+        let index = self.current_index;
+        if !new_text.is_empty() && new_text != self.sentences[index].text {
+            let (sentence, units) = self.piece(index);
+            let edited = Sentence {
+                text: new_text,
+                ..sentence.clone()
+            };
+            self.record(Edit {
+                index,
+                before: vec![(sentence, units.clone())],
+                after: vec![(edited, units)],
+            });
+        }
+    }
+
+    // The terminal is handed over to the editor and taken back afterwards
+    fn edit_externally(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let editor = editor::editor_command();
+        ratatui::restore();
+        let result = editor::edit_text(&editor, &self.sentences[self.current_index].text);
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        terminal.clear()?;
+
+        match result {
+            Ok(Some(new_text)) => self.replace_current(new_text),
+            Ok(None) => self.message = Some("Edit cancelled.".to_string()),
+            Err(e) => self.message = Some(format!("Could not run '{}': {}", editor, e)),
+        }
+        Ok(())
+    }
+
+    // Indices of the heading sentences, in document order
+    fn headings(&self) -> Vec<usize> {
+        self.sentences
+            .iter()
+            .enumerate()
+            .filter(|(_, sentence)| sentence.kind == SentenceKind::Heading)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn open_outline(&mut self) {
+        let headings = self.headings();
+        if headings.is_empty() {
+            self.message = Some("This document has no headings.".to_string());
+            return;
+        }
+
+        // Preselect the heading the current sentence belongs to
+        let selected = headings
+            .iter()
+            .rposition(|&index| index <= self.current_index)
+            .unwrap_or(0);
+        self.mode = Mode::Outline(selected);
+    }
+
+    fn handle_outline_key(&mut self, key: KeyEvent) {
+        let Mode::Outline(selected) = self.mode else {
+            return;
+        };
+        let headings = self.headings();
+        let last = headings.len().saturating_sub(1);
+
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.mode = Mode::Outline((selected + 1).min(last))
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.mode = Mode::Outline(selected.saturating_sub(1))
+            }
+            KeyCode::Home => self.mode = Mode::Outline(0),
+            KeyCode::End => self.mode = Mode::Outline(last),
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                if let Some(&heading) = headings.get(selected) {
+                    self.current_index = self.first_under_heading(heading);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('O') | KeyCode::Char('q') => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_bookmark_key(&mut self, key: KeyEvent) {
+        let setting = self.mode == Mode::SetBookmark;
+        self.mode = Mode::Normal;
+        let KeyCode::Char(name) = key.code else {
+            return;
+        };
+        if !name.is_alphanumeric() {
+            self.message = Some(format!(
+                "Bookmarks are named by a letter or digit, not '{}'.",
+                name
+            ));
+            return;
+        }
+
+        if setting {
+            self.bookmarks.insert(name, self.current_index);
+            self.message = Some(format!(
+                "Bookmark '{}' set at sentence {}.",
+                name,
+                self.current_index + 1
+            ));
+            return;
+        }
+        match self.bookmarks.get(&name) {
+            Some(&index) => self.current_index = index,
+            None => self.message = Some(format!("No bookmark '{}'.", name)),
+        }
+    }
+
+    fn open_bookmarks(&mut self) {
+        if self.bookmarks.is_empty() {
+            self.message = Some("No bookmarks yet; set one with m and a letter.".to_string());
+            return;
+        }
+        self.mode = Mode::Bookmarks(0);
+    }
+
+    fn handle_bookmark_list_key(&mut self, key: KeyEvent) {
+        let Mode::Bookmarks(selected) = self.mode else {
+            return;
+        };
+        let last = self.bookmarks.len().saturating_sub(1);
+        let name = self.bookmarks.keys().nth(selected).copied();
+
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.mode = Mode::Bookmarks((selected + 1).min(last))
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.mode = Mode::Bookmarks(selected.saturating_sub(1))
+            }
+            KeyCode::Home => self.mode = Mode::Bookmarks(0),
+            KeyCode::End => self.mode = Mode::Bookmarks(last),
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                if let Some(index) = name.and_then(|name| self.bookmarks.get(&name)) {
+                    self.current_index = *index;
+                }
+            }
+            KeyCode::Delete | KeyCode::Char('D') => {
+                if let Some(name) = name {
+                    self.bookmarks.remove(&name);
+                }
+                self.mode = if self.bookmarks.is_empty() {
+                    Mode::Normal
+                } else {
+                    Mode::Bookmarks(selected.min(self.bookmarks.len() - 1))
+                };
+            }
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    // An empty note or tag list removes it; the annotation goes once both are gone
+    fn handle_annotation_key(&mut self, key: KeyEvent) {
+        let (Mode::Note(buffer) | Mode::Tags(buffer)) = &mut self.mode else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Char(c) => buffer.push(c),
+            KeyCode::Backspace => pop_grapheme(buffer),
+            KeyCode::Enter => {
+                let mode = std::mem::replace(&mut self.mode, Mode::Normal);
+                let annotation = self.annotations.entry(self.current_index).or_default();
+                annotation.sentence = self.sentences[self.current_index].text.clone();
+                match mode {
+                    Mode::Note(note) => annotation.note = note.trim().to_string(),
+                    Mode::Tags(tags) => annotation.tags = Annotation::parse_tags(&tags),
+                    _ => {}
+                }
+                if annotation.is_empty() {
+                    self.annotations.remove(&self.current_index);
+                }
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn open_annotations(&mut self) {
+        if self.annotations.is_empty() {
+            self.message =
+                Some("No annotations yet; add a note with a or tags with t.".to_string());
+            return;
+        }
+        let selected = self
+            .annotations
+            .keys()
+            .rposition(|&index| index <= self.current_index)
+            .unwrap_or(0);
+        self.mode = Mode::Annotations(selected);
+    }
+
+    fn handle_annotation_list_key(&mut self, key: KeyEvent) {
+        let Mode::Annotations(selected) = self.mode else {
+            return;
+        };
+        let last = self.annotations.len().saturating_sub(1);
+        let index = self.annotations.keys().nth(selected).copied();
+
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.mode = Mode::Annotations((selected + 1).min(last))
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.mode = Mode::Annotations(selected.saturating_sub(1))
+            }
+            KeyCode::Home => self.mode = Mode::Annotations(0),
+            KeyCode::End => self.mode = Mode::Annotations(last),
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                if let Some(index) = index {
+                    self.current_index = index;
+                }
+            }
+            KeyCode::Delete | KeyCode::Char('D') => {
+                if let Some(index) = index {
+                    self.annotations.remove(&index);
+                }
+                self.mode = if self.annotations.is_empty() {
+                    Mode::Normal
+                } else {
+                    Mode::Annotations(selected.min(self.annotations.len() - 1))
+                };
+            }
+            KeyCode::Char('w') => self.write_annotations(),
+            KeyCode::Esc | KeyCode::Char('A') | KeyCode::Char('q') => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn write_annotations(&mut self) {
+        let Some(source_path) = self.source_path.clone() else {
+            self.message = Some("No source document to name the annotations after.".to_string());
+            return;
+        };
+
+        let path = format!("{}.annotations.md", source_path);
+        let result = fs::File::create(&path)
+            .map_err(Into::into)
+            .and_then(|mut file| {
+                annotation::export_annotations(
+                    &mut file,
+                    &self.annotations,
+                    self.file_name(),
+                    AnnotationFormat::Markdown,
+                )
+            });
+        self.message = Some(match result {
+            Ok(()) => format!("Wrote annotations to {}", path),
+            Err(e) => format!("Error writing '{}': {}", path, e),
+        });
+    }
+
+    // The sentence after the heading, unless the section is empty
+    fn first_under_heading(&self, heading: usize) -> usize {
+        match self.sentences.get(heading + 1) {
+            Some(next) if next.kind != SentenceKind::Heading => heading + 1,
+            _ => heading,
+        }
+    }
+
+    fn start_split(&mut self) {
+        let words: Vec<&str> = self.sentences[self.current_index]
+            .text
+            .split_whitespace()
+            .collect();
+        if words.len() < 2 {
+            self.message = Some("A single word cannot be split.".to_string());
+            return;
+        }
+
+        // Start after the first word that looks like a sentence end
+        let position = words[..words.len() - 1]
+            .iter()
+            .position(|word| word.ends_with(['.', '!', '?', ';', ':']))
+            .map_or(words.len() / 2, |index| index + 1);
+        self.mode = Mode::Split(position);
+    }
+
+    fn handle_split_key(&mut self, key: KeyEvent) {
+        let Mode::Split(position) = self.mode else {
+            return;
+        };
+        let word_count = self.sentences[self.current_index]
+            .text
+            .split_whitespace()
+            .count();
+
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.mode = Mode::Split(position.saturating_sub(1).max(1));
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.mode = Mode::Split((position + 1).min(word_count - 1));
+            }
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                self.split_current(position);
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn split_current(&mut self, position: usize) {
+        let index = self.current_index;
+        let (sentence, units) = self.piece(index);
+        let words: Vec<&str> = sentence.text.split_whitespace().collect();
+        let (first_text, second_text) = (words[..position].join(" "), words[position..].join(" "));
+
+        // Offsets can only be estimated once the text has been edited
+        let range = sentence.char_range.clone();
+        let middle = (range.start + first_text.chars().count()).min(range.end);
+        let first = Sentence {
+            text: first_text,
+            char_range: range.start..middle,
+            ..sentence.clone()
+        };
+        let second = Sentence {
+            text: second_text,
+            char_range: (middle + 1).min(range.end)..range.end,
+            ..sentence.clone()
+        };
+
+        self.record(Edit {
+            index,
+            before: vec![(sentence, units.clone())],
+            after: vec![(first, units.clone()), (second, units)],
+        });
+        self.message = Some("Split sentence.".to_string());
+    }
+
+    fn join_with_next(&mut self) {
+        let index = self.current_index;
+        if index + 1 >= self.sentences.len() {
+            self.message = Some("There is no next sentence to join.".to_string());
+            return;
+        }
+
+        let (first, first_units) = self.piece(index);
+        let (second, second_units) = self.piece(index + 1);
+        let joined = Sentence {
+            text: format!("{} {}", first.text, second.text),
+            char_range: first.char_range.start..second.char_range.end,
+            ..first.clone()
+        };
+        let units = first_units.start..second_units.end;
+
+        self.record(Edit {
+            index,
+            before: vec![(first, first_units), (second, second_units)],
+            after: vec![(joined, units)],
+        });
+        self.message = Some("Joined with the next sentence.".to_string());
+    }
+
+    // The parsed sentences of the deleted sentence go to the one before it,
+    // or after it at the start, so the two read as one edited group and
+    // saving takes the text out of the document
+    fn delete_current(&mut self) {
+        let index = self.current_index;
+        if self.sentences.len() < 2 {
+            self.message = Some("The only sentence cannot be deleted.".to_string());
+            return;
+        }
+
+        let (deleted, deleted_units) = self.piece(index);
+        let edit = match index.checked_sub(1) {
+            Some(previous) => {
+                let (kept, kept_units) = self.piece(previous);
+                let units = kept_units.start..deleted_units.end;
+                Edit {
+                    index: previous,
+                    before: vec![(kept.clone(), kept_units), (deleted, deleted_units)],
+                    after: vec![(kept, units)],
+                }
+            }
+            None => {
+                let (kept, kept_units) = self.piece(index + 1);
+                let units = deleted_units.start..kept_units.end;
+                Edit {
+                    index,
+                    before: vec![(deleted, deleted_units), (kept.clone(), kept_units)],
+                    after: vec![(kept, units)],
+                }
+            }
+        };
+
+        self.record(edit);
+        // The sentence after the deleted one takes its place
+        self.current_index = index.min(self.sentences.len() - 1);
+        self.message = Some("Deleted sentence.".to_string());
+    }
+
+    fn handle_insert_key(&mut self, key: KeyEvent) {
+        let Mode::Insert { text, after } = &mut self.mode else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Backspace => pop_grapheme(text),
+            KeyCode::Enter => {
+                let (text, after) = (text.trim().to_string(), *after);
+                self.mode = Mode::Normal;
+                if !text.is_empty() {
+                    self.insert_sentence(text, after);
+                }
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    // Like the halves of a split, the new sentence covers the same parsed
+    // sentences as the current one, so it is saved into the same paragraph
+    fn insert_sentence(&mut self, text: String, after: bool) {
+        let index = self.current_index;
+        let (sentence, units) = self.piece(index);
+        let offset = match after {
+            true => sentence.char_range.end,
+            false => sentence.char_range.start,
+        };
+        let inserted = Sentence {
+            text,
+            char_range: offset..offset,
+            ..sentence.clone()
+        };
+
+        let pieces = match after {
+            true => vec![(sentence.clone(), units.clone()), (inserted, units.clone())],
+            false => vec![(inserted, units.clone()), (sentence.clone(), units.clone())],
+        };
+        self.record(Edit {
+            index,
+            before: vec![(sentence, units)],
+            after: pieces,
+        });
+        self.current_index = index + usize::from(after);
+        self.message = Some("Inserted sentence.".to_string());
+    }
+
+    fn piece(&self, index: usize) -> Piece {
+        (self.sentences[index].clone(), self.units[index].clone())
+    }
+
+    fn record(&mut self, edit: Edit) {
+        self.apply(&edit, true);
+        self.undo_stack.push(edit);
+        self.redo_stack.clear();
+    }
+
+    fn undo(&mut self) {
+        match self.undo_stack.pop() {
+            Some(edit) => {
+                self.apply(&edit, false);
+                self.redo_stack.push(edit);
+                self.message = Some("Undid edit.".to_string());
+            }
+            None => self.message = Some("Nothing to undo.".to_string()),
+        }
+    }
+
+    fn redo(&mut self) {
+        match self.redo_stack.pop() {
+            Some(edit) => {
+                self.apply(&edit, true);
+                self.undo_stack.push(edit);
+                self.message = Some("Redid edit.".to_string());
+            }
+            None => self.message = Some("Nothing to redo.".to_string()),
+        }
+    }
+
+    // Applies an edit forwards or backwards and shows the affected sentence
+    fn apply(&mut self, edit: &Edit, forward: bool) {
+        let (removed, inserted) = if forward {
+            (&edit.before, &edit.after)
+        } else {
+            (&edit.after, &edit.before)
+        };
+        let range = edit.index..edit.index + removed.len();
+
+        // Bookmarks follow their sentence when sentences before it are split
+        // or joined; one on a joined sentence moves to the joined result
+        let shift = |index: usize| {
+            if index >= range.end {
+                index + inserted.len() - removed.len()
+            } else if index >= range.start {
+                range.start + (index - range.start).min(inserted.len() - 1)
+            } else {
+                index
+            }
+        };
+        for index in self.bookmarks.values_mut() {
+            *index = shift(*index);
+        }
+        let mut annotations = BTreeMap::new();
+        for (index, annotation) in std::mem::take(&mut self.annotations) {
+            match annotations.entry(shift(index)) {
+                Entry::Vacant(entry) => {
+                    entry.insert(annotation);
+                }
+                Entry::Occupied(mut entry) => entry.get_mut().merge(annotation),
+            }
+        }
+        self.annotations = annotations;
+
+        self.sentences.splice(
+            range.clone(),
+            inserted.iter().map(|(sentence, _)| sentence.clone()),
+        );
+        self.units
+            .splice(range, inserted.iter().map(|(_, units)| units.clone()));
+        self.revision += 1;
+        self.current_index = edit.index;
+        for index in edit.index..edit.index + inserted.len() {
+            if let Some(annotation) = self.annotations.get_mut(&index) {
+                annotation.sentence = self.sentences[index].text.clone();
+            }
+        }
+
+        // Undoing every edit leaves nothing to save
+        self.has_changes = self
+            .edit_groups()
+            .into_iter()
+            .any(|(sentences, units)| self.is_group_edited(sentences, units));
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        let Mode::Search { input, is_regex } = &mut self.mode else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => pop_grapheme(input),
+            KeyCode::Tab => *is_regex = !*is_regex,
+            KeyCode::Enter => {
+                let (pattern, is_regex) = (input.clone(), *is_regex);
+                self.mode = Mode::Normal;
+                if pattern.is_empty() {
+                    self.search = None;
+                    return;
+                }
+                match SearchQuery::new(&pattern, is_regex) {
+                    Ok(query) => {
+                        self.search = Some(query);
+                        self.jump_to_match(true);
+                    }
+                    Err(e) => self.message = Some(format!("Invalid search pattern: {}", e)),
+                }
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    // Lines of the current search's matches with `context` characters on
+    // either side
+    fn concordance(&self, context: usize) -> Vec<ConcordanceLine> {
+        match &self.search {
+            Some(query) => query.concordance(&sentence_texts(&self.sentences), context),
+            None => Vec::new(),
+        }
+    }
+
+    fn open_concordance(&mut self) {
+        let Some(query) = &self.search else {
+            self.message = Some("Search first to list the matches in context.".to_string());
+            return;
+        };
+        let lines = self.concordance(0);
+        if lines.is_empty() {
+            self.message = Some(format!("Pattern not found: {}", query.pattern()));
+            return;
+        }
+
+        // Preselect the first match at or after the current sentence
+        let selected = lines
+            .iter()
+            .position(|line| line.sentence >= self.current_index)
+            .unwrap_or(0);
+        self.mode = Mode::Concordance(selected);
+    }
+
+    fn handle_concordance_key(&mut self, key: KeyEvent) {
+        let Mode::Concordance(selected) = self.mode else {
+            return;
+        };
+        let lines = self.concordance(0);
+        let last = lines.len().saturating_sub(1);
+
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.mode = Mode::Concordance((selected + 1).min(last))
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.mode = Mode::Concordance(selected.saturating_sub(1))
+            }
+            KeyCode::Home => self.mode = Mode::Concordance(0),
+            KeyCode::End => self.mode = Mode::Concordance(last),
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                if let Some(line) = lines.get(selected) {
+                    self.current_index = line.sentence;
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('C') | KeyCode::Char('q') => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_replace_key(&mut self, key: KeyEvent) {
+        let Mode::Replace(input) = &mut self.mode else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => pop_grapheme(input),
+            KeyCode::Enter => {
+                let command = input.clone();
+                self.mode = Mode::Normal;
+                let substitution = match Substitution::parse(&command) {
+                    Ok(substitution) => substitution,
+                    Err(e) => {
+                        self.message = Some(e.to_string());
+                        return;
+                    }
+                };
+                let changes = substitution.changes(&sentence_texts(&self.sentences));
+                if changes.is_empty() {
+                    self.message = Some(format!("Pattern not found: {}", substitution.pattern()));
+                } else {
+                    self.mode = Mode::ConfirmReplace(changes, 0);
+                }
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_confirm_replace_key(&mut self, key: KeyEvent) {
+        let Mode::ConfirmReplace(changes, selected) = &mut self.mode else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                *selected = (*selected + 1).min(changes.len() - 1)
+            }
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                let changes = std::mem::take(changes);
+                self.mode = Mode::Normal;
+                self.replace_sentences(changes);
+            }
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Char('q') => {
+                self.mode = Mode::Normal;
+                self.message = Some("Replacement cancelled.".to_string());
+            }
+            _ => {}
+        }
+    }
+
+    // All the sentences from the first changed one to the last are replaced
+    // in one edit, so a single undo reverts the whole replacement
+    fn replace_sentences(&mut self, changes: Vec<(usize, String)>) {
+        let (Some(&(first, _)), Some(&(last, _))) = (changes.first(), changes.last()) else {
+            return;
+        };
+        let before: Vec<Piece> = (first..=last).map(|index| self.piece(index)).collect();
+        let mut after = before.clone();
+        let count = changes.len();
+        for (index, text) in changes {
+            after[index - first].0.text = text;
+        }
+
+        self.record(Edit {
+            index: first,
+            before,
+            after,
+        });
+        self.message = Some(format!(
+            "Replaced in {} sentence{}.",
+            count,
+            if count == 1 { "" } else { "s" }
+        ));
+    }
+
+    fn jump_to_match(&mut self, forward: bool) {
+        let Some(query) = &self.search else {
+            return;
+        };
+
+        match query.next_match(&self.sentences, self.current_index, forward) {
+            Some(index) => self.current_index = index,
+            None => self.message = Some(format!("Pattern not found: {}", query.pattern())),
+        }
+    }
+
+    fn handle_confirm_quit_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                self.mode = Mode::Normal;
+                self.save();
+                self.should_quit = !self.has_changes;
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') => self.should_quit = true,
+            _ => {
+                self.mode = Mode::Normal;
+                self.next_document = None;
+            }
+        }
+    }
+
+    // Other documents are opened by closing this one, after the same
+    // question about unsaved changes as quitting
+    fn switch_document(&mut self, index: usize) {
+        if self.documents.len() < 2 {
+            self.message =
+                Some("Only one document is open; name several to move between them.".to_string());
+            return;
+        }
+        if index >= self.documents.len() {
+            self.message = Some("This is the last document.".to_string());
+            return;
+        }
+        if index != self.document_index {
+            self.next_document = Some(index);
+            self.request_quit();
+        }
+    }
+
+    fn open_documents(&mut self) {
+        if self.documents.len() < 2 {
+            self.message =
+                Some("Only one document is open; name several to move between them.".to_string());
+            return;
+        }
+        self.mode = Mode::Documents(self.document_index);
+    }
+
+    fn handle_document_list_key(&mut self, key: KeyEvent) {
+        let Mode::Documents(selected) = self.mode else {
+            return;
+        };
+        let last = self.documents.len().saturating_sub(1);
+
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.mode = Mode::Documents((selected + 1).min(last))
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.mode = Mode::Documents(selected.saturating_sub(1))
+            }
+            KeyCode::Home => self.mode = Mode::Documents(0),
+            KeyCode::End => self.mode = Mode::Documents(last),
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                self.switch_document(selected);
+            }
+            KeyCode::Esc | KeyCode::Char('D') | KeyCode::Char('q') => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    // How far each open document has been read, this one as it is now
+    fn document_progress(&self) -> Vec<Option<(usize, usize)>> {
+        self.documents
+            .iter()
+            .enumerate()
+            .map(|(index, document)| match index == self.document_index {
+                true => Some((self.current_index, self.sentences.len())),
+                false => document.progress,
+            })
+            .collect()
+    }
+
+    fn request_quit(&mut self) {
+        if self.has_changes {
+            self.mode = Mode::ConfirmQuit;
+        } else {
+            self.should_quit = true;
+        }
+    }
+
+    // Goes through the kinds of sentence in the document, then back to all
+    fn cycle_kind_filter(&mut self) {
+        let kinds: Vec<SentenceKind> = SentenceKind::ALL
+            .into_iter()
+            .filter(|&kind| self.sentences.iter().any(|sentence| sentence.kind == kind))
+            .collect();
+        let next = match self.kind_filter {
+            None => kinds.first().copied(),
+            Some(current) => kinds
+                .iter()
+                .skip_while(|&&kind| kind != current)
+                .nth(1)
+                .copied(),
+        };
+        self.set_kind_filter(next);
+    }
+
+    // Moves to the nearest sentence of the kind, preferring the ones ahead
+    fn set_kind_filter(&mut self, kind: Option<SentenceKind>) {
+        self.kind_filter = kind;
+        let Some(kind) = kind else {
+            self.message = Some("Stepping through every sentence.".to_string());
+            return;
+        };
+
+        let is_kind = |index: &usize| self.sentences[*index].kind == kind;
+        let nearest = (self.current_index..self.sentences.len())
+            .find(is_kind)
+            .or_else(|| (0..self.current_index).rev().find(is_kind));
+        match nearest {
+            Some(index) => {
+                self.current_index = index;
+                self.message = Some(format!(
+                    "Stepping through {} sentences only.",
+                    kind_label(kind)
+                ));
+            }
+            None => {
+                self.kind_filter = None;
+                self.message = Some(format!(
+                    "There is no {} in this document.",
+                    kind_label(kind)
+                ));
+            }
+        }
+    }
+
+    fn jump_to(&mut self, sentence_num: usize) {
+        if sentence_num > 0 && sentence_num <= self.sentences.len() {
+            self.current_index = sentence_num - 1;
+        } else {
+            self.message = Some(format!(
+                "Invalid sentence number. Must be between 1 and {}.",
+                self.sentences.len()
+            ));
+        }
+    }
+
+    // Paragraphs and sections are entered at their first sentence; stepping
+    // back from inside one returns to its start
+    fn step(&mut self, forward: bool) {
+        if self.unit == NavigationUnit::Sentence && self.kind_filter.is_none() {
+            if forward {
+                self.move_by(1);
+            } else {
+                self.move_back_by(1);
+            }
+            return;
+        }
+
+        let current = self.current_index;
+        let target = if forward {
+            (current + 1..self.sentences.len()).find(|&index| self.starts_unit(index))
+        } else {
+            (0..current).rev().find(|&index| self.starts_unit(index))
+        };
+        match target {
+            Some(index) => self.current_index = index,
+            None => {
+                let direction = if forward { "next" } else { "previous" };
+                let unit = match self.kind_filter {
+                    Some(kind) => kind_label(kind),
+                    None => self.unit.name().to_string(),
+                };
+                self.message = Some(format!("There is no {} {}.", direction, unit));
+            }
+        }
+    }
+
+    fn starts_unit(&self, index: usize) -> bool {
+        let sentence = &self.sentences[index];
+        if self.kind_filter.is_some_and(|kind| sentence.kind != kind) {
+            return false;
+        }
+        match self.unit {
+            NavigationUnit::Sentence => true,
+            NavigationUnit::Paragraph => {
+                index == 0 || self.sentences[index - 1].paragraph_index != sentence.paragraph_index
+            }
+            NavigationUnit::Section => index == 0 || sentence.kind == SentenceKind::Heading,
+        }
+    }
+
+    fn move_by(&mut self, count: usize) {
+        let last = self.sentences.len() - 1;
+        if self.current_index == last {
+            self.message = Some("You've reached the end of the document.".to_string());
+        }
+        self.current_index = (self.current_index + count).min(last);
+    }
+
+    fn move_back_by(&mut self, count: usize) {
+        if self.current_index == 0 {
+            self.message = Some("You're at the beginning of the document.".to_string());
+        }
+        self.current_index = self.current_index.saturating_sub(count);
+    }
+
+    // Writes the unsaved edits as a patch next to the source document,
+    // leaving the document itself untouched
+    fn write_diff(&mut self) {
+        let Some(source_path) = self.source_path.clone() else {
+            self.message = Some("No source document to compare with.".to_string());
+            return;
+        };
+
+        let (original, edited): (Vec<String>, Vec<String>) = self
+            .edit_groups()
+            .into_iter()
+            .map(|(sentences, units)| (self.original_text(units), self.current_text(sentences)))
+            .unzip();
+        if original == edited {
+            self.message = Some("There are no edits to diff.".to_string());
+            return;
+        }
+
+        let name = self.file_name().to_string();
+        let diff = diff::unified_diff(
+            &original,
+            &edited,
+            &format!("a/{}", name),
+            &format!("b/{}", name),
+        );
+        let diff_path = format!("{}.diff", source_path);
+        self.message = Some(match fs::write(&diff_path, diff) {
+            Ok(()) => format!("Wrote edits to {}", diff_path),
+            Err(e) => format!("Error writing '{}': {}", diff_path, e),
+        });
+    }
+
+    fn save(&mut self) {
+        let Some(source_path) = self.source_path.clone() else {
+            self.message = Some("No source document to save to.".to_string());
+            return;
+        };
+        let output_path = self.output_path.clone().unwrap_or(source_path.clone());
+
+        let groups = self.edit_groups();
+        let original: Vec<String> = groups
+            .iter()
+            .map(|(_, units)| self.original_text(units.clone()))
+            .collect();
+        let edited: Vec<String> = groups
+            .iter()
+            .map(|(sentences, _)| self.current_text(sentences.clone()))
+            .collect();
+
+        match writer::save_odt_with(
+            &source_path,
+            &output_path,
+            &original,
+            &edited,
+            &self.save_options,
+        ) {
+            Ok(()) if self.save_options.dry_run => {
+                self.message = Some(format!(
+                    "Dry run: {} edits would be saved to {}; nothing was written.",
+                    edited.len(),
+                    output_path
+                ));
+            }
+            Ok(()) => {
+                // The source now contains the edits, so they become the baseline.
+                // Units keep their numbering so the edit history stays valid.
+                if output_path == source_path {
+                    for ((_, units), text) in groups.into_iter().zip(edited) {
+                        let start = units.start;
+                        self.original_sentences[start] = text;
+                        for index in units.skip(1) {
+                            self.original_sentences[index].clear();
+                        }
+                    }
+                }
+                self.has_changes = false;
+                self.message = Some(format!("Saved changes to {}", output_path));
+            }
+            Err(e) => {
+                self.message = Some(format!("Error saving '{}': {}", output_path, e));
+            }
+        }
+    }
+}
+
+fn sentence_texts(sentences: &[Sentence]) -> Vec<String> {
+    sentences
+        .iter()
+        .map(|sentence| sentence.text.clone())
+        .collect()
+}
+
+// The kind as it reads in messages, such as "list item"
+fn kind_label(kind: SentenceKind) -> String {
+    kind.name().replace('_', " ")
+}
+
+// Removes the last user-perceived character, such as an accented letter typed
+// with a combining mark or an emoji with a skin tone
+fn pop_grapheme(buffer: &mut String) {
+    if let Some((index, _)) = buffer.grapheme_indices(true).next_back() {
+        buffer.truncate(index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{OdtParser, Source, read_zip_entry};
+    use crate::sentence::texts;
+    use crate::test_utils::create_test_odt_file;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn press(navigator: &mut Navigator, code: KeyCode) {
+        navigator.handle_key(KeyEvent::from(code));
+    }
+
+    fn navigator() -> Navigator {
+        let sentences = ["One.", "Two.", "Three."];
+        let mut navigator = Navigator::new(sentences.into_iter().map(Sentence::new).collect());
+        navigator.mode = Mode::Normal;
+        navigator
+    }
+
+    #[test]
+    fn test_vim_keys() -> Result<()> {
+        let mut navigator = navigator().with_keymap(Keymap::preset("vim")?);
+
+        press(&mut navigator, KeyCode::Char('G'));
+        assert_eq!(navigator.position(), 2);
+        press(&mut navigator, KeyCode::Char('k'));
+        assert_eq!(navigator.position(), 1);
+        press(&mut navigator, KeyCode::Char('g'));
+        assert_eq!(navigator.position(), 1);
+        press(&mut navigator, KeyCode::Char('g'));
+        assert_eq!(navigator.position(), 0);
+
+        // A key that does not continue a sequence is taken on its own
+        press(&mut navigator, KeyCode::Char('g'));
+        press(&mut navigator, KeyCode::Char('j'));
+        assert_eq!(navigator.position(), 1);
+
+        press(&mut navigator, KeyCode::Char(':'));
+        press(&mut navigator, KeyCode::Char('q'));
+        assert!(navigator.should_quit);
+        Ok(())
+    }
+
+    #[test]
+    fn test_navigation_keys() {
+        let mut navigator = navigator();
+
+        press(&mut navigator, KeyCode::Right);
+        assert_eq!(navigator.current_index, 1);
+        press(&mut navigator, KeyCode::End);
+        assert_eq!(navigator.current_index, 2);
+        press(&mut navigator, KeyCode::PageUp);
+        assert_eq!(navigator.current_index, 0);
+
+        press(&mut navigator, KeyCode::Char('2'));
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(navigator.current_index, 1);
+    }
+
+    #[test]
+    fn test_edit_and_quit_confirmation() {
+        let mut navigator = navigator();
+
+        press(&mut navigator, KeyCode::Char('e'));
+        for _ in 0.."One.".len() {
+            press(&mut navigator, KeyCode::Backspace);
+        }
+        for c in "Uno.".chars() {
+            press(&mut navigator, KeyCode::Char(c));
+        }
+        press(&mut navigator, KeyCode::Enter);
+
+        assert_eq!(navigator.sentences()[0].text, "Uno.");
+        assert!(navigator.has_changes());
+
+        press(&mut navigator, KeyCode::Char('q'));
+        assert_eq!(navigator.mode, Mode::ConfirmQuit);
+        assert!(!navigator.should_quit);
+        press(&mut navigator, KeyCode::Char('n'));
+        assert!(navigator.should_quit);
+    }
+
+    #[test]
+    fn test_search_and_match_navigation() {
+        let mut navigator = navigator();
+
+        press(&mut navigator, KeyCode::Char('/'));
+        for c in "t".chars() {
+            press(&mut navigator, KeyCode::Char(c));
+        }
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(navigator.current_index, 1);
+
+        press(&mut navigator, KeyCode::Char('n'));
+        assert_eq!(navigator.current_index, 2);
+        press(&mut navigator, KeyCode::Char('n'));
+        assert_eq!(navigator.current_index, 1);
+        press(&mut navigator, KeyCode::Char('N'));
+        assert_eq!(navigator.current_index, 2);
+
+        press(&mut navigator, KeyCode::Esc);
+        assert!(navigator.search.is_none());
+        press(&mut navigator, KeyCode::Char('n'));
+        assert_eq!(navigator.current_index, 2);
+        assert!(navigator.message.is_some());
+    }
+
+    #[test]
+    fn test_concordance_jumps_to_match() {
+        let mut navigator = navigator();
+
+        press(&mut navigator, KeyCode::Char('C'));
+        assert!(navigator.message.is_some());
+
+        press(&mut navigator, KeyCode::Char('/'));
+        press(&mut navigator, KeyCode::Char('e'));
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(navigator.current_index, 2);
+        press(&mut navigator, KeyCode::Char('C'));
+        // "One." and the two in "Three."
+        assert_eq!(navigator.mode, Mode::Concordance(1));
+        press(&mut navigator, KeyCode::Home);
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(navigator.current_index, 0);
+        assert_eq!(navigator.mode, Mode::Normal);
+    }
+
+    #[test]
+    fn test_replace_in_all_sentences() {
+        let mut navigator = navigator();
+
+        press(&mut navigator, KeyCode::Char(':'));
+        for c in "o\\./0!/g".chars() {
+            press(&mut navigator, KeyCode::Char(c));
+        }
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(
+            navigator.mode,
+            Mode::ConfirmReplace(vec![(1, "Tw0!".to_string())], 0)
+        );
+        press(&mut navigator, KeyCode::Char('n'));
+        assert_eq!(navigator.sentences()[1].text, "Two.");
+
+        press(&mut navigator, KeyCode::Char(':'));
+        for c in "[eo]/_/g".chars() {
+            press(&mut navigator, KeyCode::Char(c));
+        }
+        press(&mut navigator, KeyCode::Enter);
+        press(&mut navigator, KeyCode::Char('y'));
+        assert_eq!(texts(navigator.sentences()), ["On_.", "Tw_.", "Thr__."]);
+        assert_eq!(
+            navigator.message.as_deref(),
+            Some("Replaced in 3 sentences.")
+        );
+
+        press(&mut navigator, KeyCode::Char('u'));
+        assert_eq!(texts(navigator.sentences()), ["One.", "Two.", "Three."]);
+        assert!(!navigator.has_changes());
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut navigator = navigator();
+
+        press(&mut navigator, KeyCode::Char('e'));
+        press(&mut navigator, KeyCode::Backspace);
+        press(&mut navigator, KeyCode::Char('!'));
+        press(&mut navigator, KeyCode::Enter);
+        press(&mut navigator, KeyCode::End);
+        assert_eq!(navigator.sentences()[0].text, "One!");
+
+        press(&mut navigator, KeyCode::Char('u'));
+        assert_eq!(navigator.sentences()[0].text, "One.");
+        assert_eq!(navigator.current_index, 0);
+        assert!(!navigator.has_changes());
+
+        navigator.handle_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(navigator.sentences()[0].text, "One!");
+        assert!(navigator.has_changes());
+
+        navigator.handle_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(navigator.message.as_deref(), Some("Nothing to redo."));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_speaking_moves_on_after_each_sentence() {
+        let mut navigator = navigator().with_speech_command("cat > /dev/null");
+
+        press(&mut navigator, KeyCode::Char('r'));
+        assert!(navigator.speaking);
+        navigator.update_speech();
+        assert_eq!(navigator.speaker.sentence(), Some(0));
+
+        // The reader moving on is followed rather than waiting for the end
+        press(&mut navigator, KeyCode::Char('n'));
+        navigator.update_speech();
+        assert_eq!(navigator.speaker.sentence(), Some(1));
+
+        while navigator.speaking {
+            navigator.update_speech();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(navigator.current_index, 2);
+        assert_eq!(
+            navigator.message.as_deref(),
+            Some("Finished reading aloud.")
+        );
+
+        press(&mut navigator, KeyCode::Char('r'));
+        navigator.update_speech();
+        press(&mut navigator, KeyCode::Char('r'));
+        assert!(!navigator.speaking);
+        assert_eq!(navigator.speaker.sentence(), None);
+    }
+
+    #[test]
+    fn test_split_and_join() {
+        let mut navigator = navigator();
+
+        press(&mut navigator, KeyCode::Char('j'));
+        assert_eq!(texts(navigator.sentences()), vec!["One. Two.", "Three."]);
+        assert!(!navigator.has_changes());
+
+        press(&mut navigator, KeyCode::Char('x'));
+        assert_eq!(navigator.mode, Mode::Split(1));
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(texts(navigator.sentences()), vec!["One.", "Two.", "Three."]);
+
+        press(&mut navigator, KeyCode::Char('u'));
+        press(&mut navigator, KeyCode::Char('u'));
+        assert_eq!(navigator.units, vec![0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn test_delete_and_insert() {
+        let mut navigator = navigator();
+
+        press(&mut navigator, KeyCode::Char('d'));
+        assert_eq!(texts(navigator.sentences()), vec!["Two.", "Three."]);
+        assert_eq!(navigator.units, vec![0..2, 2..3]);
+        assert_eq!(navigator.current_index, 0);
+        assert!(navigator.has_changes());
+
+        press(&mut navigator, KeyCode::Char('o'));
+        for c in "New.".chars() {
+            press(&mut navigator, KeyCode::Char(c));
+        }
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(texts(navigator.sentences()), vec!["Two.", "New.", "Three."]);
+        assert_eq!(navigator.current_index, 1);
+
+        press(&mut navigator, KeyCode::Char('i'));
+        press(&mut navigator, KeyCode::Char('A'));
+        press(&mut navigator, KeyCode::Esc);
+        assert_eq!(navigator.sentences().len(), 3);
+
+        press(&mut navigator, KeyCode::End);
+        press(&mut navigator, KeyCode::Char('d'));
+        assert_eq!(texts(navigator.sentences()), vec!["Two.", "New."]);
+        assert_eq!(navigator.current_index, 1);
+
+        press(&mut navigator, KeyCode::Char('u'));
+        press(&mut navigator, KeyCode::Char('u'));
+        press(&mut navigator, KeyCode::Char('u'));
+        assert_eq!(texts(navigator.sentences()), vec!["One.", "Two.", "Three."]);
+        assert_eq!(navigator.units, vec![0..1, 1..2, 2..3]);
+        assert!(!navigator.has_changes());
+    }
+
+    #[test]
+    fn test_events() {
+        let (events, received) = std::sync::mpsc::channel();
+        let mut navigator = navigator().with_events(events);
+        let texts = |texts: &[&str]| texts.iter().map(|text| text.to_string()).collect();
+
+        navigator.announce();
+        navigator.announce();
+        press(&mut navigator, KeyCode::Down);
+        navigator.announce();
+        press(&mut navigator, KeyCode::Char('j'));
+        navigator.announce();
+        assert_eq!(
+            received.try_iter().collect::<Vec<_>>(),
+            vec![
+                ReaderEvent::Edited(texts(&["One.", "Two.", "Three."]), 0),
+                ReaderEvent::Moved(1),
+                ReaderEvent::Edited(texts(&["One.", "Two. Three."]), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_save_after_join_and_edit() -> Result<()> {
+        let source = "navigator_source.odt";
+        let output = "navigator_output.odt";
+        create_test_odt_file(
+            source,
+            "<text:p>First part. Second part. Untouched.</text:p>",
+        )?;
+
+        let sentences = OdtParser::new()?.parse_file(source)?;
+        let mut navigator = Navigator::new(sentences).with_save_path(source, Some(output));
+        navigator.mode = Mode::Normal;
+
+        press(&mut navigator, KeyCode::Char('j'));
+        navigator.mode = Mode::Edit(String::new());
+        for c in "Replaced both.".chars() {
+            press(&mut navigator, KeyCode::Char(c));
+        }
+        press(&mut navigator, KeyCode::Enter);
+        press(&mut navigator, KeyCode::Char('s'));
+
+        let saved = OdtParser::new()?.parse_file(output);
+        fs::remove_file(source).ok();
+        fs::remove_file(output).ok();
+
+        assert!(!navigator.has_changes());
+        assert_eq!(texts(&saved?), vec!["Replaced both.", "Untouched."]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_after_delete_and_insert() -> Result<()> {
+        let source = "navigator_structure_source.odt";
+        let output = "navigator_structure_output.odt";
+        create_test_odt_file(
+            source,
+            "<text:p>Keep this. Drop this.</text:p><text:p>Gone too.</text:p><text:p>Last.</text:p>",
+        )?;
+
+        let sentences = OdtParser::new()?.parse_file(source)?;
+        let mut navigator = Navigator::new(sentences).with_save_path(source, Some(output));
+        navigator.mode = Mode::Normal;
+
+        press(&mut navigator, KeyCode::Down);
+        press(&mut navigator, KeyCode::Char('d'));
+        press(&mut navigator, KeyCode::Char('d'));
+        press(&mut navigator, KeyCode::Char('i'));
+        for c in "First.".chars() {
+            press(&mut navigator, KeyCode::Char(c));
+        }
+        press(&mut navigator, KeyCode::Enter);
+        press(&mut navigator, KeyCode::Char('s'));
+
+        let saved = OdtParser::new()?.parse_file(output);
+        let content = read_zip_entry(Source::File(output), "content.xml");
+        fs::remove_file(source).ok();
+        fs::remove_file(output).ok();
+
+        assert_eq!(texts(&saved?), vec!["Keep this.", "First.", "Last."]);
+        assert_eq!(content?.matches("<text:p").count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_backspace_removes_whole_graphemes() {
+        let mut buffer = "Cafe\u{301} 👍🏽".to_string();
+        pop_grapheme(&mut buffer);
+        assert_eq!(buffer, "Cafe\u{301} ");
+        pop_grapheme(&mut buffer);
+        pop_grapheme(&mut buffer);
+        assert_eq!(buffer, "Caf");
+
+        let mut empty = String::new();
+        pop_grapheme(&mut empty);
+        assert_eq!(empty, "");
+    }
+
+    #[test]
+    fn test_sentence_box_fits_terminal() -> Result<()> {
+        let box_edges = |width: u16| -> Result<(u16, u16)> {
+            let mut terminal = Terminal::new(TestBackend::new(width, 12))?;
+            let frame = terminal.draw(|frame| render::draw(frame, &navigator()))?;
+            let row = (0..width)
+                .map(|x| frame.buffer[(x, 0)].symbol())
+                .collect::<Vec<_>>();
+            let left = row.iter().position(|&s| s == "┌").unwrap_or_default();
+            let right = row.iter().position(|&s| s == "┐").unwrap_or_default();
+            Ok((left as u16, right as u16))
+        };
+
+        assert_eq!(box_edges(30)?, (0, 29));
+        assert_eq!(box_edges(80)?, (0, 79));
+        assert_eq!(box_edges(160)?, (30, 129));
+        Ok(())
+    }
+
+    #[test]
+    fn test_context_shows_neighbouring_sentences() -> Result<()> {
+        let mut navigator = navigator().with_context(true);
+        navigator.current_index = 1;
+        let rows = |navigator: &Navigator| -> Result<Vec<String>> {
+            let mut terminal = Terminal::new(TestBackend::new(30, 16))?;
+            let frame = terminal.draw(|frame| render::draw(frame, navigator))?;
+            Ok((0..16)
+                .map(|y| (0..30).map(|x| frame.buffer[(x, y)].symbol()).collect())
+                .collect())
+        };
+
+        let shown = rows(&navigator)?;
+        assert_eq!(shown[0].trim(), "One.");
+        assert!(shown[1].starts_with("┌ Sentence 2"));
+        assert_eq!(shown[13].trim(), "Three.");
+
+        press(&mut navigator, KeyCode::Char('c'));
+        assert!(rows(&navigator)?[0].starts_with("┌ Sentence 2"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_grammar_issues_shown_until_edited() -> Result<()> {
+        let mut navigator = navigator();
+        let issue = GrammarIssue {
+            offset: 0,
+            length: 3,
+            message: "Possible typo".to_string(),
+            rule: "MORFOLOGIK_RULE_EN_US".to_string(),
+            replacements: vec!["On".to_string(), "Once".to_string()],
+        };
+        navigator.checked = Some((0, "One.".to_string(), vec![issue]));
+        let rows = |navigator: &Navigator| -> Result<Vec<String>> {
+            let mut terminal = Terminal::new(TestBackend::new(40, 10))?;
+            let frame = terminal.draw(|frame| render::draw(frame, navigator))?;
+            Ok((0..10)
+                .map(|y| (0..40).map(|x| frame.buffer[(x, y)].symbol()).collect())
+                .collect())
+        };
+
+        assert_eq!(navigator.grammar_issues().len(), 1);
+        assert!(rows(&navigator)?[3].contains("⚠ Possible typo → On, Once"));
+
+        navigator.current_index = 1;
+        assert!(navigator.grammar_issues().is_empty());
+        navigator.current_index = 0;
+        navigator.replace_current("Won.".to_string());
+        assert!(navigator.grammar_issues().is_empty());
+        assert!(!rows(&navigator)?.iter().any(|row| row.contains("typo")));
+        Ok(())
+    }
+
+    #[cfg(feature = "spellcheck")]
+    #[test]
+    fn test_spelling_corrections() -> Result<()> {
+        let checker = SpellChecker::new("SET UTF-8\nTRY eorhtnwi\n", "3\none\ntwo\nthree\n")?;
+        let mut navigator =
+            Navigator::new(vec![Sentence::new("One two thre.")]).with_spell_checker(checker);
+        navigator.mode = Mode::Normal;
+        assert_eq!(navigator.misspellings(), vec![8..12]);
+
+        press(&mut navigator, KeyCode::Char('z'));
+        let Mode::Spelling(fixes, 0) = &navigator.mode else {
+            panic!("expected the spelling list, got {:?}", navigator.mode);
+        };
+        assert_eq!(fixes[0], (8..12, "three".to_string()));
+
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(navigator.sentences()[0].text, "One two three.");
+        assert!(navigator.misspellings().is_empty());
+        press(&mut navigator, KeyCode::Char('z'));
+        assert_eq!(navigator.message.as_deref(), Some("No misspelled words."));
+
+        press(&mut navigator, KeyCode::Char('u'));
+        assert_eq!(navigator.sentences()[0].text, "One two thre.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_suggestion_accepted_as_edit() -> Result<()> {
+        let (url, _) =
+            crate::test_utils::serve_once(r#"{"choices": [{"message": {"content": "Uno."}}]}"#)?;
+        let mut navigator = navigator().with_rewriter(Rewriter::new(&url));
+
+        press(&mut navigator, KeyCode::Char('e'));
+        press(&mut navigator, KeyCode::Tab);
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(navigator.mode, Mode::Edit("One.".to_string()));
+
+        press(&mut navigator, KeyCode::Tab);
+        for c in "in Spanish".chars() {
+            press(&mut navigator, KeyCode::Char(c));
+        }
+        press(&mut navigator, KeyCode::Enter);
+        assert!(matches!(navigator.mode, Mode::Rewriting { .. }));
+        navigator.rewrite();
+        assert_eq!(
+            navigator.mode,
+            Mode::RewriteSuggestion {
+                draft: "One.".to_string(),
+                suggestion: Ok("Uno.".to_string()),
+            }
+        );
+
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(navigator.mode, Mode::Normal);
+        assert_eq!(navigator.sentences()[0].text, "Uno.");
+        press(&mut navigator, KeyCode::Char('u'));
+        assert_eq!(navigator.sentences()[0].text, "One.");
+
+        // The server answers once, so the second request fails
+        navigator.mode = Mode::Rewriting {
+            draft: "One.".to_string(),
+            instruction: "in Spanish".to_string(),
+        };
+        navigator.rewrite();
+        assert!(matches!(
+            navigator.mode,
+            Mode::RewriteSuggestion {
+                suggestion: Err(_),
+                ..
+            }
+        ));
+        press(&mut navigator, KeyCode::Esc);
+        assert_eq!(navigator.mode, Mode::Edit("One.".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_edits_restored_from_session() {
+        let mut navigator = Navigator::new(
+            ["One.", "Two.", "Three.", "Four five."]
+                .into_iter()
+                .map(Sentence::new)
+                .collect(),
+        );
+        navigator.mode = Mode::Normal;
+
+        press(&mut navigator, KeyCode::Char('j'));
+        press(&mut navigator, KeyCode::End);
+        navigator.replace_current("Four, five.".to_string());
+        navigator.current_index = 2;
+        navigator.mode = Mode::Split(1);
+        press(&mut navigator, KeyCode::Enter);
+        let edits = navigator.edits();
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].original, "One. Two.");
+
+        let parsed: Vec<Sentence> = ["One.", "Two.", "Three.", "Four five."]
+            .into_iter()
+            .map(Sentence::new)
+            .collect();
+        let restored = Navigator::new(parsed.clone()).with_edits(&edits);
+        assert_eq!(texts(restored.sentences()), texts(navigator.sentences()));
+        assert!(restored.has_changes());
+        assert_eq!(restored.edits(), edits);
+
+        let mut changed = parsed;
+        changed[0] = Sentence::new("Zero.");
+        let partly = Navigator::new(changed).with_edits(&edits);
+        assert_eq!(
+            texts(partly.sentences()),
+            ["Zero.", "Two.", "Three.", "Four,", "five."]
+        );
+        assert!(
+            partly
+                .message
+                .is_some_and(|message| message.starts_with("1 edits"))
+        );
+    }
+
+    #[test]
+    fn test_paragraph_and_section_units() {
+        let sentence = |text: &str, paragraph_index: usize, kind: SentenceKind| Sentence {
+            paragraph_index,
+            kind,
+            ..Sentence::new(text)
+        };
+        let mut navigator = Navigator::new(vec![
+            sentence("Intro.", 0, SentenceKind::Body),
+            sentence("More intro.", 0, SentenceKind::Body),
+            sentence("Chapter", 1, SentenceKind::Heading),
+            sentence("First.", 2, SentenceKind::Body),
+            sentence("Second.", 2, SentenceKind::Body),
+            sentence("New paragraph.", 3, SentenceKind::Body),
+        ]);
+        navigator.mode = Mode::Normal;
+
+        press(&mut navigator, KeyCode::Tab);
+        assert_eq!(navigator.message.as_deref(), Some("Moving by paragraph."));
+        press(&mut navigator, KeyCode::Char('n'));
+        assert_eq!(navigator.current_index, 2);
+        press(&mut navigator, KeyCode::Char('n'));
+        press(&mut navigator, KeyCode::Right);
+        assert_eq!(navigator.current_index, 5);
+        navigator.current_index = 4;
+        press(&mut navigator, KeyCode::Char('p'));
+        assert_eq!(navigator.current_index, 3);
+
+        press(&mut navigator, KeyCode::Tab);
+        press(&mut navigator, KeyCode::Char('p'));
+        assert_eq!(navigator.current_index, 2);
+        press(&mut navigator, KeyCode::Char('p'));
+        assert_eq!(navigator.current_index, 0);
+        press(&mut navigator, KeyCode::Char('p'));
+        assert_eq!(navigator.current_index, 0);
+        assert_eq!(
+            navigator.message.as_deref(),
+            Some("There is no previous section.")
+        );
+
+        press(&mut navigator, KeyCode::Tab);
+        press(&mut navigator, KeyCode::Char('n'));
+        assert_eq!(navigator.current_index, 1);
+    }
+
+    #[test]
+    fn test_kind_filter() {
+        let sentence = |text: &str, kind: SentenceKind| Sentence {
+            kind,
+            ..Sentence::new(text)
+        };
+        let mut navigator = Navigator::new(vec![
+            sentence("Shopping", SentenceKind::Heading),
+            sentence("We need:", SentenceKind::Body),
+            sentence("apples", SentenceKind::ListItem),
+            sentence("That is all.", SentenceKind::Body),
+            sentence("pears", SentenceKind::ListItem),
+        ])
+        .with_kind_filter(Some(SentenceKind::ListItem));
+        navigator.mode = Mode::Normal;
+
+        assert_eq!(navigator.current_index, 2);
+        press(&mut navigator, KeyCode::Char('n'));
+        assert_eq!(navigator.current_index, 4);
+        press(&mut navigator, KeyCode::Char('n'));
+        assert_eq!(navigator.current_index, 4);
+        assert_eq!(
+            navigator.message.as_deref(),
+            Some("There is no next list item.")
+        );
+
+        // Kinds come in a fixed order, body text first, and only those the
+        // document has
+        press(&mut navigator, KeyCode::Char('K'));
+        assert_eq!(navigator.kind_filter, None);
+        press(&mut navigator, KeyCode::Char('K'));
+        assert_eq!(navigator.kind_filter, Some(SentenceKind::Body));
+        press(&mut navigator, KeyCode::Char('K'));
+        assert_eq!(navigator.kind_filter, Some(SentenceKind::Heading));
+        assert_eq!(navigator.current_index, 0);
+        assert_eq!(
+            navigator.message.as_deref(),
+            Some("Stepping through heading sentences only.")
+        );
+
+        let reader = self::navigator().with_kind_filter(Some(SentenceKind::Quote));
+        assert_eq!(reader.kind_filter, None);
+    }
+
+    #[test]
+    fn test_read_only() {
+        let mut navigator = navigator().with_read_only(true);
+
+        press(&mut navigator, KeyCode::Char('e'));
+        assert_eq!(navigator.mode, Mode::Normal);
+        press(&mut navigator, KeyCode::Char('j'));
+        assert_eq!(navigator.sentences.len(), 3);
+        assert_eq!(
+            navigator.message.as_deref(),
+            Some("The document is open read-only.")
+        );
+        press(&mut navigator, KeyCode::Char('n'));
+        assert_eq!(navigator.current_index, 1);
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let mut navigator = navigator().with_bookmarks(BTreeMap::from([('z', 9)]));
+        assert!(navigator.bookmarks().is_empty());
+
+        press(&mut navigator, KeyCode::Char('l'));
+        press(&mut navigator, KeyCode::Char('m'));
+        press(&mut navigator, KeyCode::Char('a'));
+        press(&mut navigator, KeyCode::Char('f'));
+        press(&mut navigator, KeyCode::Char('\''));
+        press(&mut navigator, KeyCode::Char('a'));
+        assert_eq!(navigator.position(), 2);
+
+        // Joining the first two sentences moves the bookmark with its sentence
+        press(&mut navigator, KeyCode::Char('f'));
+        press(&mut navigator, KeyCode::Char('j'));
+        assert_eq!(navigator.bookmarks(), &BTreeMap::from([('a', 1)]));
+
+        press(&mut navigator, KeyCode::Char('\''));
+        press(&mut navigator, KeyCode::Char('q'));
+        assert_eq!(navigator.mode, Mode::Normal);
+        assert_eq!(navigator.message.as_deref(), Some("No bookmark 'q'."));
+
+        press(&mut navigator, KeyCode::Char('b'));
+        assert_eq!(navigator.mode, Mode::Bookmarks(0));
+        press(&mut navigator, KeyCode::Char('D'));
+        assert_eq!(navigator.mode, Mode::Normal);
+        assert!(navigator.bookmarks().is_empty());
+    }
+
+    #[test]
+    fn test_annotations() {
+        let mut navigator = navigator();
+        let type_text = |navigator: &mut Navigator, text: &str| {
+            for c in text.chars() {
+                press(navigator, KeyCode::Char(c));
+            }
+            press(navigator, KeyCode::Enter);
+        };
+
+        press(&mut navigator, KeyCode::Char('a'));
+        type_text(&mut navigator, "First note");
+        press(&mut navigator, KeyCode::Char('n'));
+        press(&mut navigator, KeyCode::Char('t'));
+        type_text(&mut navigator, "typo, style");
+        assert_eq!(navigator.annotations()[&1].tags, vec!["typo", "style"]);
+
+        // Joining merges the annotations of both sentences
+        press(&mut navigator, KeyCode::Char('f'));
+        press(&mut navigator, KeyCode::Char('j'));
+        let joined = &navigator.annotations()[&0];
+        assert_eq!(navigator.annotations().len(), 1);
+        assert_eq!(joined.sentence, "One. Two.");
+        assert_eq!(joined.note, "First note");
+        assert_eq!(joined.tags, vec!["typo", "style"]);
+
+        press(&mut navigator, KeyCode::Char('A'));
+        assert_eq!(navigator.mode, Mode::Annotations(0));
+        press(&mut navigator, KeyCode::Esc);
+
+        // Clearing the note and the tags removes the annotation
+        press(&mut navigator, KeyCode::Char('a'));
+        for _ in 0.."First note".len() {
+            press(&mut navigator, KeyCode::Backspace);
+        }
+        press(&mut navigator, KeyCode::Enter);
+        press(&mut navigator, KeyCode::Char('t'));
+        for _ in 0.."typo style".len() {
+            press(&mut navigator, KeyCode::Backspace);
+        }
+        press(&mut navigator, KeyCode::Enter);
+        assert!(navigator.annotations().is_empty());
+    }
+
+    #[test]
+    fn test_replace_document_keeps_position() {
+        let mut navigator = navigator();
+        navigator.current_index = 2;
+        navigator.bookmarks.insert('a', 1);
+        navigator.annotations.insert(
+            2,
+            Annotation {
+                sentence: "Three.".to_string(),
+                note: "Last one.".to_string(),
+                ..Annotation::default()
+            },
+        );
+
+        let reloaded = ["Zero.", "One.", "Two!", "Three."];
+        navigator.replace_document(reloaded.into_iter().map(Sentence::new).collect());
+
+        assert_eq!(navigator.current_index, 3);
+        assert_eq!(navigator.bookmarks[&'a'], 2);
+        assert_eq!(navigator.annotations[&3].note, "Last one.");
+        assert_eq!(navigator.units.len(), 4);
+        assert!(navigator.message.is_some());
+    }
+
+    #[test]
+    fn test_outline_navigation() {
+        let heading = |text: &str, level| Sentence {
+            kind: SentenceKind::Heading,
+            heading_level: Some(level),
+            ..Sentence::new(text)
+        };
+        let mut navigator = Navigator::new(vec![
+            heading("Intro", 1),
+            Sentence::new("Welcome."),
+            heading("Part", 1),
+            heading("Empty section", 2),
+            Sentence::new("Body."),
+        ]);
+        navigator.mode = Mode::Normal;
+
+        press(&mut navigator, KeyCode::Char('O'));
+        assert_eq!(navigator.mode, Mode::Outline(0));
+        press(&mut navigator, KeyCode::Down);
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(navigator.current_index, 2);
+
+        press(&mut navigator, KeyCode::Char('O'));
+        assert_eq!(navigator.mode, Mode::Outline(1));
+        press(&mut navigator, KeyCode::End);
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(navigator.current_index, 4);
+    }
+
+    #[test]
+    fn test_switching_documents() {
+        let documents = vec![
+            DocumentEntry::new("a.odt"),
+            DocumentEntry::new("b.odt"),
+            DocumentEntry::new("c.odt"),
+        ];
+        let mut first = navigator().with_documents(documents.clone(), 0);
+
+        press(&mut first, KeyCode::Char('['));
+        assert!(!first.should_quit);
+        press(&mut first, KeyCode::Char(']'));
+        assert!(first.should_quit);
+        assert_eq!(first.next_document(), Some(1));
+
+        // Unsaved edits are asked about first, and staying cancels the switch
+        let mut navigator = navigator().with_documents(documents, 1);
+        navigator.replace_current("Uno.".to_string());
+        press(&mut navigator, KeyCode::Char('D'));
+        assert_eq!(navigator.mode, Mode::Documents(1));
+        press(&mut navigator, KeyCode::End);
+        press(&mut navigator, KeyCode::Enter);
+        assert_eq!(navigator.mode, Mode::ConfirmQuit);
+        press(&mut navigator, KeyCode::Esc);
+        assert_eq!(navigator.next_document(), None);
+
+        press(&mut navigator, KeyCode::Char('['));
+        press(&mut navigator, KeyCode::Char('n'));
+        assert!(navigator.should_quit);
+        assert_eq!(navigator.next_document(), Some(0));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::stats::ReadingStats;
+use std::time::{Duration, Instant};
+
+// Time on one sentence counts up to this, so a break away from the screen
+// does not drag the reading speed down
+const MAX_SENTENCE_TIME: Duration = Duration::from_secs(120);
+// Less reading than this gives no useful speed yet
+const MIN_READING_TIME: Duration = Duration::from_secs(10);
+
+// Only sentences read in order count towards the speed: moving on to the next
+// sentence adds the time spent on the one before and its words, while jumps
+// and steps back start timing afresh
+#[derive(Debug)]
+pub(super) struct ReadingTracker {
+    current: usize,
+    since: Instant,
+    time: Duration,
+    words: usize,
+    sentences: usize,
+    // Earlier sessions, which give a speed before this one has
+    history: ReadingStats,
+}
+
+impl ReadingTracker {
+    pub(super) fn new(index: usize) -> Self {
+        ReadingTracker {
+            current: index,
+            since: Instant::now(),
+            time: Duration::ZERO,
+            words: 0,
+            sentences: 0,
+            history: ReadingStats::default(),
+        }
+    }
+
+    pub(super) fn with_history(mut self, history: ReadingStats) -> Self {
+        self.history = history;
+        self
+    }
+
+    // `words` is the length of the sentence that was current until now
+    pub(super) fn moved(&mut self, index: usize, words: usize, now: Instant) {
+        if index == self.current {
+            return;
+        }
+        if index == self.current + 1 {
+            self.time += now.duration_since(self.since).min(MAX_SENTENCE_TIME);
+            self.words += words;
+            self.sentences += 1;
+        }
+        self.current = index;
+        self.since = now;
+    }
+
+    // Timing starts over at `index`, as when the reader starts somewhere
+    // other than the first sentence
+    pub(super) fn restart(&mut self, index: usize) {
+        self.current = index;
+        self.since = Instant::now();
+    }
+
+    pub(super) fn current(&self) -> usize {
+        self.current
+    }
+
+    pub(super) fn session(&self) -> ReadingStats {
+        ReadingStats {
+            seconds: self.time.as_secs(),
+            words: self.words,
+            sentences: self.sentences,
+            sessions: 1,
+        }
+    }
+
+    pub(super) fn words_per_minute(&self) -> Option<f64> {
+        let mut total = self.history;
+        total.add(&self.session());
+        if total.seconds < MIN_READING_TIME.as_secs() {
+            return None;
+        }
+        total.words_per_minute()
+    }
+
+    pub(super) fn time_left(&self, words_left: usize) -> Option<Duration> {
+        self.words_per_minute()
+            .map(|wpm| Duration::from_secs_f64(words_left as f64 * 60.0 / wpm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_speed() {
+        let start = Instant::now();
+        let mut tracker = ReadingTracker::new(0);
+        tracker.since = start;
+
+        tracker.moved(1, 10, start + Duration::from_secs(3));
+        assert_eq!(tracker.words_per_minute(), None);
+        tracker.moved(2, 20, start + Duration::from_secs(12));
+        // Jumps and idle time do not count
+        tracker.moved(40, 30, start + Duration::from_secs(14));
+        tracker.moved(41, 5, start + Duration::from_secs(600));
+
+        let session = tracker.session();
+        assert_eq!(
+            (session.seconds, session.words, session.sentences),
+            (132, 35, 3)
+        );
+
+        let tracker = ReadingTracker::new(0).with_history(ReadingStats {
+            seconds: 60,
+            words: 200,
+            sentences: 10,
+            sessions: 2,
+        });
+        assert_eq!(tracker.words_per_minute(), Some(200.0));
+        assert_eq!(tracker.time_left(500), Some(Duration::from_secs(150)));
+    }
+}
@@ -0,0 +1,797 @@
+use super::{Action, Mode, NavigationUnit, Navigator, kind_label};
+use crate::sentence::SentenceKind;
+use crate::stats::format_duration;
+use ratatui::Frame;
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use std::ops::Range;
+use unicode_width::UnicodeWidthStr;
+
+// Wider lines are hard to follow, so on wide terminals the sentence box stops
+// growing and is centred instead
+pub(super) const MAX_SENTENCE_WIDTH: u16 = 100;
+// Longest the neighbouring sentences shown for context grow, in lines
+const MAX_CONTEXT_LINES: u16 = 3;
+// Corrections shown for each grammar issue
+const MAX_REPLACEMENTS: usize = 3;
+
+// Actions named in the footer, with the first key bound to each
+const FOOTER_ACTIONS: [(Action, &str); 8] = [
+    (Action::Next, "next"),
+    (Action::Previous, "prev"),
+    (Action::Search, "search"),
+    (Action::Edit, "edit"),
+    (Action::Undo, "undo"),
+    (Action::Save, "save"),
+    (Action::Help, "help"),
+    (Action::Quit, "quit"),
+];
+
+pub(super) fn draw(frame: &mut Frame, navigator: &Navigator) {
+    let header_height = match navigator.metadata.heading() {
+        Some(_) => 1,
+        None => 0,
+    };
+    let [header_area, sentence_area, status_area, footer_area] = Layout::vertical([
+        Constraint::Length(header_height),
+        Constraint::Min(3),
+        Constraint::Length(1),
+        Constraint::Length(footer_height(navigator)),
+    ])
+    .areas(frame.area());
+
+    let mut sentence_area = centered_rect(sentence_area, navigator.max_width, sentence_area.height);
+    if navigator.show_context {
+        sentence_area = draw_context(frame, navigator, sentence_area);
+    }
+    draw_header(frame, navigator, header_area);
+    draw_sentence(frame, navigator, sentence_area);
+    draw_status_bar(frame, navigator, status_area);
+    draw_footer(frame, navigator, footer_area);
+
+    match navigator.mode {
+        Mode::Help => draw_help(frame, navigator),
+        Mode::ConfirmQuit => draw_confirm_quit(frame, navigator),
+        Mode::RewriteSuggestion {
+            ref draft,
+            ref suggestion,
+        } => draw_rewrite_suggestion(frame, draft, suggestion),
+        Mode::Outline(selected) => draw_outline(frame, navigator, selected),
+        Mode::Bookmarks(selected) => draw_bookmarks(frame, navigator, selected),
+        Mode::Documents(selected) => draw_documents(frame, navigator, selected),
+        Mode::Annotations(selected) => draw_annotations(frame, navigator, selected),
+        Mode::Concordance(selected) => draw_concordance(frame, navigator, selected),
+        Mode::Spelling(ref fixes, selected) => draw_spelling(frame, navigator, fixes, selected),
+        Mode::ConfirmReplace(ref changes, selected) => {
+            draw_replace_preview(frame, navigator, changes, selected)
+        }
+        _ => {}
+    }
+}
+
+fn footer_height(navigator: &Navigator) -> u16 {
+    match navigator.mode {
+        Mode::Edit(_)
+        | Mode::RewriteInstruction { .. }
+        | Mode::Split(_)
+        | Mode::Insert { .. }
+        | Mode::Search { .. }
+        | Mode::Replace(_)
+        | Mode::Note(_)
+        | Mode::Tags(_) => 3,
+        _ => 1,
+    }
+}
+
+fn draw_sentence(frame: &mut Frame, navigator: &Navigator, area: Rect) {
+    let sentence = &navigator.sentences[navigator.current_index];
+    let mut title = format!(" Sentence {} ", navigator.current_index + 1);
+    if navigator.is_edited(navigator.current_index) {
+        title.push_str("(edited) ");
+    }
+    let names: String = navigator
+        .bookmarks
+        .iter()
+        .filter(|&(_, &index)| index == navigator.current_index)
+        .map(|(&name, _)| name)
+        .collect();
+    if !names.is_empty() {
+        title.push_str(&format!("(bookmark {}) ", names));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_alignment(Alignment::Left);
+
+    let issues = navigator.grammar_issues();
+    let mut lines = vec![highlight_sentence(navigator, &sentence.text)];
+    if !issues.is_empty() {
+        lines.push(Line::default());
+    }
+    for issue in issues {
+        let mut line = format!("⚠ {}", issue.message);
+        if !issue.replacements.is_empty() {
+            let replacements: Vec<&str> = issue
+                .replacements
+                .iter()
+                .take(MAX_REPLACEMENTS)
+                .map(String::as_str)
+                .collect();
+            line.push_str(&format!(" → {}", replacements.join(", ")));
+        }
+        lines.push(Line::from(line).light_red());
+    }
+    if let Some(annotation) = navigator.annotations.get(&navigator.current_index) {
+        lines.push(Line::default());
+        if !annotation.note.is_empty() {
+            lines.push(Line::from(format!("✎ {}", annotation.note)).italic().cyan());
+        }
+        if !annotation.tags.is_empty() {
+            let tags: Vec<String> = annotation
+                .tags
+                .iter()
+                .map(|tag| format!("#{}", tag))
+                .collect();
+            lines.push(Line::from(tags.join(" ")).cyan());
+        }
+    }
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    frame.render_widget(paragraph, area);
+}
+
+// Draws the neighbouring sentences above and below the sentence box, each
+// given at most a quarter of the height, and returns the area left between
+// them. A long previous sentence shows its end, the part closest to the
+// current one.
+fn draw_context(frame: &mut Frame, navigator: &Navigator, area: Rect) -> Rect {
+    let index = navigator.current_index;
+    let previous = index
+        .checked_sub(1)
+        .map(|i| navigator.sentences[i].text.as_str());
+    let next = navigator.sentences.get(index + 1).map(|s| s.text.as_str());
+
+    let text_width = area.width.saturating_sub(2).max(1);
+    let max_height = (area.height / 4).min(MAX_CONTEXT_LINES);
+    let height = |text: Option<&str>| {
+        text.map_or(0, |text| {
+            (text.width() as u16)
+                .div_ceil(text_width)
+                .clamp(1, max_height)
+        })
+    };
+    let (previous_height, next_height) = (height(previous), height(next));
+    let [previous_area, sentence_area, next_area] = Layout::vertical([
+        Constraint::Length(previous_height),
+        Constraint::Min(3),
+        Constraint::Length(next_height),
+    ])
+    .areas(area);
+
+    let margin = Margin::new(1, 0);
+    if let Some(text) = previous {
+        let lines = (text.width() as u16).div_ceil(text_width);
+        let paragraph = Paragraph::new(text)
+            .wrap(Wrap { trim: true })
+            .scroll((lines.saturating_sub(previous_height), 0))
+            .dim();
+        frame.render_widget(paragraph, previous_area.inner(margin));
+    }
+    if let Some(text) = next {
+        let paragraph = Paragraph::new(text).wrap(Wrap { trim: true }).dim();
+        frame.render_widget(paragraph, next_area.inner(margin));
+    }
+
+    sentence_area
+}
+
+// Search matches, then grammar issues, then misspelled words; where they
+// overlap, the earlier kind is shown
+fn highlight_sentence<'a>(navigator: &Navigator, sentence: &'a str) -> Line<'a> {
+    let match_style = Style::default().fg(Color::Black).bg(Color::Yellow);
+    let issue_style = Style::default()
+        .fg(Color::LightRed)
+        .add_modifier(Modifier::UNDERLINED);
+    let misspelling_style = Style::default()
+        .fg(Color::Red)
+        .add_modifier(Modifier::UNDERLINED);
+
+    let mut styled: Vec<(Range<usize>, Style)> = Vec::new();
+    if let Some(query) = &navigator.search {
+        styled.extend(
+            query
+                .find_ranges(sentence)
+                .into_iter()
+                .map(|range| (range, match_style)),
+        );
+    }
+    styled.extend(
+        navigator
+            .grammar_issues()
+            .iter()
+            .map(|issue| (issue.byte_range(sentence), issue_style)),
+    );
+    styled.extend(
+        navigator
+            .misspellings()
+            .into_iter()
+            .map(|range| (range, misspelling_style)),
+    );
+
+    let mut shown: Vec<(Range<usize>, Style)> = Vec::new();
+    for (range, style) in styled {
+        let overlaps = shown
+            .iter()
+            .any(|(other, _)| range.start < other.end && other.start < range.end);
+        if !range.is_empty() && !overlaps {
+            shown.push((range, style));
+        }
+    }
+    shown.sort_by_key(|(range, _)| range.start);
+
+    let mut spans = Vec::new();
+    let mut position = 0;
+    for (range, style) in shown {
+        spans.push(Span::raw(&sentence[position..range.start]));
+        spans.push(Span::styled(&sentence[range.start..range.end], style));
+        position = range.end;
+    }
+    spans.push(Span::raw(&sentence[position..]));
+
+    Line::from(spans)
+}
+
+// Title and author of the document, with the day it was created and the word
+// count it records
+fn draw_header(frame: &mut Frame, navigator: &Navigator, area: Rect) {
+    let metadata = &navigator.metadata;
+    let Some(heading) = metadata.heading() else {
+        return;
+    };
+
+    let mut spans = vec![Span::styled(
+        format!(" {} ", heading),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    if let Some(created) = &metadata.created {
+        let day = created.split('T').next().unwrap_or(created);
+        spans.push(Span::raw(format!("│ created {} ", day)));
+    }
+    if let Some(words) = metadata.word_count {
+        spans.push(Span::raw(format!("│ {} words ", words)));
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(spans)).dim(), area);
+}
+
+fn draw_status_bar(frame: &mut Frame, navigator: &Navigator, area: Rect) {
+    let current = navigator.current_index + 1;
+    let total = navigator.sentences.len();
+    let percent = current as f64 / total as f64 * 100.0;
+
+    let mut spans = vec![
+        Span::styled(
+            format!(" {} ", navigator.file_name()),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!("│ {} of {} │ {:.1}% ", current, total, percent)),
+    ];
+    if navigator.documents.len() > 1 {
+        spans.push(Span::raw(format!(
+            "│ document {} of {} ",
+            navigator.document_index + 1,
+            navigator.documents.len()
+        )));
+    }
+    if navigator.unit != NavigationUnit::Sentence {
+        spans.push(Span::raw(format!("│ by {} ", navigator.unit.name())));
+    }
+    let kind = navigator.sentences[navigator.current_index].kind;
+    match navigator.kind_filter {
+        Some(filter) => spans.push(Span::raw(format!("│ only {} ", kind_label(filter)))),
+        None if kind != SentenceKind::Body => {
+            spans.push(Span::raw(format!("│ {} ", kind_label(kind))));
+        }
+        None => {}
+    }
+    if navigator.speaking {
+        spans.push(Span::raw("│ speaking "));
+    }
+    if let Some(heading) = navigator.sentences[navigator.current_index]
+        .heading_path
+        .last()
+    {
+        spans.push(Span::raw(format!("│ § {} ", heading)));
+    }
+    if let Some(query) = &navigator.search {
+        let matches = query.matching_indices(&navigator.sentences).len();
+        spans.push(Span::raw(format!(
+            "│ /{} ({} matches) ",
+            query.pattern(),
+            matches
+        )));
+    }
+    let words_left: usize = navigator.sentences[navigator.current_index..]
+        .iter()
+        .map(|sentence| sentence.text.split_whitespace().count())
+        .sum();
+    if let (Some(wpm), Some(time_left)) = (
+        navigator.tracker.words_per_minute(),
+        navigator.tracker.time_left(words_left),
+    ) {
+        spans.push(Span::raw(format!(
+            "│ {:.0} wpm, {} left ",
+            wpm,
+            format_duration(time_left)
+        )));
+    }
+    if navigator.has_changes {
+        spans.push(Span::styled(
+            "│ modified ",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
+    let status = Paragraph::new(Line::from(spans)).style(Style::default().reversed());
+    frame.render_widget(status, area);
+}
+
+fn draw_footer(frame: &mut Frame, navigator: &Navigator, area: Rect) {
+    match &navigator.mode {
+        Mode::Edit(buffer) => {
+            draw_input(
+                frame,
+                area,
+                " Edit sentence (Enter to apply, Tab to ask for a rewrite, Esc to cancel) ",
+                buffer,
+            );
+            return;
+        }
+        Mode::RewriteInstruction { instruction, .. } => {
+            draw_input(
+                frame,
+                area,
+                " Rewrite how? e.g. make concise, fix grammar (Enter to ask, Esc to go back) ",
+                instruction,
+            );
+            return;
+        }
+        Mode::Split(position) => {
+            draw_split_preview(frame, navigator, area, *position);
+            return;
+        }
+        Mode::Insert { text, after } => {
+            let title = match after {
+                true => " New sentence after this one (Enter to insert, Esc to cancel) ",
+                false => " New sentence before this one (Enter to insert, Esc to cancel) ",
+            };
+            draw_input(frame, area, title, text);
+            return;
+        }
+        Mode::Note(buffer) => {
+            draw_input(
+                frame,
+                area,
+                " Note (Enter to save, empty to remove, Esc to cancel) ",
+                buffer,
+            );
+            return;
+        }
+        Mode::Tags(buffer) => {
+            draw_input(
+                frame,
+                area,
+                " Tags, separated by spaces (Enter to save, Esc to cancel) ",
+                buffer,
+            );
+            return;
+        }
+        Mode::Search { input, is_regex } => {
+            let title = if *is_regex {
+                " Search regex (Tab for text, Enter to search) "
+            } else {
+                " Search text (Tab for regex, Enter to search) "
+            };
+            draw_input(frame, area, title, input);
+            return;
+        }
+        Mode::Replace(input) => {
+            draw_input(
+                frame,
+                area,
+                " Replace: s/pattern/replacement/flags (g all, i ignore case; Enter to preview) ",
+                input,
+            );
+            return;
+        }
+        _ => {}
+    }
+
+    let line = if let Mode::Rewriting { instruction, .. } = &navigator.mode {
+        Line::from(format!(
+            "Asking {} to {}…",
+            navigator.rewriter.model(),
+            instruction
+        ))
+    } else if navigator.mode == Mode::SetBookmark {
+        Line::from("Set bookmark: press a letter or digit")
+    } else if navigator.mode == Mode::JumpToBookmark {
+        Line::from("Jump to bookmark: press its letter or digit")
+    } else if !navigator.pending_number.is_empty() {
+        Line::from(format!("Go to sentence: {}", navigator.pending_number))
+    } else if let Some(message) = &navigator.message {
+        Line::from(message.as_str()).fg(Color::Yellow)
+    } else {
+        let hints: Vec<String> = FOOTER_ACTIONS
+            .iter()
+            .filter_map(|(action, name)| {
+                navigator
+                    .keymap
+                    .first_label(*action)
+                    .map(|key| format!("{} {}", key, name))
+            })
+            .collect();
+        Line::from(hints.join("  ")).dim()
+    };
+
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_input(frame: &mut Frame, area: Rect, title: &str, buffer: &str) {
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+
+    // Columns rather than chars, so wide CJK characters and emoji keep the
+    // cursor at the end of the text; long input scrolls to show its end
+    let width = buffer.width() as u16;
+    let scroll = width.saturating_sub(inner.width.saturating_sub(1));
+    frame.render_widget(
+        Paragraph::new(buffer).block(block).scroll((0, scroll)),
+        area,
+    );
+    frame.set_cursor_position((inner.x + width - scroll, inner.y));
+}
+
+fn draw_split_preview(frame: &mut Frame, navigator: &Navigator, area: Rect, position: usize) {
+    let words: Vec<&str> = navigator.sentences[navigator.current_index]
+        .text
+        .split_whitespace()
+        .collect();
+    let position = position.min(words.len());
+
+    let line = Line::from(vec![
+        Span::raw(words[..position].join(" ")),
+        Span::styled(" ┃ ", Style::default().fg(Color::Yellow).bold()),
+        Span::raw(words[position..].join(" ")),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Split sentence (←/→ to move, Enter to split, Esc to cancel) ");
+
+    frame.render_widget(Paragraph::new(line).block(block), area);
+}
+
+fn draw_help(frame: &mut Frame, navigator: &Navigator) {
+    let lines: Vec<Line> = navigator
+        .keymap
+        .help_lines()
+        .into_iter()
+        .map(|(keys, description)| {
+            Line::from(vec![
+                Span::styled(format!("{:<20}", keys), Style::default().fg(Color::Cyan)),
+                Span::raw(description),
+            ])
+        })
+        .chain([
+            Line::default(),
+            Line::from("Press any key to continue").dim(),
+        ])
+        .collect();
+
+    let area = centered_rect(frame.area(), 54, lines.len() as u16 + 2);
+    let help = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" ODT Navigator — Help "),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(help, area);
+}
+
+fn draw_outline(frame: &mut Frame, navigator: &Navigator, selected: usize) {
+    let items: Vec<ListItem> = navigator
+        .headings()
+        .into_iter()
+        .map(|index| {
+            let heading = &navigator.sentences[index];
+            let indent = "  ".repeat(heading.heading_level.unwrap_or(1) - 1);
+            ListItem::new(format!("{}{}", indent, heading.text))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(frame.area().height.saturating_sub(2));
+    let area = centered_rect(frame.area(), 60, height);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Outline (Enter to jump, Esc to close) "),
+        )
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    let mut state = ListState::default().with_selected(Some(selected));
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_bookmarks(frame: &mut Frame, navigator: &Navigator, selected: usize) {
+    let items: Vec<ListItem> = navigator
+        .bookmarks
+        .iter()
+        .map(|(name, &index)| {
+            let sentence = &navigator.sentences[index].text;
+            ListItem::new(format!("{}  {:>5}  {}", name, index + 1, sentence))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(frame.area().height.saturating_sub(2));
+    let area = centered_rect(frame.area(), 70, height);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Bookmarks (Enter to jump, D to delete, Esc to close) "),
+        )
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    let mut state = ListState::default().with_selected(Some(selected));
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_documents(frame: &mut Frame, navigator: &Navigator, selected: usize) {
+    let items: Vec<ListItem> = navigator
+        .documents
+        .iter()
+        .zip(navigator.document_progress())
+        .enumerate()
+        .map(|(index, (document, progress))| {
+            let marker = if index == navigator.document_index {
+                "▸"
+            } else {
+                " "
+            };
+            let progress = match progress {
+                Some((position, total)) => format!(
+                    "{:>5.1}%",
+                    (position + 1) as f64 / total.max(1) as f64 * 100.0
+                ),
+                None => "     -".to_string(),
+            };
+            ListItem::new(format!("{} {}  {}", marker, progress, document.name))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(frame.area().height.saturating_sub(2));
+    let area = centered_rect(frame.area(), 70, height);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Documents (Enter to open, Esc to close) "),
+        )
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    let mut state = ListState::default().with_selected(Some(selected));
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_annotations(frame: &mut Frame, navigator: &Navigator, selected: usize) {
+    let items: Vec<ListItem> = navigator
+        .annotations
+        .iter()
+        .map(|(&index, annotation)| {
+            let mut summary = annotation.note.clone();
+            for tag in &annotation.tags {
+                summary.push_str(&format!(" #{}", tag));
+            }
+            ListItem::new(format!("{:>5}  {}", index + 1, summary.trim_start()))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(frame.area().height.saturating_sub(2));
+    let area = centered_rect(frame.area(), 70, height);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Annotations (Enter to jump, D to delete, w to write, Esc to close) "),
+        )
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    let mut state = ListState::default().with_selected(Some(selected));
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_concordance(frame: &mut Frame, navigator: &Navigator, selected: usize) {
+    let area = centered_rect(frame.area(), 100, frame.area().height.saturating_sub(2));
+    // The sentence number takes 7 columns and the borders 2
+    let context = (area.width.saturating_sub(9) as usize / 2).saturating_sub(6);
+    let items: Vec<ListItem> = navigator
+        .concordance(context)
+        .into_iter()
+        .map(|line| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!(
+                    "{:>5}  {:>width$}",
+                    line.sentence + 1,
+                    line.left,
+                    width = context
+                )),
+                Span::styled(line.keyword, Style::default().fg(Color::Yellow).bold()),
+                Span::raw(line.right),
+            ]))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(area.height);
+    let area = centered_rect(frame.area(), area.width, height);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Matches in context (Enter to jump, Esc to close) "),
+        )
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    let mut state = ListState::default().with_selected(Some(selected));
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_spelling(
+    frame: &mut Frame,
+    navigator: &Navigator,
+    fixes: &[(Range<usize>, String)],
+    selected: usize,
+) {
+    let text = &navigator.sentences[navigator.current_index].text;
+    let items: Vec<ListItem> = fixes
+        .iter()
+        .map(|(range, suggestion)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    text.get(range.clone()).unwrap_or_default(),
+                    Style::default().fg(Color::Red),
+                ),
+                Span::raw(" → "),
+                Span::raw(suggestion.as_str()),
+            ]))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(frame.area().height.saturating_sub(2));
+    let area = centered_rect(frame.area(), 60, height);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Spelling (Enter to correct, Esc to close) "),
+        )
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    let mut state = ListState::default().with_selected(Some(selected));
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_replace_preview(
+    frame: &mut Frame,
+    navigator: &Navigator,
+    changes: &[(usize, String)],
+    selected: usize,
+) {
+    let items: Vec<ListItem> = changes
+        .iter()
+        .map(|(index, text)| {
+            ListItem::new(vec![
+                Line::from(format!(
+                    "{:>5}  - {}",
+                    index + 1,
+                    navigator.sentences[*index].text
+                ))
+                .fg(Color::Red),
+                Line::from(format!("       + {}", text)).fg(Color::Green),
+            ])
+        })
+        .collect();
+
+    let height = (items.len() as u16 * 2 + 2).min(frame.area().height.saturating_sub(2));
+    let area = centered_rect(frame.area(), 90, height);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            " Replace in {} sentences? (y to apply, n to cancel, ↑/↓ to scroll) ",
+            changes.len()
+        )))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+    let mut state = ListState::default().with_selected(Some(selected));
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_rewrite_suggestion(
+    frame: &mut Frame,
+    draft: &str,
+    suggestion: &std::result::Result<String, String>,
+) {
+    let lines = match suggestion {
+        Ok(suggestion) => vec![
+            Line::from(format!("- {}", draft)).red(),
+            Line::from(format!("+ {}", suggestion)).green(),
+            Line::default(),
+            Line::from("Enter/y accept · e modify · Esc/n reject").dim(),
+        ],
+        Err(error) => vec![
+            Line::from(error.as_str()).red(),
+            Line::default(),
+            Line::from("Esc to go back to editing").dim(),
+        ],
+    };
+
+    let width = frame.area().width.saturating_sub(4).min(MAX_SENTENCE_WIDTH);
+    // Each line wraps on its own; the borders take two columns and two rows
+    let height: u16 = lines
+        .iter()
+        .map(|line| {
+            (line.width() as u16)
+                .div_ceil(width.saturating_sub(2).max(1))
+                .max(1)
+        })
+        .sum::<u16>()
+        + 2;
+    let area = centered_rect(frame.area(), width, height);
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: true }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Suggested rewrite "),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_confirm_quit(frame: &mut Frame, navigator: &Navigator) {
+    let question = match navigator.next_document {
+        Some(_) => "Save changes before switching documents? (y/n)",
+        None => "Save changes before quitting? (y/n)",
+    };
+    let area = centered_rect(frame.area(), (question.len() as u16 + 4).max(44), 3);
+    let prompt = Paragraph::new(question).alignment(Alignment::Center).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Unsaved changes "),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(prompt, area);
+}
+
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
@@ -0,0 +1,122 @@
+use crate::error::Result;
+use std::fmt;
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+
+// Text-to-speech programs that read the text to speak from standard input
+pub(super) fn default_speech_command() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "say"
+    } else {
+        "espeak-ng"
+    }
+}
+
+// Reads sentences aloud through an external command, one at a time. The
+// command is run by the shell and gets the sentence on standard input, so
+// pipelines such as "piper --model en.onnx --output-raw | aplay -r 22050 -f S16_LE"
+// work as well.
+pub(super) struct Speaker {
+    command: String,
+    // The sentence being spoken and the process speaking it
+    playing: Option<(usize, Child)>,
+}
+
+impl fmt::Debug for Speaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Speaker")
+            .field("command", &self.command)
+            .field("sentence", &self.sentence())
+            .finish()
+    }
+}
+
+impl Speaker {
+    pub(super) fn new(command: &str) -> Self {
+        Speaker {
+            command: command.to_string(),
+            playing: None,
+        }
+    }
+
+    pub(super) fn command(&self) -> &str {
+        &self.command
+    }
+
+    // Stops whatever is being spoken first
+    pub(super) fn speak(&mut self, index: usize, text: &str) -> Result<()> {
+        self.stop();
+        let mut child = shell_command(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        // Closing stdin when it is dropped tells the command the text is complete
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        self.playing = Some((index, child));
+        Ok(())
+    }
+
+    pub(super) fn stop(&mut self) {
+        if let Some((_, mut child)) = self.playing.take() {
+            child.kill().ok();
+            child.wait().ok();
+        }
+    }
+
+    pub(super) fn sentence(&self) -> Option<usize> {
+        self.playing.as_ref().map(|(index, _)| *index)
+    }
+
+    // Whether the last sentence has been spoken completely
+    pub(super) fn finished(&mut self) -> bool {
+        self.playing
+            .as_mut()
+            .is_none_or(|(_, child)| !matches!(child.try_wait(), Ok(None)))
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_speaker_runs_command_until_finished() -> Result<()> {
+        let mut speaker = Speaker::new("cat > /dev/null");
+        assert!(speaker.finished());
+
+        speaker.speak(3, "A sentence to read.")?;
+        assert_eq!(speaker.sentence(), Some(3));
+        while !speaker.finished() {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        speaker.speak(4, "Interrupted.")?;
+        speaker.stop();
+        assert_eq!(speaker.sentence(), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use crate::error::Result;
+use crate::sentence::Sentence;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use similar::TextDiff;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+// How far from its old position a changed sentence is looked for
+const SEARCH_WINDOW: usize = 50;
+// Writes often arrive as several events; they settle before the reload
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+pub(super) type Reload = Box<dyn Fn() -> Result<Vec<Sentence>>>;
+
+// The directory is watched rather than the file, since many editors save by
+// replacing the file with a new one
+pub(super) struct DocumentWatcher {
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    file_name: Option<OsString>,
+    pub(super) reload: Reload,
+}
+
+impl DocumentWatcher {
+    pub(super) fn new(path: &str, reload: Reload) -> Result<Self> {
+        let path = Path::new(path);
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+        Ok(DocumentWatcher {
+            _watcher: watcher,
+            events,
+            file_name: path.file_name().map(|name| name.to_os_string()),
+            reload,
+        })
+    }
+
+    // Whether the document was written since the last call
+    pub(super) fn changed(&self) -> bool {
+        if !self.drain() {
+            return false;
+        }
+        thread::sleep(SETTLE_TIME);
+        self.drain();
+        true
+    }
+
+    fn drain(&self) -> bool {
+        let mut changed = false;
+        while let Ok(event) = self.events.try_recv() {
+            let Ok(event) = event else { continue };
+            if (event.kind.is_modify() || event.kind.is_create())
+                && event
+                    .paths
+                    .iter()
+                    .any(|path| path.file_name() == self.file_name.as_deref())
+            {
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl fmt::Debug for DocumentWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DocumentWatcher")
+            .field("file_name", &self.file_name)
+            .finish_non_exhaustive()
+    }
+}
+
+// Where a sentence most likely is after the document was parsed again: the
+// same text nearest its old position, or else the most similar text nearby
+pub(super) fn relocate(text: &str, old_index: usize, sentences: &[Sentence]) -> usize {
+    if sentences.is_empty() {
+        return 0;
+    }
+
+    let exact = sentences
+        .iter()
+        .enumerate()
+        .filter(|(_, sentence)| sentence.text == text)
+        .min_by_key(|(index, _)| index.abs_diff(old_index));
+    if let Some((index, _)) = exact {
+        return index;
+    }
+
+    let old_index = old_index.min(sentences.len() - 1);
+    let window = old_index.saturating_sub(SEARCH_WINDOW)
+        ..(old_index + SEARCH_WINDOW + 1).min(sentences.len());
+    window
+        .map(|index| {
+            let similarity = TextDiff::from_chars(text, &sentences[index].text).ratio();
+            (index, similarity)
+        })
+        .max_by(|(a, a_similarity), (b, b_similarity)| {
+            a_similarity
+                .total_cmp(b_similarity)
+                .then(b.abs_diff(old_index).cmp(&a.abs_diff(old_index)))
+        })
+        .map_or(old_index, |(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relocate() {
+        let sentences: Vec<Sentence> = [
+            "Intro.",
+            "Repeated.",
+            "A new line.",
+            "Repeated.",
+            "The quick brown fox jumps.",
+        ]
+        .into_iter()
+        .map(Sentence::new)
+        .collect();
+
+        assert_eq!(relocate("Repeated.", 4, &sentences), 3);
+        assert_eq!(relocate("The quick brown fox jumped.", 2, &sentences), 4);
+        assert_eq!(relocate("Anything.", 9, &[]), 0);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::error::Result;
+use crate::language::Language;
+use crate::sentence::Sentence;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use unicode_segmentation::UnicodeSegmentation;
+
+const ENGLISH_STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "but", "by", "can", "could", "did", "do", "does", "for", "from", "had", "has", "have", "he",
+    "her", "him", "his", "how", "i", "if", "in", "into", "is", "it", "its", "me", "my", "no",
+    "not", "of", "on", "one", "or", "our", "out", "she", "so", "some", "than", "that", "the",
+    "their", "them", "then", "there", "these", "they", "this", "those", "to", "up", "us", "was",
+    "we", "were", "what", "when", "which", "who", "will", "with", "would", "you", "your",
+];
+
+const GERMAN_STOPWORDS: &[&str] = &[
+    "aber", "als", "am", "an", "auch", "auf", "aus", "bei", "bin", "bis", "da", "das", "dass",
+    "dem", "den", "der", "des", "die", "dir", "du", "ein", "eine", "einem", "einen", "einer", "er",
+    "es", "für", "hat", "ich", "ihr", "im", "in", "ist", "ja", "mich", "mir", "mit", "nach",
+    "nicht", "noch", "nur", "oder", "sich", "sie", "sind", "so", "um", "und", "uns", "von", "vor",
+    "war", "was", "wie", "wir", "wird", "zu", "zum", "zur",
+];
+
+const SPANISH_STOPWORDS: &[&str] = &[
+    "a", "al", "como", "con", "de", "del", "el", "ella", "en", "es", "esta", "este", "fue", "ha",
+    "la", "las", "le", "les", "lo", "los", "más", "me", "mi", "muy", "no", "nos", "o", "para",
+    "pero", "por", "que", "se", "si", "sin", "su", "sus", "también", "te", "tu", "un", "una", "y",
+    "ya", "yo",
+];
+
+// Common words left out of frequency lists, as they crowd out the vocabulary
+// that is worth looking at
+#[derive(Debug, Clone, Default)]
+pub struct Stopwords {
+    entries: HashSet<String>,
+}
+
+impl Stopwords {
+    pub fn new() -> Self {
+        Stopwords::default()
+    }
+
+    // Japanese and Chinese have no built-in list
+    pub fn for_language(language: Language) -> Self {
+        let words = match language {
+            Language::English => ENGLISH_STOPWORDS,
+            Language::German => GERMAN_STOPWORDS,
+            Language::Spanish => SPANISH_STOPWORDS,
+            Language::Japanese | Language::Chinese => &[],
+        };
+        let mut stopwords = Stopwords::new();
+        for word in words {
+            stopwords.insert(word);
+        }
+        stopwords
+    }
+
+    pub fn insert(&mut self, word: &str) {
+        let normalized = word.trim().to_lowercase();
+        if !normalized.is_empty() {
+            self.entries.insert(normalized);
+        }
+    }
+
+    // One word per line; blank lines and lines starting with '#' are ignored
+    pub fn load_file(&mut self, file_path: &str) -> Result<()> {
+        let content = fs::read_to_string(file_path)?;
+        for line in content.lines() {
+            let line = line.trim();
+            if !line.is_empty() && !line.starts_with('#') {
+                self.insert(line);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.entries.contains(word)
+    }
+}
+
+// Sentence indices count from 1, as in exports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WordFrequency {
+    pub word: String,
+    pub count: usize,
+    // Every sentence the word appears in, once each
+    pub sentences: Vec<usize>,
+}
+
+// Words are lowercased and split at Unicode word boundaries, so punctuation
+// and apostrophes around them are dropped. Numbers are not counted. The most
+// frequent words come first, ties in alphabetical order.
+pub fn word_frequencies(sentences: &[Sentence], stopwords: &Stopwords) -> Vec<WordFrequency> {
+    let mut frequencies: HashMap<String, WordFrequency> = HashMap::new();
+    for (i, sentence) in sentences.iter().enumerate() {
+        for word in sentence.text.unicode_words() {
+            let word = word.to_lowercase();
+            if !word.chars().any(char::is_alphabetic) || stopwords.contains(&word) {
+                continue;
+            }
+            let entry = frequencies
+                .entry(word)
+                .or_insert_with_key(|word| WordFrequency {
+                    word: word.clone(),
+                    count: 0,
+                    sentences: Vec::new(),
+                });
+            entry.count += 1;
+            if entry.sentences.last() != Some(&(i + 1)) {
+                entry.sentences.push(i + 1);
+            }
+        }
+    }
+
+    let mut frequencies: Vec<WordFrequency> = frequencies.into_values().collect();
+    frequencies.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+    frequencies
+}
+
+// One row per word, with up to `examples` of the sentences it appears in
+pub fn write_csv<W: Write>(
+    writer: &mut W,
+    frequencies: &[WordFrequency],
+    sentences: &[Sentence],
+    examples: usize,
+) -> Result<()> {
+    let mut table = csv::Writer::from_writer(writer);
+    table.write_record(["word", "count", "sentences", "examples"])?;
+
+    for frequency in frequencies {
+        let indices: Vec<String> = frequency.sentences.iter().map(usize::to_string).collect();
+        let example_texts: Vec<&str> = frequency
+            .sentences
+            .iter()
+            .take(examples)
+            .map(|&index| sentences[index - 1].text.as_str())
+            .collect();
+        table.write_record([
+            frequency.word.as_str(),
+            &frequency.count.to_string(),
+            &indices.join(" "),
+            &example_texts.join("\n"),
+        ])?;
+    }
+    table.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_frequencies() -> Result<()> {
+        let sentences = vec![
+            Sentence::new("The whale! The white whale."),
+            Sentence::new("Whales, and the sea's 2 whales."),
+            Sentence::new("A whale."),
+        ];
+
+        let frequencies = word_frequencies(&sentences, &Stopwords::for_language(Language::English));
+        let counts: Vec<(&str, usize)> = frequencies
+            .iter()
+            .map(|frequency| (frequency.word.as_str(), frequency.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![("whale", 3), ("whales", 2), ("sea's", 1), ("white", 1)]
+        );
+        assert_eq!(frequencies[0].sentences, vec![1, 3]);
+
+        let mut output = Vec::new();
+        write_csv(&mut output, &frequencies[..1], &sentences, 2)?;
+        assert_eq!(
+            String::from_utf8(output)?,
+            "word,count,sentences,examples\n\
+             whale,3,1 3,\"The whale! The white whale.\nA whale.\"\n"
+        );
+
+        let all = word_frequencies(&sentences, &Stopwords::new());
+        assert_eq!((all[0].word.as_str(), all[0].count), ("the", 3));
+        Ok(())
+    }
+}
@@ -0,0 +1,1283 @@
+use crate::error::{Result, SentencerError};
+use crate::normalize::normalize_whitespace;
+use crate::parser::{
+    CommentsMode, DocumentFormat, OdtParser, ParseOptions, Source, is_flat_xml, read_zip_entry,
+};
+use crate::sentence::Sentence;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Cursor, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+// How edits are written into the saved document
+#[derive(Debug, Clone, Default)]
+pub struct SaveOptions {
+    // Write the edits as tracked changes by this author, for review in
+    // LibreOffice, instead of in place
+    pub tracked_changes_author: Option<String>,
+    // Check that the edits can be saved, without writing anything
+    pub dry_run: bool,
+    // The copy kept of a document that is saved over
+    pub backup: Backup,
+}
+
+// How the previous version of a document is kept when it is saved over
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backup {
+    None,
+    // The name of the copy, with {} standing for the document's file name,
+    // such as "{}.bak"
+    Pattern(String),
+    // document.odt.~1~, document.odt.~2~ and so on, taking the first free number
+    Numbered,
+}
+
+impl Default for Backup {
+    fn default() -> Self {
+        Backup::Pattern("{}.bak".to_string())
+    }
+}
+
+impl Backup {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "none" | "off" => Ok(Backup::None),
+            "simple" => Ok(Backup::default()),
+            "numbered" => Ok(Backup::Numbered),
+            pattern if pattern.contains("{}") => Ok(Backup::Pattern(pattern.to_string())),
+            _ => Err(SentencerError::InvalidArgument(format!(
+                "Unknown backup '{}' (expected none, simple, numbered or a name with {{}})",
+                name
+            ))),
+        }
+    }
+
+    // Where the copy of the document at `path` goes, if one is kept
+    fn path(&self, path: &Path) -> Option<PathBuf> {
+        let name = path.file_name()?.to_string_lossy();
+        match self {
+            Backup::None => None,
+            Backup::Pattern(pattern) => Some(path.with_file_name(pattern.replace("{}", &name))),
+            Backup::Numbered => (1..)
+                .map(|number| path.with_file_name(format!("{}.~{}~", name, number)))
+                .find(|backup| !backup.exists()),
+        }
+    }
+}
+
+pub fn save_odt(
+    source_path: &str,
+    output_path: &str,
+    original: &[String],
+    edited: &[String],
+) -> Result<()> {
+    save_odt_with(
+        source_path,
+        output_path,
+        original,
+        edited,
+        &SaveOptions::default(),
+    )
+}
+
+pub fn save_odt_with(
+    source_path: &str,
+    output_path: &str,
+    original: &[String],
+    edited: &[String],
+    options: &SaveOptions,
+) -> Result<()> {
+    if DocumentFormat::detect(source_path)? != DocumentFormat::Odt {
+        return Err(SentencerError::Save(
+            "saving is only supported for ODT documents".to_string(),
+        ));
+    }
+
+    if is_flat_xml(Source::File(source_path))? {
+        return Err(SentencerError::Save(
+            "saving is not supported for flat ODT documents".to_string(),
+        ));
+    }
+    if OdtParser::is_encrypted(source_path)? {
+        return Err(SentencerError::Save(
+            "saving is not supported for encrypted ODT documents".to_string(),
+        ));
+    }
+
+    let content = read_zip_entry(Source::File(source_path), "content.xml")?;
+    // Every text:p is read, comments and tables of contents included, so
+    // paragraphs are numbered the same way as when the XML is rewritten
+    let paragraphs = OdtParser::new()?
+        .with_options(ParseOptions {
+            comments: CommentsMode::Include,
+            include_toc: true,
+            ..ParseOptions::default()
+        })
+        .extract_paragraphs_from_xml(&content)?;
+
+    let replacements = map_edits_to_paragraphs(&paragraphs, original, edited)?;
+    let mut changes = options
+        .tracked_changes_author
+        .as_deref()
+        .map(|author| TrackedEdits::new(author, SystemTime::now()));
+    let new_content = rewrite_paragraphs(&content, &paragraphs, &replacements, changes.as_mut())?;
+    if options.dry_run {
+        return Ok(());
+    }
+
+    write_archive(source_path, output_path, &new_content, &options.backup)
+}
+
+// Works out the new text of every paragraph touched by an edit. Sentences are
+// located in the same whitespace-normalized text the splitter operated on, so
+// each one can be traced back to the paragraph(s) it came from.
+fn map_edits_to_paragraphs(
+    paragraphs: &[String],
+    original: &[String],
+    edited: &[String],
+) -> Result<HashMap<usize, String>> {
+    if original.len() != edited.len() {
+        return Err(SentencerError::Save(
+            "edited sentences do not match the original document".to_string(),
+        ));
+    }
+
+    let mut cleaned_text = String::new();
+    let mut paragraph_ranges: Vec<Option<Range<usize>>> = Vec::new();
+    for paragraph in paragraphs {
+        let normalized = normalize_whitespace(paragraph);
+        if normalized.is_empty() {
+            paragraph_ranges.push(None);
+            continue;
+        }
+        if !cleaned_text.is_empty() {
+            cleaned_text.push(' ');
+        }
+        let start = cleaned_text.len();
+        cleaned_text.push_str(&normalized);
+        paragraph_ranges.push(Some(start..cleaned_text.len()));
+    }
+
+    // Sentences that were moved out of document order (such as notes collected
+    // at the end) or tagged by the parser may not be found; that only matters
+    // when they were edited
+    let mut sentence_ranges = Vec::new();
+    let mut cursor = 0;
+    for (sentence, edited_sentence) in original.iter().zip(edited) {
+        let range = match cleaned_text[cursor..].find(sentence.as_str()) {
+            Some(offset) => {
+                let start = cursor + offset;
+                cursor = start + sentence.len();
+                Some(start..cursor)
+            }
+            None => cleaned_text
+                .find(sentence.as_str())
+                .map(|start| start..start + sentence.len()),
+        };
+
+        if range.is_none() && sentence != edited_sentence {
+            return Err(SentencerError::Save(format!(
+                "could not locate sentence in document: {}",
+                sentence
+            )));
+        }
+        sentence_ranges.push(range);
+    }
+
+    let mut replacements = HashMap::new();
+    for (paragraph_index, range) in paragraph_ranges.iter().enumerate() {
+        let Some(range) = range else { continue };
+
+        let overlapping: Vec<usize> = sentence_ranges
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.as_ref().map(|s| (i, s)))
+            .filter(|(_, s)| s.start < range.end && s.end > range.start)
+            .map(|(i, _)| i)
+            .collect();
+
+        if !overlapping.iter().any(|&i| original[i] != edited[i]) {
+            continue;
+        }
+
+        let mut new_text = String::new();
+        let mut position = range.start;
+        for i in overlapping {
+            let Some(sentence_range) = &sentence_ranges[i] else {
+                continue;
+            };
+            let start = sentence_range.start.max(range.start);
+            let end = sentence_range.end.min(range.end);
+
+            new_text.push_str(&cleaned_text[position..start]);
+            if original[i] == edited[i] {
+                new_text.push_str(&cleaned_text[start..end]);
+            } else if sentence_range.start >= range.start {
+                // An edited sentence spanning paragraphs lands in the first one
+                new_text.push_str(&edited[i]);
+            }
+            position = end;
+        }
+        new_text.push_str(&cleaned_text[position..range.end]);
+
+        replacements.insert(paragraph_index, new_text.trim().to_string());
+    }
+
+    Ok(replacements)
+}
+
+// Rewrites the paragraphs with replacements; `paragraphs` holds the text of
+// every paragraph as the parser read it
+fn rewrite_paragraphs(
+    xml_content: &str,
+    paragraphs: &[String],
+    replacements: &HashMap<usize, String>,
+    mut changes: Option<&mut TrackedEdits>,
+) -> Result<String> {
+    let mut reader = Reader::from_str(xml_content);
+    let mut writer = Writer::new(Vec::new());
+
+    let mut paragraph_index = 0;
+    // Paragraphs of tracked deletions are not numbered, as in the parser
+    let mut in_tracked_changes = false;
+    // Where tracked edits go: into the document's own list of tracked
+    // changes, or else a new one at the start of the text
+    let mut changes_position: Option<(usize, bool)> = None;
+
+    loop {
+        let event = reader.read_event()?;
+        match event {
+            Event::Eof => break,
+            Event::Start(ref e) if e.name().as_ref() == b"office:text" => {
+                writer.write_event(event)?;
+                changes_position.get_or_insert((writer.get_ref().len(), false));
+            }
+            Event::Start(ref e) if e.name().as_ref() == b"text:tracked-changes" => {
+                in_tracked_changes = true;
+                writer.write_event(event)?;
+                changes_position = Some((writer.get_ref().len(), true));
+            }
+            Event::Empty(ref e) if e.name().as_ref() == b"text:tracked-changes" => {
+                writer.write_event(Event::Start(e.clone()))?;
+                changes_position = Some((writer.get_ref().len(), true));
+                writer.write_event(Event::End(e.to_end()))?;
+            }
+            // Changes already in the document keep their ids
+            Event::Start(ref e) if e.name().as_ref() == b"text:changed-region" => {
+                if let Some(changes) = changes.as_deref_mut()
+                    && let Some(id) = e.try_get_attribute("text:id")?
+                {
+                    changes.taken.insert(id.unescape_value()?.into_owned());
+                }
+                writer.write_event(event)?;
+            }
+            Event::End(ref e) if e.name().as_ref() == b"text:tracked-changes" => {
+                in_tracked_changes = false;
+                writer.write_event(event)?;
+            }
+            Event::Start(e) if is_paragraph(e.name().as_ref()) && !in_tracked_changes => {
+                let index = paragraph_index;
+                paragraph_index += 1;
+                let Some(text) = replacements.get(&index) else {
+                    writer.write_event(Event::Start(e))?;
+                    continue;
+                };
+
+                // The paragraph keeps its element, and with it its style
+                let end = e.to_end().into_owned();
+                let inner = read_inner(&mut reader)?;
+                paragraph_index += inner
+                    .iter()
+                    .filter(
+                        |event| matches!(event, Event::Start(e) if is_paragraph(e.name().as_ref())),
+                    )
+                    .count();
+                let styled = match StyledText::read(&inner)? {
+                    // A paragraph of plain text whose sentences were all
+                    // deleted goes too, unless the deletion is tracked
+                    Some(_) if text.is_empty() && changes.is_none() => continue,
+                    Some(styled) => styled,
+                    None => StyledText::plain(paragraphs.get(index).map_or("", String::as_str)),
+                };
+                writer.write_event(Event::Start(e))?;
+                styled.write(text, changes.as_deref_mut(), &mut writer)?;
+                writer.write_event(Event::End(end))?;
+            }
+            event => writer.write_event(event)?,
+        }
+    }
+
+    let mut content = writer.into_inner();
+    if let (Some(changes), Some((position, inside))) = (changes, changes_position)
+        && !changes.regions.is_empty()
+    {
+        content.splice(position..position, changes.to_xml(inside)?);
+    }
+
+    Ok(String::from_utf8(content)?)
+}
+
+// The events inside the element just read, up to its end
+fn read_inner<'a>(reader: &mut Reader<&'a [u8]>) -> Result<Vec<Event<'a>>> {
+    let mut events = Vec::new();
+    let mut depth = 0;
+    loop {
+        let event = reader.read_event()?;
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) if depth == 0 => return Ok(events),
+            Event::End(_) => depth -= 1,
+            Event::Eof => {
+                return Err(SentencerError::Save(
+                    "content.xml ends inside a paragraph".to_string(),
+                ));
+            }
+            _ => {}
+        }
+        events.push(event);
+    }
+}
+
+// Part of a word, inside the inline elements with these indexes, outermost first
+type Segment = (String, Vec<usize>);
+
+// The words of a paragraph along with the spans and links around them, so an
+// edit can be written back without losing their formatting
+struct StyledText<'a> {
+    elements: Vec<BytesStart<'a>>,
+    words: Vec<Vec<Segment>>,
+}
+
+impl<'a> StyledText<'a> {
+    // Paragraphs holding anything but text, spans and links, such as notes,
+    // frames, fields or tracked changes, are not read; their edits are
+    // written as plain text
+    fn read(events: &[Event<'a>]) -> Result<Option<Self>> {
+        let mut styled = StyledText {
+            elements: Vec::new(),
+            words: Vec::new(),
+        };
+        let mut stack = Vec::new();
+        let mut word: Vec<Segment> = Vec::new();
+
+        for event in events {
+            let text = match event {
+                Event::Start(e) if matches!(e.name().as_ref(), b"text:span" | b"text:a") => {
+                    stack.push(styled.elements.len());
+                    styled.elements.push(e.clone());
+                    continue;
+                }
+                Event::End(_) => {
+                    stack.pop();
+                    continue;
+                }
+                Event::Text(e) => e.unescape()?,
+                Event::Empty(e) => match e.name().as_ref() {
+                    b"text:s" | b"text:tab" | b"text:line-break" => " ".into(),
+                    b"text:soft-page-break" => continue,
+                    _ => return Ok(None),
+                },
+                Event::Start(_) => return Ok(None),
+                _ => continue,
+            };
+
+            for c in text.chars() {
+                if c.is_whitespace() {
+                    if !word.is_empty() {
+                        styled.words.push(std::mem::take(&mut word));
+                    }
+                    continue;
+                }
+                match word.last_mut() {
+                    Some((part, elements)) if *elements == stack => part.push(c),
+                    _ => word.push((c.to_string(), stack.clone())),
+                }
+            }
+        }
+        if !word.is_empty() {
+            styled.words.push(word);
+        }
+
+        Ok(Some(styled))
+    }
+
+    // The words of a paragraph without formatting
+    fn plain(text: &str) -> Self {
+        StyledText {
+            elements: Vec::new(),
+            words: text
+                .split_whitespace()
+                .map(|word| vec![(word.to_string(), Vec::new())])
+                .collect(),
+        }
+    }
+
+    // Words the edit kept are written with the formatting they had. New words
+    // take that of the words they replace, or else of the word before them.
+    fn write(
+        &self,
+        text: &str,
+        mut changes: Option<&mut TrackedEdits>,
+        writer: &mut Writer<Vec<u8>>,
+    ) -> Result<()> {
+        let old: Vec<String> = self
+            .words
+            .iter()
+            .map(|word| {
+                normalize_whitespace(
+                    &word
+                        .iter()
+                        .map(|(part, _)| part.as_str())
+                        .collect::<String>(),
+                )
+            })
+            .collect();
+        let new: Vec<&str> = text.split_whitespace().collect();
+        let steps = self.diff(&old, &new);
+
+        let mut parts = Parts::default();
+        for (n, step) in steps.iter().enumerate() {
+            let (deleted, inserted) = match step {
+                Step::Keep(i) => {
+                    parts.word(self.words[*i].clone());
+                    continue;
+                }
+                Step::Change(deleted, inserted) => (deleted, inserted),
+            };
+            let Some(changes) = changes.as_deref_mut() else {
+                for word in inserted {
+                    parts.word(word.clone());
+                }
+                continue;
+            };
+
+            // The spaces around a change belong to it only when it inserts
+            // or deletes words without replacing them
+            let deleted_text = deleted
+                .iter()
+                .map(|&i| old[i].as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let more = n + 1 < steps.len();
+            match inserted.first() {
+                Some(first) => {
+                    if !deleted.is_empty() {
+                        parts.separate(&first[0].1);
+                        parts.mark(changes.deletion(deleted_text));
+                    } else if more {
+                        parts.separate(&first[0].1);
+                    }
+                    let (start, end) = changes.insertion();
+                    parts.mark(start);
+                    for word in inserted {
+                        parts.word(word.clone());
+                    }
+                    if deleted.is_empty() && more {
+                        parts.trailing_space();
+                    }
+                    parts.mark(end);
+                }
+                None if more => {
+                    parts.separate(&self.words[deleted[0]][0].1);
+                    parts.mark(changes.deletion(format!("{} ", deleted_text)));
+                }
+                None if parts.has_text() => {
+                    parts.mark(changes.deletion(format!(" {}", deleted_text)));
+                }
+                None => parts.mark(changes.deletion(deleted_text)),
+            }
+        }
+
+        let mut open: Vec<usize> = Vec::new();
+        for part in parts.parts {
+            let (part, elements) = match part {
+                Part::Mark(mark) => {
+                    writer.write_event(Event::Empty(mark))?;
+                    continue;
+                }
+                Part::Text(segment) => segment,
+            };
+            let shared = open
+                .iter()
+                .zip(&elements)
+                .take_while(|(a, b)| a == b)
+                .count();
+            while open.len() > shared {
+                if let Some(index) = open.pop() {
+                    writer.write_event(Event::End(self.elements[index].to_end()))?;
+                }
+            }
+            for &index in &elements[shared..] {
+                writer.write_event(Event::Start(self.elements[index].clone()))?;
+                open.push(index);
+            }
+            writer.write_event(Event::Text(BytesText::new(&part)))?;
+        }
+        while let Some(index) = open.pop() {
+            writer.write_event(Event::End(self.elements[index].to_end()))?;
+        }
+
+        Ok(())
+    }
+
+    // The words kept from `old`, and between them the changes that turn the
+    // rest into `new`, with the formatting each inserted word gets
+    fn diff(&self, old: &[String], new: &[&str]) -> Vec<Step> {
+        // Lengths of the longest common subsequences of the word lists' tails
+        let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                common[i][j] = match old[i] == new[j] {
+                    true => common[i + 1][j + 1] + 1,
+                    false => common[i + 1][j].max(common[i][j + 1]),
+                };
+            }
+        }
+
+        let mut steps = Vec::new();
+        let mut previous: Option<&Vec<usize>> = None;
+        let (mut i, mut j) = (0, 0);
+        while i < old.len() || j < new.len() {
+            if i < old.len() && j < new.len() && old[i] == new[j] {
+                steps.push(Step::Keep(i));
+                previous = self.words[i].last().map(|(_, elements)| elements);
+                i += 1;
+                j += 1;
+                continue;
+            }
+
+            let mut deleted = Vec::new();
+            let mut added = Vec::new();
+            while (i < old.len() || j < new.len())
+                && !(i < old.len() && j < new.len() && old[i] == new[j])
+            {
+                if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+                    deleted.push(i);
+                    i += 1;
+                } else {
+                    added.push(new[j]);
+                    j += 1;
+                }
+            }
+            let elements = deleted
+                .first()
+                .map(|&i| &self.words[i][0].1)
+                .or(previous)
+                .or(self.words.get(i).map(|word| &word[0].1))
+                .cloned()
+                .unwrap_or_default();
+            let inserted = added
+                .into_iter()
+                .map(|word| vec![(word.to_string(), elements.clone())])
+                .collect();
+            steps.push(Step::Change(deleted, inserted));
+        }
+
+        steps
+    }
+}
+
+// A word an edit kept, by its index, or the words it deleted, by their
+// indexes, and those it put in their place
+enum Step {
+    Keep(usize),
+    Change(Vec<usize>, Vec<Vec<Segment>>),
+}
+
+// Text and tracked change marks in the order they are written
+enum Part {
+    Text(Segment),
+    Mark(BytesStart<'static>),
+}
+
+#[derive(Default)]
+struct Parts {
+    parts: Vec<Part>,
+    // Whether the space before the next word is already written
+    spaced: bool,
+}
+
+impl Parts {
+    fn has_text(&self) -> bool {
+        self.last_elements().is_some()
+    }
+
+    fn last_elements(&self) -> Option<&Vec<usize>> {
+        self.parts.iter().rev().find_map(|part| match part {
+            Part::Text((_, elements)) => Some(elements),
+            Part::Mark(_) => None,
+        })
+    }
+
+    fn word(&mut self, word: Vec<Segment>) {
+        if let Some((_, elements)) = word.first() {
+            self.separate(elements);
+        }
+        self.spaced = false;
+        for segment in word {
+            self.push_text(segment);
+        }
+    }
+
+    // The space between two words is inside the elements around both
+    fn separate(&mut self, next: &[usize]) {
+        if self.spaced {
+            return;
+        }
+        if let Some(before) = self.last_elements() {
+            let shared = before.iter().zip(next).take_while(|(a, b)| a == b).count();
+            self.push_text((" ".to_string(), next[..shared].to_vec()));
+            self.spaced = true;
+        }
+    }
+
+    fn trailing_space(&mut self) {
+        if let Some(elements) = self.last_elements().cloned() {
+            self.push_text((" ".to_string(), elements));
+            self.spaced = true;
+        }
+    }
+
+    fn mark(&mut self, mark: BytesStart<'static>) {
+        self.parts.push(Part::Mark(mark));
+    }
+
+    // Text inside the same elements as the text before it joins it
+    fn push_text(&mut self, segment: Segment) {
+        match self.parts.last_mut() {
+            Some(Part::Text((part, elements))) if *elements == segment.1 => {
+                part.push_str(&segment.0)
+            }
+            _ => self.parts.push(Part::Text(segment)),
+        }
+    }
+}
+
+// Edits written as tracked changes, which a reviewer accepts or rejects one
+// by one. Each is dated with the time of saving.
+struct TrackedEdits {
+    author: String,
+    date: String,
+    // The id of each change, with the text it deleted if it is a deletion
+    regions: Vec<(String, Option<String>)>,
+    // Ids of the changes the document already has, which come before its
+    // paragraphs, so a document saved again gets no duplicates
+    taken: HashSet<String>,
+    next_id: usize,
+}
+
+impl TrackedEdits {
+    fn new(author: &str, now: SystemTime) -> Self {
+        TrackedEdits {
+            author: author.to_string(),
+            date: timestamp(now),
+            regions: Vec::new(),
+            taken: HashSet::new(),
+            next_id: 1,
+        }
+    }
+
+    fn add(&mut self, deleted: Option<String>) -> String {
+        let id = loop {
+            let id = format!("sentencer-ct{}", self.next_id);
+            self.next_id += 1;
+            if !self.taken.contains(&id) {
+                break id;
+            }
+        };
+        self.regions.push((id.clone(), deleted));
+        id
+    }
+
+    // The mark where the deleted text was
+    fn deletion(&mut self, text: String) -> BytesStart<'static> {
+        let id = self.add(Some(text));
+        BytesStart::new("text:change").with_attributes([("text:change-id", id.as_str())])
+    }
+
+    // The marks before and after inserted text
+    fn insertion(&mut self) -> (BytesStart<'static>, BytesStart<'static>) {
+        let id = self.add(None);
+        let mark = |name: &'static str| {
+            BytesStart::new(name).with_attributes([("text:change-id", id.as_str())])
+        };
+        (mark("text:change-start"), mark("text:change-end"))
+    }
+
+    // The changed regions, in a text:tracked-changes element unless they go
+    // into the document's own
+    fn to_xml(&self, inside: bool) -> Result<Vec<u8>> {
+        let mut writer = Writer::new(Vec::new());
+        if !inside {
+            writer.write_event(Event::Start(BytesStart::new("text:tracked-changes")))?;
+        }
+        for (id, deleted) in &self.regions {
+            writer.write_event(Event::Start(
+                BytesStart::new("text:changed-region").with_attributes([("text:id", id.as_str())]),
+            ))?;
+            let change = match deleted {
+                Some(_) => "text:deletion",
+                None => "text:insertion",
+            };
+            writer.write_event(Event::Start(BytesStart::new(change)))?;
+            writer.write_event(Event::Start(BytesStart::new("office:change-info")))?;
+            for (name, text) in [("dc:creator", &self.author), ("dc:date", &self.date)] {
+                writer.write_event(Event::Start(BytesStart::new(name)))?;
+                writer.write_event(Event::Text(BytesText::new(text)))?;
+                writer.write_event(Event::End(BytesEnd::new(name)))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("office:change-info")))?;
+            if let Some(deleted) = deleted {
+                writer.write_event(Event::Start(BytesStart::new("text:p")))?;
+                writer.write_event(Event::Text(BytesText::new(deleted)))?;
+                writer.write_event(Event::End(BytesEnd::new("text:p")))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new(change)))?;
+            writer.write_event(Event::End(BytesEnd::new("text:changed-region")))?;
+        }
+        if !inside {
+            writer.write_event(Event::End(BytesEnd::new("text:tracked-changes")))?;
+        }
+
+        Ok(writer.into_inner())
+    }
+}
+
+// A UTC time as ODF dates are written, such as "2024-03-01T09:30:00"
+fn timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (days, time_of_day) = ((seconds / 86400) as i64, seconds % 86400);
+
+    // Days since 1970 to a civil date, counting in 400-year eras from March
+    let shifted = days + 719468;
+    let era = shifted.div_euclid(146097);
+    let day_of_era = shifted.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+// Headings are paragraphs too, matching how the parser numbers them
+fn is_paragraph(name: &[u8]) -> bool {
+    matches!(name, b"text:p" | b"text:h")
+}
+
+const MANIFEST_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.2">
+ <manifest:file-entry manifest:full-path="/" manifest:media-type="application/vnd.oasis.opendocument.text"/>
+ <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>
+"#;
+
+// Builds a new, minimal ODT from parsed (and possibly edited) sentences.
+// Sentences from the same paragraph share a text:p again and headings become
+// text:h; several documents follow one another.
+pub fn new_odt(documents: &[&[Sentence]]) -> Result<Vec<u8>> {
+    let content = new_content_xml(documents)?;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    // The uncompressed mimetype entry must come first
+    zip.start_file(
+        "mimetype",
+        FileOptions::default().compression_method(CompressionMethod::Stored),
+    )?;
+    zip.write_all(b"application/vnd.oasis.opendocument.text")?;
+    zip.start_file("META-INF/manifest.xml", FileOptions::default())?;
+    zip.write_all(MANIFEST_XML.as_bytes())?;
+    zip.start_file("content.xml", FileOptions::default())?;
+    zip.write_all(content.as_bytes())?;
+
+    Ok(zip.finish()?.into_inner())
+}
+
+fn new_content_xml(documents: &[&[Sentence]]) -> Result<String> {
+    let mut writer = Writer::new(Vec::new());
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer.write_event(Event::Start(
+        BytesStart::new("office:document-content").with_attributes([
+            (
+                "xmlns:office",
+                "urn:oasis:names:tc:opendocument:xmlns:office:1.0",
+            ),
+            (
+                "xmlns:text",
+                "urn:oasis:names:tc:opendocument:xmlns:text:1.0",
+            ),
+            ("office:version", "1.2"),
+        ]),
+    ))?;
+    writer.write_event(Event::Start(BytesStart::new("office:body")))?;
+    writer.write_event(Event::Start(BytesStart::new("office:text")))?;
+
+    for sentences in documents {
+        for paragraph in sentences.chunk_by(|a, b| {
+            a.paragraph_index == b.paragraph_index && a.kind == b.kind && a.heading_level.is_none()
+        }) {
+            let text = paragraph
+                .iter()
+                .map(|sentence| sentence.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let element = match paragraph[0].heading_level {
+                Some(level) => BytesStart::new("text:h")
+                    .with_attributes([("text:outline-level", level.to_string().as_str())]),
+                None => BytesStart::new("text:p"),
+            };
+            let end = element.to_end().into_owned();
+            writer.write_event(Event::Start(element))?;
+            writer.write_event(Event::Text(BytesText::new(&text)))?;
+            writer.write_event(Event::End(end))?;
+        }
+    }
+
+    writer.write_event(Event::End(BytesEnd::new("office:text")))?;
+    writer.write_event(Event::End(BytesEnd::new("office:body")))?;
+    writer.write_event(Event::End(BytesEnd::new("office:document-content")))?;
+
+    Ok(String::from_utf8(writer.into_inner())?)
+}
+
+// The new archive is read back before it takes the place of the document,
+// and the document it replaces is copied first, so a failed save never
+// leaves the only copy damaged
+fn write_archive(
+    source_path: &str,
+    output_path: &str,
+    content_xml: &str,
+    backup: &Backup,
+) -> Result<()> {
+    let temp_path = format!("{}.tmp", output_path);
+
+    let result = copy_archive_with_content(source_path, &temp_path, content_xml)
+        .and_then(|_| verify_archive(&temp_path))
+        .and_then(|_| back_up(output_path, backup));
+    if let Err(e) = result {
+        fs::remove_file(&temp_path).ok();
+        return Err(e);
+    }
+
+    fs::rename(&temp_path, output_path)?;
+    Ok(())
+}
+
+// Every entry is decompressed, which checks it against its checksum, and
+// content.xml must still be well-formed
+fn verify_archive(path: &str) -> Result<()> {
+    let read_back = || -> Result<()> {
+        let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+        for i in 0..archive.len() {
+            io::copy(&mut archive.by_index(i)?, &mut io::sink())?;
+        }
+        let content = read_zip_entry(Source::File(path), "content.xml")?;
+        let mut reader = Reader::from_str(&content);
+        while reader.read_event()? != Event::Eof {}
+        Ok(())
+    };
+
+    read_back().map_err(|e| {
+        SentencerError::Save(format!("the saved document could not be read back: {}", e))
+    })
+}
+
+fn back_up(path: &str, backup: &Backup) -> Result<()> {
+    let path = Path::new(path);
+    if !path.exists() {
+        return Ok(());
+    }
+    if let Some(backup_path) = backup.path(path) {
+        fs::copy(path, &backup_path).map_err(|e| {
+            SentencerError::Save(format!(
+                "could not back up {} to {}: {}",
+                path.display(),
+                backup_path.display(),
+                e
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+fn copy_archive_with_content(
+    source_path: &str,
+    target_path: &str,
+    content_xml: &str,
+) -> Result<()> {
+    let source = File::open(source_path)?;
+    let mut archive = ZipArchive::new(BufReader::new(source))?;
+    let mut zip = ZipWriter::new(File::create(target_path)?);
+
+    // Entries are copied in their original order so the uncompressed
+    // mimetype entry stays first, as the ODF spec requires
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        if entry.name() == "content.xml" {
+            let options = FileOptions::default().compression_method(entry.compression());
+            zip.start_file("content.xml", options)?;
+            zip.write_all(content_xml.as_bytes())?;
+        } else {
+            zip.raw_copy_file(entry)?;
+        }
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ChangesView;
+    use crate::sentence::texts;
+    use crate::test_utils::create_test_odt_file;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_map_edits_to_paragraphs() -> Result<()> {
+        let paragraphs = strings(&["First one. Second one.", "  Third   one. "]);
+        let original = strings(&["First one.", "Second one.", "Third one."]);
+        let edited = strings(&["First one.", "Changed!", "Third one."]);
+
+        let replacements = map_edits_to_paragraphs(&paragraphs, &original, &edited)?;
+
+        assert_eq!(replacements.len(), 1);
+        assert_eq!(replacements[&0], "First one. Changed!");
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_keeps_formatting() -> Result<()> {
+        let content = r#"<office:text>
+            <text:p text:style-name="P1">Keep <text:span text:style-name="T1">this</text:span> here. Change <text:span text:style-name="T2">bold words</text:span> now.</text:p>
+            <text:p text:style-name="P2">A note<text:note><text:note-body><text:p>Noted.</text:p></text:note-body></text:note> here.</text:p>
+            <text:p>Untouched.</text:p>
+        </office:text>"#;
+        let replacements = HashMap::from([
+            (0, "Keep this here. Change bold text now.".to_string()),
+            (1, "A note there.".to_string()),
+            (3, "Touched.".to_string()),
+        ]);
+
+        let paragraphs = strings(&[
+            "Keep this here. Change bold words now.",
+            "A note here.",
+            "Noted.",
+            "Untouched.",
+        ]);
+        let rewritten = rewrite_paragraphs(content, &paragraphs, &replacements, None)?;
+
+        assert!(rewritten.contains(
+            r#"<text:p text:style-name="P1">Keep <text:span text:style-name="T1">this</text:span> here. Change <text:span text:style-name="T2">bold text</text:span> now.</text:p>"#
+        ));
+        // Paragraphs with notes are written as plain text
+        assert!(rewritten.contains(r#"<text:p text:style-name="P2">A note there.</text:p>"#));
+        assert!(rewritten.contains("<text:p>Touched.</text:p>"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_odt_round_trip() -> Result<()> {
+        let source = "save_source.odt";
+        let output = "save_output.odt";
+        create_test_odt_file(
+            source,
+            r#"<text:h text:outline-level="1">Title</text:h>
+            <text:p>Keep this. Change this one.</text:p>
+            <text:p>Untouched paragraph.</text:p>"#,
+        )?;
+
+        let parser = OdtParser::new()?;
+        let original: Vec<String> = parser
+            .parse_file(source)?
+            .into_iter()
+            .map(|sentence| sentence.text)
+            .collect();
+        let mut edited = original.clone();
+        edited[2] = "Now it is different.".to_string();
+
+        let result = save_odt(source, output, &original, &edited)
+            .and_then(|_| parser.parse_file(output))
+            .and_then(|sentences| {
+                Ok((
+                    sentences,
+                    read_zip_entry(Source::File(output), "content.xml")?,
+                ))
+            });
+
+        fs::remove_file(source).ok();
+        fs::remove_file(output).ok();
+
+        let (sentences, content) = result?;
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "Title",
+                "Keep this.",
+                "Now it is different.",
+                "Untouched paragraph."
+            ]
+        );
+        assert!(content.contains("<text:p>Untouched paragraph.</text:p>"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_as_tracked_changes() -> Result<()> {
+        let source = "tracked_source.odt";
+        let output = "tracked_output.odt";
+        create_test_odt_file(
+            source,
+            r#"<text:p>Keep this here. Change <text:span text:style-name="T1">bold words</text:span> now.</text:p>
+            <text:p>Add to this. Drop the last words here.</text:p>"#,
+        )?;
+        let original = strings(&[
+            "Keep this here.",
+            "Change bold words now.",
+            "Add to this.",
+            "Drop the last words here.",
+        ]);
+        let edited = strings(&[
+            "Keep here.",
+            "Change bold text now.",
+            "Add more to this.",
+            "Drop the last.",
+        ]);
+        let options = SaveOptions {
+            tracked_changes_author: Some("Ann".to_string()),
+            ..SaveOptions::default()
+        };
+
+        let parse = |changes| {
+            OdtParser::new()?
+                .with_options(ParseOptions {
+                    changes,
+                    ..ParseOptions::default()
+                })
+                .parse_file(output)
+        };
+        let result = save_odt_with(source, output, &original, &edited, &options).and_then(|_| {
+            Ok((
+                parse(ChangesView::Accepted)?,
+                parse(ChangesView::Rejected)?,
+                read_zip_entry(Source::File(output), "content.xml")?,
+            ))
+        });
+
+        fs::remove_file(source).ok();
+        fs::remove_file(output).ok();
+
+        let (accepted, rejected, content) = result?;
+        assert_eq!(texts(&accepted), edited);
+        assert_eq!(texts(&rejected), original);
+        assert!(content.contains(r#"<text:span text:style-name="T1">bold <text:change text:change-id="sentencer-ct2"/><text:change-start text:change-id="sentencer-ct3"/>text<text:change-end text:change-id="sentencer-ct3"/></text:span>"#));
+        assert_eq!(content.matches("<dc:creator>Ann</dc:creator>").count(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_tracked_changes_twice() -> Result<()> {
+        let source = "tracked_twice_source.odt";
+        let first = "tracked_twice_first.odt";
+        let second = "tracked_twice_second.odt";
+        create_test_odt_file(
+            source,
+            "<text:p>First words here.</text:p><text:p>Second words here.</text:p>",
+        )?;
+        let options = SaveOptions {
+            tracked_changes_author: Some("Ann".to_string()),
+            ..SaveOptions::default()
+        };
+
+        let result = save_odt_with(
+            source,
+            first,
+            &strings(&["First words here.", "Second words here."]),
+            &strings(&["First here.", "Second words here."]),
+            &options,
+        )
+        .and_then(|_| {
+            save_odt_with(
+                first,
+                second,
+                &strings(&["First here.", "Second words here."]),
+                &strings(&["First here.", "Second here."]),
+                &options,
+            )
+        })
+        .and_then(|_| {
+            Ok((
+                OdtParser::new()?.parse_file(second)?,
+                read_zip_entry(Source::File(second), "content.xml")?,
+            ))
+        });
+
+        for file in [source, first, second] {
+            fs::remove_file(file).ok();
+        }
+
+        let (sentences, content) = result?;
+        assert_eq!(texts(&sentences), vec!["First here.", "Second here."]);
+        let mut ids: Vec<&str> = content
+            .split(r#"<text:changed-region text:id=""#)
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["sentencer-ct1", "sentencer-ct2"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run() -> Result<()> {
+        let source = "dry_run_source.odt";
+        let output = "dry_run_output.odt";
+        create_test_odt_file(source, "<text:p>Keep this. Change this.</text:p>")?;
+        let options = SaveOptions {
+            dry_run: true,
+            ..SaveOptions::default()
+        };
+
+        let original = strings(&["Change this."]);
+        let saved = save_odt_with(source, output, &original, &strings(&["Changed."]), &options);
+        let missing = save_odt_with(
+            source,
+            output,
+            &strings(&["Not there."]),
+            &original,
+            &options,
+        );
+        let written = fs::exists(output);
+        fs::remove_file(source).ok();
+        fs::remove_file(output).ok();
+
+        saved?;
+        assert!(missing.is_err());
+        assert!(!written?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_backups() -> Result<()> {
+        let source = "backup_source.odt";
+        create_test_odt_file(source, "<text:p>First version.</text:p>")?;
+        let save = |from: &str, to: &str, backup: Backup| {
+            let options = SaveOptions {
+                backup,
+                ..SaveOptions::default()
+            };
+            save_odt_with(source, source, &strings(&[from]), &strings(&[to]), &options)
+        };
+        let read = |path: &str| {
+            let sentences = OdtParser::new()?.parse_file(path)?;
+            Ok::<_, SentencerError>(sentences.into_iter().map(|s| s.text).collect::<Vec<_>>())
+        };
+
+        let result = save("First version.", "Second version.", Backup::default())
+            .and_then(|_| save("Second version.", "Third version.", Backup::Numbered))
+            .and_then(|_| save("Third version.", "Fourth version.", Backup::Numbered))
+            .and_then(|_| save("Fourth version.", "Fifth version.", Backup::None))
+            .and_then(|_| {
+                Ok((
+                    read("backup_source.odt.bak")?,
+                    read("backup_source.odt.~1~")?,
+                    read("backup_source.odt.~2~")?,
+                    fs::exists("backup_source.odt.~3~")?,
+                    read(source)?,
+                ))
+            });
+        for path in [
+            source,
+            "backup_source.odt.bak",
+            "backup_source.odt.~1~",
+            "backup_source.odt.~2~",
+        ] {
+            fs::remove_file(path).ok();
+        }
+
+        let (bak, first, second, third, current) = result?;
+        assert_eq!(bak, vec!["First version."]);
+        assert_eq!(first, vec!["Second version."]);
+        assert_eq!(second, vec!["Third version."]);
+        assert!(!third);
+        assert_eq!(current, vec!["Fifth version."]);
+        assert_eq!(
+            Backup::from_name("old/{}")?.path(Path::new("dir/a.odt")),
+            Some(PathBuf::from("dir/old/a.odt"))
+        );
+        assert!(Backup::from_name("copy").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let leap_day = UNIX_EPOCH + std::time::Duration::from_secs(951_782_400);
+        assert_eq!(timestamp(leap_day), "2000-02-29T00:00:00");
+    }
+
+    #[test]
+    fn test_new_odt_round_trip() -> Result<()> {
+        let source = "new_odt_source.odt";
+        let output = "new_odt_output.odt";
+        create_test_odt_file(
+            source,
+            r#"<text:h text:outline-level="2">Title &amp; more</text:h>
+            <text:p>First sentence. Second sentence.</text:p>
+            <text:p>Another paragraph.</text:p>"#,
+        )?;
+
+        let parser = OdtParser::new()?;
+        let result = parser.parse_file(source).and_then(|mut sentences| {
+            sentences[2].text = "Edited sentence.".to_string();
+            fs::write(output, new_odt(&[&sentences])?)?;
+            Ok((
+                parser.parse_file(output)?,
+                read_zip_entry(Source::File(output), "content.xml")?,
+            ))
+        });
+
+        fs::remove_file(source).ok();
+        fs::remove_file(output).ok();
+
+        let (sentences, content) = result?;
+        assert_eq!(
+            texts(&sentences),
+            vec![
+                "Title & more",
+                "First sentence.",
+                "Edited sentence.",
+                "Another paragraph."
+            ]
+        );
+        assert_eq!(sentences[0].heading_level, Some(2));
+        assert!(content.contains("<text:p>First sentence. Edited sentence.</text:p>"));
+        Ok(())
+    }
+}